@@ -1,7 +1,49 @@
-use core::ptr;
+use core::{
+    f32::consts::{FRAC_PI_6, PI, TAU},
+    ptr,
+};
 
 use bevy_math::Vec2;
 
+/// Largest steering angle in radians that the built-in drivers use; the front
+/// wheels turn by the written steering value, positive to the right.
+pub const MAX_STEER: f32 = FRAC_PI_6;
+
+/// Accelerator (or brake) per world unit per second of speed error in
+/// `CarControls::hold_speed`.
+pub const HOLD_SPEED_GAIN: f32 = 0.25;
+
+/// Distance `TrackRadar::best_sector` assumes for rays that hit nothing.
+pub const RADAR_NO_HIT_DISTANCE: f32 = 80.0;
+
+/// Signed shortest turn from `heading` to `target_heading`, both in radians
+/// counter-clockwise from +x. The result is in `(-π, π]`; positive turns left.
+pub fn heading_error(heading: f32, target_heading: f32) -> f32 {
+    let error = (target_heading - heading) % TAU;
+    if error > PI {
+        error - TAU
+    } else if error <= -PI {
+        error + TAU
+    } else {
+        error
+    }
+}
+
+/// Steering value that turns a car facing `heading` towards `target_heading`
+/// the short way round: the wheels follow the heading error, limited to
+/// `MAX_STEER`.
+pub fn steering_towards(heading: f32, target_heading: f32) -> f32 {
+    // Positive steering turns right, positive heading error is to the left.
+    (-heading_error(heading, target_heading)).clamp(-MAX_STEER, MAX_STEER)
+}
+
+/// `(accelerator, brake)` that move `speed` towards `target_speed`: a
+/// proportional controller that only ever uses one pedal at a time.
+pub fn speed_controls(speed: f32, target_speed: f32) -> (f32, f32) {
+    let error = (target_speed - speed) * HOLD_SPEED_GAIN;
+    (error.clamp(0.0, 1.0), (-error).clamp(0.0, 1.0))
+}
+
 pub struct CarControls {
     accelerator: *mut f32,
     brake: *mut f32,
@@ -40,6 +82,21 @@ impl CarControls {
     pub fn steering(&self) -> f32 {
         unsafe { ptr::read_volatile(self.steering) }
     }
+
+    /// Steer towards `target_heading` (radians counter-clockwise from +x),
+    /// turning whichever way is shorter. See `steering_towards`.
+    pub fn steer_towards(&mut self, state: &CarState, target_heading: f32) {
+        let heading = state.forward().to_angle();
+        self.set_steering(steering_towards(heading, target_heading));
+    }
+
+    /// Accelerate or brake towards `target_speed` in world units per second.
+    /// See `speed_controls`.
+    pub fn hold_speed(&mut self, state: &CarState, target_speed: f32) {
+        let (accelerator, brake) = speed_controls(state.speed(), target_speed);
+        self.set_accelerator(accelerator);
+        self.set_brake(brake);
+    }
 }
 
 pub struct CarState {
@@ -48,6 +105,8 @@ pub struct CarState {
     position_y: *const f32,
     forward_x: *const f32,
     forward_y: *const f32,
+    race_started: *const u32,
+    tick_us: *const u32,
 }
 
 impl CarState {
@@ -58,6 +117,8 @@ impl CarState {
             position_y: (slot + 0x08) as *const f32,
             forward_x: (slot + 0x0C) as *const f32,
             forward_y: (slot + 0x10) as *const f32,
+            race_started: (slot + 0x14) as *const u32,
+            tick_us: (slot + 0x18) as *const u32,
         }
     }
     pub fn speed(&self) -> f32 {
@@ -79,6 +140,18 @@ impl CarState {
             )
         }
     }
+    /// Whether the pre-race countdown has elapsed and the race is running.
+    pub fn race_started(&self) -> bool {
+        unsafe { ptr::read_volatile(self.race_started) != 0 }
+    }
+    /// Length of one simulation tick in microseconds (5000 at the default 200 Hz).
+    pub fn tick_micros(&self) -> u32 {
+        unsafe { ptr::read_volatile(self.tick_us) }
+    }
+    /// Length of one simulation tick in seconds.
+    pub fn tick_secs(&self) -> f32 {
+        self.tick_micros() as f32 / 1_000_000.0
+    }
 }
 
 pub struct SplineQuery {
@@ -110,11 +183,54 @@ impl SplineQuery {
     }
 }
 
+/// Part of the radar cone, see `TrackRadar::best_sector`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RadarSector {
+    Right,
+    Ahead,
+    Left,
+}
+
+/// The sector with the most room on average among `distances` (right to
+/// left, NaN for no hit, counted as `RADAR_NO_HIT_DISTANCE`). The outer
+/// `(len - 1) / 2` rays on each side form `Right` and `Left`, the rest
+/// `Ahead`; ties go to `Ahead`, then `Right`.
+pub fn best_sector(distances: &[f32]) -> RadarSector {
+    let side = distances.len().saturating_sub(1) / 2;
+    let clearance = |rays: &[f32]| {
+        if rays.is_empty() {
+            return f32::NEG_INFINITY;
+        }
+        let total: f32 = rays
+            .iter()
+            .map(|&d| if d.is_nan() { RADAR_NO_HIT_DISTANCE } else { d })
+            .sum();
+        total / rays.len() as f32
+    };
+    let right = clearance(&distances[..side]);
+    let ahead = clearance(&distances[side..distances.len() - side]);
+    let left = clearance(&distances[distances.len() - side..]);
+
+    if ahead >= right && ahead >= left {
+        RadarSector::Ahead
+    } else if right >= left {
+        RadarSector::Right
+    } else {
+        RadarSector::Left
+    }
+}
+
+/// Distances to the track borders along rays spread over a 90° forward cone,
+/// right to left.
 pub struct TrackRadar {
     distances: [*const f32; 7],
+    ray_count: *const u32,
 }
 
 impl TrackRadar {
+    /// Most rays the host can publish (the words below the count at `0xFC`).
+    pub const MAX_RAYS: usize = 63;
+
     pub const fn bind(slot: usize) -> Self {
         Self {
             distances: [
@@ -126,9 +242,17 @@ impl TrackRadar {
                 (slot + 0x14) as *const f32,
                 (slot + 0x18) as *const f32,
             ],
+            ray_count: (slot + 0xFC) as *const u32,
         }
     }
 
+    /// Number of rays the host casts; 7 unless the game configured the radar
+    /// differently.
+    pub fn ray_count(&self) -> usize {
+        unsafe { ptr::read_volatile(self.ray_count) as usize }
+    }
+
+    /// The first seven rays, which is all of them on the default radar.
     pub fn distances(&self) -> [f32; 7] {
         unsafe {
             [
@@ -143,12 +267,27 @@ impl TrackRadar {
         }
     }
 
+    /// Distance along ray `index`; NaN for no hit or an index past `ray_count()`.
     pub fn distance(&self, index: usize) -> f32 {
-        if index >= self.distances.len() {
+        if index >= self.ray_count() {
             return f32::NAN;
         }
 
-        unsafe { ptr::read_volatile(self.distances[index]) }
+        unsafe { ptr::read_volatile(self.distances[0].add(index)) }
+    }
+
+    /// Which part of the cone has the most room, over all `ray_count()` rays.
+    /// See `best_sector`.
+    pub fn best_sector(&self) -> RadarSector {
+        let count = self.ray_count().min(Self::MAX_RAYS);
+        let distances: [f32; Self::MAX_RAYS] = core::array::from_fn(|index| {
+            if index < count {
+                self.distance(index)
+            } else {
+                f32::NAN
+            }
+        });
+        best_sector(&distances[..count])
     }
 }
 
@@ -227,3 +366,152 @@ impl CarRadar {
         }
     }
 }
+
+pub struct LapProgress {
+    lap_fraction: *const f32,
+    track_length: *const f32,
+    lap_distance: *const f32,
+}
+
+impl LapProgress {
+    pub const fn bind(slot: usize) -> Self {
+        Self {
+            lap_fraction: (slot + 0x00) as *const f32,
+            track_length: (slot + 0x04) as *const f32,
+            lap_distance: (slot + 0x08) as *const f32,
+        }
+    }
+
+    /// Fraction of the current lap completed, from 0 at the start line towards 1.
+    pub fn lap_fraction(&self) -> f32 {
+        unsafe { ptr::read_volatile(self.lap_fraction) }
+    }
+
+    /// Centre-line length of one lap in world units.
+    pub fn track_length(&self) -> f32 {
+        unsafe { ptr::read_volatile(self.track_length) }
+    }
+
+    /// Distance driven along the centre line since the start line.
+    pub fn lap_distance(&self) -> f32 {
+        unsafe { ptr::read_volatile(self.lap_distance) }
+    }
+}
+
+/// Car speed as a fixed-point integer (thousandths of a world unit per second).
+pub struct Speedometer {
+    speed: *const u32,
+}
+
+impl Speedometer {
+    /// Divide `speed_fixed()` by this to get world units per second.
+    pub const SCALE: u32 = 1000;
+
+    pub const fn bind(slot: usize) -> Self {
+        Self {
+            speed: slot as *const u32,
+        }
+    }
+
+    /// Speed in thousandths of a world unit per second.
+    pub fn speed_fixed(&self) -> u32 {
+        unsafe { ptr::read_volatile(self.speed) }
+    }
+}
+
+/// Signed curvature of the centre line ahead of the car, in fixed point.
+pub struct TrackAhead {
+    samples: *const i32,
+}
+
+impl TrackAhead {
+    /// Number of samples, taken every `SPACING` world units ahead of the car.
+    pub const SAMPLES: usize = 8;
+    /// Centre-line distance between samples, in world units.
+    pub const SPACING: f32 = 10.0;
+    /// Divide a sample by this to get 1 / turn radius in world units.
+    pub const SCALE: i32 = 10_000;
+
+    pub const fn bind(slot: usize) -> Self {
+        Self {
+            samples: slot as *const i32,
+        }
+    }
+
+    /// Curvature `(index + 1) * SPACING` ahead; positive turns left, negative
+    /// right, 0 on a straight. Indices past `SAMPLES` read 0.
+    pub fn curvature_fixed(&self, index: usize) -> i32 {
+        if index >= Self::SAMPLES {
+            return 0;
+        }
+        unsafe { ptr::read_volatile(self.samples.add(index)) }
+    }
+
+    /// All samples, nearest first.
+    pub fn curvatures_fixed(&self) -> [i32; Self::SAMPLES] {
+        core::array::from_fn(|index| self.curvature_fixed(index))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::f32::consts::FRAC_PI_2;
+
+    use super::*;
+
+    fn assert_close(actual: f32, expected: f32) {
+        assert!((actual - expected).abs() < 1e-5, "{actual} != {expected}");
+    }
+
+    #[test]
+    fn heading_error_takes_the_short_way_across_the_wrap() {
+        assert_close(heading_error(0.0, FRAC_PI_2), FRAC_PI_2);
+        assert_close(heading_error(FRAC_PI_2, 0.0), -FRAC_PI_2);
+        // 350° to 10° is 20° to the left, not 340° to the right.
+        assert_close(heading_error(TAU - 0.1745, 0.1745), 0.349);
+        assert_close(heading_error(0.1745, TAU - 0.1745), -0.349);
+        // atan2-style headings in (-π, π] wrap the same way.
+        assert_close(heading_error(PI - 0.1, -PI + 0.1), 0.2);
+        assert_close(heading_error(0.0, 4.0 * TAU + 0.5), 0.5);
+        assert_close(heading_error(0.0, PI), PI);
+    }
+
+    #[test]
+    fn steering_turns_towards_the_target_and_saturates() {
+        // Target to the left needs negative (left) steering.
+        assert!(steering_towards(0.0, 0.2) < 0.0);
+        assert!(steering_towards(TAU - 0.1, 0.1) < 0.0);
+        assert!(steering_towards(0.1, TAU - 0.1) > 0.0);
+        assert_close(steering_towards(1.0, 1.1), -0.1);
+        assert_eq!(steering_towards(0.0, 3.0), -MAX_STEER);
+        assert_eq!(steering_towards(0.0, -3.0), MAX_STEER);
+        assert_eq!(steering_towards(2.0, 2.0), 0.0);
+    }
+
+    #[test]
+    fn speed_controls_use_one_pedal_in_proportion() {
+        assert_eq!(speed_controls(10.0, 12.0), (0.5, 0.0));
+        assert_eq!(speed_controls(0.0, 30.0), (1.0, 0.0));
+        assert_eq!(speed_controls(20.0, 18.0), (0.0, 0.5));
+        assert_eq!(speed_controls(15.0, 15.0), (0.0, 0.0));
+    }
+
+    #[test]
+    fn best_sector_picks_the_most_open_side() {
+        let nan = f32::NAN;
+        assert_eq!(
+            best_sector(&[30.0, 25.0, 20.0, 8.0, 5.0, 6.0, 7.0]),
+            RadarSector::Right
+        );
+        assert_eq!(
+            best_sector(&[5.0, 6.0, 7.0, 8.0, nan, nan, 40.0]),
+            RadarSector::Left
+        );
+        assert_eq!(
+            best_sector(&[10.0, 10.0, 10.0, 10.0, 10.0, 10.0, 10.0]),
+            RadarSector::Ahead
+        );
+        assert_eq!(best_sector(&[1.0]), RadarSector::Ahead);
+        assert_eq!(best_sector(&[]), RadarSector::Ahead);
+    }
+}
@@ -0,0 +1,381 @@
+//! Free-list heap used by the `global-allocator` feature.
+//!
+//! `FreeListHeap` keeps an address-ordered list of free blocks inside its own
+//! arena: allocation is first fit, and freeing coalesces with both neighbours,
+//! so alloc/dealloc cycles reuse memory instead of exhausting it like a bump
+//! allocator. The tradeoffs are an O(free blocks) walk per call and
+//! fragmentation when many differently sized allocations are interleaved.
+//! Every block is at least two words, so tiny allocations cost 8 bytes.
+//!
+//! `GlobalHeap` wraps it behind an atomic spin lock so it is sound as a
+//! `#[global_allocator]` (bots run on a single hart, so the lock is never
+//! contended). To pick a size other than the feature profiles, disable the
+//! `global-allocator` feature and install your own:
+//!
+//! ```ignore
+//! #[global_allocator]
+//! static HEAP: botracers_bot_sdk::heap::GlobalHeap<32768> =
+//!     botracers_bot_sdk::heap::GlobalHeap::new();
+//! ```
+
+use core::alloc::{GlobalAlloc, Layout};
+use core::cell::UnsafeCell;
+use core::mem::{align_of, size_of};
+use core::ptr::{self, NonNull};
+use core::sync::atomic::{AtomicBool, Ordering};
+
+/// Heap size installed by the `global-allocator` feature; the largest enabled
+/// `allocator-*` profile wins.
+#[cfg(feature = "allocator-64k")]
+pub const HEAP_SIZE: usize = 64 * 1024;
+#[cfg(all(feature = "allocator-16k", not(feature = "allocator-64k")))]
+pub const HEAP_SIZE: usize = 16 * 1024;
+#[cfg(not(any(feature = "allocator-16k", feature = "allocator-64k")))]
+pub const HEAP_SIZE: usize = 4 * 1024;
+
+/// End-of-list marker for free block offsets.
+const NONE: usize = usize::MAX;
+
+/// Header written into the first bytes of every free block.
+#[derive(Clone, Copy)]
+#[repr(C)]
+struct FreeBlock {
+    size: usize,
+    next: usize,
+}
+
+const BLOCK: usize = size_of::<FreeBlock>();
+const BLOCK_ALIGN: usize = align_of::<FreeBlock>();
+
+#[repr(C, align(16))]
+struct Arena<const N: usize>([u8; N]);
+
+/// Bytes a request of `size` occupies: whole blocks, so any free remainder can
+/// hold a header.
+fn block_size(size: usize) -> usize {
+    size.max(BLOCK).next_multiple_of(BLOCK_ALIGN)
+}
+
+/// A first-fit free-list allocator over an inline `N`-byte arena.
+///
+/// Pointers it returns point into the arena, so the heap must not move while
+/// any allocation is live (it normally lives in a `static`).
+pub struct FreeListHeap<const N: usize> {
+    arena: Arena<N>,
+    /// Offset of the first free block, in address order.
+    head: usize,
+    initialized: bool,
+}
+
+impl<const N: usize> Default for FreeListHeap<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> FreeListHeap<N> {
+    /// Usable arena bytes: whole header-aligned units.
+    const LIMIT: usize = N - N % BLOCK_ALIGN;
+
+    pub const fn new() -> Self {
+        Self {
+            arena: Arena([0; N]),
+            head: NONE,
+            initialized: false,
+        }
+    }
+
+    fn read(&self, offset: usize) -> FreeBlock {
+        // Free block offsets are header-aligned and inside the arena.
+        unsafe { ptr::read(self.arena.0.as_ptr().add(offset) as *const FreeBlock) }
+    }
+
+    fn write(&mut self, offset: usize, block: FreeBlock) {
+        unsafe {
+            ptr::write(
+                self.arena.0.as_mut_ptr().add(offset) as *mut FreeBlock,
+                block,
+            )
+        }
+    }
+
+    /// Point `prev` (or the list head) at `next`.
+    fn link(&mut self, prev: usize, next: usize) {
+        if prev == NONE {
+            self.head = next;
+        } else {
+            let mut block = self.read(prev);
+            block.next = next;
+            self.write(prev, block);
+        }
+    }
+
+    fn init(&mut self) {
+        if !self.initialized {
+            self.initialized = true;
+            if Self::LIMIT >= BLOCK {
+                self.write(
+                    0,
+                    FreeBlock {
+                        size: Self::LIMIT,
+                        next: NONE,
+                    },
+                );
+                self.head = 0;
+            }
+        }
+    }
+
+    /// Allocate `layout`, or `None` if no free block fits.
+    pub fn alloc(&mut self, layout: Layout) -> Option<NonNull<u8>> {
+        self.init();
+        let size = block_size(layout.size());
+        let align = layout.align().max(BLOCK_ALIGN);
+        let base = self.arena.0.as_ptr() as usize;
+
+        let mut prev = NONE;
+        let mut current = self.head;
+        while current != NONE {
+            let block = self.read(current);
+            let end = current + block.size;
+            let mut start = (base + current).next_multiple_of(align) - base;
+            // Leading padding must be empty or big enough to stay a free block.
+            if start != current && start - current < BLOCK {
+                start = (base + current + BLOCK).next_multiple_of(align) - base;
+            }
+            if start + size <= end {
+                let mut next = block.next;
+                let tail = end - (start + size);
+                // A tail too small for a header is orphaned until a neighbour
+                // is freed and `dealloc` coalesces across it.
+                if tail >= BLOCK {
+                    self.write(start + size, FreeBlock { size: tail, next });
+                    next = start + size;
+                }
+                if start > current {
+                    self.write(
+                        current,
+                        FreeBlock {
+                            size: start - current,
+                            next,
+                        },
+                    );
+                } else {
+                    self.link(prev, next);
+                }
+                return NonNull::new(unsafe { self.arena.0.as_mut_ptr().add(start) });
+            }
+            prev = current;
+            current = block.next;
+        }
+        None
+    }
+
+    /// Return an allocation to the free list, merging it with adjacent free space.
+    ///
+    /// # Safety
+    /// `ptr` must come from `alloc` on this heap with the same `layout`, and
+    /// must not be freed twice.
+    pub unsafe fn dealloc(&mut self, ptr: NonNull<u8>, layout: Layout) {
+        let offset = ptr.as_ptr() as usize - self.arena.0.as_ptr() as usize;
+        let mut size = block_size(layout.size());
+
+        let mut prev = NONE;
+        let mut next = self.head;
+        while next != NONE && next < offset {
+            prev = next;
+            next = self.read(next).next;
+        }
+
+        // Live allocations are at least `BLOCK` bytes, so a smaller gap to a
+        // neighbour can only be orphaned padding: absorb it.
+        if next != NONE && next - (offset + size) < BLOCK {
+            let block = self.read(next);
+            size = next + block.size - offset;
+            next = block.next;
+        } else if next == NONE && Self::LIMIT - (offset + size) < BLOCK {
+            size = Self::LIMIT - offset;
+        }
+
+        if prev != NONE {
+            let block = self.read(prev);
+            if offset - (prev + block.size) < BLOCK {
+                self.write(
+                    prev,
+                    FreeBlock {
+                        size: offset + size - prev,
+                        next,
+                    },
+                );
+                return;
+            }
+        }
+        self.write(offset, FreeBlock { size, next });
+        self.link(prev, offset);
+    }
+
+    /// Total bytes currently on the free list.
+    pub fn free_bytes(&self) -> usize {
+        if !self.initialized {
+            return Self::LIMIT;
+        }
+        let mut total = 0;
+        let mut current = self.head;
+        while current != NONE {
+            let block = self.read(current);
+            total += block.size;
+            current = block.next;
+        }
+        total
+    }
+}
+
+/// `FreeListHeap` behind a spin lock, usable as a `#[global_allocator]`.
+pub struct GlobalHeap<const N: usize> {
+    locked: AtomicBool,
+    heap: UnsafeCell<FreeListHeap<N>>,
+}
+
+// SAFETY: every access to `heap` happens inside `with`, which holds `locked`.
+unsafe impl<const N: usize> Sync for GlobalHeap<N> {}
+
+impl<const N: usize> Default for GlobalHeap<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> GlobalHeap<N> {
+    pub const fn new() -> Self {
+        Self {
+            locked: AtomicBool::new(false),
+            heap: UnsafeCell::new(FreeListHeap::new()),
+        }
+    }
+
+    fn with<R>(&self, f: impl FnOnce(&mut FreeListHeap<N>) -> R) -> R {
+        while self
+            .locked
+            .compare_exchange_weak(false, true, Ordering::Acquire, Ordering::Relaxed)
+            .is_err()
+        {
+            core::hint::spin_loop();
+        }
+        let result = f(unsafe { &mut *self.heap.get() });
+        self.locked.store(false, Ordering::Release);
+        result
+    }
+
+    /// Total bytes currently free.
+    pub fn free_bytes(&self) -> usize {
+        self.with(|heap| heap.free_bytes())
+    }
+}
+
+unsafe impl<const N: usize> GlobalAlloc for GlobalHeap<N> {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        self.with(|heap| heap.alloc(layout))
+            .map_or(ptr::null_mut(), NonNull::as_ptr)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        if let Some(ptr) = NonNull::new(ptr) {
+            self.with(|heap| unsafe { heap.dealloc(ptr, layout) });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use std::boxed::Box;
+    use std::vec::Vec;
+
+    use super::*;
+
+    fn heap() -> Box<FreeListHeap<4096>> {
+        Box::new(FreeListHeap::new())
+    }
+
+    #[test]
+    fn alloc_dealloc_cycles_do_not_exhaust_the_heap() {
+        let mut heap = heap();
+        let total = heap.free_bytes();
+        let layout = Layout::from_size_align(1000, 4).unwrap();
+        // A bump allocator would run out after four of these.
+        for _ in 0..100 {
+            let a = heap.alloc(layout).expect("alloc");
+            let b = heap.alloc(layout).expect("alloc");
+            unsafe {
+                heap.dealloc(a, layout);
+                heap.dealloc(b, layout);
+            }
+        }
+        assert_eq!(heap.free_bytes(), total);
+    }
+
+    #[test]
+    fn realloc_style_growth_reuses_freed_space() {
+        let heap = GlobalHeap::<4096>::new();
+        let total = heap.free_bytes();
+        let mut layout = Layout::from_size_align(16, 8).unwrap();
+        let mut ptr = unsafe { heap.alloc(layout) };
+        for round in 0..50 {
+            assert!(!ptr.is_null(), "round {round}");
+            unsafe { ptr.write(round as u8) };
+            let new_size = if layout.size() >= 2048 {
+                16
+            } else {
+                layout.size() * 2
+            };
+            ptr = unsafe { heap.realloc(ptr, layout, new_size) };
+            layout = Layout::from_size_align(new_size, 8).unwrap();
+            assert_eq!(unsafe { ptr.read() }, round as u8);
+        }
+        unsafe { heap.dealloc(ptr, layout) };
+        assert_eq!(heap.free_bytes(), total);
+    }
+
+    #[test]
+    fn honours_alignment_and_coalesces_mixed_sizes() {
+        let mut heap = heap();
+        let total = heap.free_bytes();
+        let layouts = [
+            Layout::from_size_align(3, 1).unwrap(),
+            Layout::from_size_align(24, 8).unwrap(),
+            Layout::from_size_align(100, 64).unwrap(),
+            Layout::from_size_align(7, 2).unwrap(),
+        ];
+        let ptrs: Vec<_> = layouts
+            .iter()
+            .map(|&layout| {
+                let ptr = heap.alloc(layout).expect("alloc");
+                assert_eq!(ptr.as_ptr() as usize % layout.align(), 0);
+                ptr
+            })
+            .collect();
+        // Free out of order so both neighbour merges are exercised.
+        for index in [1, 3, 0, 2] {
+            unsafe { heap.dealloc(ptrs[index], layouts[index]) };
+        }
+        assert_eq!(heap.free_bytes(), total);
+        assert!(
+            heap.alloc(Layout::from_size_align(total, 4).unwrap())
+                .is_some()
+        );
+    }
+
+    #[test]
+    fn oversized_requests_fail_cleanly() {
+        let mut heap = heap();
+        assert!(
+            heap.alloc(Layout::from_size_align(8192, 4).unwrap())
+                .is_none()
+        );
+        assert!(
+            heap.alloc(Layout::from_size_align(64, 4).unwrap())
+                .is_some()
+        );
+    }
+}
@@ -1,9 +1,11 @@
-#![no_std]
+#![cfg_attr(not(test), no_std)]
 
 use crate::log::Log;
 
 pub mod driving;
+pub mod heap;
 pub mod log;
+pub mod syscall;
 
 pub const SLOT1: usize = 0x100;
 pub const SLOT2: usize = 0x200;
@@ -11,11 +13,31 @@ pub const SLOT3: usize = 0x300;
 pub const SLOT4: usize = 0x400;
 pub const SLOT5: usize = 0x500;
 pub const SLOT6: usize = 0x600;
+pub const SLOT7: usize = 0x700;
+pub const SLOT8: usize = 0x800;
+pub const SLOT9: usize = 0x900;
+pub const SLOT10: usize = 0xA00;
 
 pub fn log() -> Log {
     Log::bind(SLOT1)
 }
 
+/// Fraction of the current lap completed (0 at the start line, towards 1).
+pub fn lap_fraction() -> f32 {
+    driving::LapProgress::bind(SLOT7).lap_fraction()
+}
+
+/// Which part of the track radar cone (SLOT5) has the most room.
+pub fn read_radar_best_sector() -> driving::RadarSector {
+    driving::TrackRadar::bind(SLOT5).best_sector()
+}
+
+/// Give up the rest of this tick's instruction budget; the bot resumes right
+/// after this call on the next physics tick, with fresh sensor readings.
+pub fn yield_tick() {
+    unsafe { core::ptr::write_volatile(SLOT10 as *mut u32, 1) }
+}
+
 #[cfg(all(feature = "panic-handler", target_os = "none"))]
 mod panic_support {
     use core::{fmt::Write, panic::PanicInfo};
@@ -30,36 +52,5 @@ mod panic_support {
 }
 
 #[cfg(all(feature = "global-allocator", target_os = "none"))]
-mod allocator_support {
-    use core::alloc::{GlobalAlloc, Layout};
-    use core::cell::UnsafeCell;
-
-    const HEAP_SIZE: usize = 4096;
-    static HEAP: [u8; HEAP_SIZE] = [0u8; HEAP_SIZE];
-
-    struct BumpAllocator {
-        pos: UnsafeCell<usize>,
-    }
-
-    unsafe impl Sync for BumpAllocator {}
-
-    unsafe impl GlobalAlloc for BumpAllocator {
-        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
-            let pos = unsafe { &mut *self.pos.get() };
-            let aligned = (*pos + layout.align() - 1) & !(layout.align() - 1);
-            let new_pos = aligned + layout.size();
-            if new_pos > HEAP_SIZE {
-                return core::ptr::null_mut();
-            }
-            *pos = new_pos;
-            unsafe { HEAP.as_ptr().add(aligned) as *mut u8 }
-        }
-
-        unsafe fn dealloc(&self, _ptr: *mut u8, _layout: Layout) {}
-    }
-
-    #[global_allocator]
-    static ALLOCATOR: BumpAllocator = BumpAllocator {
-        pos: UnsafeCell::new(0),
-    };
-}
+#[global_allocator]
+static ALLOCATOR: heap::GlobalHeap<{ heap::HEAP_SIZE }> = heap::GlobalHeap::new();
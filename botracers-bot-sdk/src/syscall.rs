@@ -0,0 +1,25 @@
+//! Host calls through `ecall`.
+//!
+//! Calling convention: the syscall number goes in `a7`, up to seven arguments
+//! in `a0..=a6`, and the host's result comes back in `a0`. A number the host
+//! does not serve stops the bot with an "unknown syscall" console message.
+
+/// Issue `ecall` for syscall `number` and return the host's `a0`.
+#[cfg(target_arch = "riscv32")]
+pub fn syscall(number: u32, args: [u32; 7]) -> u32 {
+    let ret;
+    unsafe {
+        core::arch::asm!(
+            "ecall",
+            inlateout("a0") args[0] => ret,
+            in("a1") args[1],
+            in("a2") args[2],
+            in("a3") args[3],
+            in("a4") args[4],
+            in("a5") args[5],
+            in("a6") args[6],
+            in("a7") number,
+        );
+    }
+    ret
+}
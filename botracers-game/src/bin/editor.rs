@@ -1,9 +1,9 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use bevy::{color::palettes::css, input::mouse::MouseWheel, prelude::*, window::PrimaryWindow};
 
 use botracers_game::track::{self, TrackSpline};
-use botracers_game::track_format::TrackFile;
+use botracers_game::track_format::{MIN_CONTROL_POINTS, TrackError, TrackFile};
 
 // ---------------------------------------------------------------------------
 // Main
@@ -49,10 +49,10 @@ struct EditorState {
     dragging: bool,
     /// Last cursor position in world coords while dragging (for deltas).
     drag_prev_world: Option<Vec2>,
-    /// Undo stack: snapshots of control_points *before* a modification.
-    undo_stack: Vec<Vec<[f32; 2]>>,
+    /// Undo stack: snapshots of the track (points and widths) *before* a modification.
+    undo_stack: Vec<TrackFile>,
     /// Redo stack: snapshots popped from undo.
-    redo_stack: Vec<Vec<[f32; 2]>>,
+    redo_stack: Vec<TrackFile>,
     /// Ruler start in world coords (Shift+LMB).
     ruler_start: Option<Vec2>,
     ruler_end: Option<Vec2>,
@@ -67,13 +67,30 @@ struct EditorState {
     show_help: bool,
     /// Dirty flag — unsaved changes.
     dirty: bool,
+    /// Issues from the last validation run (V or a save attempt), shown in the overlay.
+    validation: Option<Vec<String>>,
+    /// A save was refused because validation failed; the next Ctrl+S saves anyway.
+    save_confirm_pending: bool,
+}
+
+/// Control points sampled from each cubic segment of an imported SVG path.
+const SVG_CURVE_SAMPLES: usize = 8;
+
+/// Load a track TOML, or import the first path of an `.svg`. Imports have no
+/// file path yet, so the next save asks where to write the TOML.
+fn open_track(path: &Path) -> Result<(TrackFile, Option<PathBuf>), TrackError> {
+    if path.extension().is_some_and(|ext| ext == "svg") {
+        Ok((TrackFile::load_svg(path, SVG_CURVE_SAMPLES)?, None))
+    } else {
+        Ok((TrackFile::load(path)?, Some(path.to_path_buf())))
+    }
 }
 
 impl EditorState {
     fn new(path: Option<PathBuf>) -> Self {
         let (track_file, file_path) = if let Some(ref p) = path {
-            match TrackFile::load(p) {
-                Ok(tf) => (tf, Some(p.clone())),
+            match open_track(p) {
+                Ok(opened) => opened,
                 Err(e) => {
                     eprintln!("Warning: {e}. Starting with empty track.");
                     (TrackFile::new_empty("Untitled"), None)
@@ -99,19 +116,117 @@ impl EditorState {
             show_labels: true,
             show_help: true,
             dirty: false,
+            validation: None,
+            save_confirm_pending: false,
         }
     }
 
     fn push_undo(&mut self) {
-        self.undo_stack.push(self.track_file.control_points.clone());
+        self.undo_stack.push(self.track_file.clone());
         self.redo_stack.clear();
         self.dirty = true;
+        self.save_confirm_pending = false;
+    }
+
+    /// Replacing the track (open/new) would lose edits, so ask first.
+    fn should_prompt_before_discard(&self) -> bool {
+        self.dirty
+    }
+
+    /// Problems that would break the closed spline, as human-readable messages.
+    fn validate(&self) -> Vec<String> {
+        let mut issues = Vec::new();
+        if let Err(error) = self.track_file.validate() {
+            issues.push(error.to_string());
+        }
+
+        let pts = self.track_file.control_points_vec2();
+        let n = pts.len();
+        if n < MIN_CONTROL_POINTS {
+            return issues;
+        }
+
+        // Control polygon segment i runs from point i to i + 1, wrapping round.
+        let segment = |i: usize| (pts[i], pts[(i + 1) % n]);
+        for i in 0..n {
+            // Adjacent segments share an endpoint; skip them (and the wrap-around pair).
+            for j in (i + 2)..n {
+                if i == 0 && j == n - 1 {
+                    continue;
+                }
+                let (a, b) = segment(i);
+                let (c, d) = segment(j);
+                if segments_intersect(a, b, c, d) {
+                    issues.push(format!(
+                        "segment {i}-{} crosses segment {j}-{}",
+                        (i + 1) % n,
+                        (j + 1) % n
+                    ));
+                }
+            }
+        }
+
+        let closing_gap = pts[n - 1].distance(pts[0]);
+        let mean_gap =
+            (0..n - 1).map(|i| pts[i].distance(pts[i + 1])).sum::<f32>() / (n - 1) as f32;
+        if closing_gap > mean_gap * MAX_CLOSING_GAP_RATIO {
+            issues.push(format!(
+                "gap of {closing_gap:.1} m between the last and first point is over \
+                 {MAX_CLOSING_GAP_RATIO}x the mean spacing ({mean_gap:.1} m); is the loop closed?"
+            ));
+        }
+        issues
+    }
+
+    /// Reflect the track across the horizontal line through its centroid
+    /// (y flips). See `reflect_points` for what happens to the start direction.
+    fn mirror_x(&mut self) {
+        self.reflect_points(|offset| vec2(offset.x, -offset.y));
+    }
+
+    /// Reflect the track across the vertical line through its centroid (x flips).
+    fn mirror_y(&mut self) {
+        self.reflect_points(|offset| vec2(-offset.x, offset.y));
+    }
+
+    /// Rotate the track 90° counter-clockwise around its centroid. Point order,
+    /// and so winding and start direction, is unchanged.
+    fn rotate_90(&mut self) {
+        self.transform_points(|offset| vec2(-offset.y, offset.x));
+    }
+
+    /// A mirror image keeps the point order, so cars still start at point 0
+    /// and head for point 1, but the loop's winding flips: a clockwise track
+    /// becomes counter-clockwise and every left-hander a right-hander. Bank
+    /// angles are negated to keep each corner leaning the same way into it.
+    fn reflect_points(&mut self, reflect: impl Fn(Vec2) -> Vec2) {
+        if self.transform_points(reflect) {
+            for angle in &mut self.track_file.metadata.bank_angles {
+                *angle = -*angle;
+            }
+        }
+    }
+
+    /// Apply `transform` to every control point's offset from the centroid,
+    /// pushing an undo snapshot first. `false` (and no snapshot) for an empty track.
+    fn transform_points(&mut self, transform: impl Fn(Vec2) -> Vec2) -> bool {
+        if self.track_file.control_points.is_empty() {
+            return false;
+        }
+        self.push_undo();
+        let pts = &self.track_file.control_points;
+        let n = pts.len() as f32;
+        let centroid = pts.iter().map(|&p| Vec2::from(p)).sum::<Vec2>() / n;
+        for p in &mut self.track_file.control_points {
+            *p = (centroid + transform(Vec2::from(*p) - centroid)).into();
+        }
+        true
     }
 
     fn undo(&mut self) -> bool {
         if let Some(prev) = self.undo_stack.pop() {
-            self.redo_stack.push(self.track_file.control_points.clone());
-            self.track_file.control_points = prev;
+            self.redo_stack.push(self.track_file.clone());
+            self.track_file = prev;
             self.dirty = true;
             true
         } else {
@@ -121,8 +236,8 @@ impl EditorState {
 
     fn redo(&mut self) -> bool {
         if let Some(next) = self.redo_stack.pop() {
-            self.undo_stack.push(self.track_file.control_points.clone());
-            self.track_file.control_points = next;
+            self.undo_stack.push(self.track_file.clone());
+            self.track_file = next;
             self.dirty = true;
             true
         } else {
@@ -230,23 +345,30 @@ fn editor_setup(
     rebuild.0 = 0;
 }
 
+/// A closing gap this many times the mean point spacing suggests an open loop.
+const MAX_CLOSING_GAP_RATIO: f32 = 3.0;
+
 const HELP_TEXT: &str = "\
 Controls:
   LMB          Select / drag control point
   Right-drag   Pan camera
   Scroll       Zoom
   A            Add point at cursor
+  Ctrl+LMB     Insert point on the track under the cursor
   Del/Bksp     Delete selected point
   Ctrl+Z       Undo
   Ctrl+Y       Redo
-  Ctrl+S       Save
-  Ctrl+O       Open
-  Ctrl+N       New track
+  Ctrl+S       Save (asks again if validation fails)
+  Ctrl+O       Open a track or import an SVG path (asks before discarding changes)
+  Ctrl+N       New track (asks before discarding changes)
   Shift+drag   Ruler measurement
   [ / ]        Decrease / increase track width
   - / =        Scale track down / up
+  X / Y        Mirror track top-bottom / left-right (reverses winding)
+  R            Rotate track 90° counter-clockwise
   C            Toggle curvature heatmap
   L            Toggle point labels
+  V            Validate track
   H            Toggle this help";
 
 // ---------------------------------------------------------------------------
@@ -266,7 +388,7 @@ fn spawn_track_visuals(
         return;
     }
     let spline = track::build_spline(&pts);
-    let tw = track_file.metadata.track_width;
+    let tw = track::TrackWidth::from_file(track_file);
     let kw = track_file.metadata.kerb_width;
 
     // Ground
@@ -279,9 +401,9 @@ fn spawn_track_visuals(
 
     // Track surface
     let track_mesh = if show_curvature {
-        create_curvature_track_mesh(&spline, tw, 1000)
+        create_curvature_track_mesh(&spline, &tw, 1000)
     } else {
-        track::create_track_mesh(&spline, tw, 1000)
+        track::create_track_mesh(&spline, &tw, 1000)
     };
     commands.spawn((
         Mesh2d(meshes.add(track_mesh)),
@@ -295,7 +417,7 @@ fn spawn_track_visuals(
     ));
 
     // Kerbs
-    let (inner_kerb, outer_kerb) = track::create_kerb_meshes(&spline, tw, kw, 1000);
+    let (inner_kerb, outer_kerb) = track::create_kerb_meshes(&spline, &tw, kw, 1000);
     commands.spawn((
         Mesh2d(meshes.add(inner_kerb)),
         MeshMaterial2d(materials.add(ColorMaterial::default())),
@@ -365,7 +487,7 @@ fn spawn_point_visuals(
 
 fn create_curvature_track_mesh(
     spline: &CubicCurve<Vec2>,
-    track_width: f32,
+    track_width: &track::TrackWidth,
     segments: usize,
 ) -> Mesh {
     let domain = spline.domain();
@@ -403,8 +525,9 @@ fn create_curvature_track_mesh(
         let tangent = (p2 - p1).normalize_or_zero();
         let normal = Vec2::new(-tangent.y, tangent.x);
 
-        let inner = p1 - normal * track_width * 0.5;
-        let outer = p1 + normal * track_width * 0.5;
+        let half_width = track_width.at(t1) * 0.5;
+        let inner = p1 - normal * half_width;
+        let outer = p1 + normal * half_width;
         positions.push([inner.x, inner.y, 0.0]);
         positions.push([outer.x, outer.y, 0.0]);
 
@@ -524,6 +647,7 @@ fn handle_mouse_input(
     keyboard: Res<ButtonInput<KeyCode>>,
     windows: Query<&Window, With<PrimaryWindow>>,
     camera_q: Query<(&Camera, &GlobalTransform), With<Camera2d>>,
+    track_spline: Option<Res<TrackSpline>>,
     mut rebuild: ResMut<RebuildFlag>,
 ) {
     let Ok(window) = windows.single() else { return };
@@ -535,6 +659,29 @@ fn handle_mouse_input(
     };
 
     let shift = keyboard.pressed(KeyCode::ShiftLeft) || keyboard.pressed(KeyCode::ShiftRight);
+    let ctrl = keyboard.pressed(KeyCode::ControlLeft) || keyboard.pressed(KeyCode::ControlRight);
+
+    // --- Insert point on the track surface (Ctrl + LMB) ---
+    if ctrl {
+        let n = editor.track_file.control_points.len();
+        // The spline resource is only rebuilt for 4+ points; skip if it is stale.
+        if buttons.just_pressed(MouseButton::Left)
+            && let Some(ts) = track_spline
+            && ts.spline.segments().len() == n
+        {
+            let (insert_idx, on_spline, t) = nearest_spline_insertion(&ts.spline, n, world_pos);
+            let half_width = track::TrackWidth::from_file(&editor.track_file).at(t) * 0.5;
+            if world_pos.distance(on_spline) <= half_width {
+                editor.push_undo();
+                editor
+                    .track_file
+                    .insert_control_point(insert_idx, [on_spline.x, on_spline.y]);
+                editor.selected_point = Some(insert_idx);
+                rebuild.0 += 1;
+            }
+        }
+        return;
+    }
 
     // --- Ruler (Shift + LMB) ---
     if shift {
@@ -627,6 +774,17 @@ fn handle_keyboard(
 
     // --- Save (Ctrl+S) ---
     if ctrl && keyboard.just_pressed(KeyCode::KeyS) {
+        let issues = editor.validate();
+        if !issues.is_empty() && !editor.save_confirm_pending {
+            warn!(
+                "Track has {} validation issue(s); press Ctrl+S again to save anyway",
+                issues.len()
+            );
+            editor.validation = Some(issues);
+            editor.save_confirm_pending = true;
+            return;
+        }
+        editor.save_confirm_pending = false;
         let path = if let Some(ref p) = editor.file_path {
             Some(p.clone())
         } else {
@@ -650,18 +808,24 @@ fn handle_keyboard(
 
     // --- Open (Ctrl+O) ---
     if ctrl && keyboard.just_pressed(KeyCode::KeyO) {
+        if !confirm_discard(&editor) {
+            return;
+        }
         if let Some(path) = rfd::FileDialog::new()
             .add_filter("Track files", &["toml"])
+            .add_filter("SVG paths", &["svg"])
             .pick_file()
         {
-            match TrackFile::load(&path) {
-                Ok(tf) => {
+            match open_track(&path) {
+                Ok((tf, file_path)) => {
                     editor.track_file = tf;
-                    editor.file_path = Some(path);
+                    editor.file_path = file_path;
                     editor.selected_point = None;
                     editor.undo_stack.clear();
                     editor.redo_stack.clear();
                     editor.dirty = false;
+                    editor.validation = None;
+                    editor.save_confirm_pending = false;
                     rebuild.0 += 1;
                     info!("Opened track");
                 }
@@ -673,12 +837,17 @@ fn handle_keyboard(
 
     // --- New (Ctrl+N) ---
     if ctrl && keyboard.just_pressed(KeyCode::KeyN) {
+        if !confirm_discard(&editor) {
+            return;
+        }
         editor.track_file = TrackFile::new_empty("Untitled");
         editor.file_path = None;
         editor.selected_point = None;
         editor.undo_stack.clear();
         editor.redo_stack.clear();
         editor.dirty = false;
+        editor.validation = None;
+        editor.save_confirm_pending = false;
         rebuild.0 += 1;
         return;
     }
@@ -697,8 +866,7 @@ fn handle_keyboard(
         let insert_idx = find_insert_index(world_pos, &editor.track_file.control_points);
         editor
             .track_file
-            .control_points
-            .insert(insert_idx, [world_pos.x, world_pos.y]);
+            .insert_control_point(insert_idx, [world_pos.x, world_pos.y]);
         editor.selected_point = Some(insert_idx);
         rebuild.0 += 1;
         return;
@@ -710,7 +878,7 @@ fn handle_keyboard(
             && editor.track_file.control_points.len() > 1
         {
             editor.push_undo();
-            editor.track_file.control_points.remove(idx);
+            editor.track_file.remove_control_point(idx);
             // Adjust selection
             if idx >= editor.track_file.control_points.len() {
                 editor.selected_point = Some(editor.track_file.control_points.len() - 1);
@@ -747,6 +915,23 @@ fn handle_keyboard(
         return;
     }
 
+    // --- Mirror (X / Y) and rotate (R) the whole track ---
+    if keyboard.just_pressed(KeyCode::KeyX) {
+        editor.mirror_x();
+        rebuild.0 += 1;
+        return;
+    }
+    if keyboard.just_pressed(KeyCode::KeyY) {
+        editor.mirror_y();
+        rebuild.0 += 1;
+        return;
+    }
+    if keyboard.just_pressed(KeyCode::KeyR) {
+        editor.rotate_90();
+        rebuild.0 += 1;
+        return;
+    }
+
     // --- Toggle curvature (C) ---
     if keyboard.just_pressed(KeyCode::KeyC) {
         editor.show_curvature = !editor.show_curvature;
@@ -759,12 +944,36 @@ fn handle_keyboard(
         rebuild.0 += 1;
     }
 
+    // --- Validate (V) ---
+    if keyboard.just_pressed(KeyCode::KeyV) {
+        let issues = editor.validate();
+        for issue in &issues {
+            warn!("Validation: {issue}");
+        }
+        editor.validation = Some(issues);
+        return;
+    }
+
     // --- Toggle help (H) ---
     if keyboard.just_pressed(KeyCode::KeyH) {
         editor.show_help = !editor.show_help;
     }
 }
 
+/// Ask before throwing away unsaved changes; `true` means go ahead.
+fn confirm_discard(editor: &EditorState) -> bool {
+    if !editor.should_prompt_before_discard() {
+        return true;
+    }
+    rfd::MessageDialog::new()
+        .set_level(rfd::MessageLevel::Warning)
+        .set_title("Unsaved changes")
+        .set_description("The current track has unsaved changes. Discard them?")
+        .set_buttons(rfd::MessageButtons::YesNo)
+        .show()
+        == rfd::MessageDialogResult::Yes
+}
+
 /// Scale all control points around their centroid by the given factor.
 fn scale_track(editor: &mut ResMut<EditorState>, factor: f32) {
     if editor.track_file.control_points.is_empty() {
@@ -807,6 +1016,44 @@ fn find_insert_index(click: Vec2, points: &[[f32; 2]]) -> usize {
     best_idx
 }
 
+/// Nearest point on a closed track spline to `click`, as `(insert index, position, t)`.
+///
+/// Spline segment `k` of a cyclic B-spline over `point_count` control points runs
+/// between control points `k + 1` and `k + 2`, so the new point goes before
+/// `k + 2`. Between the last and first point it is appended at the end.
+fn nearest_spline_insertion(
+    spline: &CubicCurve<Vec2>,
+    point_count: usize,
+    click: Vec2,
+) -> (usize, Vec2, f32) {
+    let t_max = spline.domain().end();
+    let samples = point_count * 64;
+    let (t, position) = (0..samples)
+        .map(|i| {
+            let t = i as f32 / samples as f32 * t_max;
+            (t, spline.position(t))
+        })
+        .min_by(|(_, a), (_, b)| {
+            a.distance_squared(click)
+                .total_cmp(&b.distance_squared(click))
+        })
+        .expect("spline has samples");
+    let segment = (t.floor() as usize).min(point_count - 1);
+    let index = match (segment + 2) % point_count {
+        0 => point_count,
+        index => index,
+    };
+    (index, position, t)
+}
+
+/// Whether segments `ab` and `cd` cross at a single interior point.
+fn segments_intersect(a: Vec2, b: Vec2, c: Vec2, d: Vec2) -> bool {
+    let side = |p: Vec2, q: Vec2, r: Vec2| (q - p).perp_dot(r - p);
+    let (d1, d2) = (side(c, d, a), side(c, d, b));
+    let (d3, d4) = (side(a, b, c), side(a, b, d));
+    d1 * d2 < 0.0 && d3 * d4 < 0.0
+}
+
 fn point_to_segment_dist(p: Vec2, a: Vec2, b: Vec2) -> f32 {
     let ab = b - a;
     let ap = p - a;
@@ -895,9 +1142,25 @@ fn update_ui_text(
             ""
         };
 
+        let validation_str = match &editor.validation {
+            None => String::new(),
+            Some(issues) if issues.is_empty() => "\nValidation: OK".to_string(),
+            Some(issues) => {
+                let mut lines = format!("\nValidation: {} issue(s)", issues.len());
+                for issue in issues {
+                    lines.push_str(&format!("\n  - {issue}"));
+                }
+                if editor.save_confirm_pending {
+                    lines.push_str("\nPress Ctrl+S again to save anyway");
+                }
+                lines
+            }
+        };
+
         **text = format!(
             "{name}{dirty_marker}  |  {file_str}\n\
-             Points: {n_pts}  |  Width: {tw:.1}  |  Length: {length_str}{selected_str}{curvature_str}"
+             Points: {n_pts}  |  Width: {tw:.1}  |  Length: {length_str}{selected_str}{curvature_str}\
+             {validation_str}"
         );
     }
 
@@ -981,3 +1244,136 @@ fn draw_editor_gizmos(
         );
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn editor_with_points(points: &[[f32; 2]]) -> EditorState {
+        let mut editor = EditorState::new(None);
+        editor.track_file.control_points = points.to_vec();
+        editor
+    }
+
+    #[test]
+    fn spline_insertion_finds_enclosing_points() {
+        let points = [
+            vec2(0.0, 0.0),
+            vec2(50.0, 0.0),
+            vec2(100.0, 0.0),
+            vec2(110.0, 20.0),
+            vec2(100.0, 40.0),
+            vec2(50.0, 40.0),
+            vec2(0.0, 40.0),
+            vec2(-10.0, 20.0),
+        ];
+        let spline = track::build_spline(&points);
+
+        // On the bottom straight between points 1 and 2.
+        let (index, position, _) = nearest_spline_insertion(&spline, points.len(), vec2(75.0, 1.0));
+        assert_eq!(index, 2);
+        assert!(position.distance(vec2(75.0, 1.0)) < 2.0, "{position}");
+
+        // Between the last and the first point: appended after the last.
+        let (index, _, _) = nearest_spline_insertion(&spline, points.len(), vec2(-5.0, 8.0));
+        assert_eq!(index, points.len());
+    }
+
+    #[test]
+    fn discarding_prompts_only_with_unsaved_changes() {
+        let mut editor = editor_with_points(&[[0.0, 0.0], [10.0, 0.0], [10.0, 10.0]]);
+        assert!(!editor.should_prompt_before_discard());
+
+        editor.push_undo();
+        assert!(editor.should_prompt_before_discard());
+
+        // A successful save clears the flag again.
+        editor.dirty = false;
+        assert!(!editor.should_prompt_before_discard());
+    }
+
+    #[test]
+    fn mirroring_flips_one_axis_around_the_centroid() {
+        // Centroid (2, 1).
+        let points = [[0.0, 0.0], [4.0, 0.0], [4.0, 2.0], [0.0, 3.0], [2.0, 0.0]];
+        let mut editor = editor_with_points(&points);
+        editor.track_file.metadata.bank_angles = vec![0.1, 0.0, -0.2, 0.0, 0.3];
+
+        editor.mirror_x();
+        assert_eq!(
+            editor.track_file.control_points,
+            vec![[0.0, 2.0], [4.0, 2.0], [4.0, 0.0], [0.0, -1.0], [2.0, 2.0]]
+        );
+        assert_eq!(
+            editor.track_file.metadata.bank_angles,
+            vec![-0.1, 0.0, 0.2, 0.0, -0.3]
+        );
+
+        editor.track_file.control_points = points.to_vec();
+        editor.mirror_y();
+        assert_eq!(
+            editor.track_file.control_points,
+            vec![[4.0, 0.0], [0.0, 0.0], [0.0, 2.0], [4.0, 3.0], [2.0, 0.0]]
+        );
+        assert_eq!(editor.undo_stack.len(), 2);
+    }
+
+    #[test]
+    fn rotation_turns_the_track_counter_clockwise_and_undoes() {
+        let points = [[0.0, 0.0], [4.0, 0.0], [4.0, 2.0], [0.0, 2.0]];
+        let mut editor = editor_with_points(&points);
+
+        editor.rotate_90();
+        assert_eq!(
+            editor.track_file.control_points,
+            vec![[3.0, -1.0], [3.0, 3.0], [1.0, 3.0], [1.0, -1.0]]
+        );
+
+        assert!(editor.undo());
+        assert_eq!(editor.track_file.control_points, points.to_vec());
+    }
+
+    #[test]
+    fn transforming_an_empty_track_records_no_undo() {
+        let mut editor = editor_with_points(&[]);
+        editor.mirror_x();
+        editor.rotate_90();
+        assert!(editor.undo_stack.is_empty());
+        assert!(!editor.dirty);
+    }
+
+    #[test]
+    fn square_loop_validates_cleanly() {
+        let editor = editor_with_points(&[[0.0, 0.0], [10.0, 0.0], [10.0, 10.0], [0.0, 10.0]]);
+        assert_eq!(editor.validate(), Vec::<String>::new());
+    }
+
+    #[test]
+    fn too_few_points_are_flagged() {
+        let editor = editor_with_points(&[[0.0, 0.0], [10.0, 0.0]]);
+        let issues = editor.validate();
+        assert_eq!(issues.len(), 1);
+        assert!(issues[0].contains("at least 3"), "{issues:?}");
+    }
+
+    #[test]
+    fn self_intersecting_loop_is_flagged() {
+        // A bow tie: segments 0-1 and 2-3 cross in the middle.
+        let editor = editor_with_points(&[[0.0, 0.0], [10.0, 10.0], [10.0, 0.0], [0.0, 10.0]]);
+        assert_eq!(editor.validate(), vec!["segment 0-1 crosses segment 2-3"]);
+    }
+
+    #[test]
+    fn large_closing_gap_is_flagged() {
+        let editor = editor_with_points(&[
+            [0.0, 0.0],
+            [5.0, 1.0],
+            [10.0, 0.0],
+            [15.0, 1.0],
+            [60.0, -5.0],
+        ]);
+        let issues = editor.validate();
+        assert_eq!(issues.len(), 1);
+        assert!(issues[0].contains("is the loop closed"), "{issues:?}");
+    }
+}
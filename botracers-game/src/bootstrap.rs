@@ -8,16 +8,23 @@ use std::{
 use base64::Engine;
 use bevy::prelude::*;
 use botracers_protocol::{
-    ArtifactSummary, ServerCapabilities, UpdateArtifactVisibilityRequest, UploadArtifactRequest,
-    UploadArtifactResponse,
+    API_VERSION, ArtifactSummary, DEFAULT_ARTIFACT_TARGET, PublishRaceRecordRequest,
+    PublishRaceRecordResponse, RaceRecordSummary, RaceReplay, ServerCapabilities,
+    UpdateArtifactVisibilityRequest, UploadArtifactRequest, UploadArtifactResponse,
 };
 #[cfg(not(target_arch = "wasm32"))]
 use botracers_protocol::{LoginRequest, LoginResponse};
 #[cfg(not(target_arch = "wasm32"))]
 use botracers_server::{AuthMode, ServerConfig};
+use serde::Serialize;
 
-use crate::game_api::{DriverType, SpawnCarRequest, SpawnResolvedCarRequest, WebApiCommand};
-use crate::race_runtime::SimState;
+use crate::game_api::{
+    DriverType, GhostReplayRequest, SpawnCarRequest, SpawnResolvedCarRequest,
+    SpectateReplayRequest, WebApiCommand,
+};
+use crate::race_runtime::{
+    ActiveTrack, CarEntry, RaceLaps, RaceManager, RaceSeed, RaceStateHash, SimState,
+};
 
 pub struct BootstrapPlugin;
 
@@ -88,6 +95,13 @@ enum WebApiEvent {
         is_public: bool,
         result: Result<(), String>,
     },
+    RaceRecords(Result<Vec<RaceRecordSummary>, String>),
+    Replay {
+        record_id: i64,
+        as_ghost: bool,
+        result: Result<RaceReplay, String>,
+    },
+    PublishResult(Result<PublishRaceRecordResponse, String>),
 }
 
 #[derive(Resource, Clone)]
@@ -108,11 +122,14 @@ pub struct WebPortalState {
     pub server_url: String,
     pub standalone_mode: bool,
     pub auth_required: Option<bool>,
+    /// Last capabilities the server reported (API version, upload limit, features).
+    pub capabilities: Option<ServerCapabilities>,
     #[cfg(not(target_arch = "wasm32"))]
     pub token: Option<String>,
     #[cfg(not(target_arch = "wasm32"))]
     pub cli_credentials: Option<(String, String)>,
     pub artifacts: Vec<ArtifactSummary>,
+    pub race_records: Vec<RaceRecordSummary>,
     pub status_message: Option<String>,
 }
 
@@ -132,11 +149,13 @@ impl Default for WebPortalState {
             },
             standalone_mode: false,
             auth_required: None,
+            capabilities: None,
             #[cfg(not(target_arch = "wasm32"))]
             token: None,
             #[cfg(not(target_arch = "wasm32"))]
             cli_credentials: None,
             artifacts: Vec::new(),
+            race_records: Vec::new(),
             status_message: None,
         }
     }
@@ -319,7 +338,7 @@ fn web_fetch_capabilities(server_url: &str, queue: Arc<Mutex<Vec<WebApiEvent>>>)
 }
 
 fn web_fetch_artifacts(server_url: &str, token: Option<&str>, queue: Arc<Mutex<Vec<WebApiEvent>>>) {
-    let url = web_api_url(server_url, "/api/v1/artifacts");
+    let url = web_api_url(server_url, "/api/v1/artifacts?limit=200");
     let request = web_request_with_auth(url, token);
     ehttp::fetch(request, move |result| {
         let event = match result {
@@ -348,8 +367,10 @@ fn web_upload_artifact(
         &UploadArtifactRequest {
             name,
             note,
-            target: "riscv32imafc-unknown-none-elf".to_string(),
+            target: DEFAULT_ARTIFACT_TARGET.to_string(),
             elf_base64: base64::engine::general_purpose::STANDARD.encode(elf),
+            // A prebuilt ELF picked from disk; nothing is known about its build.
+            build_meta_json: None,
         },
     ) {
         Ok(req) => req,
@@ -484,6 +505,176 @@ fn web_set_artifact_visibility(
     });
 }
 
+fn web_fetch_race_records(
+    server_url: &str,
+    token: Option<&str>,
+    queue: Arc<Mutex<Vec<WebApiEvent>>>,
+) {
+    let url = web_api_url(server_url, "/api/v1/race-records");
+    let request = web_request_with_auth(url, token);
+    ehttp::fetch(request, move |result| {
+        let event = match result {
+            Ok(resp) if resp.ok => WebApiEvent::RaceRecords(
+                resp.json::<Vec<RaceRecordSummary>>()
+                    .map_err(|err| format!("invalid race records response: {err}")),
+            ),
+            Ok(resp) => WebApiEvent::RaceRecords(Err(response_error(&resp))),
+            Err(err) => WebApiEvent::RaceRecords(Err(format!("network error: {err}"))),
+        };
+        push_web_event(&queue, event);
+    });
+}
+
+fn web_fetch_replay(
+    server_url: &str,
+    token: Option<&str>,
+    record_id: i64,
+    as_ghost: bool,
+    queue: Arc<Mutex<Vec<WebApiEvent>>>,
+) {
+    let url = web_api_url(
+        server_url,
+        &format!("/api/v1/race-records/{record_id}/replay"),
+    );
+    let request = web_request_with_auth(url, token);
+    ehttp::fetch(request, move |result| {
+        let result = match result {
+            Ok(resp) if resp.ok => resp
+                .json::<RaceReplay>()
+                .map_err(|err| format!("invalid replay response: {err}")),
+            Ok(resp) => Err(response_error(&resp)),
+            Err(err) => Err(format!("network error: {err}")),
+        };
+        push_web_event(
+            &queue,
+            WebApiEvent::Replay {
+                record_id,
+                as_ghost,
+                result,
+            },
+        );
+    });
+}
+
+fn web_publish_race_result(
+    server_url: &str,
+    _token: Option<&str>,
+    payload: PublishRaceRecordRequest,
+    queue: Arc<Mutex<Vec<WebApiEvent>>>,
+) {
+    let url = web_api_url(server_url, "/api/v1/race-records");
+    let mut request = match ehttp::Request::json(url, &payload) {
+        Ok(req) => req,
+        Err(err) => {
+            push_web_event(
+                &queue,
+                WebApiEvent::PublishResult(Err(format!(
+                    "failed to serialize race record payload: {err}"
+                ))),
+            );
+            return;
+        }
+    };
+    request.method = "POST".to_string();
+    #[cfg(not(target_arch = "wasm32"))]
+    let token = _token;
+    #[cfg(target_arch = "wasm32")]
+    let token: Option<&str> = None;
+    if let Some(token) = token {
+        request
+            .headers
+            .insert("Authorization", format!("Bearer {token}"));
+    }
+
+    ehttp::fetch(request, move |result| {
+        let event = match result {
+            Ok(resp) if resp.ok => WebApiEvent::PublishResult(
+                resp.json::<PublishRaceRecordResponse>()
+                    .map_err(|err| format!("invalid race record response: {err}")),
+            ),
+            Ok(resp) => WebApiEvent::PublishResult(Err(response_error(&resp))),
+            Err(err) => WebApiEvent::PublishResult(Err(format!("network error: {err}"))),
+        };
+        push_web_event(&queue, event);
+    });
+}
+
+/// `result_json` of a published race.
+#[derive(Serialize)]
+struct PublishedRaceResult {
+    /// The winner's race time; the server ranks leaderboards by it.
+    time_ms: u64,
+    laps: u32,
+    seed: u64,
+    /// Hex `RaceStateHash`; equal hashes mean the runs raced identically.
+    state_hash: String,
+    /// Finishing order.
+    cars: Vec<PublishedCarResult>,
+}
+
+#[derive(Serialize)]
+struct PublishedCarResult {
+    position: u32,
+    name: String,
+    driver: String,
+    laps: u32,
+    finish_secs: Option<f32>,
+    best_lap_secs: Option<f32>,
+    last_lap_secs: Option<f32>,
+    dnf: bool,
+}
+
+/// The race record for a finished race: finishers by finish time, then
+/// retired cars by laps completed, grid order breaking ties.
+fn race_record_request(
+    manager: &RaceManager,
+    laps: RaceLaps,
+    track_id: String,
+    seed: RaceSeed,
+    state_hash: &RaceStateHash,
+) -> Result<PublishRaceRecordRequest, String> {
+    if !manager.race_finished() {
+        return Err("[publish] The race has not finished yet".to_string());
+    }
+    let mut order: Vec<&CarEntry> = manager.cars.iter().collect();
+    order.sort_by(|a, b| match (a.finish_secs, b.finish_secs) {
+        (Some(a), Some(b)) => a.total_cmp(&b),
+        (Some(_), None) => std::cmp::Ordering::Less,
+        (None, Some(_)) => std::cmp::Ordering::Greater,
+        (None, None) => b.laps_completed.cmp(&a.laps_completed),
+    });
+    let Some(winner_secs) = order.first().and_then(|car| car.finish_secs) else {
+        return Err("[publish] No car finished the race".to_string());
+    };
+
+    let result = PublishedRaceResult {
+        time_ms: (winner_secs * 1000.0).round() as u64,
+        laps: laps.0,
+        seed: seed.0,
+        state_hash: state_hash.0.to_hex(),
+        cars: order
+            .into_iter()
+            .zip(1..)
+            .map(|(car, position)| PublishedCarResult {
+                position,
+                name: car.name.clone(),
+                driver: car.driver.label(),
+                laps: car.laps_completed,
+                finish_secs: car.finish_secs,
+                best_lap_secs: car.best_lap_secs,
+                last_lap_secs: car.last_lap_secs,
+                dnf: car.dnf,
+            })
+            .collect(),
+    };
+    let result_json = serde_json::to_string(&result)
+        .map_err(|err| format!("[publish] Failed to serialize race result: {err}"))?;
+    Ok(PublishRaceRecordRequest {
+        track_id,
+        result_json,
+    })
+}
+
 fn web_fetch_artifact_elf(
     server_url: &str,
     token: Option<&str>,
@@ -571,6 +762,11 @@ fn handle_web_api_commands(
     mut commands: MessageReader<WebApiCommand>,
     mut web_state: ResMut<WebPortalState>,
     web_queue: Res<WebApiQueue>,
+    manager: Res<RaceManager>,
+    laps: Res<RaceLaps>,
+    active_track: Res<ActiveTrack>,
+    seed: Res<RaceSeed>,
+    state_hash: Res<RaceStateHash>,
 ) {
     for command in commands.read() {
         match command {
@@ -691,11 +887,101 @@ fn handle_web_api_commands(
                     web_queue.events.clone(),
                 );
             }
+            WebApiCommand::LoadRaceRecords => {
+                if web_state.auth_required.is_none() {
+                    web_state.status_message =
+                        Some("[capabilities] Checking server capabilities first...".to_string());
+                    web_fetch_capabilities(&web_state.server_url, web_queue.events.clone());
+                    continue;
+                }
+                let token = match maybe_auth_token(&web_state) {
+                    Ok(token) => token,
+                    Err(error) => {
+                        web_state.status_message = Some(error);
+                        continue;
+                    }
+                };
+                web_fetch_race_records(
+                    &web_state.server_url,
+                    token.as_deref(),
+                    web_queue.events.clone(),
+                );
+            }
+            WebApiCommand::LoadReplay {
+                record_id,
+                as_ghost,
+            } => {
+                if web_state.auth_required.is_none() {
+                    web_state.status_message =
+                        Some("[capabilities] Checking server capabilities first...".to_string());
+                    web_fetch_capabilities(&web_state.server_url, web_queue.events.clone());
+                    continue;
+                }
+                let token = match maybe_auth_token(&web_state) {
+                    Ok(token) => token,
+                    Err(error) => {
+                        web_state.status_message = Some(error);
+                        continue;
+                    }
+                };
+                web_state.status_message =
+                    Some(format!("[replay] Loading replay of race #{record_id}..."));
+                web_fetch_replay(
+                    &web_state.server_url,
+                    token.as_deref(),
+                    *record_id,
+                    *as_ghost,
+                    web_queue.events.clone(),
+                );
+            }
+            WebApiCommand::PublishResult => {
+                if web_state.auth_required.is_none() {
+                    web_state.status_message =
+                        Some("[capabilities] Checking server capabilities first...".to_string());
+                    web_fetch_capabilities(&web_state.server_url, web_queue.events.clone());
+                    continue;
+                }
+                let token = match maybe_auth_token(&web_state) {
+                    Ok(token) => token,
+                    Err(error) => {
+                        web_state.status_message = Some(error);
+                        continue;
+                    }
+                };
+                let payload = match race_record_request(
+                    &manager,
+                    *laps,
+                    active_track.track_id(),
+                    *seed,
+                    &state_hash,
+                ) {
+                    Ok(payload) => payload,
+                    Err(error) => {
+                        web_state.status_message = Some(error);
+                        continue;
+                    }
+                };
+                web_state.status_message = Some(format!(
+                    "[publish] Publishing result on '{}'...",
+                    payload.track_id
+                ));
+                web_publish_race_result(
+                    &web_state.server_url,
+                    token.as_deref(),
+                    payload,
+                    web_queue.events.clone(),
+                );
+            }
         }
     }
 }
 
-fn process_web_api_events(mut web_state: ResMut<WebPortalState>, web_queue: Res<WebApiQueue>) {
+fn process_web_api_events(
+    mut web_state: ResMut<WebPortalState>,
+    web_queue: Res<WebApiQueue>,
+    mut spectate_events: MessageWriter<SpectateReplayRequest>,
+    mut ghost_events: MessageWriter<GhostReplayRequest>,
+) {
     let mut events = Vec::new();
     if let Ok(mut queue) = web_queue.events.lock() {
         events.append(&mut *queue);
@@ -707,9 +993,21 @@ fn process_web_api_events(mut web_state: ResMut<WebPortalState>, web_queue: Res<
                 Ok(caps) => {
                     web_state.auth_required = Some(caps.auth_required);
                     web_state.status_message = Some(format!(
-                        "[capabilities] Connected: mode={}, auth_required={}, registration_enabled={}",
-                        caps.mode, caps.auth_required, caps.registration_enabled
+                        "[capabilities] Connected: api={}, mode={}, auth_required={}, registration_enabled={}, max_upload={} bytes, features=[{}]",
+                        caps.api_version,
+                        caps.mode,
+                        caps.auth_required,
+                        caps.registration_enabled,
+                        caps.max_artifact_bytes,
+                        caps.features.join(", ")
                     ));
+                    if !caps.api_version.is_empty() && caps.api_version != API_VERSION {
+                        warn!(
+                            "server API {} differs from the client's {API_VERSION}",
+                            caps.api_version
+                        );
+                    }
+                    web_state.capabilities = Some(caps.clone());
                     #[cfg(not(target_arch = "wasm32"))]
                     if caps.auth_required && web_state.token.is_none() {
                         if let Some((username, password)) = web_state.cli_credentials.clone() {
@@ -761,6 +1059,13 @@ fn process_web_api_events(mut web_state: ResMut<WebPortalState>, web_queue: Res<
                         "[load] Loaded {} artifacts",
                         web_state.artifacts.len()
                     ));
+                    if let Ok(token) = maybe_auth_token(&web_state) {
+                        web_fetch_race_records(
+                            &web_state.server_url,
+                            token.as_deref(),
+                            web_queue.events.clone(),
+                        );
+                    }
                 }
                 Err(error) => {
                     web_state.status_message =
@@ -831,12 +1136,67 @@ fn process_web_api_events(mut web_state: ResMut<WebPortalState>, web_queue: Res<
                     ));
                 }
             },
+            WebApiEvent::RaceRecords(result) => match result {
+                Ok(records) => {
+                    web_state.race_records = records;
+                }
+                Err(error) => {
+                    web_state.status_message = Some(format!(
+                        "[error][replay] Loading race records failed: {error}"
+                    ));
+                }
+            },
+            WebApiEvent::Replay {
+                record_id,
+                as_ghost,
+                result,
+            } => match result {
+                Ok(replay) => {
+                    web_state.status_message = Some(format!(
+                        "[replay] Loaded replay of race #{record_id} ({} frames)",
+                        replay.frames.len()
+                    ));
+                    if as_ghost {
+                        ghost_events.write(GhostReplayRequest {
+                            replay,
+                            car_index: 0,
+                        });
+                    } else {
+                        spectate_events.write(SpectateReplayRequest { record_id, replay });
+                    }
+                }
+                Err(error) => {
+                    web_state.status_message = Some(format!(
+                        "[error][replay] Failed to load replay of race #{record_id}: {error}"
+                    ));
+                }
+            },
+            WebApiEvent::PublishResult(result) => match result {
+                Ok(published) => {
+                    web_state.status_message = Some(format!(
+                        "[publish] Published race record #{}",
+                        published.record_id
+                    ));
+                    if let Ok(token) = maybe_auth_token(&web_state) {
+                        web_fetch_race_records(
+                            &web_state.server_url,
+                            token.as_deref(),
+                            web_queue.events.clone(),
+                        );
+                    }
+                }
+                Err(error) => {
+                    web_state.status_message =
+                        Some(format!("[error][publish] Publishing failed: {error}"));
+                }
+            },
         }
     }
 }
 
 fn handle_spawn_car_request(
     mut events: MessageReader<SpawnCarRequest>,
+    mut resolved_events: MessageWriter<SpawnResolvedCarRequest>,
     mut fetch_pipeline: ResMut<ArtifactFetchPipeline>,
     mut web_state: ResMut<WebPortalState>,
     state: Res<State<SimState>>,
@@ -871,6 +1231,20 @@ fn handle_spawn_car_request(
                     fetch_pipeline.async_results.clone(),
                 );
             }
+            DriverType::Manual => {
+                fetch_pipeline.pending.remove(&request_id);
+                resolved_events.write(SpawnResolvedCarRequest {
+                    driver: DriverType::Manual,
+                    elf_bytes: Vec::new(),
+                    binary_name: "manual".to_string(),
+                });
+            }
+            DriverType::LocalElf { path } => {
+                fetch_pipeline.pending.remove(&request_id);
+                web_state.status_message = Some(format!(
+                    "[error] Local ELF '{path}' can only be raced in headless mode"
+                ));
+            }
         }
     }
 }
@@ -917,3 +1291,79 @@ fn process_artifact_fetch_results(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::Entity;
+
+    use super::race_record_request;
+    use crate::game_api::DriverType;
+    use crate::race_runtime::{CarEntry, RaceLaps, RaceManager, RaceSeed, RaceStateHash};
+
+    fn car(id: u32, laps: u32, finish_secs: Option<f32>, dnf: bool) -> CarEntry {
+        CarEntry {
+            entity: Entity::PLACEHOLDER,
+            id,
+            name: format!("Car {id}"),
+            driver: DriverType::Manual,
+            console_output: String::new(),
+            laps_completed: laps,
+            last_lap_secs: Some(20.0),
+            best_lap_secs: Some(19.5),
+            dnf,
+            finish_secs,
+        }
+    }
+
+    #[test]
+    fn finished_race_serializes_finishing_order_and_winning_time() {
+        let mut manager = RaceManager::default();
+        manager.cars.push(car(1, 1, None, true));
+        manager.cars.push(car(2, 3, Some(61.25), false));
+        manager.cars.push(car(3, 3, Some(60.5), false));
+
+        let mut state_hash = RaceStateHash::default();
+        state_hash
+            .0
+            .push_car(0, bevy::math::Vec2::new(1.0, 2.0), 0.5);
+        state_hash.0.finish_tick();
+        let request = race_record_request(
+            &manager,
+            RaceLaps(3),
+            "track1".to_string(),
+            RaceSeed(42),
+            &state_hash,
+        )
+        .expect("finished");
+        assert_eq!(request.track_id, "track1");
+
+        let result: serde_json::Value =
+            serde_json::from_str(&request.result_json).expect("result json");
+        assert_eq!(result["time_ms"], 60500);
+        assert_eq!(result["laps"], 3);
+        assert_eq!(result["seed"], 42);
+        assert_eq!(result["state_hash"], state_hash.0.to_hex().as_str());
+        let names: Vec<&str> = result["cars"]
+            .as_array()
+            .expect("cars")
+            .iter()
+            .map(|car| car["name"].as_str().expect("name"))
+            .collect();
+        assert_eq!(names, ["Car 3", "Car 2", "Car 1"]);
+        assert_eq!(result["cars"][0]["position"], 1);
+        assert_eq!(result["cars"][0]["best_lap_secs"], 19.5);
+        assert_eq!(result["cars"][2]["dnf"], true);
+
+        manager.cars.push(car(4, 2, None, false));
+        assert!(
+            race_record_request(
+                &manager,
+                RaceLaps(3),
+                "track1".to_string(),
+                RaceSeed(42),
+                &state_hash,
+            )
+            .is_err()
+        );
+    }
+}
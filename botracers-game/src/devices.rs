@@ -1,18 +1,27 @@
 mod car_controls;
 mod car_radar;
 mod car_state;
+mod lap_progress;
+mod speedometer;
 mod spline_query;
+mod track_ahead;
 mod track_radar;
 
 pub use car_controls::CarControlsDevice;
 pub use car_radar::CarRadarDevice;
 pub use car_state::CarStateDevice;
+pub use lap_progress::LapProgressDevice;
+pub use speedometer::SpeedometerDevice;
 pub use spline_query::SplineDevice;
-pub use track_radar::TrackRadarDevice;
+pub use track_ahead::TrackAheadDevice;
+pub use track_radar::{TRACK_RADAR_DEFAULT_RAYS, TRACK_RADAR_MAX_RAYS, TrackRadarDevice};
 
 pub use car_controls::update_system as car_controls_system;
 pub use car_radar::update_system as car_radar_system;
 pub use car_state::system as car_state_system;
+pub use lap_progress::update_system as lap_progress_system;
+pub use speedometer::update_system as speedometer_system;
+pub use track_ahead::update_system as track_ahead_system;
 pub use track_radar::update_system as track_radar_system;
 
 pub use track_radar::TrackRadarBorders;
@@ -4,20 +4,22 @@ use emulator::cpu::Device;
 
 /// Memory-mapped device that provides car state to the RISC-V bot.
 ///
-/// Layout (all f32, little-endian):
-///   0x00: speed
-///   0x04: position_x
-///   0x08: position_y
-///   0x0C: forward_x
-///   0x10: forward_y
+/// Layout (little-endian):
+///   0x00: speed (f32)
+///   0x04: position_x (f32)
+///   0x08: position_y (f32)
+///   0x0C: forward_x (f32)
+///   0x10: forward_y (f32)
+///   0x14: race_started (u32, 0 during the countdown, 1 once racing)
+///   0x18: tick_us (u32, length of one simulation tick in microseconds)
 #[derive(Component)]
 pub struct CarStateDevice {
-    data: [u8; 20], // 5 × f32
+    data: [u8; 28], // 5 × f32 + 2 × u32
 }
 
 impl Default for CarStateDevice {
     fn default() -> Self {
-        Self { data: [0u8; 20] }
+        Self { data: [0u8; 28] }
     }
 }
 
@@ -35,6 +37,16 @@ impl CarStateDevice {
         self.write_f32(0x0C, forward.x);
         self.write_f32(0x10, forward.y);
     }
+
+    /// Write the race-started flag read by bots waiting out the countdown.
+    pub fn set_race_started(&mut self, started: bool) {
+        self.data[0x14..0x18].copy_from_slice(&(started as u32).to_le_bytes());
+    }
+
+    /// Write the simulation tick length bots scale their control math by.
+    pub fn set_tick_micros(&mut self, micros: u32) {
+        self.data[0x18..0x1C].copy_from_slice(&micros.to_le_bytes());
+    }
 }
 
 impl Device for CarStateDevice {
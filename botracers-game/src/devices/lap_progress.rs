@@ -0,0 +1,82 @@
+use bevy::prelude::*;
+use emulator::cpu::Device;
+
+use crate::track::{TrackProgress, TrackProgressTable};
+
+/// Memory-mapped device exposing the car's position along the lap.
+///
+/// Layout (all f32, little-endian):
+///   0x00: lap_fraction (0..1, resets to 0 at the start line)
+///   0x04: track_length (centre-line length of one lap)
+///   0x08: lap_distance (distance along the centre line since the start line)
+#[derive(Component)]
+pub struct LapProgressDevice {
+    data: [u8; 12], // 3 × f32
+}
+
+impl Default for LapProgressDevice {
+    fn default() -> Self {
+        Self { data: [0u8; 12] }
+    }
+}
+
+impl LapProgressDevice {
+    fn write_f32(&mut self, offset: usize, value: f32) {
+        let bytes = value.to_le_bytes();
+        self.data[offset..offset + 4].copy_from_slice(&bytes);
+    }
+
+    pub fn update(&mut self, lap_fraction: f32, track_length: f32, lap_distance: f32) {
+        self.write_f32(0x00, lap_fraction);
+        self.write_f32(0x04, track_length);
+        self.write_f32(0x08, lap_distance);
+    }
+}
+
+impl Device for LapProgressDevice {
+    fn load(&self, addr: u32, size: u32) -> Result<u32, ()> {
+        let addr = addr as usize;
+        match size {
+            8 => {
+                if addr < self.data.len() {
+                    Ok(self.data[addr] as u32)
+                } else {
+                    Ok(0)
+                }
+            }
+            16 => {
+                if addr + 1 < self.data.len() {
+                    Ok((self.data[addr] as u32) | ((self.data[addr + 1] as u32) << 8))
+                } else {
+                    Ok(0)
+                }
+            }
+            32 => {
+                if addr + 3 < self.data.len() {
+                    Ok((self.data[addr] as u32)
+                        | ((self.data[addr + 1] as u32) << 8)
+                        | ((self.data[addr + 2] as u32) << 16)
+                        | ((self.data[addr + 3] as u32) << 24))
+                } else {
+                    Ok(0)
+                }
+            }
+            _ => Err(()),
+        }
+    }
+
+    fn store(&mut self, _addr: u32, _size: u32, _value: u32) -> Result<(), ()> {
+        // Lap progress is read-only from the bot's perspective; silently ignore writes.
+        Ok(())
+    }
+}
+
+/// Runs BEFORE cpu_system::<RacingCpuConfig>, after `track_progress_system`.
+pub fn update_system(
+    table: Res<TrackProgressTable>,
+    mut query: Query<(&TrackProgress, &mut LapProgressDevice)>,
+) {
+    for (progress, mut device) in &mut query {
+        device.update(progress.lap_fraction, table.length(), progress.distance);
+    }
+}
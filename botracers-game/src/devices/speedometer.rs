@@ -0,0 +1,89 @@
+use avian2d::prelude::*;
+use bevy::prelude::*;
+use emulator::cpu::Device;
+
+/// Fixed-point scale of the speed readout: units are thousandths of a world
+/// unit per second.
+pub const SPEED_SCALE: f32 = 1000.0;
+
+/// Memory-mapped device exposing the car's speed as a fixed-point integer, for
+/// bots that would rather not touch floats.
+///
+/// Layout (little-endian):
+///   0x00: speed (u32, |linear velocity| × `SPEED_SCALE`, rounded, saturating)
+#[derive(Component, Default)]
+pub struct SpeedometerDevice {
+    data: [u8; 4], // 1 × u32
+}
+
+impl SpeedometerDevice {
+    pub fn update(&mut self, speed: f32) {
+        // Float-to-int `as` saturates and maps NaN to 0.
+        let fixed = (speed * SPEED_SCALE).round() as u32;
+        self.data.copy_from_slice(&fixed.to_le_bytes());
+    }
+}
+
+impl Device for SpeedometerDevice {
+    fn load(&self, addr: u32, size: u32) -> Result<u32, ()> {
+        let addr = addr as usize;
+        match size {
+            8 => {
+                if addr < self.data.len() {
+                    Ok(self.data[addr] as u32)
+                } else {
+                    Ok(0)
+                }
+            }
+            16 => {
+                if addr + 1 < self.data.len() {
+                    Ok((self.data[addr] as u32) | ((self.data[addr + 1] as u32) << 8))
+                } else {
+                    Ok(0)
+                }
+            }
+            32 => {
+                if addr + 3 < self.data.len() {
+                    Ok((self.data[addr] as u32)
+                        | ((self.data[addr + 1] as u32) << 8)
+                        | ((self.data[addr + 2] as u32) << 16)
+                        | ((self.data[addr + 3] as u32) << 24))
+                } else {
+                    Ok(0)
+                }
+            }
+            _ => Err(()),
+        }
+    }
+
+    fn store(&mut self, _addr: u32, _size: u32, _value: u32) -> Result<(), ()> {
+        // The speedometer is read-only from the bot's perspective; silently ignore writes.
+        Ok(())
+    }
+}
+
+/// Runs BEFORE cpu_system::<RacingCpuConfig>: writes the car's speed into SpeedometerDevice.
+pub fn update_system(mut query: Query<(&LinearVelocity, &mut SpeedometerDevice)>) {
+    for (velocity, mut device) in &mut query {
+        device.update(velocity.length());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encodes_velocity_magnitude_as_fixed_point() {
+        let mut device = SpeedometerDevice::default();
+        device.update(Vec2::new(3.0, -4.0).length());
+
+        assert_eq!(device.data, 5000u32.to_le_bytes());
+        assert_eq!(device.load(0x00, 32), Ok(5000));
+        assert_eq!(device.load(0x00, 16), Ok(5000));
+        assert_eq!(device.load(0x04, 32), Ok(0));
+
+        device.update(f32::NAN);
+        assert_eq!(device.load(0x00, 32), Ok(0));
+    }
+}
@@ -0,0 +1,168 @@
+use bevy::prelude::*;
+use emulator::cpu::Device;
+
+use crate::track::{TrackProgress, TrackProgressTable, TrackSpline};
+
+/// Number of curvature samples published each tick.
+pub const TRACK_AHEAD_SAMPLES: usize = 8;
+/// Centre-line distance between consecutive samples, in world units.
+pub const TRACK_AHEAD_SPACING: f32 = 10.0;
+/// Fixed-point scale of the curvature readout: units are ten-thousandths of
+/// 1 / world unit.
+pub const CURVATURE_SCALE: f32 = 10_000.0;
+
+/// Memory-mapped device describing the centre line ahead of the car, so bots
+/// can brake before a corner instead of reacting to the radar.
+///
+/// Layout (little-endian):
+///   0x00..0x20: 8 × i32 signed curvature (1 / turn radius × `CURVATURE_SCALE`)
+///               at 10, 20, …, 80 world units ahead of the car's projection onto
+///               the centre line; positive turns left, negative turns right
+#[derive(Component, Default)]
+pub struct TrackAheadDevice {
+    data: [u8; 4 * TRACK_AHEAD_SAMPLES], // 8 × i32
+}
+
+impl TrackAheadDevice {
+    pub fn update(&mut self, samples: &[i32; TRACK_AHEAD_SAMPLES]) {
+        for (chunk, sample) in self.data.chunks_exact_mut(4).zip(samples) {
+            chunk.copy_from_slice(&sample.to_le_bytes());
+        }
+    }
+}
+
+/// Signed curvature of the centre line in fixed point, sampled every
+/// `TRACK_AHEAD_SPACING` after `distance` along the lap.
+pub fn curvature_ahead(
+    spline: &CubicCurve<Vec2>,
+    table: &TrackProgressTable,
+    distance: f32,
+) -> [i32; TRACK_AHEAD_SAMPLES] {
+    std::array::from_fn(|i| {
+        let t = table.t_at_distance(distance + (i + 1) as f32 * TRACK_AHEAD_SPACING);
+        // Float-to-int `as` saturates and maps NaN to 0.
+        (signed_curvature(spline, t) * CURVATURE_SCALE).round() as i32
+    })
+}
+
+fn signed_curvature(spline: &CubicCurve<Vec2>, t: f32) -> f32 {
+    let velocity = spline.velocity(t);
+    let speed = velocity.length();
+    if speed <= f32::EPSILON {
+        return 0.0;
+    }
+    velocity.perp_dot(spline.acceleration(t)) / (speed * speed * speed)
+}
+
+impl Device for TrackAheadDevice {
+    fn load(&self, addr: u32, size: u32) -> Result<u32, ()> {
+        let addr = addr as usize;
+        match size {
+            8 => {
+                if addr < self.data.len() {
+                    Ok(self.data[addr] as u32)
+                } else {
+                    Ok(0)
+                }
+            }
+            16 => {
+                if addr + 1 < self.data.len() {
+                    Ok((self.data[addr] as u32) | ((self.data[addr + 1] as u32) << 8))
+                } else {
+                    Ok(0)
+                }
+            }
+            32 => {
+                if addr + 3 < self.data.len() {
+                    Ok((self.data[addr] as u32)
+                        | ((self.data[addr + 1] as u32) << 8)
+                        | ((self.data[addr + 2] as u32) << 16)
+                        | ((self.data[addr + 3] as u32) << 24))
+                } else {
+                    Ok(0)
+                }
+            }
+            _ => Err(()),
+        }
+    }
+
+    fn store(&mut self, _addr: u32, _size: u32, _value: u32) -> Result<(), ()> {
+        // The lookahead is read-only from the bot's perspective; silently ignore writes.
+        Ok(())
+    }
+}
+
+/// Runs BEFORE cpu_system::<RacingCpuConfig>, after `track_progress_system`.
+pub fn update_system(
+    track: Res<TrackSpline>,
+    table: Res<TrackProgressTable>,
+    mut query: Query<(&TrackProgress, &mut TrackAheadDevice)>,
+) {
+    for (progress, mut device) in &mut query {
+        device.update(&curvature_ahead(&track.spline, &table, progress.distance));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::TAU;
+
+    use super::*;
+    use crate::track::build_spline;
+
+    fn polygon(radius: f32, sides: usize) -> Vec<Vec2> {
+        (0..sides)
+            .map(|i| Vec2::from_angle(i as f32 / sides as f32 * TAU) * radius)
+            .collect()
+    }
+
+    #[test]
+    fn circular_track_has_constant_curvature_with_turn_sign() {
+        // A cubic B-spline pulls a regular polygon's vertices inwards to
+        // radius R * (4 + 2 cos θ) / 6, θ being the angle between vertices.
+        let (radius, sides) = (50.0, 32);
+        let theta = TAU / sides as f32;
+        let expected = CURVATURE_SCALE / (radius * (4.0 + 2.0 * theta.cos()) / 6.0);
+
+        let mut points = polygon(radius, sides);
+        for sign in [1.0, -1.0] {
+            let spline = build_spline(&points);
+            let table = TrackProgressTable::new(&spline, 512);
+            for sample in curvature_ahead(&spline, &table, 37.0) {
+                let error = (sample as f32 - sign * expected).abs();
+                assert!(
+                    error < expected * 0.02,
+                    "sample {sample}, expected {expected}"
+                );
+            }
+            // Driving the same circle clockwise turns right.
+            points.reverse();
+        }
+    }
+
+    #[test]
+    fn straight_sections_read_zero_and_samples_are_exposed_as_i32() {
+        let spline = build_spline(&[
+            vec2(0.0, 0.0),
+            vec2(100.0, 0.0),
+            vec2(200.0, 0.0),
+            vec2(300.0, 0.0),
+            vec2(300.0, 100.0),
+            vec2(0.0, 100.0),
+        ]);
+        let table = TrackProgressTable::new(&spline, 1024);
+        // Segment 0 runs 100 units from control point 1 to 2 along y = 0, so
+        // every sample ahead of its start lies on the straight.
+        let start = table.distance_along(vec2(100.0, 0.0));
+        assert_eq!(
+            curvature_ahead(&spline, &table, start),
+            [0; TRACK_AHEAD_SAMPLES]
+        );
+
+        let mut device = TrackAheadDevice::default();
+        device.update(&[1, -2, 3, -4, 5, -6, 7, -8]);
+        assert_eq!(device.load(0x04, 32), Ok((-2i32) as u32));
+        assert_eq!(device.load(0x1C, 32), Ok((-8i32) as u32));
+        assert_eq!(device.load(0x20, 32), Ok(0));
+    }
+}
@@ -2,42 +2,56 @@ use avian2d::math::PI;
 use bevy::prelude::*;
 use emulator::cpu::Device;
 
-const TRACK_RADAR_RAY_COUNT: usize = 7;
+/// Rays cast by a `TrackRadarDevice::default()`.
+pub const TRACK_RADAR_DEFAULT_RAYS: usize = 7;
+/// Most rays that fit below the ray-count word.
+pub const TRACK_RADAR_MAX_RAYS: usize = RAY_COUNT_OFFSET / 4;
+const RAY_COUNT_OFFSET: usize = 0xFC;
 const TRACK_RADAR_CONE_HALF_ANGLE_RAD: f32 = PI * 0.25;
 const TRACK_RADAR_MAX_DISTANCE: f32 = 200.0;
 
-/// Memory-mapped device exposing distances to track borders along 7 forward rays.
+/// Memory-mapped device exposing distances to track borders along rays spread
+/// evenly over a 90° forward cone, right to left.
 ///
-/// Layout (all f32, little-endian):
-///   0x00: ray_0_distance
-///   0x04: ray_1_distance
-///   0x08: ray_2_distance
-///   0x0C: ray_3_distance
-///   0x10: ray_4_distance
-///   0x14: ray_5_distance
-///   0x18: ray_6_distance
+/// Layout (little-endian):
+///   0x00..:  ray_count × f32 ray distance (ray 0 at offset 0x00, ray 1 at 0x04, …)
+///   0xFC:    u32 ray_count (7 by default, at most `TRACK_RADAR_MAX_RAYS`)
 ///
 /// Distances are nearest-hit distances in world units. If a ray has no hit,
-/// the value is NaN.
+/// the value is NaN. Offsets past the last ray read 0.
 #[derive(Component)]
 pub struct TrackRadarDevice {
-    data: [u8; 28], // 7 × f32
+    data: [u8; 0x100],
+    ray_count: usize,
 }
 
 impl Default for TrackRadarDevice {
     fn default() -> Self {
-        Self { data: [0u8; 28] }
+        Self::new(TRACK_RADAR_DEFAULT_RAYS)
     }
 }
 
 impl TrackRadarDevice {
+    /// A radar casting `ray_count` rays, clamped to `1..=TRACK_RADAR_MAX_RAYS`.
+    pub fn new(ray_count: usize) -> Self {
+        let ray_count = ray_count.clamp(1, TRACK_RADAR_MAX_RAYS);
+        let mut data = [0u8; 0x100];
+        data[RAY_COUNT_OFFSET..].copy_from_slice(&(ray_count as u32).to_le_bytes());
+        Self { data, ray_count }
+    }
+
+    pub fn ray_count(&self) -> usize {
+        self.ray_count
+    }
+
     fn write_f32(&mut self, offset: usize, value: f32) {
         let bytes = value.to_le_bytes();
         self.data[offset..offset + 4].copy_from_slice(&bytes);
     }
 
-    pub fn update(&mut self, distances: [f32; 7]) {
-        for (i, distance) in distances.into_iter().enumerate() {
+    /// Publish one distance per ray; extra entries are ignored.
+    pub fn update(&mut self, distances: &[f32]) {
+        for (i, &distance) in distances.iter().take(self.ray_count).enumerate() {
             self.write_f32(i * 4, distance);
         }
     }
@@ -95,28 +109,36 @@ pub fn update_system(
     for (transform, mut track_radar_dev) in &mut emu_query {
         let car_pos = transform.translation.xy();
         let car_forward = transform.up().xy().normalize();
-        track_radar_dev.update(compute_track_radar_distances(
-            car_pos,
-            car_forward,
-            &borders,
-        ));
+        let mut distances = [f32::NAN; TRACK_RADAR_MAX_RAYS];
+        let distances = &mut distances[..track_radar_dev.ray_count()];
+        compute_track_radar_distances(car_pos, car_forward, &borders, distances);
+        track_radar_dev.update(distances);
     }
 }
 
+/// Angle of ray `ray_index` of `ray_count` from the car's forward direction;
+/// negative is to the right. A single ray looks straight ahead.
+fn ray_angle(ray_index: usize, ray_count: usize) -> f32 {
+    let t = if ray_count <= 1 {
+        0.5
+    } else {
+        ray_index as f32 / (ray_count - 1) as f32
+    };
+    -TRACK_RADAR_CONE_HALF_ANGLE_RAD + t * (2.0 * TRACK_RADAR_CONE_HALF_ANGLE_RAD)
+}
+
+/// Fill `distances` with one border distance per ray, NaN where a ray hits
+/// nothing within range.
 fn compute_track_radar_distances(
     origin: Vec2,
     forward: Vec2,
     borders: &TrackRadarBorders,
-) -> [f32; TRACK_RADAR_RAY_COUNT] {
-    let mut distances = [f32::NAN; TRACK_RADAR_RAY_COUNT];
-
+    distances: &mut [f32],
+) {
+    let ray_count = distances.len();
     for (ray_index, distance_slot) in distances.iter_mut().enumerate() {
-        let t = if TRACK_RADAR_RAY_COUNT <= 1 {
-            0.5
-        } else {
-            ray_index as f32 / (TRACK_RADAR_RAY_COUNT - 1) as f32
-        };
-        let angle = -TRACK_RADAR_CONE_HALF_ANGLE_RAD + t * (2.0 * TRACK_RADAR_CONE_HALF_ANGLE_RAD);
+        *distance_slot = f32::NAN;
+        let angle = ray_angle(ray_index, ray_count);
         let ray_direction = Vec2::from_angle(angle).rotate(forward).normalize();
 
         let mut best = f32::INFINITY;
@@ -137,8 +159,6 @@ fn compute_track_radar_distances(
             *distance_slot = best;
         }
     }
-
-    distances
 }
 
 fn closest_intersection_in_polyline(
@@ -188,3 +208,42 @@ fn ray_segment_intersection_distance(
         None
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn thirty_two_ray_radar_sees_a_post_only_on_the_matching_ray() {
+        let mut device = TrackRadarDevice::new(32);
+        assert_eq!(device.load(0xFC, 32), Ok(32));
+        assert_eq!(TrackRadarDevice::default().load(0xFC, 32), Ok(7));
+
+        // A 2-unit wide post 50 units out along ray 20, facing the car. Rays
+        // are 90° / 31 ≈ 2.9° (≈ 2.5 units at that range) apart, so only
+        // ray 20 hits it.
+        let forward = Vec2::Y;
+        let direction = Vec2::from_angle(ray_angle(20, 32)).rotate(forward);
+        let centre = direction * 50.0;
+        let borders = TrackRadarBorders {
+            inner: vec![centre - direction.perp(), centre + direction.perp()],
+            outer: Vec::new(),
+        };
+        // Ray 20 is left of straight ahead.
+        assert!(direction.x < 0.0);
+
+        let mut distances = [0.0; 32];
+        compute_track_radar_distances(Vec2::ZERO, forward, &borders, &mut distances);
+        for (index, distance) in distances.iter().enumerate() {
+            if index == 20 {
+                assert!((distance - 50.0).abs() < 1e-3, "ray 20 read {distance}");
+            } else {
+                assert!(distance.is_nan(), "ray {index} read {distance}");
+            }
+        }
+
+        device.update(&distances);
+        assert_eq!(device.load(20 * 4, 32), Ok(distances[20].to_bits()));
+        assert_eq!(device.load(32 * 4, 32), Ok(0));
+    }
+}
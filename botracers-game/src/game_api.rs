@@ -1,16 +1,43 @@
+use std::path::PathBuf;
+
 use bevy::prelude::*;
+use botracers_protocol::{ArtifactSummary, RaceReplay};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum DriverType {
-    RemoteArtifact { id: i64 },
+    RemoteArtifact {
+        id: i64,
+    },
+    /// ELF read from disk by a headless run.
+    LocalElf {
+        path: String,
+    },
+    /// Keyboard-driven car (W/S/A/D) without a bot.
+    Manual,
 }
 
 impl DriverType {
     pub fn label(&self) -> String {
         match self {
             DriverType::RemoteArtifact { id } => format!("Artifact: #{id}"),
+            DriverType::LocalElf { path } => format!("File: {path}"),
+            DriverType::Manual => "Manual".to_string(),
         }
     }
+
+    /// Like [`label`](Self::label), but names a remote artifact and its owner
+    /// when it is among `artifacts`.
+    pub fn label_with_artifacts(&self, artifacts: &[ArtifactSummary]) -> String {
+        if let DriverType::RemoteArtifact { id } = self
+            && let Some(artifact) = artifacts.iter().find(|artifact| artifact.id == *id)
+        {
+            return format!(
+                "Artifact: {} by {} (#{id})",
+                artifact.name, artifact.owner_username
+            );
+        }
+        self.label()
+    }
 }
 
 #[derive(Message)]
@@ -26,13 +53,44 @@ pub struct SpawnResolvedCarRequest {
     pub binary_name: String,
 }
 
+#[derive(Message)]
+pub struct SpectateReplayRequest {
+    pub record_id: i64,
+    pub replay: RaceReplay,
+}
+
+/// Race against car `car_index` of `replay` as a ghost; honoured only before a race.
+#[derive(Message)]
+pub struct GhostReplayRequest {
+    pub replay: RaceReplay,
+    pub car_index: usize,
+}
+
+/// Replace the race track with the TOML file at `path`; honoured only before a race.
+#[derive(Message)]
+pub struct LoadTrackRequest {
+    pub path: PathBuf,
+}
+
 #[derive(Message)]
 pub enum WebApiCommand {
     RefreshCapabilities,
     LoadArtifacts,
     UploadArtifact,
-    DeleteArtifact { id: i64 },
-    SetArtifactVisibility { id: i64, is_public: bool },
+    DeleteArtifact {
+        id: i64,
+    },
+    SetArtifactVisibility {
+        id: i64,
+        is_public: bool,
+    },
+    LoadRaceRecords,
+    /// Fetch a race record's replay to spectate it, or to race its first car as a ghost.
+    LoadReplay {
+        record_id: i64,
+        as_ghost: bool,
+    },
+    PublishResult,
 }
 
 pub struct GameApiPlugin;
@@ -41,6 +99,49 @@ impl Plugin for GameApiPlugin {
     fn build(&self, app: &mut App) {
         app.add_message::<SpawnCarRequest>()
             .add_message::<SpawnResolvedCarRequest>()
+            .add_message::<SpectateReplayRequest>()
+            .add_message::<GhostReplayRequest>()
+            .add_message::<LoadTrackRequest>()
             .add_message::<WebApiCommand>();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn artifact(id: i64, name: &str, owner: &str) -> ArtifactSummary {
+        ArtifactSummary {
+            id,
+            owner_user_id: 1,
+            owner_username: owner.to_string(),
+            name: name.to_string(),
+            note: None,
+            target: "riscv32imafc-unknown-none-elf".to_string(),
+            is_public: true,
+            owned_by_me: false,
+            created_at: String::new(),
+            sha256: None,
+            build_meta_json: None,
+            download_count: 0,
+            tags: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn artifact_labels_use_name_and_owner_when_known() {
+        let artifacts = [artifact(3, "racer", "alice"), artifact(7, "drifter", "bob")];
+        assert_eq!(
+            DriverType::RemoteArtifact { id: 7 }.label_with_artifacts(&artifacts),
+            "Artifact: drifter by bob (#7)"
+        );
+        assert_eq!(
+            DriverType::RemoteArtifact { id: 9 }.label_with_artifacts(&artifacts),
+            "Artifact: #9"
+        );
+        assert_eq!(
+            DriverType::Manual.label_with_artifacts(&artifacts),
+            "Manual"
+        );
+    }
+}
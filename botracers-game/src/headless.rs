@@ -0,0 +1,420 @@
+//! Batch race simulation without a window: loads bot ELFs from disk, runs the
+//! race on a fixed manual timestep and prints the results as JSON on stdout.
+//!
+//! Every frame advances exactly one fixed tick, so results and `state_hash`
+//! depend only on the bots, the laps/ticks limits, the tick rate and the
+//! physics substeps.
+
+use std::{path::PathBuf, time::Duration};
+
+use avian2d::prelude::*;
+use bevy::{
+    app::ScheduleRunnerPlugin, gizmos::GizmoPlugin, prelude::*, scene::ScenePlugin,
+    state::app::StatesPlugin, time::TimeUpdateStrategy,
+};
+use serde::Serialize;
+
+use crate::game_api::{DriverType, GameApiPlugin};
+use crate::race_runtime::{
+    self, ActiveTrack, CpuFrequencySetting, OffTrackLimit, PhysicsSubsteps, RaceClock,
+    RaceCountdown, RaceManager, RaceSeed, RaceSimPlugin, RaceStateHash, RadarRays, ReplayRecorder,
+    SimState, SimTickHz,
+};
+use botracers_game::track;
+
+/// Fixed ticks a headless race may run before it is cut off (five minutes).
+pub fn default_max_ticks(tick_hz: SimTickHz) -> u64 {
+    5 * 60 * tick_hz.hz() as u64
+}
+
+pub struct HeadlessConfig {
+    /// Bots in grid order, as `(path, elf bytes)`.
+    pub bots: Vec<(String, Vec<u8>)>,
+    /// The race ends once every car has completed this many laps...
+    pub laps: u32,
+    /// ...or after this many racing ticks, whichever comes first.
+    pub max_ticks: u64,
+    pub countdown: RaceCountdown,
+    pub substeps: PhysicsSubsteps,
+    pub radar_rays: RadarRays,
+    pub tick_hz: SimTickHz,
+    pub seed: RaceSeed,
+    pub off_track_limit: OffTrackLimit,
+    /// Where to write the recorded `RaceReplay` JSON when the race ends.
+    pub replay_path: Option<PathBuf>,
+}
+
+#[derive(Resource)]
+struct HeadlessRace {
+    bots: Vec<(String, Vec<u8>)>,
+    laps: u32,
+    max_ticks: u64,
+    replay_path: Option<PathBuf>,
+    /// Race tick at which each car (by grid index) completed its last lap.
+    finish_ticks: Vec<Option<u64>>,
+    reported: bool,
+}
+
+#[derive(Serialize)]
+struct HeadlessResults {
+    ticks: u64,
+    tick_hz: u32,
+    seed: u64,
+    state_hash: String,
+    cars: Vec<HeadlessCarResult>,
+}
+
+#[derive(Serialize)]
+struct HeadlessCarResult {
+    name: String,
+    driver: String,
+    laps: u32,
+    best_lap_secs: Option<f32>,
+    last_lap_secs: Option<f32>,
+    /// Race time at which the car completed the target laps, if it did.
+    finish_secs: Option<f32>,
+    /// Retired after leaving the track for too long.
+    dnf: bool,
+}
+
+/// Builds the headless race app. Call `run()` on it to race to completion.
+pub fn build_app(config: HeadlessConfig) -> App {
+    let laps = race_runtime::RaceLaps::new(config.laps);
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins.set(ScheduleRunnerPlugin::run_loop(Duration::ZERO)),
+        StatesPlugin,
+        TransformPlugin,
+        AssetPlugin::default(),
+        // Avian's collider hierarchy system reads `SceneSpawner`, and
+        // `apply_car_forces` draws debug gizmos.
+        ScenePlugin,
+        GizmoPlugin,
+    ))
+    .init_asset::<Mesh>()
+    .insert_resource(TimeUpdateStrategy::ManualDuration(
+        config.tick_hz.tick_duration(),
+    ))
+    .insert_resource(config.countdown)
+    .insert_resource(config.substeps)
+    .insert_resource(config.radar_rays)
+    .insert_resource(config.tick_hz)
+    .insert_resource(config.seed)
+    .insert_resource(config.off_track_limit)
+    .insert_resource(laps)
+    .insert_resource(HeadlessRace {
+        finish_ticks: vec![None; config.bots.len()],
+        bots: config.bots,
+        laps: laps.0,
+        max_ticks: config.max_ticks,
+        replay_path: config.replay_path,
+        reported: false,
+    })
+    .add_plugins((PhysicsPlugins::default(), GameApiPlugin, RaceSimPlugin))
+    .add_systems(
+        Startup,
+        spawn_headless_cars.after(race_runtime::setup_track),
+    )
+    .add_systems(
+        FixedUpdate,
+        finish_headless_race
+            .after(race_runtime::CpuSystems::PostCpu)
+            .run_if(in_state(SimState::Racing)),
+    );
+    app
+}
+
+fn spawn_headless_cars(
+    mut commands: Commands,
+    race: Res<HeadlessRace>,
+    track_spline: Res<track::TrackSpline>,
+    active_track: Res<ActiveTrack>,
+    mut manager: ResMut<RaceManager>,
+    cpu_frequency: Res<CpuFrequencySetting>,
+    tick_hz: Res<SimTickHz>,
+    radar_rays: Res<RadarRays>,
+    countdown: Res<RaceCountdown>,
+    mut next_state: ResMut<NextState<SimState>>,
+) {
+    for (path, elf_bytes) in &race.bots {
+        race_runtime::spawn_car_entry(
+            &mut commands,
+            &track_spline,
+            &active_track,
+            &mut manager,
+            cpu_frequency.instructions_per_update(*tick_hz),
+            *radar_rays,
+            DriverType::LocalElf { path: path.clone() },
+            elf_bytes,
+        );
+    }
+    next_state.set(countdown.start_state(*tick_hz));
+}
+
+fn finish_headless_race(
+    mut race: ResMut<HeadlessRace>,
+    manager: Res<RaceManager>,
+    clock: Res<RaceClock>,
+    hash: Res<RaceStateHash>,
+    recorder: Res<ReplayRecorder>,
+    tick_hz: Res<SimTickHz>,
+    seed: Res<RaceSeed>,
+    mut exit: MessageWriter<AppExit>,
+) {
+    let Some(tick) = clock.race_ticks() else {
+        return;
+    };
+    if race.reported {
+        return;
+    }
+
+    let target_laps = race.laps;
+    for (entry, finish) in manager.cars.iter().zip(race.finish_ticks.iter_mut()) {
+        if finish.is_none() && entry.laps_completed >= target_laps {
+            *finish = Some(tick);
+        }
+    }
+
+    // Retired cars will never finish, so the race does not wait for them.
+    let all_finished = manager
+        .cars
+        .iter()
+        .zip(&race.finish_ticks)
+        .all(|(entry, finish)| finish.is_some() || entry.dnf);
+    if !all_finished && tick < race.max_ticks {
+        return;
+    }
+    race.reported = true;
+
+    if let Some(path) = &race.replay_path {
+        let written = recorder
+            .to_json(*tick_hz)
+            .map_err(|error| error.to_string())
+            .and_then(|json| std::fs::write(path, json).map_err(|error| error.to_string()));
+        if let Err(error) = written {
+            eprintln!("failed to write replay to {}: {error}", path.display());
+        }
+    }
+
+    let to_secs = |ticks: u64| tick_hz.ticks_to_secs(ticks);
+    let mut order: Vec<usize> = (0..manager.cars.len()).collect();
+    // Finishers by finish time, then cars still running before DNFs, each by
+    // laps completed; grid order breaks ties.
+    order.sort_by_key(|&index| {
+        let finish = race.finish_ticks.get(index).copied().flatten();
+        (
+            finish.unwrap_or(u64::MAX),
+            manager.cars[index].dnf,
+            std::cmp::Reverse(manager.cars[index].laps_completed),
+            index,
+        )
+    });
+
+    let results = HeadlessResults {
+        ticks: tick,
+        tick_hz: tick_hz.hz(),
+        seed: seed.0,
+        state_hash: hash.0.to_hex(),
+        cars: order
+            .into_iter()
+            .map(|index| {
+                let entry = &manager.cars[index];
+                HeadlessCarResult {
+                    name: entry.name.clone(),
+                    driver: entry.driver.label(),
+                    laps: entry.laps_completed,
+                    best_lap_secs: entry.best_lap_secs,
+                    last_lap_secs: entry.last_lap_secs,
+                    finish_secs: race.finish_ticks[index].map(to_secs),
+                    dnf: entry.dnf,
+                }
+            })
+            .collect(),
+    };
+
+    match serde_json::to_string_pretty(&results) {
+        Ok(json) => {
+            println!("{json}");
+            exit.write(AppExit::Success);
+        }
+        Err(error) => {
+            eprintln!("failed to serialize race results: {error}");
+            exit.write(AppExit::error());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_race_reports_and_exits_after_one_tick() {
+        let mut app = build_app(HeadlessConfig {
+            bots: Vec::new(),
+            laps: 1,
+            max_ticks: 1,
+            countdown: RaceCountdown::new(0.0),
+            substeps: PhysicsSubsteps::default(),
+            radar_rays: RadarRays::default(),
+            tick_hz: SimTickHz::default(),
+            seed: RaceSeed(0),
+            off_track_limit: OffTrackLimit::default(),
+            replay_path: None,
+        });
+        app.finish();
+        app.cleanup();
+
+        for _ in 0..10 {
+            app.update();
+            if app.should_exit().is_some() {
+                break;
+            }
+        }
+
+        assert_eq!(app.should_exit(), Some(AppExit::Success));
+        assert!(app.world().resource::<HeadlessRace>().reported);
+    }
+
+    /// ELF32 RISC-V image with `words` as a single segment at 0x1000.
+    fn elf_with_code(words: &[u32]) -> Vec<u8> {
+        const EHDR_SIZE: u32 = 52;
+        const PHDR_SIZE: u32 = 32;
+        let mut elf = vec![0x7f, b'E', b'L', b'F', 1, 1, 1];
+        elf.resize(16, 0);
+        for half in [2u16, 0xf3] {
+            elf.extend_from_slice(&half.to_le_bytes());
+        }
+        for word in [1, 0x1000, EHDR_SIZE, 0, 0] {
+            elf.extend_from_slice(&word.to_le_bytes());
+        }
+        for half in [EHDR_SIZE as u16, PHDR_SIZE as u16, 1, 40, 0, 0] {
+            elf.extend_from_slice(&half.to_le_bytes());
+        }
+        let size = words.len() as u32 * 4;
+        for word in [
+            1,
+            EHDR_SIZE + PHDR_SIZE,
+            0x1000,
+            0x1000,
+            size,
+            size,
+            0b111,
+            4,
+        ] {
+            elf.extend_from_slice(&word.to_le_bytes());
+        }
+        for word in words {
+            elf.extend_from_slice(&word.to_le_bytes());
+        }
+        elf
+    }
+
+    /// Full throttle and a constant steering input, then spin. `steer_lui` is
+    /// the `lui sp, ..` that loads the steering value's f32 bits:
+    /// lui ra, 0x3f800; sw ra, 0x300(zero); <steer_lui>; sw sp, 0x308(zero); j .
+    fn steering_bot(steer_lui: u32) -> Vec<u8> {
+        elf_with_code(&[
+            0x3f80_00b7,
+            0x3010_2023,
+            steer_lui,
+            0x3020_2423,
+            0x0000_006f,
+        ])
+    }
+
+    /// `lui sp, 0x3e800`: steer 0.25.
+    const STEER_QUARTER: u32 = 0x3e80_0137;
+    /// `lui sp, 0x3f000`: steer 0.5.
+    const STEER_HALF: u32 = 0x3f00_0137;
+
+    /// Runs a short two-car race of `bot` against itself to the end.
+    fn run_race(bot: Vec<u8>) -> App {
+        let mut app = build_app(HeadlessConfig {
+            bots: vec![("a".to_string(), bot.clone()), ("b".to_string(), bot)],
+            laps: 1,
+            max_ticks: 120,
+            countdown: RaceCountdown::new(0.0),
+            substeps: PhysicsSubsteps::default(),
+            radar_rays: RadarRays::default(),
+            tick_hz: SimTickHz::default(),
+            seed: RaceSeed(0),
+            off_track_limit: OffTrackLimit::default(),
+            replay_path: None,
+        });
+        app.finish();
+        app.cleanup();
+        for _ in 0..1000 {
+            app.update();
+            if app.should_exit().is_some() {
+                break;
+            }
+        }
+        assert_eq!(app.should_exit(), Some(AppExit::Success));
+        app
+    }
+
+    /// Final position and speed of each car after a short two-car race.
+    fn race_to_the_end() -> Vec<(Vec2, f32)> {
+        let app = run_race(steering_bot(STEER_QUARTER));
+        let world = app.world();
+        world
+            .resource::<RaceManager>()
+            .cars
+            .iter()
+            .map(|car| {
+                let position = world.get::<Position>(car.entity).expect("car position");
+                let velocity = world
+                    .get::<LinearVelocity>(car.entity)
+                    .expect("car velocity");
+                (position.0, velocity.0.length())
+            })
+            .collect()
+    }
+
+    fn race_state_hash(bot: Vec<u8>) -> String {
+        run_race(bot).world().resource::<RaceStateHash>().0.to_hex()
+    }
+
+    #[test]
+    fn repeated_runs_reproduce_final_car_positions() {
+        let first = race_to_the_end();
+        assert_eq!(first.len(), 2);
+        assert!(first.iter().all(|&(_, speed)| speed > 0.1), "{first:?}");
+        assert_eq!(race_to_the_end(), first);
+    }
+
+    #[test]
+    fn state_hash_repeats_for_the_same_bots_and_changes_with_a_different_bot() {
+        let first = race_state_hash(steering_bot(STEER_QUARTER));
+        assert_eq!(race_state_hash(steering_bot(STEER_QUARTER)), first);
+        assert_ne!(race_state_hash(steering_bot(STEER_HALF)), first);
+    }
+
+    #[test]
+    fn bot_cars_cast_the_configured_number_of_radar_rays() {
+        let mut app = build_app(HeadlessConfig {
+            bots: vec![("a".to_string(), steering_bot(STEER_QUARTER))],
+            laps: 1,
+            max_ticks: 1,
+            countdown: RaceCountdown::new(0.0),
+            substeps: PhysicsSubsteps::default(),
+            radar_rays: RadarRays::new(12),
+            tick_hz: SimTickHz::default(),
+            seed: RaceSeed(0),
+            off_track_limit: OffTrackLimit::default(),
+            replay_path: None,
+        });
+        app.finish();
+        app.cleanup();
+        app.update();
+
+        let rays: Vec<usize> = app
+            .world_mut()
+            .query::<&botracers_game::devices::TrackRadarDevice>()
+            .iter(app.world())
+            .map(|radar| radar.ray_count())
+            .collect();
+        assert_eq!(rays, vec![12]);
+    }
+}
@@ -1,6 +1,7 @@
 use bevy::prelude::*;
 
 pub mod devices;
+pub mod state_hash;
 pub mod track;
 pub mod track_format;
 
@@ -3,17 +3,142 @@ use bevy::{diagnostic::FrameTimeDiagnosticsPlugin, prelude::*};
 
 mod bootstrap;
 mod game_api;
+#[cfg(not(target_arch = "wasm32"))]
+mod headless;
 mod race_runtime;
+mod replay;
 mod ui;
 
 fn main() {
     #[cfg(not(target_arch = "wasm32"))]
     let mut standalone_mode = false;
+    let mut auto_pause = true;
+    let mut countdown = race_runtime::RaceCountdown::default();
+    let mut substeps = race_runtime::PhysicsSubsteps::default();
+    let mut radar_rays = race_runtime::RadarRays::default();
+    let mut tick_hz = race_runtime::SimTickHz::default();
+    let mut seed = race_runtime::RaceSeed::default();
+    let mut off_track_limit = race_runtime::OffTrackLimit::default();
+    let mut race_laps = None;
+    #[cfg(not(target_arch = "wasm32"))]
+    let mut headless_mode = false;
+    #[cfg(not(target_arch = "wasm32"))]
+    let mut headless_ticks = None;
+    #[cfg(not(target_arch = "wasm32"))]
+    let mut elf_paths = Vec::new();
+    #[cfg(not(target_arch = "wasm32"))]
+    let mut replay_path = None;
+    #[cfg(not(target_arch = "wasm32"))]
+    let mut ghost_path = None;
     for arg in std::env::args().skip(1) {
         #[cfg(not(target_arch = "wasm32"))]
         if arg == "--standalone" {
             standalone_mode = true;
         }
+        #[cfg(not(target_arch = "wasm32"))]
+        if arg == "--headless" {
+            headless_mode = true;
+        }
+        if let Some(laps) = arg.strip_prefix("--laps=") {
+            match laps.parse::<u32>() {
+                Ok(laps) => race_laps = Some(laps),
+                Err(_) => eprintln!("ignoring invalid --laps value: {laps}"),
+            }
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(ticks) = arg.strip_prefix("--ticks=") {
+            match ticks.parse::<u64>() {
+                Ok(ticks) => headless_ticks = Some(ticks),
+                Err(_) => eprintln!("ignoring invalid --ticks value: {ticks}"),
+            }
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(path) = arg.strip_prefix("--replay=") {
+            replay_path = Some(std::path::PathBuf::from(path));
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(path) = arg.strip_prefix("--ghost=") {
+            ghost_path = Some(replay::GhostReplayFile(path.into()));
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        if !arg.starts_with("--") {
+            elf_paths.push(arg.clone());
+        }
+        if arg == "--no-auto-pause" {
+            auto_pause = false;
+        }
+        if let Some(secs) = arg.strip_prefix("--countdown=") {
+            match secs.parse::<f32>() {
+                Ok(secs) => countdown = race_runtime::RaceCountdown::new(secs),
+                Err(_) => eprintln!("ignoring invalid --countdown value: {secs}"),
+            }
+        }
+        if let Some(count) = arg.strip_prefix("--substeps=") {
+            match count.parse::<u32>() {
+                Ok(count) => substeps = race_runtime::PhysicsSubsteps::new(count),
+                Err(_) => eprintln!("ignoring invalid --substeps value: {count}"),
+            }
+        }
+        if let Some(count) = arg.strip_prefix("--radar-rays=") {
+            match count.parse::<usize>() {
+                Ok(count) => radar_rays = race_runtime::RadarRays::new(count),
+                Err(_) => eprintln!("ignoring invalid --radar-rays value: {count}"),
+            }
+        }
+        if let Some(hz) = arg.strip_prefix("--tick-hz=") {
+            match hz.parse::<u32>() {
+                Ok(hz) => tick_hz = race_runtime::SimTickHz::new(hz),
+                Err(_) => eprintln!("ignoring invalid --tick-hz value: {hz}"),
+            }
+        }
+        if let Some(value) = arg.strip_prefix("--seed=") {
+            match value.parse::<u64>() {
+                Ok(value) => seed = race_runtime::RaceSeed(value),
+                Err(_) => eprintln!("ignoring invalid --seed value: {value}"),
+            }
+        }
+        if let Some(secs) = arg.strip_prefix("--dnf-after=") {
+            match secs.parse::<f32>() {
+                Ok(secs) => off_track_limit = race_runtime::OffTrackLimit::new(secs),
+                Err(_) => eprintln!("ignoring invalid --dnf-after value: {secs}"),
+            }
+        }
+    }
+    // stderr, so headless JSON results on stdout stay parseable.
+    eprintln!("race seed: {}", seed.0);
+    if tick_hz.is_below_stable() {
+        eprintln!(
+            "warning: --tick-hz={} is low; cars may tunnel through walls or miss the track edge",
+            tick_hz.hz()
+        );
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    if headless_mode {
+        let mut bots = Vec::new();
+        for path in elf_paths {
+            match std::fs::read(&path) {
+                Ok(bytes) => bots.push((path, bytes)),
+                Err(error) => {
+                    eprintln!("failed to read bot ELF {path}: {error}");
+                    std::process::exit(2);
+                }
+            }
+        }
+        let exit = headless::build_app(headless::HeadlessConfig {
+            bots,
+            laps: race_laps.unwrap_or(1),
+            max_ticks: headless_ticks.unwrap_or_else(|| headless::default_max_ticks(tick_hz)),
+            countdown,
+            substeps,
+            radar_rays,
+            tick_hz,
+            seed,
+            off_track_limit,
+            replay_path,
+        })
+        .run();
+        std::process::exit(if exit.is_success() { 0 } else { 1 });
     }
 
     #[cfg(not(target_arch = "wasm32"))]
@@ -31,8 +156,20 @@ fn main() {
     #[cfg(target_arch = "wasm32")]
     let bootstrap_config = bootstrap::BootstrapConfig::default();
 
-    App::new()
-        .insert_resource(bootstrap_config)
+    let mut app = App::new();
+    app.insert_resource(bootstrap_config)
+        .insert_resource(race_runtime::AutoPauseSetting::new(auto_pause))
+        .insert_resource(countdown)
+        .insert_resource(substeps)
+        .insert_resource(radar_rays)
+        .insert_resource(tick_hz)
+        .insert_resource(seed)
+        .insert_resource(off_track_limit)
+        .insert_resource(
+            race_laps
+                .map(race_runtime::RaceLaps::new)
+                .unwrap_or_default(),
+        )
         .add_plugins((
             DefaultPlugins.set(WindowPlugin {
                 primary_window: Some(Window {
@@ -46,9 +183,14 @@ fn main() {
             PhysicsPlugins::default(),
             game_api::GameApiPlugin,
             race_runtime::RaceRuntimePlugin,
+            replay::ReplayPlugin,
             bootstrap::BootstrapPlugin,
             ui::BootstrapUiPlugin,
             ui::RaceRuntimeUiPlugin,
-        ))
-        .run();
+        ));
+    #[cfg(not(target_arch = "wasm32"))]
+    if let Some(ghost_path) = ghost_path {
+        app.insert_resource(ghost_path);
+    }
+    app.run();
 }
@@ -1,4 +1,5 @@
-use std::f32::consts::PI;
+use std::f32::consts::{PI, TAU};
+use std::path::PathBuf;
 
 use avian2d::prelude::{forces::ForcesItem, *};
 use bevy::{
@@ -6,64 +7,168 @@ use bevy::{
     diagnostic::{DiagnosticsStore, FrameTimeDiagnosticsPlugin},
     input::mouse::{MouseMotion, MouseWheel},
     prelude::*,
+    window::WindowFocused,
 };
-use emulator::bevy::{CpuComponent, cpu_system};
-use emulator::cpu::LogDevice;
+use emulator::bevy::{CpuComponent, CpuHalted, cpu_system};
+use emulator::cpu::{LogDevice, YieldDevice};
 
 use botracers_game::Car;
 use botracers_game::devices::TrackRadarBorders;
 use botracers_game::devices::{
-    self, CarControlsDevice, CarRadarDevice, CarStateDevice, SplineDevice, TrackRadarDevice,
+    self, CarControlsDevice, CarRadarDevice, CarStateDevice, LapProgressDevice, SpeedometerDevice,
+    SplineDevice, TrackAheadDevice, TrackRadarDevice,
 };
+use botracers_game::state_hash::StateHasher;
 use botracers_game::track;
-use botracers_game::track_format::TrackFile;
+use botracers_game::track_format::{TrackFile, TrackPhysics};
+use botracers_protocol::{RaceReplay, ReplayCar, ReplayCarControls, ReplayCarPose, ReplayFrame};
 
-use crate::game_api::{DriverType, SpawnResolvedCarRequest};
+use crate::game_api::{DriverType, LoadTrackRequest, SpawnResolvedCarRequest};
 
+/// Simulation core of a race: track resources, physics, bot CPUs and race timing.
+/// Carries no rendering or input, so it also drives headless runs.
+pub struct RaceSimPlugin;
+
+/// Windowed race: the simulation plus meshes, sprites, camera and keyboard controls.
 pub struct RaceRuntimePlugin;
 
-impl Plugin for RaceRuntimePlugin {
+impl Plugin for RaceSimPlugin {
     fn build(&self, app: &mut App) {
         app.init_state::<SimState>()
             .insert_resource(Gravity::ZERO)
-            .insert_resource(Time::<Fixed>::from_duration(
-                std::time::Duration::from_secs_f32(1.0 / FIXED_TICK_HZ as f32),
-            ))
+            .insert_resource(Time::<Fixed>::from_hz(DEFAULT_TICK_HZ as f64))
+            .init_resource::<SimTickHz>()
             .insert_resource(RaceManager::default())
             .insert_resource(FollowCar::default())
-            .insert_resource(KartLongitudinalParams::default())
+            .init_resource::<CarPhysics>()
             .insert_resource(CpuFrequencySetting::default())
-            .add_systems(Startup, (setup_track, setup.after(setup_track)))
-            .add_systems(Startup, set_default_zoom.after(setup))
+            .init_resource::<SkidMarks>()
+            .init_resource::<AutoPauseSetting>()
+            .init_resource::<RaceCountdown>()
+            .init_resource::<RaceClock>()
+            .init_resource::<PhysicsSubsteps>()
+            .init_resource::<RadarRays>()
+            .init_resource::<OffTrackLimit>()
+            .init_resource::<RaceLaps>()
+            .init_resource::<RaceSeed>()
+            .init_resource::<ActiveTrack>()
+            .init_resource::<RaceStateHash>()
+            .init_resource::<ReplayRecorder>()
+            .add_systems(Startup, setup_track)
+            .add_systems(Startup, (apply_sim_tick_hz, apply_physics_substeps))
             .add_systems(Startup, pause_physics)
-            .add_systems(OnEnter(SimState::Racing), unpause_physics)
+            .add_systems(
+                OnEnter(SimState::Racing),
+                (mark_race_start, unpause_physics),
+            )
             .add_systems(OnEnter(SimState::Paused), pause_physics)
-            .add_systems(OnEnter(SimState::PreRace), pause_physics)
+            .add_systems(
+                OnEnter(SimState::PreRace),
+                (
+                    pause_physics,
+                    clear_skid_marks,
+                    reset_race_clock,
+                    reset_race_state_hash,
+                    reset_replay_recorder,
+                ),
+            )
             .add_systems(
                 Update,
                 (handle_spawn_resolved_event, apply_cpu_frequency_setting),
             )
-            .add_systems(Update, handle_car_input)
             .configure_sets(
                 FixedUpdate,
                 (CpuSystems::PreCpu, CpuSystems::Cpu, CpuSystems::PostCpu).chain(),
             )
+            .add_systems(
+                FixedUpdate,
+                (
+                    advance_countdown.run_if(in_state(SimState::Countdown)),
+                    (advance_race_clock, hash_race_state, record_replay_frame)
+                        .run_if(in_state(SimState::Racing)),
+                )
+                    .before(CpuSystems::PreCpu),
+            )
             .add_systems(
                 FixedUpdate,
                 (
                     devices::car_state_system.in_set(CpuSystems::PreCpu),
+                    update_race_started_flag
+                        .in_set(CpuSystems::PreCpu)
+                        .after(devices::car_state_system),
+                    track::track_progress_system.in_set(CpuSystems::PreCpu),
+                    track::checkpoint_system
+                        .in_set(CpuSystems::PreCpu)
+                        .after(track::track_progress_system),
+                    devices::lap_progress_system
+                        .in_set(CpuSystems::PreCpu)
+                        .after(track::track_progress_system),
+                    devices::track_ahead_system
+                        .in_set(CpuSystems::PreCpu)
+                        .after(track::track_progress_system),
+                    lap_timer_system
+                        .in_set(CpuSystems::PreCpu)
+                        .after(track::track_progress_system)
+                        .run_if(in_state(SimState::Racing)),
+                    off_track_system
+                        .in_set(CpuSystems::PreCpu)
+                        .run_if(in_state(SimState::Racing)),
                     devices::car_radar_system.in_set(CpuSystems::PreCpu),
+                    devices::speedometer_system.in_set(CpuSystems::PreCpu),
                     devices::track_radar_system.in_set(CpuSystems::PreCpu),
                     cpu_system::<RacingCpuConfig>.in_set(CpuSystems::Cpu),
                     devices::car_controls_system.in_set(CpuSystems::PostCpu),
+                    report_cpu_stops.in_set(CpuSystems::PostCpu),
                 )
-                    .run_if(in_state(SimState::Racing)),
+                    .run_if(bots_running),
             )
             .add_systems(
                 FixedUpdate,
-                apply_car_forces.run_if(in_state(SimState::Racing)),
+                (
+                    hold_retired_cars
+                        .after(CpuSystems::PostCpu)
+                        .before(apply_car_forces),
+                    apply_car_forces,
+                    age_skid_marks,
+                )
+                    .run_if(in_state(SimState::Racing)),
+            );
+    }
+}
+
+impl Plugin for RaceRuntimePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(RaceSimPlugin)
+            .add_systems(
+                Startup,
+                (
+                    spawn_track_meshes.after(setup_track),
+                    setup,
+                    set_default_zoom.after(setup),
+                ),
+            )
+            .add_systems(
+                Update,
+                (
+                    handle_car_input,
+                    attach_car_sprites,
+                    handle_load_track_request,
+                ),
             )
-            .add_systems(Update, (update_fps_counter, update_camera, draw_gizmos));
+            .add_systems(
+                Update,
+                reset_to_checkpoint.run_if(in_state(SimState::Racing)),
+            )
+            .add_systems(Update, auto_pause_on_focus_change)
+            .add_systems(
+                Update,
+                (
+                    update_fps_counter,
+                    update_camera,
+                    draw_gizmos,
+                    draw_skid_marks,
+                ),
+            );
     }
 }
 
@@ -71,8 +176,10 @@ impl Plugin for RaceRuntimePlugin {
 pub enum SimState {
     #[default]
     PreRace,
+    Countdown,
     Racing,
     Paused,
+    Spectating,
 }
 
 #[derive(Resource)]
@@ -81,6 +188,17 @@ pub struct RaceManager {
     pub next_car_id: u32,
 }
 
+impl RaceManager {
+    /// Every car has completed `RaceLaps` or retired; false with no cars.
+    pub fn race_finished(&self) -> bool {
+        !self.cars.is_empty()
+            && self
+                .cars
+                .iter()
+                .all(|car| car.finish_secs.is_some() || car.dnf)
+    }
+}
+
 impl Default for RaceManager {
     fn default() -> Self {
         Self {
@@ -92,17 +210,101 @@ impl Default for RaceManager {
 
 pub struct CarEntry {
     pub entity: Entity,
+    /// The number in the car's name; also picks its `color_for_car` tint.
+    pub id: u32,
     pub name: String,
     pub driver: DriverType,
     pub console_output: String,
+    pub laps_completed: u32,
+    pub last_lap_secs: Option<f32>,
+    pub best_lap_secs: Option<f32>,
+    /// Out of the race after leaving the track for longer than `OffTrackLimit`.
+    pub dnf: bool,
+    /// Race time at which the car completed `RaceLaps`, if it has.
+    pub finish_secs: Option<f32>,
 }
 
+/// A distinct tint per car id: successive ids step the hue by the golden
+/// ratio, so neighbouring ids land far apart on the color wheel.
+pub fn color_for_car(id: u32) -> Color {
+    const GOLDEN_RATIO_CONJUGATE: f32 = 0.618_034;
+    let hue = (id as f32 * GOLDEN_RATIO_CONJUGATE).fract() * 360.0;
+    Color::hsl(hue, 0.75, 0.65)
+}
+
+/// Tint of a car's kart sprite, set from `color_for_car` at spawn.
+#[derive(Component, Clone, Copy)]
+pub struct CarColor(pub Color);
+
 #[derive(Resource, Default)]
 pub struct FollowCar {
     pub target: Option<Entity>,
 }
 
-pub const FIXED_TICK_HZ: u32 = 200;
+pub const DEFAULT_TICK_HZ: u32 = 200;
+const MIN_TICK_HZ: u32 = 10;
+const MAX_TICK_HZ: u32 = 2_000;
+/// Below this rate a fast car covers more than its own length per tick, so
+/// collisions and off-track detection start to miss contacts.
+const MIN_STABLE_TICK_HZ: u32 = 60;
+
+/// Fixed simulation rate (`--tick-hz=<n>`, default 200). Physics, bot CPUs and
+/// race timing all advance once per tick, and bots read the tick length from
+/// `CarStateDevice` to keep their control math time-correct.
+///
+/// Part of the simulation definition, like `PhysicsSubsteps`: replays record
+/// the rate they were captured at.
+#[derive(Resource, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SimTickHz(u32);
+
+impl Default for SimTickHz {
+    fn default() -> Self {
+        Self(DEFAULT_TICK_HZ)
+    }
+}
+
+impl SimTickHz {
+    pub fn new(hz: u32) -> Self {
+        Self(hz.clamp(MIN_TICK_HZ, MAX_TICK_HZ))
+    }
+
+    pub fn hz(&self) -> u32 {
+        self.0
+    }
+
+    /// Length of one tick in microseconds, as published to bots.
+    pub fn tick_micros(&self) -> u32 {
+        1_000_000 / self.0
+    }
+
+    pub fn tick_duration(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(1) / self.0
+    }
+
+    /// Whether physics may tunnel or miss contacts at this rate.
+    pub fn is_below_stable(&self) -> bool {
+        self.0 < MIN_STABLE_TICK_HZ
+    }
+
+    pub fn secs_to_ticks(&self, secs: f32) -> u64 {
+        (secs * self.0 as f32).round() as u64
+    }
+
+    pub fn ticks_to_secs(&self, ticks: u64) -> f32 {
+        ticks as f32 / self.0 as f32
+    }
+}
+
+fn apply_sim_tick_hz(tick_hz: Res<SimTickHz>, mut fixed_time: ResMut<Time<Fixed>>) {
+    if tick_hz.is_below_stable() {
+        warn!(
+            "tick rate {} Hz is below {MIN_STABLE_TICK_HZ} Hz; physics may become unstable",
+            tick_hz.hz()
+        );
+    }
+    fixed_time.set_timestep(tick_hz.tick_duration());
+}
+
 const CPU_FREQUENCY_PRESETS_HZ: [u32; 10] = [
     1_000, 5_000, 10_000, 20_000, 50_000, 100_000, 200_000, 500_000, 1_000_000, 2_000_000,
 ];
@@ -125,8 +327,8 @@ impl CpuFrequencySetting {
         CPU_FREQUENCY_PRESETS_HZ[self.preset_index]
     }
 
-    pub fn instructions_per_update(&self) -> u32 {
-        (self.hz() / FIXED_TICK_HZ).max(1)
+    pub fn instructions_per_update(&self, tick_hz: SimTickHz) -> u32 {
+        (self.hz() / tick_hz.hz()).max(1)
     }
 
     pub fn step_up(&mut self) {
@@ -151,6 +353,435 @@ impl CpuFrequencySetting {
     }
 }
 
+/// Pauses a running race while the window is unfocused and resumes it on
+/// focus regain. Only races paused by this setting are resumed automatically.
+#[derive(Resource, Clone, Copy)]
+pub struct AutoPauseSetting {
+    enabled: bool,
+    paused_by_focus: bool,
+}
+
+impl Default for AutoPauseSetting {
+    fn default() -> Self {
+        Self::new(true)
+    }
+}
+
+impl AutoPauseSetting {
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            paused_by_focus: false,
+        }
+    }
+
+    /// Returns the state to transition to after a window focus change, if any.
+    fn on_focus_changed(&mut self, focused: bool, current: SimState) -> Option<SimState> {
+        if !self.enabled {
+            return None;
+        }
+
+        if !focused {
+            if current == SimState::Racing {
+                self.paused_by_focus = true;
+                return Some(SimState::Paused);
+            }
+            return None;
+        }
+
+        if std::mem::take(&mut self.paused_by_focus) && current == SimState::Paused {
+            return Some(SimState::Racing);
+        }
+        None
+    }
+}
+
+/// Grace period between pressing Start and the race going live. Physics stays
+/// paused while it runs, but bots already execute so they can boot and wait
+/// for the `race_started` flag. A zero duration starts the race immediately.
+#[derive(Resource, Clone, Copy)]
+pub struct RaceCountdown {
+    pub duration_s: f32,
+}
+
+impl Default for RaceCountdown {
+    fn default() -> Self {
+        Self { duration_s: 3.0 }
+    }
+}
+
+impl RaceCountdown {
+    pub fn new(duration_s: f32) -> Self {
+        Self {
+            duration_s: duration_s.max(0.0),
+        }
+    }
+
+    pub fn ticks(&self, tick_hz: SimTickHz) -> u64 {
+        tick_hz.secs_to_ticks(self.duration_s)
+    }
+
+    /// State the Start button should move to from `PreRace`.
+    pub fn start_state(&self, tick_hz: SimTickHz) -> SimState {
+        if self.ticks(tick_hz) > 0 {
+            SimState::Countdown
+        } else {
+            SimState::Racing
+        }
+    }
+}
+
+/// Number of avian solver substeps per fixed tick. More substeps make contacts
+/// between fast cars stiffer and less prone to tunneling. Car forces and bots
+/// still run once per fixed tick: `apply_car_forces` evaluates them from the
+/// tick's start state and avian applies them unchanged on every substep.
+///
+/// Part of the simulation definition: runs are only comparable (and replays
+/// only reproducible) when recorded with the same substep count.
+#[derive(Resource, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PhysicsSubsteps {
+    count: u32,
+}
+
+impl Default for PhysicsSubsteps {
+    fn default() -> Self {
+        Self {
+            count: DEFAULT_PHYSICS_SUBSTEPS,
+        }
+    }
+}
+
+impl PhysicsSubsteps {
+    pub fn new(count: u32) -> Self {
+        Self {
+            count: count.clamp(1, MAX_PHYSICS_SUBSTEPS),
+        }
+    }
+
+    pub fn count(&self) -> u32 {
+        self.count
+    }
+}
+
+/// Rays cast by each bot car's `TrackRadarDevice`, set with `--radar-rays=<n>`.
+///
+/// Part of the simulation definition: bots that read every ray see a different
+/// world with a different count, so compare runs only at the same setting.
+#[derive(Resource, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RadarRays {
+    count: usize,
+}
+
+impl Default for RadarRays {
+    fn default() -> Self {
+        Self {
+            count: devices::TRACK_RADAR_DEFAULT_RAYS,
+        }
+    }
+}
+
+impl RadarRays {
+    pub fn new(count: usize) -> Self {
+        Self {
+            count: count.clamp(1, devices::TRACK_RADAR_MAX_RAYS),
+        }
+    }
+
+    pub fn count(&self) -> usize {
+        self.count
+    }
+}
+
+/// How long a car may stay off the track surface before it is out of the race
+/// (DNF), set with `--dnf-after=<secs>`. Zero never retires a car.
+#[derive(Resource, Clone, Copy, Debug, PartialEq)]
+pub struct OffTrackLimit {
+    pub secs: f32,
+}
+
+impl Default for OffTrackLimit {
+    fn default() -> Self {
+        Self { secs: 5.0 }
+    }
+}
+
+impl OffTrackLimit {
+    pub fn new(secs: f32) -> Self {
+        Self {
+            secs: secs.max(0.0),
+        }
+    }
+
+    /// Consecutive off-track ticks allowed, or `None` when the limit is disabled.
+    pub fn ticks(&self, tick_hz: SimTickHz) -> Option<u64> {
+        let ticks = tick_hz.secs_to_ticks(self.secs);
+        (ticks > 0).then_some(ticks)
+    }
+}
+
+/// Laps that make up a race (`--laps=<n>`, default 3). A car that completes
+/// them gets its `CarEntry::finish_secs`.
+#[derive(Resource, Clone, Copy, Debug, PartialEq)]
+pub struct RaceLaps(pub u32);
+
+impl RaceLaps {
+    pub fn new(laps: u32) -> Self {
+        Self(laps.max(1))
+    }
+}
+
+impl Default for RaceLaps {
+    fn default() -> Self {
+        Self(3)
+    }
+}
+
+/// The track being raced: the built-in track until a `LoadTrackRequest`
+/// swaps in a file from the assets directory.
+#[derive(Resource, Clone)]
+pub struct ActiveTrack {
+    /// Source file, or `None` for the built-in track.
+    pub path: Option<PathBuf>,
+    pub file: TrackFile,
+}
+
+impl ActiveTrack {
+    /// Id that race records are filed under: the track file's stem, or the
+    /// track name for the built-in track.
+    pub fn track_id(&self) -> String {
+        self.path
+            .as_deref()
+            .and_then(std::path::Path::file_stem)
+            .map_or_else(
+                || self.file.metadata.name.clone(),
+                |stem| stem.to_string_lossy().into_owned(),
+            )
+    }
+}
+
+impl Default for ActiveTrack {
+    fn default() -> Self {
+        Self {
+            path: None,
+            file: load_race_track(),
+        }
+    }
+}
+
+/// Directory the track selector lists, resolved like Bevy's asset root:
+/// `BEVY_ASSET_ROOT` or `CARGO_MANIFEST_DIR` if set, else the executable's directory.
+#[cfg(not(target_arch = "wasm32"))]
+fn track_asset_dir() -> PathBuf {
+    std::env::var_os("BEVY_ASSET_ROOT")
+        .or_else(|| std::env::var_os("CARGO_MANIFEST_DIR"))
+        .map(PathBuf::from)
+        .or_else(|| {
+            std::env::current_exe()
+                .ok()?
+                .parent()
+                .map(std::path::Path::to_path_buf)
+        })
+        .unwrap_or_default()
+        .join("assets")
+}
+
+/// Track files offered by the track selector. The web build cannot list
+/// directories and only races the built-in track.
+pub fn available_tracks() -> Vec<PathBuf> {
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        let dir = track_asset_dir();
+        botracers_game::track_format::list_track_files(&dir).unwrap_or_else(|error| {
+            warn!("cannot list tracks in {}: {error}", dir.display());
+            Vec::new()
+        })
+    }
+    #[cfg(target_arch = "wasm32")]
+    {
+        Vec::new()
+    }
+}
+
+/// Seed for everything random in a race, set with `--seed=<n>` and printed at
+/// startup. The simulation has no random inputs yet, so today's runs repeat
+/// whatever the seed; anything that adds some (grid jitter, sensor noise) must
+/// derive it from this seed rather than from an OS source.
+#[derive(Resource, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RaceSeed(pub u64);
+
+impl Default for RaceSeed {
+    /// Derived from the wall clock. The web build has no clock without extra
+    /// dependencies and always starts from 0.
+    fn default() -> Self {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let nanos = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map_or(0, |elapsed| elapsed.as_nanos() as u64);
+            Self(nanos)
+        }
+        #[cfg(target_arch = "wasm32")]
+        {
+            Self(0)
+        }
+    }
+}
+
+/// Matches avian's own `SubstepCount` default.
+const DEFAULT_PHYSICS_SUBSTEPS: u32 = 6;
+const MAX_PHYSICS_SUBSTEPS: u32 = 64;
+
+fn apply_physics_substeps(mut commands: Commands, substeps: Res<PhysicsSubsteps>) {
+    commands.insert_resource(SubstepCount(substeps.count()));
+}
+
+/// Fixed-tick clock covering the countdown and the race. `start_tick` is set
+/// when the countdown elapses; race timing is measured from it.
+#[derive(Resource, Default)]
+pub struct RaceClock {
+    tick: u64,
+    start_tick: Option<u64>,
+}
+
+impl RaceClock {
+    /// Advances one countdown tick; returns true once the countdown has elapsed.
+    /// Never advances past the countdown so extra ticks before the state
+    /// transition applies do not shift the start.
+    fn advance_countdown(&mut self, countdown_ticks: u64) -> bool {
+        if self.tick < countdown_ticks {
+            self.tick += 1;
+        }
+        self.tick >= countdown_ticks
+    }
+
+    fn advance_race(&mut self) {
+        if self.start_tick.is_some() {
+            self.tick += 1;
+        }
+    }
+
+    fn mark_start(&mut self) {
+        self.start_tick.get_or_insert(self.tick);
+    }
+
+    pub fn start_tick(&self) -> Option<u64> {
+        self.start_tick
+    }
+
+    /// Fixed ticks since the race started, or `None` during the countdown.
+    pub fn race_ticks(&self) -> Option<u64> {
+        self.start_tick.map(|start| self.tick - start)
+    }
+
+    pub fn race_time_secs(&self, tick_hz: SimTickHz) -> Option<f32> {
+        self.race_ticks().map(|ticks| tick_hz.ticks_to_secs(ticks))
+    }
+
+    pub fn countdown_remaining_secs(&self, countdown: &RaceCountdown, tick_hz: SimTickHz) -> f32 {
+        tick_hz.ticks_to_secs(countdown.ticks(tick_hz).saturating_sub(self.tick))
+    }
+}
+
+/// Determinism hash of the current race: every car's pose, in grid order, at
+/// the start of each racing tick. Published with results as `state_hash`.
+#[derive(Resource, Default)]
+pub struct RaceStateHash(pub StateHasher);
+
+fn reset_race_state_hash(mut hash: ResMut<RaceStateHash>) {
+    hash.0 = StateHasher::default();
+}
+
+fn hash_race_state(
+    manager: Res<RaceManager>,
+    car_query: Query<&Transform, With<Car>>,
+    mut hash: ResMut<RaceStateHash>,
+) {
+    for (index, entry) in manager.cars.iter().enumerate() {
+        let Ok(transform) = car_query.get(entry.entity) else {
+            continue;
+        };
+        hash.0.push_car(
+            index as u32,
+            transform.translation.xy(),
+            transform.up().xy().to_angle(),
+        );
+    }
+    hash.0.finish_tick();
+}
+
+/// Per-tick poses and control inputs of every car while racing, in grid order.
+/// Reset on `PreRace`; `to_replay`/`to_json` produce the `RaceReplay` stored
+/// as a race record's `replay_json` and played back by `ReplayPlugin`.
+#[derive(Resource, Default)]
+pub struct ReplayRecorder {
+    cars: Vec<ReplayCar>,
+    frames: Vec<ReplayFrame>,
+}
+
+impl ReplayRecorder {
+    fn clear(&mut self) {
+        self.cars.clear();
+        self.frames.clear();
+    }
+
+    /// Appends one tick. The car list is fixed by the first recorded frame.
+    fn record_frame(&mut self, names: &[&str], frame: ReplayFrame) {
+        if self.frames.is_empty() {
+            self.cars = names
+                .iter()
+                .map(|name| ReplayCar {
+                    name: name.to_string(),
+                })
+                .collect();
+        }
+        self.frames.push(frame);
+    }
+
+    pub fn to_replay(&self, tick_hz: SimTickHz) -> RaceReplay {
+        RaceReplay {
+            tick_hz: tick_hz.hz(),
+            cars: self.cars.clone(),
+            frames: self.frames.clone(),
+        }
+    }
+
+    pub fn to_json(&self, tick_hz: SimTickHz) -> serde_json::Result<String> {
+        serde_json::to_string(&self.to_replay(tick_hz))
+    }
+}
+
+fn reset_replay_recorder(mut recorder: ResMut<ReplayRecorder>) {
+    recorder.clear();
+}
+
+fn record_replay_frame(
+    manager: Res<RaceManager>,
+    car_query: Query<(&Transform, &Car)>,
+    mut recorder: ResMut<ReplayRecorder>,
+) {
+    let mut names = Vec::with_capacity(manager.cars.len());
+    let mut poses = Vec::with_capacity(manager.cars.len());
+    let mut controls = Vec::with_capacity(manager.cars.len());
+    for entry in &manager.cars {
+        let Ok((transform, car)) = car_query.get(entry.entity) else {
+            continue;
+        };
+        let (heading, _, _) = transform.rotation.to_euler(EulerRot::ZYX);
+        names.push(entry.name.as_str());
+        poses.push(ReplayCarPose {
+            x: transform.translation.x,
+            y: transform.translation.y,
+            heading,
+        });
+        controls.push(ReplayCarControls {
+            steer: car.steer,
+            accelerator: car.accelerator,
+            brake: car.brake,
+        });
+    }
+    recorder.record_frame(&names, ReplayFrame { poses, controls });
+}
+
 #[derive(Component)]
 pub struct CarLabel {
     pub name: String,
@@ -159,6 +790,23 @@ pub struct CarLabel {
 #[derive(Component)]
 pub struct DebugGizmos;
 
+/// Rolling phase of the wheels (radians), integrated from `Car::wheel_omega`.
+#[derive(Component, Default)]
+pub struct WheelSpin {
+    pub phase: f32,
+}
+
+/// Short-lived skid marks left by wheels of gizmo-enabled cars.
+#[derive(Resource, Default)]
+pub struct SkidMarks {
+    marks: Vec<SkidMark>,
+}
+
+struct SkidMark {
+    position: Vec2,
+    age_s: f32,
+}
+
 #[derive(Component, Default, Clone)]
 pub struct LongitudinalDebugData {
     pub speed_mps: f32,
@@ -180,7 +828,7 @@ pub struct LongitudinalDebugData {
     pub brake: f32,
 }
 
-#[derive(Resource, Clone, Copy)]
+#[derive(Clone, Copy)]
 struct KartLongitudinalParams {
     mass_kg: f32,
     wheel_radius_m: f32,
@@ -229,8 +877,59 @@ impl Default for KartLongitudinalParams {
     }
 }
 
+/// The kart model `apply_car_forces` drives with: the defaults below, with
+/// any overrides from the active track's `[physics]` table applied.
+#[derive(Resource, Clone, Copy)]
+struct CarPhysics {
+    longitudinal: KartLongitudinalParams,
+    /// Cap on each wheel's sideways grip acceleration.
+    lateral_grip: f32,
+    /// Sideways grip per unit of wheel speed, up to `lateral_grip`.
+    lateral_grip_per_speed: f32,
+    wheel_base: f32,
+    wheel_track: f32,
+}
+
+impl Default for CarPhysics {
+    fn default() -> Self {
+        Self {
+            longitudinal: KartLongitudinalParams::default(),
+            lateral_grip: 10.0,
+            lateral_grip_per_speed: 5.0,
+            wheel_base: WHEEL_BASE,
+            wheel_track: WHEEL_TRACK,
+        }
+    }
+}
+
+impl CarPhysics {
+    fn from_track(overrides: &TrackPhysics) -> Self {
+        let defaults = Self::default();
+        let longitudinal = defaults.longitudinal;
+        Self {
+            longitudinal: KartLongitudinalParams {
+                mass_kg: overrides.mass_kg.unwrap_or(longitudinal.mass_kg),
+                torque_peak_nm: overrides
+                    .torque_peak_nm
+                    .unwrap_or(longitudinal.torque_peak_nm),
+                brake_max_axle_nm: overrides
+                    .brake_max_axle_nm
+                    .unwrap_or(longitudinal.brake_max_axle_nm),
+                tire_mu: overrides.tire_mu.unwrap_or(longitudinal.tire_mu),
+                ..longitudinal
+            },
+            lateral_grip: overrides.lateral_grip.unwrap_or(defaults.lateral_grip),
+            lateral_grip_per_speed: overrides
+                .lateral_grip_per_speed
+                .unwrap_or(defaults.lateral_grip_per_speed),
+            wheel_base: overrides.wheel_base.unwrap_or(defaults.wheel_base),
+            wheel_track: overrides.wheel_track.unwrap_or(defaults.wheel_track),
+        }
+    }
+}
+
 #[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
-enum CpuSystems {
+pub(crate) enum CpuSystems {
     PreCpu,
     Cpu,
     PostCpu,
@@ -238,9 +937,45 @@ enum CpuSystems {
 
 #[cfg(test)]
 mod tests {
+    use bevy::{prelude::Entity, window::WindowFocused};
+
     use super::{
-        CpuFrequencySetting, KartLongitudinalParams, engine_torque_full, governor_scale, smoothstep,
+        AutoPauseSetting, Car, CarEntry, CarPhysics, CpuFrequencySetting, DEFAULT_TICK_HZ,
+        EmulatorDriver, KartLongitudinalParams, OffTrack, OffTrackLimit, PhysicsSubsteps,
+        RaceClock, RaceCountdown, RaceManager, RadarRays, ReplayRecorder, SimState, SimTickHz,
+        TrackRadarBorders, apply_sim_tick_hz, auto_pause_on_focus_change, color_for_car,
+        engine_torque_full, governor_scale, hold_retired_cars, is_skidding, lateral_slip,
+        off_track_system, smoothstep, spawn_car_body, spawn_car_entry, update_race_started_flag,
     };
+    use botracers_protocol::{RaceReplay, ReplayCarControls, ReplayCarPose, ReplayFrame};
+
+    /// An app running only `auto_pause_on_focus_change`, starting in `state`.
+    fn focus_app(setting: AutoPauseSetting, state: SimState) -> bevy::prelude::App {
+        use bevy::{prelude::*, state::app::StatesPlugin};
+
+        let mut app = App::new();
+        app.add_plugins(StatesPlugin)
+            .insert_state(state)
+            .insert_resource(setting)
+            .add_message::<WindowFocused>()
+            .add_systems(Update, auto_pause_on_focus_change);
+        app.update();
+        app
+    }
+
+    /// Delivers a window focus change and returns the state it leads to.
+    fn change_focus(app: &mut bevy::prelude::App, focused: bool) -> SimState {
+        use bevy::prelude::State;
+
+        app.world_mut().write_message(WindowFocused {
+            window: Entity::PLACEHOLDER,
+            focused,
+        });
+        // The system queues the transition; the next frame applies it.
+        app.update();
+        app.update();
+        *app.world().resource::<State<SimState>>().get()
+    }
 
     #[test]
     fn cpu_frequency_setting_clamps_at_boundaries() {
@@ -260,14 +995,21 @@ mod tests {
     #[test]
     fn cpu_frequency_setting_maps_to_instruction_budget() {
         let setting = CpuFrequencySetting::default();
-        assert_eq!(setting.instructions_per_update(), 10_000);
+        assert_eq!(
+            setting.instructions_per_update(SimTickHz::default()),
+            10_000
+        );
+        assert_eq!(
+            setting.instructions_per_update(SimTickHz::new(1_000)),
+            2_000
+        );
 
         let mut setting = CpuFrequencySetting::default();
         for _ in 0..6 {
             setting.step_down();
         }
         assert_eq!(setting.hz(), 20_000);
-        assert_eq!(setting.instructions_per_update(), 100);
+        assert_eq!(setting.instructions_per_update(SimTickHz::default()), 100);
     }
 
     #[test]
@@ -282,6 +1024,116 @@ mod tests {
         assert_eq!(setting.format_hz_label(), "20 kHz");
     }
 
+    #[test]
+    fn auto_pause_pauses_on_focus_loss_and_resumes_on_focus_regain() {
+        let mut app = focus_app(AutoPauseSetting::default(), SimState::Racing);
+        assert_eq!(change_focus(&mut app, false), SimState::Paused);
+        assert_eq!(change_focus(&mut app, true), SimState::Racing);
+        assert_eq!(change_focus(&mut app, true), SimState::Racing);
+    }
+
+    #[test]
+    fn auto_pause_keeps_manual_pause_and_respects_disabled_flag() {
+        let mut paused = focus_app(AutoPauseSetting::default(), SimState::Paused);
+        assert_eq!(change_focus(&mut paused, false), SimState::Paused);
+        assert_eq!(change_focus(&mut paused, true), SimState::Paused);
+
+        let mut pre_race = focus_app(AutoPauseSetting::default(), SimState::PreRace);
+        assert_eq!(change_focus(&mut pre_race, false), SimState::PreRace);
+
+        let mut disabled = focus_app(AutoPauseSetting::new(false), SimState::Racing);
+        assert_eq!(change_focus(&mut disabled, false), SimState::Racing);
+    }
+
+    #[test]
+    fn race_timing_starts_only_after_countdown_elapses() {
+        let tick_hz = SimTickHz::default();
+        let countdown = RaceCountdown::new(0.05);
+        assert_eq!(countdown.ticks(tick_hz), 10);
+        assert_eq!(countdown.start_state(tick_hz), SimState::Countdown);
+
+        let mut clock = RaceClock::default();
+        for _ in 0..9 {
+            assert!(!clock.advance_countdown(countdown.ticks(tick_hz)));
+            clock.advance_race();
+            assert_eq!(clock.race_ticks(), None);
+        }
+        assert!(clock.advance_countdown(countdown.ticks(tick_hz)));
+        assert_eq!(clock.countdown_remaining_secs(&countdown, tick_hz), 0.0);
+        // Ticks that run before the state transition applies do not shift the start.
+        assert!(clock.advance_countdown(countdown.ticks(tick_hz)));
+        assert_eq!(clock.race_time_secs(tick_hz), None);
+
+        clock.mark_start();
+        assert_eq!(clock.start_tick(), Some(10));
+        assert_eq!(clock.race_ticks(), Some(0));
+        for _ in 0..DEFAULT_TICK_HZ {
+            clock.advance_race();
+        }
+        assert_eq!(clock.race_time_secs(tick_hz), Some(1.0));
+        assert_eq!(clock.race_time_secs(SimTickHz::new(100)), Some(2.0));
+
+        // Resuming from pause must not move the start.
+        clock.mark_start();
+        assert_eq!(clock.start_tick(), Some(10));
+        assert_eq!(
+            RaceCountdown::new(0.0).start_state(tick_hz),
+            SimState::Racing
+        );
+    }
+
+    #[test]
+    fn physics_substeps_default_to_avian_and_clamp_to_supported_range() {
+        assert_eq!(
+            PhysicsSubsteps::default().count(),
+            avian2d::prelude::SubstepCount::default().0
+        );
+        assert_eq!(PhysicsSubsteps::new(0).count(), 1);
+        assert_eq!(PhysicsSubsteps::new(12).count(), 12);
+        assert_eq!(PhysicsSubsteps::new(1_000).count(), 64);
+    }
+
+    #[test]
+    fn sim_tick_hz_clamps_and_flags_unstable_rates() {
+        assert_eq!(SimTickHz::default().hz(), DEFAULT_TICK_HZ);
+        assert_eq!(SimTickHz::default().tick_micros(), 5_000);
+        assert_eq!(SimTickHz::new(0).hz(), 10);
+        assert_eq!(SimTickHz::new(1_000_000).hz(), 2_000);
+        assert!(SimTickHz::new(30).is_below_stable());
+        assert!(!SimTickHz::new(60).is_below_stable());
+        assert_eq!(SimTickHz::new(400).secs_to_ticks(1.5), 600);
+    }
+
+    #[test]
+    fn configured_tick_rate_reaches_fixed_time_and_car_state() {
+        use bevy::ecs::system::RunSystemOnce;
+        use bevy::prelude::{Fixed, Time, World};
+        use botracers_game::devices::CarStateDevice;
+        use emulator::cpu::Device;
+
+        let mut world = World::new();
+        world.insert_resource(Time::<Fixed>::default());
+        world.insert_resource(SimTickHz::new(50));
+        world.insert_resource(RaceClock::default());
+        let car = world.spawn(CarStateDevice::default()).id();
+
+        world
+            .run_system_once(apply_sim_tick_hz)
+            .expect("apply tick rate");
+        world
+            .run_system_once(update_race_started_flag)
+            .expect("update car state");
+
+        assert_eq!(world.resource::<SimTickHz>().hz(), 50);
+        assert_eq!(
+            world.resource::<Time<Fixed>>().timestep(),
+            std::time::Duration::from_millis(20)
+        );
+        let state = world.get::<CarStateDevice>(car).expect("car state device");
+        assert_eq!(state.load(0x18, 32), Ok(20_000));
+        assert_eq!(state.load(0x14, 32), Ok(0));
+    }
+
     #[test]
     fn smoothstep_clamps_and_is_monotonic() {
         assert_eq!(smoothstep(2.0, 4.0, 1.0), 0.0);
@@ -308,76 +1160,440 @@ mod tests {
     }
 
     #[test]
-    fn governor_reduces_torque_above_redline() {
-        let params = KartLongitudinalParams::default();
-        assert_eq!(governor_scale(params.redline_rpm, &params), 1.0);
-        assert!(governor_scale(params.redline_rpm + 250.0, &params) < 1.0);
-        assert_eq!(governor_scale(params.redline_rpm + 1000.0, &params), 0.0);
-    }
+    fn governor_reduces_torque_above_redline() {
+        let params = KartLongitudinalParams::default();
+        assert_eq!(governor_scale(params.redline_rpm, &params), 1.0);
+        assert!(governor_scale(params.redline_rpm + 250.0, &params) < 1.0);
+        assert_eq!(governor_scale(params.redline_rpm + 1000.0, &params), 0.0);
+    }
+
+    #[test]
+    fn skid_threshold_applies_to_slip_magnitude() {
+        assert!(!is_skidding(0.0));
+        assert!(!is_skidding(0.2));
+        assert!(is_skidding(0.5));
+        assert!(is_skidding(-0.5));
+
+        let left = bevy::math::Vec2::Y;
+        assert_eq!(lateral_slip(bevy::math::Vec2::new(0.0, 3.0), left), 1.0);
+        assert_eq!(lateral_slip(bevy::math::Vec2::new(3.0, 0.0), left), 0.0);
+        assert_eq!(lateral_slip(bevy::math::Vec2::new(0.0, 0.05), left), 0.0);
+    }
+
+    #[test]
+    fn traction_clamp_enforces_limit() {
+        let params = KartLongitudinalParams::default();
+        let limit = params.tire_mu * params.mass_kg * 9.81;
+        let clamped = (limit * 3.0).clamp(-limit, limit);
+        assert!(clamped <= limit);
+        assert!(clamped >= -limit);
+    }
+
+    #[test]
+    fn car_physics_applies_track_overrides_over_defaults() {
+        use botracers_game::track_format::TrackFile;
+
+        let points = "control_points = [[0.0, 0.0], [10.0, 0.0], [0.0, 10.0]]\n";
+        let track = TrackFile::parse(points).unwrap();
+        let physics = CarPhysics::from_track(&track.physics);
+        let defaults = CarPhysics::default();
+        assert_eq!(physics.lateral_grip, 10.0);
+        assert_eq!(physics.lateral_grip_per_speed, 5.0);
+        assert_eq!(physics.wheel_base, 1.18);
+        assert_eq!(physics.wheel_track, 0.95);
+        assert_eq!(
+            physics.longitudinal.brake_max_axle_nm,
+            defaults.longitudinal.brake_max_axle_nm
+        );
+
+        let text = format!("{points}[physics]\nbrake_max_axle_nm = 600.0\nlateral_grip = 14.0\n");
+        let physics = CarPhysics::from_track(&TrackFile::parse(&text).unwrap().physics);
+        assert_eq!(physics.longitudinal.brake_max_axle_nm, 600.0);
+        assert_eq!(physics.lateral_grip, 14.0);
+        assert_eq!(
+            physics.longitudinal.torque_peak_nm,
+            defaults.longitudinal.torque_peak_nm
+        );
+        assert_eq!(physics.wheel_base, defaults.wheel_base);
+    }
+
+    #[test]
+    fn overlapping_cars_are_pushed_apart() {
+        use std::time::Duration;
+
+        use avian2d::prelude::{Gravity, PhysicsPlugins};
+        use bevy::{prelude::*, scene::ScenePlugin, time::TimeUpdateStrategy};
+
+        let mut app = App::new();
+        app.add_plugins((
+            MinimalPlugins,
+            TransformPlugin,
+            AssetPlugin::default(),
+            ScenePlugin,
+        ))
+        .init_asset::<Mesh>()
+        .add_plugins(PhysicsPlugins::default())
+        .insert_resource(Gravity::ZERO)
+        .insert_resource(Time::<Fixed>::from_hz(DEFAULT_TICK_HZ as f64))
+        .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_nanos(
+            1_000_000_000 / DEFAULT_TICK_HZ as u64,
+        )));
+        app.finish();
+        app.cleanup();
+
+        // Side by side, half a car width apart: the colliders overlap.
+        let (left, right) = {
+            let world = app.world_mut();
+            let mut commands = world.commands();
+            let physics = CarPhysics::default();
+            let left = spawn_car_body(&mut commands, Vec2::ZERO, "Car 1", &physics);
+            let right = spawn_car_body(&mut commands, Vec2::new(0.0, 0.6), "Car 2", &physics);
+            drop(commands);
+            world.flush();
+            (left, right)
+        };
+
+        for _ in 0..100 {
+            app.update();
+        }
+
+        let position = |entity: Entity| {
+            app.world()
+                .get::<Transform>(entity)
+                .expect("car transform")
+                .translation
+                .truncate()
+        };
+        let distance = position(left).distance(position(right));
+        assert!(distance > 1.2, "cars still overlap: {distance}");
+    }
+
+    #[test]
+    fn consecutive_car_ids_get_distinct_hues() {
+        use bevy::color::Color;
+
+        let hue = |id: u32| -> f32 {
+            let Color::Hsla(hsla) = color_for_car(id) else {
+                panic!("car colors are built as HSL");
+            };
+            hsla.hue
+        };
+        for id in 1..64 {
+            let delta = (hue(id) - hue(id + 1)).abs();
+            let delta = delta.min(360.0 - delta);
+            assert!(
+                delta > 90.0,
+                "ids {id} and {} are {delta} degrees apart",
+                id + 1
+            );
+        }
+    }
+
+    #[test]
+    fn manual_car_spawns_without_cpu_and_only_once() {
+        use bevy::prelude::World;
+        use botracers_game::{track, track_format::TrackFile};
+        use emulator::bevy::CpuComponent;
+
+        use super::ActiveTrack;
+        use crate::game_api::DriverType;
+
+        let track_file = TrackFile::load_builtin().expect("builtin track");
+        let track_spline = track::TrackSpline {
+            spline: track::build_spline(&track_file.control_points_vec2()),
+        };
+        let mut world = World::new();
+        let mut manager = RaceManager::default();
+        let mut commands = world.commands();
+        for _ in 0..2 {
+            spawn_car_entry(
+                &mut commands,
+                &track_spline,
+                &ActiveTrack::default(),
+                &mut manager,
+                CpuFrequencySetting::default().instructions_per_update(SimTickHz::default()),
+                RadarRays::default(),
+                DriverType::Manual,
+                &[],
+            );
+        }
+        drop(commands);
+        world.flush();
+
+        assert_eq!(manager.cars.len(), 1);
+        let car = manager.cars[0].entity;
+        assert!(world.get::<Car>(car).is_some());
+        assert!(world.get::<CpuComponent>(car).is_none());
+        assert!(world.get::<EmulatorDriver>(car).is_none());
+    }
+
+    #[test]
+    fn retired_cars_halt_their_cpu_and_hold_the_brakes() {
+        use bevy::ecs::system::RunSystemOnce;
+        use bevy::prelude::{Transform, World};
+        use emulator::bevy::CpuHalted;
+
+        use crate::game_api::DriverType;
+
+        let driving = || Car {
+            steer: 0.3,
+            accelerator: 1.0,
+            brake: 0.0,
+            engine_rpm: 0.0,
+            wheel_omega: 0.0,
+        };
+        let mut world = World::new();
+        world.insert_resource(OffTrackLimit::new(0.01));
+        world.insert_resource(SimTickHz::default());
+        // No borders: every position is off the track.
+        world.insert_resource(TrackRadarBorders {
+            inner: Vec::new(),
+            outer: Vec::new(),
+        });
+        let off_track = world
+            .spawn((Transform::default(), OffTrack::default(), driving()))
+            .id();
+        // A bot car that finished its laps.
+        let finished = world.spawn((driving(), CpuHalted)).id();
+        world.insert_resource(RaceManager {
+            cars: vec![CarEntry {
+                entity: off_track,
+                id: 1,
+                name: "Car 1".to_string(),
+                driver: DriverType::Manual,
+                console_output: String::new(),
+                laps_completed: 0,
+                last_lap_secs: None,
+                best_lap_secs: None,
+                dnf: false,
+                finish_secs: None,
+            }],
+            next_car_id: 2,
+        });
+
+        let limit_ticks = OffTrackLimit::new(0.01)
+            .ticks(SimTickHz::default())
+            .expect("limit enabled");
+        for _ in 0..limit_ticks {
+            world
+                .run_system_once(off_track_system)
+                .expect("off track system");
+        }
+        assert!(world.get::<CpuHalted>(off_track).is_none());
+        world
+            .run_system_once(off_track_system)
+            .expect("off track system");
+        assert!(world.get::<CpuHalted>(off_track).is_some());
+        assert!(world.resource::<RaceManager>().cars[0].dnf);
+
+        world
+            .run_system_once(hold_retired_cars)
+            .expect("hold retired cars");
+        for car in [off_track, finished] {
+            let car = world.get::<Car>(car).expect("car");
+            assert_eq!((car.steer, car.accelerator, car.brake), (0.0, 0.0, 1.0));
+        }
+    }
+
+    #[test]
+    fn recorded_replay_round_trips_through_json() {
+        let mut recorder = ReplayRecorder::default();
+        for tick in 0..3 {
+            let x = tick as f32 * 0.5;
+            recorder.record_frame(
+                &["Car 1", "Car 2"],
+                ReplayFrame {
+                    poses: vec![
+                        ReplayCarPose {
+                            x,
+                            y: 0.0,
+                            heading: 0.0,
+                        },
+                        ReplayCarPose {
+                            x: -x,
+                            y: 2.0,
+                            heading: 1.0,
+                        },
+                    ],
+                    controls: vec![
+                        ReplayCarControls {
+                            steer: 0.25,
+                            accelerator: 1.0,
+                            brake: 0.0,
+                        },
+                        ReplayCarControls::default(),
+                    ],
+                },
+            );
+        }
 
-    #[test]
-    fn traction_clamp_enforces_limit() {
-        let params = KartLongitudinalParams::default();
-        let limit = params.tire_mu * params.mass_kg * 9.81;
-        let clamped = (limit * 3.0).clamp(-limit, limit);
-        assert!(clamped <= limit);
-        assert!(clamped >= -limit);
+        let json = recorder
+            .to_json(SimTickHz::new(120))
+            .expect("serialize replay");
+        let replay: RaceReplay = serde_json::from_str(&json).expect("parse replay");
+        assert_eq!(replay.tick_hz, 120);
+        assert_eq!(replay.cars.len(), 2);
+        assert_eq!(replay.cars[1].name, "Car 2");
+        assert_eq!(replay.frames.len(), recorder.frames.len());
+        assert_eq!(replay.frames.len(), 3);
+        assert_eq!(replay.frames[2].poses[1].x, -1.0);
+        assert_eq!(replay.frames[2].controls[0].steer, 0.25);
     }
 }
 
+/// Default kart geometry; a track's `[physics]` table may override both.
 const WHEEL_BASE: f32 = 1.18;
 const WHEEL_TRACK: f32 = 0.95;
+/// Lateral slip (sine of the wheel slip angle) above which a wheel leaves skid marks.
+const SKID_SLIP_THRESHOLD: f32 = 0.35;
+const SKID_MARK_LIFETIME_S: f32 = 1.5;
+const MAX_SKID_MARKS: usize = 2000;
+/// Bounciness of car-to-car contacts: enough for a visible knock without
+/// launching cars apart.
+const CAR_RESTITUTION: f32 = 0.3;
+
+/// Collision layers of race objects. Every car uses the `Car` layer, whoever drives it.
+#[derive(PhysicsLayer, Default, Clone, Copy, Debug)]
+pub enum GameLayer {
+    #[default]
+    Default,
+    Car,
+    /// Solid track boundaries; cars collide with anything placed on this layer.
+    TrackBorder,
+}
 
-fn setup_track(
-    mut commands: Commands,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<ColorMaterial>>,
-) {
-    let track_file =
-        TrackFile::load_builtin().unwrap_or_else(|_| panic!("Failed to load track file"));
+/// Cars bump into each other and into track borders.
+fn car_collision_layers() -> CollisionLayers {
+    CollisionLayers::new(GameLayer::Car, [GameLayer::Car, GameLayer::TrackBorder])
+}
 
-    let control_points = track_file.control_points_vec2();
-    let track_width = track_file.metadata.track_width;
-    let kerb_width = track_file.metadata.kerb_width;
+/// The race track, or `TrackFile::fallback()` if the built-in file is invalid.
+fn load_race_track() -> TrackFile {
+    TrackFile::load_builtin().unwrap_or_else(|error| {
+        error!("Failed to load track, using the fallback oval: {error}");
+        TrackFile::fallback()
+    })
+}
 
-    commands.spawn((
-        Mesh2d(meshes.add(Rectangle::new(800.0, 800.0))),
-        MeshMaterial2d(materials.add(Color::srgb(0.2, 0.6, 0.2))),
-        Transform::from_xyz(0.0, 0.0, -1.0),
-    ));
+pub(crate) fn setup_track(mut commands: Commands, track: Res<ActiveTrack>) {
+    insert_track_resources(&mut commands, &track.file);
+}
+
+/// Spline, progress table, radar borders and car physics derived from `track_file`.
+fn insert_track_resources(commands: &mut Commands, track_file: &TrackFile) {
+    let control_points = track_file.control_points_vec2();
+    let track_width = track::TrackWidth::from_file(track_file);
 
     let spline = track::build_spline(&control_points);
 
     commands.insert_resource(track::TrackSpline {
         spline: spline.clone(),
     });
-    let (inner_border, outer_border) = track::sample_track_borders(&spline, track_width, 1000);
+    commands.insert_resource(track::TrackProgressTable::new(&spline, 1000));
+    let (inner_border, outer_border) = track::sample_track_borders(&spline, &track_width, 1000);
     commands.insert_resource(TrackRadarBorders {
         inner: inner_border,
         outer: outer_border,
     });
+    commands.insert_resource(CarPhysics::from_track(&track_file.physics));
+}
+
+/// Grass, asphalt and kerb meshes, despawned when another track is loaded.
+#[derive(Component)]
+struct TrackMesh;
+
+fn spawn_track_meshes(
+    mut commands: Commands,
+    track: Res<ActiveTrack>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    spawn_track_mesh_entities(&mut commands, &mut meshes, &mut materials, &track.file);
+}
+
+fn spawn_track_mesh_entities(
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<ColorMaterial>,
+    track_file: &TrackFile,
+) {
+    let track_width = track::TrackWidth::from_file(track_file);
+    let kerb_width = track_file.metadata.kerb_width;
+    let spline = &track::build_spline(&track_file.control_points_vec2());
+
+    commands.spawn((
+        Mesh2d(meshes.add(Rectangle::new(800.0, 800.0))),
+        MeshMaterial2d(materials.add(Color::srgb(0.2, 0.6, 0.2))),
+        Transform::from_xyz(0.0, 0.0, -1.0),
+        TrackMesh,
+    ));
 
-    let track_mesh = track::create_track_mesh(&spline, track_width, 1000);
+    let track_mesh = track::create_track_mesh(spline, &track_width, 1000);
     commands.spawn((
         Mesh2d(meshes.add(track_mesh)),
         MeshMaterial2d(materials.add(Color::srgb(0.3, 0.3, 0.3))),
         Transform::from_xyz(0.0, 0.0, 0.0),
+        TrackMesh,
     ));
 
     let (inner_kerb, outer_kerb) =
-        track::create_kerb_meshes(&spline, track_width, kerb_width, 1000);
+        track::create_kerb_meshes(spline, &track_width, kerb_width, 1000);
     commands.spawn((
         Mesh2d(meshes.add(inner_kerb)),
         MeshMaterial2d(materials.add(ColorMaterial::default())),
         Transform::from_xyz(0.0, 0.0, 0.1),
+        TrackMesh,
     ));
     commands.spawn((
         Mesh2d(meshes.add(outer_kerb)),
         MeshMaterial2d(materials.add(ColorMaterial::default())),
         Transform::from_xyz(0.0, 0.0, 0.1),
+        TrackMesh,
     ));
 }
 
+/// Swap the active track before a race: the cars on the grid are removed and
+/// the track resources and meshes rebuilt. Requests during a race are ignored.
+fn handle_load_track_request(
+    mut events: MessageReader<LoadTrackRequest>,
+    mut commands: Commands,
+    state: Res<State<SimState>>,
+    mut manager: ResMut<RaceManager>,
+    stale_query: Query<Entity, Or<(With<CarLabel>, With<TrackMesh>)>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    // Only the last request of a frame matters.
+    let Some(request) = events.read().last() else {
+        return;
+    };
+    if *state.get() != SimState::PreRace {
+        warn!("tracks can only be changed before the race starts");
+        return;
+    }
+    let track_file = match TrackFile::load(&request.path) {
+        Ok(track_file) => track_file,
+        Err(error) => {
+            error!("Failed to load track: {error}");
+            return;
+        }
+    };
+
+    for entity in &stale_query {
+        commands.entity(entity).despawn();
+    }
+    manager.cars.clear();
+    manager.next_car_id = 1;
+
+    insert_track_resources(&mut commands, &track_file);
+    spawn_track_mesh_entities(&mut commands, &mut meshes, &mut materials, &track_file);
+    info!("loaded track '{}'", track_file.metadata.name);
+    commands.insert_resource(ActiveTrack {
+        path: Some(request.path.clone()),
+        file: track_file,
+    });
+}
+
 fn rpm_to_rad_per_sec(rpm: f32) -> f32 {
     rpm * (2.0 * PI / 60.0)
 }
@@ -395,7 +1611,8 @@ fn smoothstep(edge0: f32, edge1: f32, value: f32) -> f32 {
 }
 
 fn engine_torque_full(rpm: f32, params: &KartLongitudinalParams) -> f32 {
-    let x = ((rpm - params.torque_peak_rpm) / (params.redline_rpm - params.torque_peak_rpm)).clamp(0.0, 1.0);
+    let x = ((rpm - params.torque_peak_rpm) / (params.redline_rpm - params.torque_peak_rpm))
+        .clamp(0.0, 1.0);
     params.torque_peak_nm * (1.0 - (1.0 - params.redline_torque_fraction) * x * x)
 }
 
@@ -466,6 +1683,167 @@ fn unpause_physics(mut physics_time: ResMut<Time<Physics>>) {
     physics_time.unpause();
 }
 
+fn reset_race_clock(mut clock: ResMut<RaceClock>) {
+    *clock = RaceClock::default();
+}
+
+fn mark_race_start(mut clock: ResMut<RaceClock>) {
+    clock.mark_start();
+}
+
+fn advance_countdown(
+    mut clock: ResMut<RaceClock>,
+    countdown: Res<RaceCountdown>,
+    tick_hz: Res<SimTickHz>,
+    mut next_state: ResMut<NextState<SimState>>,
+) {
+    if clock.advance_countdown(countdown.ticks(*tick_hz)) {
+        next_state.set(SimState::Racing);
+    }
+}
+
+fn advance_race_clock(mut clock: ResMut<RaceClock>) {
+    clock.advance_race();
+}
+
+/// Counts laps from `TrackProgress` and copies completed-lap times into the
+/// car's `CarEntry`; the manager is only touched when a lap completes. A bot
+/// car that completes `RaceLaps` is marked `CpuHalted`.
+fn lap_timer_system(
+    mut commands: Commands,
+    clock: Res<RaceClock>,
+    tick_hz: Res<SimTickHz>,
+    laps: Res<RaceLaps>,
+    mut manager: ResMut<RaceManager>,
+    mut query: Query<(
+        Entity,
+        &track::TrackProgress,
+        &mut track::LapTimer,
+        Has<CpuComponent>,
+    )>,
+) {
+    let Some(tick) = clock.race_ticks() else {
+        return;
+    };
+    let to_secs = |ticks: u64| tick_hz.ticks_to_secs(ticks);
+    for (entity, progress, mut timer, has_cpu) in &mut query {
+        if !timer.observe(progress.lap_fraction, tick) {
+            continue;
+        }
+        if let Some(entry) = manager.cars.iter_mut().find(|c| c.entity == entity) {
+            entry.laps_completed = timer.laps;
+            entry.last_lap_secs = timer.last_lap_ticks.map(to_secs);
+            entry.best_lap_secs = timer.best_lap_ticks.map(to_secs);
+            if entry.finish_secs.is_none() && entry.laps_completed >= laps.0 {
+                entry.finish_secs = Some(to_secs(tick));
+                if has_cpu {
+                    commands.entity(entity).insert(CpuHalted);
+                }
+            }
+        }
+    }
+}
+
+/// Consecutive racing ticks a car has spent off the track surface, and
+/// whether that has put it out of the race.
+#[derive(Component, Default)]
+pub struct OffTrack {
+    pub ticks: u64,
+    pub dnf: bool,
+}
+
+/// Retires cars that stay outside the track borders for longer than
+/// `OffTrackLimit`, marking their `CarEntry` as DNF and their CPU `CpuHalted`.
+fn off_track_system(
+    mut commands: Commands,
+    limit: Res<OffTrackLimit>,
+    tick_hz: Res<SimTickHz>,
+    borders: Res<TrackRadarBorders>,
+    mut manager: ResMut<RaceManager>,
+    mut query: Query<(Entity, &Transform, &mut OffTrack)>,
+) {
+    let Some(limit_ticks) = limit.ticks(*tick_hz) else {
+        return;
+    };
+    for (entity, transform, mut off_track) in &mut query {
+        if off_track.dnf {
+            continue;
+        }
+        let position = transform.translation.xy();
+        if track::point_in_track_band(position, &borders.inner, &borders.outer) {
+            off_track.ticks = 0;
+            continue;
+        }
+        off_track.ticks += 1;
+        if off_track.ticks <= limit_ticks {
+            continue;
+        }
+        off_track.dnf = true;
+        commands.entity(entity).insert(CpuHalted);
+        if let Some(entry) = manager.cars.iter_mut().find(|c| c.entity == entity) {
+            entry.dnf = true;
+            info!(
+                "{} is out of the race (DNF): off track for {:.1}s",
+                entry.name, limit.secs
+            );
+        }
+    }
+}
+
+/// DNF cars and bot cars whose CPU was halted keep their brakes on and ignore
+/// bot or keyboard controls; a halted bot would otherwise drive on with the
+/// last controls it wrote.
+fn hold_retired_cars(mut query: Query<(Option<&OffTrack>, Has<CpuHalted>, &mut Car)>) {
+    for (off_track, halted, mut car) in &mut query {
+        if halted || off_track.is_some_and(|off_track| off_track.dnf) {
+            car.steer = 0.0;
+            car.accelerator = 0.0;
+            car.brake = 1.0;
+        }
+    }
+}
+
+/// Bots run while racing and for the remaining ticks of the countdown.
+fn bots_running(
+    state: Res<State<SimState>>,
+    clock: Res<RaceClock>,
+    countdown: Res<RaceCountdown>,
+    tick_hz: Res<SimTickHz>,
+) -> bool {
+    match state.get() {
+        SimState::Racing => true,
+        SimState::Countdown => clock.countdown_remaining_secs(&countdown, *tick_hz) > 0.0,
+        _ => false,
+    }
+}
+
+fn update_race_started_flag(
+    clock: Res<RaceClock>,
+    tick_hz: Res<SimTickHz>,
+    mut query: Query<&mut devices::CarStateDevice>,
+) {
+    let started = clock.start_tick().is_some();
+    for mut state_dev in &mut query {
+        state_dev.set_race_started(started);
+        state_dev.set_tick_micros(tick_hz.tick_micros());
+    }
+}
+
+fn auto_pause_on_focus_change(
+    mut focus_events: MessageReader<WindowFocused>,
+    mut auto_pause: ResMut<AutoPauseSetting>,
+    state: Res<State<SimState>>,
+    mut next_state: ResMut<NextState<SimState>>,
+) {
+    let mut current = *state.get();
+    for event in focus_events.read() {
+        if let Some(next) = auto_pause.on_focus_changed(event.focused, current) {
+            next_state.set(next);
+            current = next;
+        }
+    }
+}
+
 fn grid_offset(index: usize) -> Vec2 {
     let row = index as f32;
     let side = if index % 2 == 0 { 1.0 } else { -1.0 };
@@ -475,10 +1853,12 @@ fn grid_offset(index: usize) -> Vec2 {
 fn handle_spawn_resolved_event(
     mut events: MessageReader<SpawnResolvedCarRequest>,
     mut commands: Commands,
-    asset_server: Res<AssetServer>,
     track_spline: Res<track::TrackSpline>,
+    active_track: Res<ActiveTrack>,
     mut manager: ResMut<RaceManager>,
     cpu_frequency: Res<CpuFrequencySetting>,
+    tick_hz: Res<SimTickHz>,
+    radar_rays: Res<RadarRays>,
     state: Res<State<SimState>>,
 ) {
     for event in events.read() {
@@ -488,71 +1868,126 @@ fn handle_spawn_resolved_event(
 
         spawn_car_entry(
             &mut commands,
-            &asset_server,
             &track_spline,
+            &active_track,
             &mut manager,
-            &cpu_frequency,
+            cpu_frequency.instructions_per_update(*tick_hz),
+            *radar_rays,
             event.driver.clone(),
             &event.elf_bytes,
         );
     }
 }
 
-fn spawn_car_entry(
+pub(crate) fn spawn_car_entry(
     commands: &mut Commands,
-    asset_server: &AssetServer,
     track_spline: &track::TrackSpline,
+    active_track: &ActiveTrack,
     manager: &mut RaceManager,
-    cpu_frequency: &CpuFrequencySetting,
+    instructions_per_update: u32,
+    radar_rays: RadarRays,
     driver: DriverType,
     elf_bytes: &[u8],
 ) {
     let car_index = manager.cars.len();
     let offset = grid_offset(car_index);
 
-    let track_file =
-        TrackFile::load_builtin().unwrap_or_else(|_| panic!("Failed to load track file"));
-    let start_point = track::first_point_from_file(&track_file);
+    let start_point = track::first_point_from_file(&active_track.file);
+    let physics = CarPhysics::from_track(&active_track.file.physics);
 
     let position = start_point + offset;
-    let car_name = format!("Car {}", manager.next_car_id);
-    let entity = spawn_car(
-        commands,
-        asset_server,
-        position,
-        track_spline,
-        &car_name,
-        elf_bytes,
-        cpu_frequency.instructions_per_update(),
-    );
+    let car_id = manager.next_car_id;
+    let car_name = format!("Car {car_id}");
+    let entity = if driver == DriverType::Manual {
+        // All keyboard cars would follow the same keys, so allow only one.
+        if manager
+            .cars
+            .iter()
+            .any(|car| car.driver == DriverType::Manual)
+        {
+            warn!("only one manual car can race at a time");
+            return;
+        }
+        spawn_car_body(commands, position, &car_name, &physics)
+    } else {
+        let cpu = match CpuComponent::new(elf_bytes, instructions_per_update) {
+            Ok(cpu) => cpu,
+            Err(error) => {
+                warn!("failed to load bot for {car_name}: {error}");
+                return;
+            }
+        };
+        let entity = spawn_car(
+            commands,
+            position,
+            track_spline,
+            &car_name,
+            &physics,
+            cpu,
+            radar_rays,
+        );
+        commands.entity(entity).insert(BotElf(elf_bytes.into()));
+        entity
+    };
+    commands
+        .entity(entity)
+        .insert(CarColor(color_for_car(car_id)));
     manager.cars.push(CarEntry {
         entity,
+        id: car_id,
         name: car_name,
         driver,
         console_output: String::new(),
+        laps_completed: 0,
+        last_lap_secs: None,
+        best_lap_secs: None,
+        dnf: false,
+        finish_secs: None,
     });
     manager.next_car_id += 1;
 }
 
 fn spawn_car(
     commands: &mut Commands,
-    asset_server: &AssetServer,
     position: Vec2,
     track_spline: &track::TrackSpline,
     name: &str,
-    bot_elf: &[u8],
-    instructions_per_update: u32,
+    physics: &CarPhysics,
+    cpu: CpuComponent,
+    radar_rays: RadarRays,
 ) -> Entity {
-    let sprite_scale = Vec3::splat(0.008);
+    let entity = spawn_car_body(commands, position, name, physics);
+    commands.entity(entity).insert((
+        EmulatorDriver,
+        cpu,
+        LogDevice::default(),
+        CarStateDevice::default(),
+        CarControlsDevice::default(),
+        SplineDevice::new(track_spline),
+        TrackRadarDevice::new(radar_rays.count()),
+        CarRadarDevice::default(),
+        LapProgressDevice::default(),
+        SpeedometerDevice::default(),
+        TrackAheadDevice::default(),
+        YieldDevice::default(),
+    ));
+    entity
+}
 
+/// Physics body, collider and race bookkeeping shared by every car regardless
+/// of who drives it.
+fn spawn_car_body(
+    commands: &mut Commands,
+    position: Vec2,
+    name: &str,
+    physics: &CarPhysics,
+) -> Entity {
     let mut entity = commands.spawn((
         Transform::from_xyz(position.x, position.y, 1.0)
             .with_rotation(Quat::from_axis_angle(Vec3::Z, PI / 2.0)),
         Visibility::default(),
         RigidBody::Dynamic,
         //LinearDamping(0.1),
-        Friction::new(0.1),
-        Restitution::new(0.2),
         Car {
             steer: 0.0,
             accelerator: 0.0,
@@ -564,18 +1999,11 @@ fn spawn_car(
             name: name.to_string(),
         },
         LongitudinalDebugData::default(),
-    ));
-
-    let cpu = CpuComponent::new(bot_elf, instructions_per_update);
-    entity.insert((
-        EmulatorDriver,
-        cpu,
-        LogDevice::default(),
-        CarStateDevice::default(),
-        CarControlsDevice::default(),
-        SplineDevice::new(track_spline),
-        TrackRadarDevice::default(),
-        CarRadarDevice::default(),
+        WheelSpin::default(),
+        track::TrackProgress::default(),
+        track::CheckpointTracker::default(),
+        track::LapTimer::default(),
+        OffTrack::default(),
     ));
 
     let entity_id = entity.id();
@@ -583,57 +2011,72 @@ fn spawn_car(
     entity.with_children(|parent| {
         parent.spawn((
             Collider::rectangle(1.25, 2.0),
+            car_collision_layers(),
+            Friction::new(0.1),
+            Restitution::new(CAR_RESTITUTION),
             Transform::from_xyz(0.0, 0.66, 0.0),
         ));
 
         parent.spawn((
-            Sprite::from_image(asset_server.load("kart.png")),
-            Transform::from_xyz(0.0, 0.66, 0.1).with_scale(sprite_scale),
+            Transform::from_xyz(-physics.wheel_track / 2.0, physics.wheel_base, 0.1),
+            Visibility::default(),
+            FrontWheel,
+        ));
+        parent.spawn((
+            Transform::from_xyz(physics.wheel_track / 2.0, physics.wheel_base, 0.1),
+            Visibility::default(),
+            FrontWheel,
         ));
-
-        parent
-            .spawn((
-                Transform::from_xyz(-WHEEL_TRACK / 2.0, WHEEL_BASE, 0.1),
-                Visibility::default(),
-                FrontWheel,
-            ))
-            .with_children(|parent| {
-                parent.spawn((
-                    Sprite::from_image(asset_server.load("kart_wheel.png")),
-                    Transform::default()
-                        .with_scale(sprite_scale)
-                        .with_rotation(Quat::from_rotation_z(0.0)),
-                ));
-            });
-
-        parent
-            .spawn((
-                Transform::from_xyz(WHEEL_TRACK / 2.0, WHEEL_BASE, 0.1),
-                Visibility::default(),
-                FrontWheel,
-            ))
-            .with_children(|parent| {
-                parent.spawn((
-                    Sprite::from_image(asset_server.load("kart_wheel.png")),
-                    Transform::default()
-                        .with_scale(sprite_scale)
-                        .with_rotation(Quat::from_rotation_z(PI)),
-                ));
-            });
     });
 
     entity_id
 }
 
+/// Gives newly spawned cars their kart and wheel sprites; the simulation itself spawns none.
+fn attach_car_sprites(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    cars: Query<(Entity, Option<&CarColor>), Added<Car>>,
+    wheels: Query<(Entity, &Transform), Added<FrontWheel>>,
+) {
+    let sprite_scale = Vec3::splat(0.008);
+
+    for (car, color) in &cars {
+        let mut sprite = Sprite::from_image(asset_server.load("kart.png"));
+        if let Some(CarColor(color)) = color {
+            sprite.color = *color;
+        }
+        commands.entity(car).with_child((
+            sprite,
+            Transform::from_xyz(0.0, 0.66, 0.1).with_scale(sprite_scale),
+        ));
+    }
+
+    for (wheel, transform) in &wheels {
+        let rotation = if transform.translation.x < 0.0 {
+            0.0
+        } else {
+            PI
+        };
+        commands.entity(wheel).with_child((
+            Sprite::from_image(asset_server.load("kart_wheel.png")),
+            Transform::default()
+                .with_scale(sprite_scale)
+                .with_rotation(Quat::from_rotation_z(rotation)),
+        ));
+    }
+}
+
 fn apply_cpu_frequency_setting(
     cpu_frequency: Res<CpuFrequencySetting>,
+    tick_hz: Res<SimTickHz>,
     mut cpu_query: Query<&mut CpuComponent>,
 ) {
-    if !cpu_frequency.is_changed() {
+    if !cpu_frequency.is_changed() && !tick_hz.is_changed() {
         return;
     }
 
-    let instructions_per_update = cpu_frequency.instructions_per_update();
+    let instructions_per_update = cpu_frequency.instructions_per_update(*tick_hz);
     for mut cpu in &mut cpu_query {
         cpu.set_instructions_per_update(instructions_per_update);
     }
@@ -642,6 +2085,104 @@ fn apply_cpu_frequency_setting(
 #[derive(Component)]
 struct EmulatorDriver;
 
+/// Writes a note to the bot's console when its program traps or halts, so a
+/// bad instruction shows up next to the bot's own output.
+fn report_cpu_stops(mut cpu_query: Query<(&mut CpuComponent, &mut LogDevice)>) {
+    for (mut cpu, mut log) in &mut cpu_query {
+        match cpu.take_unreported_stop() {
+            // Traps already read "trap: ..."; give them a line of their own.
+            Some(reason) if reason.is_trap() => log.push_str(&format!("\n{reason}\n")),
+            Some(reason) => log.push_str(&format!("\n[cpu stopped: {reason}]\n")),
+            None => {}
+        }
+    }
+}
+
+/// ELF the car's bot was loaded from, kept so the CPU can be rebuilt fresh.
+#[derive(Component)]
+struct BotElf(std::sync::Arc<[u8]>);
+
+/// Practice mode: with a single car on track, R respawns it at its last
+/// checkpoint at rest with a freshly loaded CPU.
+fn reset_to_checkpoint(
+    mut commands: Commands,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut manager: ResMut<RaceManager>,
+    cpu_frequency: Res<CpuFrequencySetting>,
+    tick_hz: Res<SimTickHz>,
+    physics: Res<CarPhysics>,
+    mut car_query: Query<(
+        Entity,
+        &mut Transform,
+        &mut Position,
+        &mut Rotation,
+        &mut LinearVelocity,
+        &mut AngularVelocity,
+        &mut Car,
+        &mut track::CheckpointTracker,
+        &mut OffTrack,
+        Option<&BotElf>,
+    )>,
+) {
+    if !keyboard.just_pressed(KeyCode::KeyR) || manager.cars.len() != 1 {
+        return;
+    }
+
+    for (
+        entity,
+        mut transform,
+        mut position,
+        mut rotation,
+        mut linear_velocity,
+        mut angular_velocity,
+        mut car,
+        mut tracker,
+        mut off_track,
+        elf,
+    ) in &mut car_query
+    {
+        let Some(checkpoint) = tracker.restore() else {
+            continue;
+        };
+        // Bot cars restart their program; manual cars only move.
+        let cpu = match elf
+            .map(|elf| CpuComponent::new(&elf.0, cpu_frequency.instructions_per_update(*tick_hz)))
+        {
+            Some(Ok(cpu)) => Some(cpu),
+            Some(Err(error)) => {
+                warn!("failed to reload bot for checkpoint reset: {error}");
+                continue;
+            }
+            None => None,
+        };
+
+        // The car's forward axis is local +Y, a quarter turn ahead of its rotation.
+        let angle = checkpoint.heading - PI / 2.0;
+        transform.translation = checkpoint.position.extend(transform.translation.z);
+        transform.rotation = Quat::from_rotation_z(angle);
+        *position = Position(checkpoint.position);
+        *rotation = Rotation::radians(angle);
+        *linear_velocity = LinearVelocity::ZERO;
+        *angular_velocity = AngularVelocity(0.0);
+        car.steer = 0.0;
+        car.accelerator = 0.0;
+        car.brake = 0.0;
+        car.engine_rpm = physics.longitudinal.idle_rpm;
+        car.wheel_omega = 0.0;
+        // Back on track, so a practice car that was retired can drive again.
+        *off_track = OffTrack::default();
+        commands.entity(entity).remove::<CpuHalted>();
+        if let Some(entry) = manager.cars.iter_mut().find(|c| c.entity == entity) {
+            entry.dnf = false;
+        }
+        if let Some(cpu) = cpu {
+            commands
+                .entity(entity)
+                .insert((cpu, CarControlsDevice::default()));
+        }
+    }
+}
+
 #[derive(Component)]
 struct FrontWheel;
 
@@ -653,6 +2194,10 @@ emulator::define_cpu_config! {
         4 => SplineDevice,
         5 => TrackRadarDevice,
         6 => CarRadarDevice,
+        7 => LapProgressDevice,
+        8 => SpeedometerDevice,
+        9 => TrackAheadDevice,
+        10 => YieldDevice,
     }
 }
 
@@ -694,29 +2239,40 @@ fn apply_car_forces(
         &Transform,
         &mut Car,
         &mut LongitudinalDebugData,
+        &mut WheelSpin,
         &Children,
         Forces,
         Has<DebugGizmos>,
     )>,
     mut wheel_query: Query<&mut Transform, (With<FrontWheel>, Without<Car>)>,
     mut gizmos: Gizmos,
-    params: Res<KartLongitudinalParams>,
+    mut skid_marks: ResMut<SkidMarks>,
+    physics: Res<CarPhysics>,
     time: Res<Time<Fixed>>,
 ) {
+    let params = physics.longitudinal;
     let dt = time.delta_secs();
     let g = 9.81_f32;
 
-    for (_entity, transform, mut car, mut debug_data, children, mut forces, show_gizmos) in
-        &mut car_query
+    for (
+        _entity,
+        transform,
+        mut car,
+        mut debug_data,
+        mut wheel_spin,
+        children,
+        mut forces,
+        show_gizmos,
+    ) in &mut car_query
     {
         let position = transform.translation.xy();
         let forward = transform.up().xy().normalize();
-        let left = forward.perp();
         let throttle = car.accelerator.clamp(0.0, 1.0);
         let brake = car.brake.clamp(0.0, 1.0);
         let v_long = forces.linear_velocity().dot(forward);
 
         car.wheel_omega = v_long / params.wheel_radius_m;
+        wheel_spin.phase = (wheel_spin.phase + car.wheel_omega * dt).rem_euclid(TAU);
         let wheel_rpm = rad_per_sec_to_rpm(car.wheel_omega.abs());
 
         let engine_rpm_prev = car.engine_rpm.max(params.idle_rpm);
@@ -794,38 +2350,20 @@ fn apply_car_forces(
             gizmos.arrow_2d(position, position + forward * a_long * 0.3, WHITE);
         }
 
-        apply_wheel_force(
-            position,
-            forward * WHEEL_BASE + left * -WHEEL_TRACK / 2.0,
-            Vec2::from_angle(-car.steer).rotate(forward),
-            &mut forces,
-            &mut gizmos,
-            show_gizmos,
-        );
-        apply_wheel_force(
-            position,
-            forward * WHEEL_BASE + left * WHEEL_TRACK / 2.0,
-            Vec2::from_angle(-car.steer).rotate(forward),
-            &mut forces,
-            &mut gizmos,
-            show_gizmos,
-        );
-        apply_wheel_force(
-            position,
-            left * -WHEEL_TRACK / 2.0,
-            forward,
-            &mut forces,
-            &mut gizmos,
-            show_gizmos,
-        );
-        apply_wheel_force(
-            position,
-            left * WHEEL_TRACK / 2.0,
-            forward,
-            &mut forces,
-            &mut gizmos,
-            show_gizmos,
-        );
+        for (wheel_offset, wheel_forward) in wheel_layout(forward, car.steer, &physics) {
+            let slip = apply_wheel_force(
+                position,
+                wheel_offset,
+                wheel_forward,
+                &physics,
+                &mut forces,
+                &mut gizmos,
+                show_gizmos,
+            );
+            if show_gizmos && is_skidding(slip) {
+                skid_marks.push(position + wheel_offset);
+            }
+        }
 
         for child in children.iter() {
             if let Ok(mut wheel_transform) = wheel_query.get_mut(child) {
@@ -835,14 +2373,35 @@ fn apply_car_forces(
     }
 }
 
+/// Wheel offsets from the car origin paired with each wheel's rolling direction
+/// (front-left, front-right, rear-left, rear-right).
+fn wheel_layout(forward: Vec2, steer: f32, physics: &CarPhysics) -> [(Vec2, Vec2); 4] {
+    let left = forward.perp();
+    let front_forward = Vec2::from_angle(-steer).rotate(forward);
+    let half_track = physics.wheel_track / 2.0;
+    [
+        (
+            forward * physics.wheel_base - left * half_track,
+            front_forward,
+        ),
+        (
+            forward * physics.wheel_base + left * half_track,
+            front_forward,
+        ),
+        (-left * half_track, forward),
+        (left * half_track, forward),
+    ]
+}
+
 fn apply_wheel_force(
     car_position: Vec2,
     wheel_offset: Vec2,
     wheel_forward: Vec2,
+    physics: &CarPhysics,
     forces: &mut ForcesItem<'_, '_>,
     gizmos: &mut Gizmos,
     show_gizmos: bool,
-) {
+) -> f32 {
     let wheel_pos = car_position + wheel_offset;
     let wheel_left = wheel_forward.perp();
 
@@ -860,23 +2419,90 @@ fn apply_wheel_force(
         gizmos.arrow_2d(wheel_pos, wheel_pos + wheel_velocity * 0.1, GREEN);
     }
 
+    let slip = lateral_slip(wheel_velocity, wheel_left);
     if wheel_velocity.length() > 0.1 {
-        let force = -wheel_velocity.normalize().dot(wheel_left)
-            * wheel_left
-            * 10.0_f32.min(wheel_velocity.length() * 5.0);
+        let grip = physics
+            .lateral_grip
+            .min(wheel_velocity.length() * physics.lateral_grip_per_speed);
+        let force = -slip * wheel_left * grip;
         if show_gizmos {
             gizmos.arrow_2d(wheel_pos, wheel_pos + force, RED);
         }
         forces.apply_linear_acceleration_at_point(force, wheel_pos);
     }
+    slip
+}
+
+/// Sine of the angle between the wheel's velocity and its rolling direction.
+fn lateral_slip(wheel_velocity: Vec2, wheel_left: Vec2) -> f32 {
+    if wheel_velocity.length() > 0.1 {
+        wheel_velocity.normalize().dot(wheel_left)
+    } else {
+        0.0
+    }
+}
+
+fn is_skidding(lateral_slip: f32) -> bool {
+    lateral_slip.abs() > SKID_SLIP_THRESHOLD
+}
+
+impl SkidMarks {
+    fn push(&mut self, position: Vec2) {
+        if self.marks.len() >= MAX_SKID_MARKS {
+            self.marks.remove(0);
+        }
+        self.marks.push(SkidMark {
+            position,
+            age_s: 0.0,
+        });
+    }
 }
 
-fn draw_gizmos(car_query: Query<(&Transform, &Car), With<DebugGizmos>>, mut gizmos: Gizmos) {
-    for (transform, _car) in &car_query {
+fn age_skid_marks(mut skid_marks: ResMut<SkidMarks>, time: Res<Time<Fixed>>) {
+    let dt = time.delta_secs();
+    skid_marks.marks.retain_mut(|mark| {
+        mark.age_s += dt;
+        mark.age_s < SKID_MARK_LIFETIME_S
+    });
+}
+
+fn clear_skid_marks(mut skid_marks: ResMut<SkidMarks>) {
+    skid_marks.marks.clear();
+}
+
+fn draw_skid_marks(skid_marks: Res<SkidMarks>, mut gizmos: Gizmos) {
+    for mark in &skid_marks.marks {
+        let alpha = 0.8 * (1.0 - mark.age_s / SKID_MARK_LIFETIME_S);
+        gizmos.circle_2d(mark.position, 0.06, Color::srgba(0.05, 0.05, 0.05, alpha));
+    }
+}
+
+fn draw_gizmos(
+    car_query: Query<(&Transform, &Car, &WheelSpin), With<DebugGizmos>>,
+    physics: Res<CarPhysics>,
+    mut gizmos: Gizmos,
+) {
+    for (transform, car, wheel_spin) in &car_query {
+        let position = transform.translation.xy();
+        let forward = transform.up().xy().normalize();
+        // A tread stripe travelling over the top of each wheel visualizes rolling.
+        if wheel_spin.phase.sin() > 0.0 {
+            let along = physics.longitudinal.wheel_radius_m * wheel_spin.phase.cos();
+            for (wheel_offset, wheel_forward) in wheel_layout(forward, car.steer, &physics) {
+                let stripe_center = position + wheel_offset + wheel_forward * along;
+                let half_width = wheel_forward.perp() * 0.08;
+                gizmos.line_2d(
+                    stripe_center - half_width,
+                    stripe_center + half_width,
+                    WHITE,
+                );
+            }
+        }
+
         gizmos.cross(transform.to_isometry(), 0.2, RED);
         gizmos.cross(
             Isometry3d::new(
-                transform.translation + transform.up() * WHEEL_BASE,
+                transform.translation + transform.up() * physics.wheel_base,
                 transform.rotation,
             ),
             0.2,
@@ -0,0 +1,507 @@
+use std::path::{Path, PathBuf};
+
+use bevy::prelude::*;
+use botracers_protocol::{RaceReplay, ReplayCarPose};
+
+use crate::game_api::{GhostReplayRequest, SpectateReplayRequest};
+use crate::race_runtime::SimState;
+
+/// Seconds skipped per seek key press while spectating.
+const SEEK_STEP_SECS: f32 = 5.0;
+
+pub struct ReplayPlugin;
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, load_ghost_file)
+            .add_systems(Update, (handle_spectate_request, handle_ghost_request))
+            .add_systems(OnEnter(SimState::PreRace), rewind_race_ghosts)
+            .add_systems(
+                FixedUpdate,
+                advance_race_ghosts.run_if(in_state(SimState::Racing)),
+            )
+            .add_systems(OnEnter(SimState::Spectating), spawn_replay_ghosts)
+            .add_systems(OnExit(SimState::Spectating), despawn_replay_ghosts)
+            .add_systems(
+                Update,
+                (
+                    handle_replay_controls,
+                    advance_replay,
+                    apply_ghost_poses,
+                    update_replay_status_text,
+                )
+                    .chain()
+                    .run_if(in_state(SimState::Spectating)),
+            );
+    }
+}
+
+/// Playback state of a replay fetched from the server.
+#[derive(Resource)]
+pub struct ReplayPlayer {
+    pub record_id: i64,
+    replay: RaceReplay,
+    /// Fractional frame index into `replay.frames`.
+    cursor: f32,
+    playing: bool,
+}
+
+impl ReplayPlayer {
+    pub fn new(record_id: i64, replay: RaceReplay) -> Self {
+        Self {
+            record_id,
+            replay,
+            cursor: 0.0,
+            playing: true,
+        }
+    }
+
+    fn tick_hz(&self) -> f32 {
+        self.replay.tick_hz.max(1) as f32
+    }
+
+    fn last_frame(&self) -> f32 {
+        self.replay.frames.len().saturating_sub(1) as f32
+    }
+
+    fn current_poses(&self) -> &[ReplayCarPose] {
+        self.replay
+            .frames
+            .get(self.cursor as usize)
+            .map(|frame| frame.poses.as_slice())
+            .unwrap_or(&[])
+    }
+
+    fn advance(&mut self, dt: f32) {
+        if !self.playing {
+            return;
+        }
+        self.cursor = (self.cursor + dt * self.tick_hz()).min(self.last_frame());
+        if self.cursor >= self.last_frame() {
+            self.playing = false;
+        }
+    }
+
+    fn seek_secs(&mut self, delta: f32) {
+        self.cursor = (self.cursor + delta * self.tick_hz()).clamp(0.0, self.last_frame());
+    }
+
+    fn toggle_playing(&mut self) {
+        if !self.playing && self.cursor >= self.last_frame() {
+            self.cursor = 0.0;
+        }
+        self.playing = !self.playing;
+    }
+
+    fn elapsed_secs(&self) -> f32 {
+        self.cursor / self.tick_hz()
+    }
+
+    fn duration_secs(&self) -> f32 {
+        self.last_frame() / self.tick_hz()
+    }
+}
+
+/// Non-physics car entity driven by recorded poses.
+#[derive(Component)]
+pub struct ReplayGhost {
+    pub car_index: usize,
+}
+
+#[derive(Component)]
+struct ReplayStatusText;
+
+/// One car of a stored replay raced against live. Shows the recorded pose of
+/// the current fixed tick; once the replay runs out it stays on the last frame.
+#[derive(Component)]
+pub struct RaceGhost {
+    replay: RaceReplay,
+    car_index: usize,
+    frame: usize,
+}
+
+impl RaceGhost {
+    pub fn new(replay: RaceReplay, car_index: usize) -> Self {
+        Self {
+            replay,
+            car_index,
+            frame: 0,
+        }
+    }
+
+    fn pose(&self) -> Option<&ReplayCarPose> {
+        let last = self.replay.frames.len().checked_sub(1)?;
+        self.replay.frames[self.frame.min(last)]
+            .poses
+            .get(self.car_index)
+    }
+
+    fn advance(&mut self) {
+        self.frame = (self.frame + 1).min(self.replay.frames.len().saturating_sub(1));
+    }
+}
+
+/// Replay JSON file (as written by `--headless --replay=<path>`) to race
+/// against from startup, set by `--ghost=<path>`.
+#[derive(Resource)]
+pub struct GhostReplayFile(pub PathBuf);
+
+/// Read a `RaceReplay` from a JSON file.
+pub fn load_replay_file(path: &Path) -> Result<RaceReplay, String> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|error| format!("failed to read {}: {error}", path.display()))?;
+    serde_json::from_str(&text)
+        .map_err(|error| format!("invalid replay {}: {error}", path.display()))
+}
+
+pub fn ghost_transform(pose: &ReplayCarPose) -> Transform {
+    Transform::from_xyz(pose.x, pose.y, 1.0).with_rotation(Quat::from_rotation_z(pose.heading))
+}
+
+/// Spawn a translucent kart without physics or CPU components.
+pub fn spawn_ghost_car(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    transform: Transform,
+) -> Entity {
+    let sprite_scale = Vec3::splat(0.008);
+    let mut sprite = Sprite::from_image(asset_server.load("kart.png"));
+    sprite.color = Color::srgba(1.0, 1.0, 1.0, 0.5);
+
+    commands
+        .spawn((transform, Visibility::default()))
+        .with_children(|parent| {
+            parent.spawn((
+                sprite,
+                Transform::from_xyz(0.0, 0.66, 0.1).with_scale(sprite_scale),
+            ));
+        })
+        .id()
+}
+
+/// Spawn a translucent ghost following car `car_index` of `replay`, placed on
+/// its first recorded pose. `None` if the replay has no pose for that car.
+pub fn spawn_ghost_from_replay(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    replay: RaceReplay,
+    car_index: usize,
+) -> Option<Entity> {
+    let ghost = RaceGhost::new(replay, car_index);
+    let transform = ghost_transform(ghost.pose()?);
+    let entity = spawn_ghost_car(commands, asset_server, transform);
+    commands.entity(entity).insert(ghost);
+    Some(entity)
+}
+
+fn load_ghost_file(
+    file: Option<Res<GhostReplayFile>>,
+    mut events: MessageWriter<GhostReplayRequest>,
+) {
+    let Some(file) = file else {
+        return;
+    };
+    match load_replay_file(&file.0) {
+        Ok(replay) => {
+            events.write(GhostReplayRequest {
+                replay,
+                car_index: 0,
+            });
+        }
+        Err(error) => error!("Failed to load ghost: {error}"),
+    }
+}
+
+/// Replaces any current ghost; honoured only before a race.
+fn handle_ghost_request(
+    mut events: MessageReader<GhostReplayRequest>,
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    state: Res<State<SimState>>,
+    ghosts: Query<Entity, With<RaceGhost>>,
+) {
+    let Some(request) = events.read().last() else {
+        return;
+    };
+    if *state.get() != SimState::PreRace {
+        warn!("ghosts can only be loaded before the race starts");
+        return;
+    }
+    for entity in &ghosts {
+        commands.entity(entity).despawn();
+    }
+    if spawn_ghost_from_replay(
+        &mut commands,
+        &asset_server,
+        request.replay.clone(),
+        request.car_index,
+    )
+    .is_none()
+    {
+        warn!("replay has no car {} to race against", request.car_index);
+    }
+}
+
+/// Runs once per fixed tick while racing, in step with `ReplayRecorder`.
+fn advance_race_ghosts(mut ghosts: Query<(&mut RaceGhost, &mut Transform)>) {
+    for (mut ghost, mut transform) in &mut ghosts {
+        if let Some(pose) = ghost.pose() {
+            *transform = ghost_transform(pose);
+        }
+        ghost.advance();
+    }
+}
+
+fn rewind_race_ghosts(mut ghosts: Query<(&mut RaceGhost, &mut Transform)>) {
+    for (mut ghost, mut transform) in &mut ghosts {
+        ghost.frame = 0;
+        if let Some(pose) = ghost.pose() {
+            *transform = ghost_transform(pose);
+        }
+    }
+}
+
+fn handle_spectate_request(
+    mut events: MessageReader<SpectateReplayRequest>,
+    mut commands: Commands,
+    state: Res<State<SimState>>,
+    mut next_state: ResMut<NextState<SimState>>,
+) {
+    for event in events.read() {
+        if *state.get() != SimState::PreRace {
+            continue;
+        }
+        commands.insert_resource(ReplayPlayer::new(event.record_id, event.replay.clone()));
+        next_state.set(SimState::Spectating);
+    }
+}
+
+fn spawn_replay_ghosts(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    player: Option<Res<ReplayPlayer>>,
+) {
+    let Some(player) = player else {
+        return;
+    };
+
+    for (car_index, pose) in player.current_poses().iter().enumerate() {
+        let entity = spawn_ghost_car(&mut commands, &asset_server, ghost_transform(pose));
+        commands.entity(entity).insert(ReplayGhost { car_index });
+    }
+
+    commands.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(8.0),
+            left: Val::Px(8.0),
+            padding: UiRect::axes(Val::Px(8.0), Val::Px(4.0)),
+            ..default()
+        },
+        BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.55)),
+        Text::new("Replay"),
+        TextFont {
+            font_size: 16.0,
+            ..default()
+        },
+        TextColor(Color::WHITE),
+        ReplayStatusText,
+    ));
+}
+
+fn despawn_replay_ghosts(
+    mut commands: Commands,
+    ghosts: Query<Entity, With<ReplayGhost>>,
+    status_text: Query<Entity, With<ReplayStatusText>>,
+) {
+    for entity in ghosts.iter().chain(status_text.iter()) {
+        commands.entity(entity).despawn();
+    }
+    commands.remove_resource::<ReplayPlayer>();
+}
+
+fn handle_replay_controls(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut player: ResMut<ReplayPlayer>,
+    mut next_state: ResMut<NextState<SimState>>,
+) {
+    if keyboard.just_pressed(KeyCode::Escape) {
+        next_state.set(SimState::PreRace);
+        return;
+    }
+    if keyboard.just_pressed(KeyCode::Space) {
+        player.toggle_playing();
+    }
+    if keyboard.just_pressed(KeyCode::ArrowLeft) {
+        player.seek_secs(-SEEK_STEP_SECS);
+    }
+    if keyboard.just_pressed(KeyCode::ArrowRight) {
+        player.seek_secs(SEEK_STEP_SECS);
+    }
+}
+
+fn advance_replay(time: Res<Time>, mut player: ResMut<ReplayPlayer>) {
+    player.advance(time.delta_secs());
+}
+
+fn apply_ghost_poses(player: Res<ReplayPlayer>, mut ghosts: Query<(&ReplayGhost, &mut Transform)>) {
+    let poses = player.current_poses();
+    for (ghost, mut transform) in &mut ghosts {
+        if let Some(pose) = poses.get(ghost.car_index) {
+            *transform = ghost_transform(pose);
+        }
+    }
+}
+
+fn update_replay_status_text(
+    player: Res<ReplayPlayer>,
+    mut text_query: Query<&mut Text, With<ReplayStatusText>>,
+) {
+    let Ok(mut text) = text_query.single_mut() else {
+        return;
+    };
+    let state = if player.playing { "playing" } else { "paused" };
+    text.0 = format!(
+        "Replay #{} | {:.1}s / {:.1}s ({state}) | Space: play/pause, Left/Right: seek, Esc: exit",
+        player.record_id,
+        player.elapsed_secs(),
+        player.duration_secs()
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::FRAC_PI_2;
+
+    use bevy::prelude::*;
+    use botracers_protocol::RaceReplay;
+
+    use super::{RaceGhost, ReplayPlayer, advance_race_ghosts, ghost_transform};
+
+    const SAMPLE_REPLAY: &str = r#"{
+        "tick_hz": 200,
+        "cars": [{ "name": "Car 1" }, { "name": "Car 2" }],
+        "frames": [
+            { "poses": [
+                { "x": 1.0, "y": 2.0, "heading": 0.0 },
+                { "x": -3.0, "y": 4.0, "heading": 1.5707964 }
+            ] },
+            { "poses": [
+                { "x": 1.5, "y": 2.0, "heading": 0.0 },
+                { "x": -3.0, "y": 4.5, "heading": 1.5707964 }
+            ] }
+        ]
+    }"#;
+
+    #[test]
+    fn sample_replay_places_ghosts_on_first_frame() {
+        let replay: RaceReplay = serde_json::from_str(SAMPLE_REPLAY).expect("parse replay");
+        assert_eq!(replay.cars.len(), 2);
+        assert_eq!(replay.frames.len(), 2);
+
+        let player = ReplayPlayer::new(7, replay);
+        let poses = player.current_poses();
+        assert_eq!(poses.len(), 2);
+
+        let first = ghost_transform(&poses[0]);
+        assert_eq!(first.translation.truncate(), Vec2::new(1.0, 2.0));
+
+        let second = ghost_transform(&poses[1]);
+        assert_eq!(second.translation.truncate(), Vec2::new(-3.0, 4.0));
+        let (angle, _, _) = second.rotation.to_euler(EulerRot::ZYX);
+        assert!((angle - FRAC_PI_2).abs() < 1e-5);
+    }
+
+    #[test]
+    fn playback_advances_and_seeks_within_bounds() {
+        let replay: RaceReplay = serde_json::from_str(SAMPLE_REPLAY).expect("parse replay");
+        let mut player = ReplayPlayer::new(7, replay);
+
+        player.advance(1.0);
+        assert_eq!(player.current_poses()[0].x, 1.5);
+        assert!(!player.playing);
+
+        player.seek_secs(-10.0);
+        assert_eq!(player.current_poses()[0].x, 1.0);
+    }
+
+    #[test]
+    fn race_ghost_follows_recorded_frames_and_freezes_at_the_end() {
+        use bevy::ecs::system::RunSystemOnce;
+
+        let replay: RaceReplay = serde_json::from_str(SAMPLE_REPLAY).expect("parse replay");
+        let mut world = World::new();
+        let ghost = world
+            .spawn((Transform::default(), RaceGhost::new(replay, 1)))
+            .id();
+
+        // One tick per frame, then two more past the end of the replay.
+        for expected in [
+            Vec2::new(-3.0, 4.0),
+            Vec2::new(-3.0, 4.5),
+            Vec2::new(-3.0, 4.5),
+            Vec2::new(-3.0, 4.5),
+        ] {
+            world
+                .run_system_once(advance_race_ghosts)
+                .expect("advance ghosts");
+            let transform = world.get::<Transform>(ghost).expect("ghost transform");
+            assert_eq!(transform.translation.truncate(), expected);
+        }
+        let empty = RaceReplay {
+            tick_hz: 200,
+            cars: Vec::new(),
+            frames: Vec::new(),
+        };
+        assert!(RaceGhost::new(empty, 0).pose().is_none());
+    }
+
+    #[test]
+    fn leaving_spectate_despawns_replay_ghosts() {
+        use bevy::state::app::StatesPlugin;
+
+        use super::{ReplayGhost, ReplayPlugin};
+        use crate::game_api::{GhostReplayRequest, SpectateReplayRequest};
+        use crate::race_runtime::SimState;
+
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default(), StatesPlugin))
+            .init_asset::<Image>()
+            .init_state::<SimState>()
+            .init_resource::<ButtonInput<KeyCode>>()
+            .add_message::<SpectateReplayRequest>()
+            .add_message::<GhostReplayRequest>()
+            .add_plugins(ReplayPlugin);
+        let ghost_count = |app: &mut App| {
+            app.world_mut()
+                .query_filtered::<(), With<ReplayGhost>>()
+                .iter(app.world())
+                .count()
+        };
+
+        let replay: RaceReplay = serde_json::from_str(SAMPLE_REPLAY).expect("parse replay");
+        app.world_mut().write_message(SpectateReplayRequest {
+            record_id: 7,
+            replay,
+        });
+        app.update();
+        app.update();
+        assert_eq!(
+            *app.world().resource::<State<SimState>>(),
+            SimState::Spectating
+        );
+        assert_eq!(ghost_count(&mut app), 2);
+
+        app.world_mut()
+            .resource_mut::<ButtonInput<KeyCode>>()
+            .press(KeyCode::Escape);
+        app.update();
+        app.update();
+        assert_eq!(
+            *app.world().resource::<State<SimState>>(),
+            SimState::PreRace
+        );
+        assert_eq!(ghost_count(&mut app), 0);
+        assert!(app.world().get_resource::<ReplayPlayer>().is_none());
+    }
+}
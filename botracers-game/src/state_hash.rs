@@ -0,0 +1,102 @@
+use bevy::prelude::*;
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// Rolling FNV-1a hash over the exact bit patterns of every car pose, fed once
+/// per fixed tick. Two runs with the same track, bots, tick rate and substeps
+/// must produce the same hash; any floating-point divergence changes it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StateHasher {
+    state: u64,
+    ticks: u64,
+}
+
+impl Default for StateHasher {
+    fn default() -> Self {
+        Self {
+            state: FNV_OFFSET_BASIS,
+            ticks: 0,
+        }
+    }
+}
+
+impl StateHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.state ^= u64::from(*byte);
+            self.state = self.state.wrapping_mul(FNV_PRIME);
+        }
+    }
+
+    /// Feeds one car's pose. Cars must be pushed in a stable order (grid order).
+    pub fn push_car(&mut self, car_index: u32, position: Vec2, heading: f32) {
+        self.write(&car_index.to_le_bytes());
+        self.write(&position.x.to_bits().to_le_bytes());
+        self.write(&position.y.to_bits().to_le_bytes());
+        self.write(&heading.to_bits().to_le_bytes());
+    }
+
+    /// Closes the current tick so that a pose moving between ticks changes the hash.
+    pub fn finish_tick(&mut self) {
+        self.ticks += 1;
+        self.write(&self.ticks.to_le_bytes());
+    }
+
+    pub fn ticks(&self) -> u64 {
+        self.ticks
+    }
+
+    /// Value published as `state_hash` in race results.
+    pub fn to_hex(&self) -> String {
+        format!("{:016x}", self.state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+
+    use super::StateHasher;
+
+    /// Deterministic stand-in for a race: two cars driving circles, with an
+    /// optional one-tick nudge applied to the second car.
+    fn run(ticks: u32, perturb_at: Option<u32>) -> StateHasher {
+        let mut hasher = StateHasher::default();
+        let mut poses = [(vec2(0.0, 0.0), 0.0_f32), (vec2(2.0, 0.0), 0.0_f32)];
+        for tick in 0..ticks {
+            for (index, (position, heading)) in poses.iter_mut().enumerate() {
+                *heading += 0.01 * (index as f32 + 1.0);
+                *position += Vec2::from_angle(*heading) * 0.05;
+                if index == 1 && perturb_at == Some(tick) {
+                    position.x += 1e-6;
+                }
+                hasher.push_car(index as u32, *position, *heading);
+            }
+            hasher.finish_tick();
+        }
+        hasher
+    }
+
+    #[test]
+    fn identical_runs_hash_equal_and_perturbed_runs_differ() {
+        let first = run(2_000, None);
+        let second = run(2_000, None);
+        assert_eq!(first.to_hex(), second.to_hex());
+        assert_eq!(first.ticks(), 2_000);
+
+        assert_ne!(run(2_000, Some(1_500)).to_hex(), first.to_hex());
+        assert_ne!(run(2_001, None).to_hex(), first.to_hex());
+    }
+
+    #[test]
+    fn car_order_is_part_of_the_hash() {
+        let mut a = StateHasher::default();
+        a.push_car(0, vec2(1.0, 2.0), 0.5);
+        a.push_car(1, vec2(3.0, 4.0), 0.5);
+        let mut b = StateHasher::default();
+        b.push_car(1, vec2(3.0, 4.0), 0.5);
+        b.push_car(0, vec2(1.0, 2.0), 0.5);
+        assert_ne!(a.to_hex(), b.to_hex());
+    }
+}
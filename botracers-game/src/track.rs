@@ -28,13 +28,82 @@ pub fn spline_length(spline: &CubicCurve<Vec2>, samples: usize) -> f32 {
     length
 }
 
+/// Track width along the centre line. Per-control-point widths are blended by
+/// the same closed B-spline as the centre line, so `at(t)` shares its parameter.
+#[derive(Clone, Debug)]
+pub enum TrackWidth {
+    Uniform(f32),
+    Varying(CubicCurve<f32>),
+}
+
+impl TrackWidth {
+    /// Uniform `track_width` unless the file lists one width per control point.
+    pub fn from_file(track_file: &crate::track_format::TrackFile) -> Self {
+        let metadata = &track_file.metadata;
+        if metadata.widths.is_empty() || metadata.widths.len() != track_file.control_points.len() {
+            return Self::Uniform(metadata.track_width);
+        }
+        CubicBSpline::new(metadata.widths.iter().copied())
+            .to_curve_cyclic()
+            .map_or(Self::Uniform(metadata.track_width), Self::Varying)
+    }
+
+    /// Full track width at spline parameter `t`.
+    pub fn at(&self, t: f32) -> f32 {
+        match self {
+            Self::Uniform(width) => *width,
+            Self::Varying(curve) => curve.position(t),
+        }
+    }
+}
+
+/// Banking along the centre line in radians, blended from the per-point
+/// `bank_angles` like `TrackWidth` so `at(t)` shares the spline parameter.
+/// Only data for now; physics and rendering do not read it yet.
+#[derive(Clone, Debug)]
+pub enum TrackBank {
+    Flat,
+    Banked(CubicCurve<f32>),
+}
+
+impl TrackBank {
+    /// Flat unless the file lists one bank angle per control point.
+    pub fn from_file(track_file: &crate::track_format::TrackFile) -> Self {
+        let angles = &track_file.metadata.bank_angles;
+        if angles.is_empty() || angles.len() != track_file.control_points.len() {
+            return Self::Flat;
+        }
+        CubicBSpline::new(angles.iter().copied())
+            .to_curve_cyclic()
+            .map_or(Self::Flat, Self::Banked)
+    }
+
+    /// Bank angle at spline parameter `t`.
+    pub fn at(&self, t: f32) -> f32 {
+        match self {
+            Self::Flat => 0.0,
+            Self::Banked(curve) => curve.position(t),
+        }
+    }
+}
+
+impl From<f32> for TrackWidth {
+    fn from(width: f32) -> Self {
+        Self::Uniform(width)
+    }
+}
+
 /// Get the first control point from a track file (useful for spawn position).
 pub fn first_point_from_file(track_file: &crate::track_format::TrackFile) -> Vec2 {
     let pts = track_file.control_points_vec2();
     pts[0]
 }
 
-pub fn create_track_mesh(spline: &CubicCurve<Vec2>, track_width: f32, segments: usize) -> Mesh {
+pub fn create_track_mesh(
+    spline: &CubicCurve<Vec2>,
+    track_width: &TrackWidth,
+    segments: usize,
+) -> Mesh {
     let domain = spline.domain();
     let t_max = domain.end();
 
@@ -54,8 +123,9 @@ pub fn create_track_mesh(spline: &CubicCurve<Vec2>, track_width: f32, segments:
         let normal = vec2(-tangent.y, tangent.x);
 
         // Inner and outer edge vertices
-        let inner = p1 - normal * track_width * 0.5;
-        let outer = p1 + normal * track_width * 0.5;
+        let half_width = track_width.at(t1) * 0.5;
+        let inner = p1 - normal * half_width;
+        let outer = p1 + normal * half_width;
 
         positions.push([inner.x, inner.y, 0.0]);
         positions.push([outer.x, outer.y, 0.0]);
@@ -87,7 +157,7 @@ pub fn create_track_mesh(spline: &CubicCurve<Vec2>, track_width: f32, segments:
 
 pub fn create_kerb_meshes(
     spline: &CubicCurve<Vec2>,
-    track_width: f32,
+    track_width: &TrackWidth,
     kerb_width: f32,
     segments: usize,
 ) -> (Mesh, Mesh) {
@@ -134,11 +204,13 @@ pub fn create_kerb_meshes(
             [0.95, 0.95, 0.95, 1.0]
         };
 
-        // Inner kerb - use appropriate normal at each end
-        let inner_edge_start = p - normal_start * track_width * 0.5;
-        let inner_outer_start = p - normal_start * (track_width * 0.5 - kerb_width);
-        let inner_edge_end = p_next - normal_end * track_width * 0.5;
-        let inner_outer_end = p_next - normal_end * (track_width * 0.5 - kerb_width);
+        // Inner kerb - use appropriate normal and width at each end
+        let half_start = track_width.at(t) * 0.5;
+        let half_end = track_width.at(t_next) * 0.5;
+        let inner_edge_start = p - normal_start * half_start;
+        let inner_outer_start = p - normal_start * (half_start - kerb_width);
+        let inner_edge_end = p_next - normal_end * half_end;
+        let inner_outer_end = p_next - normal_end * (half_end - kerb_width);
 
         let base_idx = inner_positions.len() as u32;
         inner_positions.push([inner_edge_start.x, inner_edge_start.y, 0.0]);
@@ -161,11 +233,11 @@ pub fn create_kerb_meshes(
         inner_indices.push(base_idx + 2);
         inner_indices.push(base_idx + 3);
 
-        // Outer kerb - use appropriate normal at each end
-        let outer_inner_start = p + normal_start * (track_width * 0.5 - kerb_width);
-        let outer_edge_start = p + normal_start * track_width * 0.5;
-        let outer_inner_end = p_next + normal_end * (track_width * 0.5 - kerb_width);
-        let outer_edge_end = p_next + normal_end * track_width * 0.5;
+        // Outer kerb - use appropriate normal and width at each end
+        let outer_inner_start = p + normal_start * (half_start - kerb_width);
+        let outer_edge_start = p + normal_start * half_start;
+        let outer_inner_end = p_next + normal_end * (half_end - kerb_width);
+        let outer_edge_end = p_next + normal_end * half_end;
 
         let base_idx = outer_positions.len() as u32;
         outer_positions.push([outer_inner_start.x, outer_inner_start.y, 0.0]);
@@ -209,10 +281,10 @@ pub fn create_kerb_meshes(
 /// Sample inner and outer track borders as closed polylines.
 ///
 /// This uses spline tangents with neighboring samples to compute a stable normal,
-/// then offsets by half the track width on both sides.
+/// then offsets by half the local track width on both sides.
 pub fn sample_track_borders(
     spline: &CubicCurve<Vec2>,
-    track_width: f32,
+    track_width: &TrackWidth,
     segments: usize,
 ) -> (Vec<Vec2>, Vec<Vec2>) {
     let domain = spline.domain();
@@ -235,9 +307,505 @@ pub fn sample_track_borders(
         let tangent = (p_next - p_prev).normalize();
         let normal = vec2(-tangent.y, tangent.x);
 
-        inner.push(p - normal * track_width * 0.5);
-        outer.push(p + normal * track_width * 0.5);
+        let half_width = track_width.at(t) * 0.5;
+        inner.push(p - normal * half_width);
+        outer.push(p + normal * half_width);
     }
 
     (inner, outer)
 }
+
+/// Even-odd test of `point` against the closed polygon through `polygon`.
+fn point_in_polygon(point: Vec2, polygon: &[Vec2]) -> bool {
+    let mut inside = false;
+    let mut previous = match polygon.last() {
+        Some(&last) => last,
+        None => return false,
+    };
+    for &current in polygon {
+        if (current.y > point.y) != (previous.y > point.y) {
+            let x = current.x
+                + (point.y - current.y) * (previous.x - current.x) / (previous.y - current.y);
+            if point.x < x {
+                inside = !inside;
+            }
+        }
+        previous = current;
+    }
+    inside
+}
+
+/// Whether `point` is on the track surface between the closed border
+/// polylines from `sample_track_borders`. That is the case when it lies inside
+/// exactly one of them, whichever border encloses the other.
+pub fn point_in_track_band(point: Vec2, inner: &[Vec2], outer: &[Vec2]) -> bool {
+    point_in_polygon(point, inner) != point_in_polygon(point, outer)
+}
+
+/// Arc-length table of the closed centre line, used to turn a world position
+/// into the distance driven since the start line (spline `t = 0`).
+#[derive(Resource, Clone)]
+pub struct TrackProgressTable {
+    points: Vec<Vec2>,
+    cumulative: Vec<f32>,
+    length: f32,
+    t_max: f32,
+}
+
+impl TrackProgressTable {
+    pub fn new(spline: &CubicCurve<Vec2>, samples: usize) -> Self {
+        let t_max = spline.domain().end();
+        let mut points = Vec::with_capacity(samples);
+        let mut cumulative = Vec::with_capacity(samples);
+        let mut length = 0.0f32;
+        for i in 0..samples {
+            let p = spline.position((i as f32 / samples as f32) * t_max);
+            if let Some(prev) = points.last() {
+                length += p.distance(*prev);
+            }
+            points.push(p);
+            cumulative.push(length);
+        }
+        length += points[samples - 1].distance(points[0]);
+        Self {
+            points,
+            cumulative,
+            length,
+            t_max,
+        }
+    }
+
+    /// Total centre-line length of one lap in world units.
+    pub fn length(&self) -> f32 {
+        self.length
+    }
+
+    /// Distance along the lap of the centre-line point closest to `position`,
+    /// in `[0, length)`.
+    pub fn distance_along(&self, position: Vec2) -> f32 {
+        let n = self.points.len();
+        let mut best_dist_sq = f32::INFINITY;
+        let mut best_distance = 0.0;
+        for i in 0..n {
+            let a = self.points[i];
+            let b = self.points[(i + 1) % n];
+            let ab = b - a;
+            let seg_len_sq = ab.length_squared();
+            let s = if seg_len_sq > 0.0 {
+                ((position - a).dot(ab) / seg_len_sq).clamp(0.0, 1.0)
+            } else {
+                0.0
+            };
+            let dist_sq = position.distance_squared(a + ab * s);
+            if dist_sq < best_dist_sq {
+                best_dist_sq = dist_sq;
+                best_distance = self.cumulative[i] + seg_len_sq.sqrt() * s;
+            }
+        }
+        best_distance % self.length
+    }
+
+    /// Spline parameter of the centre-line point `distance` along the lap
+    /// (wrapped into one lap), interpolated between the table's samples.
+    pub fn t_at_distance(&self, distance: f32) -> f32 {
+        let distance = distance.rem_euclid(self.length);
+        let i = self
+            .cumulative
+            .partition_point(|&c| c <= distance)
+            .saturating_sub(1);
+        let start = self.cumulative[i];
+        let end = self.cumulative.get(i + 1).copied().unwrap_or(self.length);
+        let frac = if end > start {
+            (distance - start) / (end - start)
+        } else {
+            0.0
+        };
+        (i as f32 + frac) / self.points.len() as f32 * self.t_max
+    }
+
+    /// Fraction of the lap completed at `position`, in `[0, 1)`.
+    pub fn lap_fraction(&self, position: Vec2) -> f32 {
+        self.distance_along(position) / self.length
+    }
+}
+
+/// Where a car currently is along the lap, refreshed every fixed tick.
+#[derive(Component, Default, Clone, Copy)]
+pub struct TrackProgress {
+    pub distance: f32,
+    pub lap_fraction: f32,
+}
+
+/// Runs in PreCpu before the devices read it: projects each car onto the centre line.
+pub fn track_progress_system(
+    table: Res<TrackProgressTable>,
+    mut query: Query<(&Transform, &mut TrackProgress)>,
+) {
+    for (transform, mut progress) in &mut query {
+        let distance = table.distance_along(transform.translation.xy());
+        progress.distance = distance;
+        progress.lap_fraction = distance / table.length();
+    }
+}
+
+/// Number of equal-length sectors a lap is split into for practice checkpoints.
+pub const PRACTICE_SECTORS: u32 = 8;
+
+/// Car pose captured at a sector boundary. `heading` is the angle of the car's
+/// forward vector.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Checkpoint {
+    pub sector: u32,
+    pub position: Vec2,
+    pub heading: f32,
+}
+
+/// Remembers the pose at the last sector boundary a car crossed going forward,
+/// so practice mode can respawn it there. The first observation (the grid
+/// position) counts as a checkpoint.
+#[derive(Component, Clone, Debug)]
+pub struct CheckpointTracker {
+    sectors: u32,
+    current_sector: Option<u32>,
+    last: Option<Checkpoint>,
+}
+
+impl Default for CheckpointTracker {
+    fn default() -> Self {
+        Self::new(PRACTICE_SECTORS)
+    }
+}
+
+impl CheckpointTracker {
+    pub fn new(sectors: u32) -> Self {
+        Self {
+            sectors: sectors.max(1),
+            current_sector: None,
+            last: None,
+        }
+    }
+
+    fn sector_of(&self, lap_fraction: f32) -> u32 {
+        ((lap_fraction * self.sectors as f32) as u32).min(self.sectors - 1)
+    }
+
+    /// Feeds the car's current progress and pose; returns true when a new
+    /// checkpoint was captured. Backwards crossings only move the current sector.
+    pub fn observe(&mut self, lap_fraction: f32, position: Vec2, heading: f32) -> bool {
+        let sector = self.sector_of(lap_fraction);
+        let captured = match self.current_sector {
+            None => true,
+            Some(current) => sector == (current + 1) % self.sectors,
+        };
+        self.current_sector = Some(sector);
+        if captured {
+            self.last = Some(Checkpoint {
+                sector,
+                position,
+                heading,
+            });
+        }
+        captured
+    }
+
+    pub fn last(&self) -> Option<Checkpoint> {
+        self.last
+    }
+
+    /// Returns the checkpoint to respawn at and rewinds the current sector to it.
+    pub fn restore(&mut self) -> Option<Checkpoint> {
+        let checkpoint = self.last?;
+        self.current_sector = Some(checkpoint.sector);
+        Some(checkpoint)
+    }
+}
+
+/// Runs in PreCpu after `track_progress_system`.
+pub fn checkpoint_system(mut query: Query<(&Transform, &TrackProgress, &mut CheckpointTracker)>) {
+    for (transform, progress, mut tracker) in &mut query {
+        tracker.observe(
+            progress.lap_fraction,
+            transform.translation.xy(),
+            transform.up().xy().to_angle(),
+        );
+    }
+}
+
+/// Direction a car crossed the start/finish line between two successive
+/// lap fractions, if it crossed at all.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LineCrossing {
+    Forward,
+    Backward,
+}
+
+/// A wrap of the lap fraction from the last quarter to the first (or back)
+/// between two ticks is a line crossing; a car cannot cover half a lap in one tick.
+pub fn line_crossing(previous: f32, current: f32) -> Option<LineCrossing> {
+    if previous > 0.75 && current < 0.25 {
+        Some(LineCrossing::Forward)
+    } else if previous < 0.25 && current > 0.75 {
+        Some(LineCrossing::Backward)
+    } else {
+        None
+    }
+}
+
+/// Lap count and lap times of one car, in fixed ticks. A forward line
+/// crossing completes a lap only if the car went round the far half of the
+/// track since the last one, so reversing over the line and grid slots just
+/// behind it never count; the first such crossing restarts the lap clock.
+#[derive(Component, Default, Clone, Debug)]
+pub struct LapTimer {
+    previous_fraction: Option<f32>,
+    passed_halfway: bool,
+    lap_start_tick: u64,
+    pub laps: u32,
+    pub last_lap_ticks: Option<u64>,
+    pub best_lap_ticks: Option<u64>,
+}
+
+impl LapTimer {
+    /// Feeds the car's lap fraction at race tick `tick`; returns true when a
+    /// lap was completed.
+    pub fn observe(&mut self, lap_fraction: f32, tick: u64) -> bool {
+        let previous = self.previous_fraction.replace(lap_fraction);
+        if (0.25..=0.75).contains(&lap_fraction) {
+            self.passed_halfway = true;
+        }
+        let Some(previous) = previous else {
+            self.lap_start_tick = tick;
+            return false;
+        };
+        match line_crossing(previous, lap_fraction) {
+            Some(LineCrossing::Forward) if self.passed_halfway => {
+                let lap_ticks = tick - self.lap_start_tick;
+                self.laps += 1;
+                self.last_lap_ticks = Some(lap_ticks);
+                self.best_lap_ticks = Some(
+                    self.best_lap_ticks
+                        .map_or(lap_ticks, |best| best.min(lap_ticks)),
+                );
+                self.lap_start_tick = tick;
+                self.passed_halfway = false;
+                true
+            }
+            Some(LineCrossing::Forward) => {
+                if self.laps == 0 {
+                    self.lap_start_tick = tick;
+                }
+                false
+            }
+            Some(LineCrossing::Backward) => {
+                self.passed_halfway = false;
+                false
+            }
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+
+    use super::{
+        CheckpointTracker, LapTimer, LineCrossing, TrackBank, TrackProgressTable, TrackWidth,
+        build_spline, line_crossing, point_in_track_band, sample_track_borders,
+    };
+    use crate::track_format::TrackFile;
+
+    fn straightish_loop() -> CubicCurve<Vec2> {
+        build_spline(&[
+            vec2(0.0, 0.0),
+            vec2(50.0, 0.0),
+            vec2(100.0, 0.0),
+            vec2(110.0, 20.0),
+            vec2(100.0, 40.0),
+            vec2(50.0, 40.0),
+            vec2(0.0, 40.0),
+            vec2(-10.0, 20.0),
+        ])
+    }
+
+    #[test]
+    fn per_point_widths_vary_border_separation() {
+        let spline = straightish_loop();
+        let mut track_file = TrackFile::new_empty("test");
+        track_file.control_points = vec![[0.0, 0.0]; 8];
+        track_file.metadata.widths = vec![4.0, 4.0, 4.0, 4.0, 20.0, 20.0, 20.0, 20.0];
+        let widths = TrackWidth::from_file(&track_file);
+
+        let (inner, outer) = sample_track_borders(&spline, &widths, 64);
+        let separation: Vec<f32> = inner
+            .iter()
+            .zip(&outer)
+            .map(|(a, b)| a.distance(*b))
+            .collect();
+        let narrowest = separation.iter().copied().fold(f32::INFINITY, f32::min);
+        let widest = separation.iter().copied().fold(0.0, f32::max);
+        assert!(narrowest < 6.0, "narrowest separation {narrowest}");
+        assert!(widest > 18.0, "widest separation {widest}");
+
+        // Without per-point widths the borders stay `track_width` apart.
+        track_file.metadata.widths.clear();
+        let (inner, outer) = sample_track_borders(&spline, &TrackWidth::from_file(&track_file), 64);
+        for (a, b) in inner.iter().zip(&outer) {
+            assert!((a.distance(*b) - track_file.metadata.track_width).abs() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn bank_angles_blend_between_control_points() {
+        let mut track_file = TrackFile::new_empty("test");
+        track_file.control_points = vec![[0.0, 0.0]; 6];
+        assert_eq!(TrackBank::from_file(&track_file).at(2.5), 0.0);
+
+        track_file.metadata.bank_angles = vec![0.0, 0.2, 0.4, 0.0, 0.0, 0.0];
+        let bank = TrackBank::from_file(&track_file);
+        // Segment 0 runs between control points 1 and 2; its midpoint weighs
+        // the four points it spans by 1/48, 23/48, 23/48, 1/48.
+        let expected = (23.0 * 0.2 + 23.0 * 0.4) / 48.0;
+        assert!((bank.at(0.5) - expected).abs() < 1e-6, "{}", bank.at(0.5));
+        // Far from the banked corner the track is flat.
+        assert!(bank.at(3.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn track_band_contains_the_surface_but_not_infield_or_outfield() {
+        let square = |half: f32| {
+            vec![
+                vec2(-half, -half),
+                vec2(half, -half),
+                vec2(half, half),
+                vec2(-half, half),
+            ]
+        };
+        let (inner, outer) = (square(5.0), square(10.0));
+        for (point, on_track) in [
+            (vec2(7.5, 0.0), true),
+            (vec2(-9.0, 9.0), true),
+            (vec2(0.0, 0.0), false),
+            (vec2(4.0, 4.0), false),
+            (vec2(12.0, 0.0), false),
+            (vec2(0.0, -30.0), false),
+        ] {
+            assert_eq!(
+                point_in_track_band(point, &inner, &outer),
+                on_track,
+                "{point}"
+            );
+            // The result must not depend on which border is passed as inner.
+            assert_eq!(
+                point_in_track_band(point, &outer, &inner),
+                on_track,
+                "{point}"
+            );
+        }
+
+        let spline = straightish_loop();
+        let (inner, outer) = sample_track_borders(&spline, &TrackWidth::Uniform(6.0), 256);
+        assert!(point_in_track_band(spline.position(0.0), &inner, &outer));
+        assert!(!point_in_track_band(vec2(50.0, 20.0), &inner, &outer));
+        assert!(!point_in_track_band(vec2(50.0, -20.0), &inner, &outer));
+    }
+
+    #[test]
+    fn lap_fraction_is_half_way_round_the_loop() {
+        let spline = straightish_loop();
+        let table = TrackProgressTable::new(&spline, 512);
+        let t_max = spline.domain().end();
+
+        assert!(table.lap_fraction(spline.position(0.0)) < 0.01);
+        let half = table.lap_fraction(spline.position(t_max * 0.5));
+        assert!((half - 0.5).abs() < 0.02, "half-way fraction was {half}");
+        assert!(table.length() > 200.0);
+    }
+
+    #[test]
+    fn lap_fraction_is_monotonic_and_wraps_at_start_line() {
+        let spline = straightish_loop();
+        let table = TrackProgressTable::new(&spline, 512);
+        let t_max = spline.domain().end();
+
+        let mut previous = 0.0;
+        for i in 0..200 {
+            let fraction = table.lap_fraction(spline.position(t_max * i as f32 / 200.0));
+            assert!(fraction >= previous, "fraction went backwards at step {i}");
+            previous = fraction;
+        }
+        assert!(previous > 0.98);
+        assert!(table.lap_fraction(spline.position(t_max * 0.999_9)) > 0.99);
+    }
+
+    #[test]
+    fn checkpoint_tracker_captures_forward_crossings_and_restores() {
+        let mut tracker = CheckpointTracker::new(4);
+        assert_eq!(tracker.restore(), None);
+
+        assert!(tracker.observe(0.0, vec2(0.0, 0.0), 0.0));
+        assert!(!tracker.observe(0.2, vec2(2.0, 0.0), 0.0));
+        assert!(tracker.observe(0.26, vec2(2.6, 0.0), 0.1));
+        let first = tracker.last().expect("checkpoint");
+        assert_eq!(first.sector, 1);
+        assert_eq!(first.position, vec2(2.6, 0.0));
+        assert_eq!(first.heading, 0.1);
+
+        // Reversing over the boundary does not capture, driving forward again does.
+        assert!(!tracker.observe(0.24, vec2(2.4, 0.0), 3.0));
+        assert_eq!(tracker.last(), Some(first));
+        assert!(tracker.observe(0.251, vec2(2.51, 0.0), 0.2));
+
+        assert!(tracker.observe(0.5, vec2(5.0, 0.0), 0.0));
+        assert!(tracker.observe(0.75, vec2(7.5, 0.0), 0.0));
+        assert!(tracker.observe(0.01, vec2(0.1, 0.0), 0.0));
+        assert_eq!(tracker.last().map(|c| c.sector), Some(0));
+
+        // Skipping a sector (e.g. a cut) does not capture. Restoring rewinds the
+        // current sector so the next crossing is captured again.
+        assert!(!tracker.observe(0.6, vec2(6.0, 0.0), 0.0));
+        let restored = tracker.restore().expect("restore");
+        assert_eq!(restored.position, vec2(0.1, 0.0));
+        assert!(!tracker.observe(0.02, vec2(0.2, 0.0), 0.0));
+        assert!(tracker.observe(0.3, vec2(3.0, 0.0), 0.0));
+    }
+
+    #[test]
+    fn line_crossing_direction_from_successive_fractions() {
+        assert_eq!(line_crossing(0.99, 0.01), Some(LineCrossing::Forward));
+        assert_eq!(line_crossing(0.01, 0.99), Some(LineCrossing::Backward));
+        assert_eq!(line_crossing(0.4, 0.6), None);
+        assert_eq!(line_crossing(0.6, 0.4), None);
+        assert_eq!(line_crossing(0.98, 0.99), None);
+    }
+
+    #[test]
+    fn lap_timer_counts_only_full_forward_laps() {
+        let mut timer = LapTimer::default();
+        // Grid slot just behind the line: the first crossing starts the clock.
+        assert!(!timer.observe(0.98, 0));
+        assert!(!timer.observe(0.02, 10));
+        assert_eq!(timer.laps, 0);
+
+        // Reversing over the line and back does not count.
+        assert!(!timer.observe(0.97, 20));
+        assert!(!timer.observe(0.03, 30));
+        assert_eq!(timer.laps, 0);
+
+        for (fraction, tick) in [(0.3, 100), (0.6, 200), (0.9, 300)] {
+            assert!(!timer.observe(fraction, tick));
+        }
+        assert!(timer.observe(0.01, 410));
+        assert_eq!(timer.laps, 1);
+        // Timed from the last forward crossing at tick 30.
+        assert_eq!(timer.last_lap_ticks, Some(380));
+
+        for (fraction, tick) in [(0.5, 500), (0.9, 600)] {
+            timer.observe(fraction, tick);
+        }
+        assert!(timer.observe(0.05, 710));
+        assert_eq!(timer.laps, 2);
+        assert_eq!(timer.last_lap_ticks, Some(300));
+        assert_eq!(timer.best_lap_ticks, Some(300));
+    }
+}
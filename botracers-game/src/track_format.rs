@@ -1,11 +1,105 @@
 use bevy::math::Vec2;
 use serde::{Deserialize, Serialize};
-use std::path::Path;
+use std::{
+    fmt, io,
+    path::{Path, PathBuf},
+};
+
+mod svg;
+
+/// Why a track file could not be loaded.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TrackError {
+    /// The file could not be read.
+    Io { path: String, message: String },
+    /// The contents are not valid track TOML.
+    Parse(String),
+    /// A closed spline needs at least three control points.
+    TooFewControlPoints(usize),
+    /// `track_width` and every entry of `widths` must be positive.
+    InvalidTrackWidth(f32),
+    /// `widths` must be empty or have one entry per control point.
+    WidthCountMismatch {
+        widths: usize,
+        control_points: usize,
+    },
+    /// Kerbs wider than half the (narrowest) track would overlap in the middle.
+    KerbTooWide { kerb_width: f32, track_width: f32 },
+    /// `bank_angles` must be empty or have one entry per control point.
+    BankCountMismatch {
+        bank_angles: usize,
+        control_points: usize,
+    },
+    /// Every entry of `bank_angles` must be finite.
+    InvalidBankAngle(f32),
+    /// Every `[physics]` override must be positive and finite.
+    InvalidPhysics { field: &'static str, value: f32 },
+    /// The SVG path uses a command other than M/L/C/Z (or their relative forms).
+    UnsupportedSvgCommand(char),
+    /// The SVG file or path data could not be understood.
+    InvalidSvgPath(String),
+}
+
+impl fmt::Display for TrackError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io { path, message } => write!(f, "failed to read {path}: {message}"),
+            Self::Parse(message) => write!(f, "failed to parse track: {message}"),
+            Self::TooFewControlPoints(count) => write!(
+                f,
+                "track needs at least {MIN_CONTROL_POINTS} control points, found {count}"
+            ),
+            Self::InvalidTrackWidth(width) => {
+                write!(f, "track_width must be positive, found {width}")
+            }
+            Self::WidthCountMismatch {
+                widths,
+                control_points,
+            } => write!(
+                f,
+                "widths has {widths} entries but the track has {control_points} control points"
+            ),
+            Self::KerbTooWide {
+                kerb_width,
+                track_width,
+            } => write!(
+                f,
+                "kerb_width {kerb_width} exceeds half the track_width {track_width}"
+            ),
+            Self::BankCountMismatch {
+                bank_angles,
+                control_points,
+            } => write!(
+                f,
+                "bank_angles has {bank_angles} entries but the track has {control_points} control points"
+            ),
+            Self::InvalidBankAngle(angle) => {
+                write!(f, "bank angles must be finite, found {angle}")
+            }
+            Self::InvalidPhysics { field, value } => {
+                write!(f, "physics.{field} must be positive, found {value}")
+            }
+            Self::UnsupportedSvgCommand(command) => write!(
+                f,
+                "unsupported SVG path command '{command}' (only M, L, C and Z are supported)"
+            ),
+            Self::InvalidSvgPath(message) => write!(f, "invalid SVG path: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for TrackError {}
+
+/// Fewest control points that form a closed track.
+pub const MIN_CONTROL_POINTS: usize = 3;
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct TrackFile {
     #[serde(default)]
     pub metadata: TrackMetadata,
+    /// Optional `[physics]` table; unset fields keep the built-in kart.
+    #[serde(default, skip_serializing_if = "TrackPhysics::is_empty")]
+    pub physics: TrackPhysics,
     pub control_points: Vec<[f32; 2]>,
 }
 
@@ -19,6 +113,12 @@ pub struct TrackMetadata {
     pub track_width: f32,
     #[serde(default = "default_kerb_width")]
     pub kerb_width: f32,
+    /// Optional per-control-point widths; empty means `track_width` everywhere.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub widths: Vec<f32>,
+    /// Optional per-control-point banking in radians; empty means flat.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub bank_angles: Vec<f32>,
 }
 
 impl Default for TrackMetadata {
@@ -28,10 +128,54 @@ impl Default for TrackMetadata {
             author: String::new(),
             track_width: default_track_width(),
             kerb_width: default_kerb_width(),
+            widths: Vec::new(),
+            bank_angles: Vec::new(),
         }
     }
 }
 
+/// Kart model overrides for a track. `None` keeps the game's default.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct TrackPhysics {
+    /// Kart mass in kg.
+    pub mass_kg: Option<f32>,
+    /// Peak engine torque in N·m; scales acceleration.
+    pub torque_peak_nm: Option<f32>,
+    /// Maximum brake torque at the rear axle in N·m.
+    pub brake_max_axle_nm: Option<f32>,
+    /// Tyre friction coefficient limiting drive and brake force.
+    pub tire_mu: Option<f32>,
+    /// Cap on each wheel's sideways grip acceleration.
+    pub lateral_grip: Option<f32>,
+    /// Sideways grip per unit of wheel speed, up to `lateral_grip`.
+    pub lateral_grip_per_speed: Option<f32>,
+    /// Distance from the rear to the front axle.
+    pub wheel_base: Option<f32>,
+    /// Distance between the left and right wheels.
+    pub wheel_track: Option<f32>,
+}
+
+impl TrackPhysics {
+    /// True when the track keeps every default.
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Set overrides by their TOML key.
+    fn overrides(&self) -> [(&'static str, Option<f32>); 8] {
+        [
+            ("mass_kg", self.mass_kg),
+            ("torque_peak_nm", self.torque_peak_nm),
+            ("brake_max_axle_nm", self.brake_max_axle_nm),
+            ("tire_mu", self.tire_mu),
+            ("lateral_grip", self.lateral_grip),
+            ("lateral_grip_per_speed", self.lateral_grip_per_speed),
+            ("wheel_base", self.wheel_base),
+            ("wheel_track", self.wheel_track),
+        ]
+    }
+}
+
 fn default_name() -> String {
     "Untitled".to_string()
 }
@@ -53,21 +197,154 @@ impl TrackFile {
                 author: String::new(),
                 track_width: default_track_width(),
                 kerb_width: default_kerb_width(),
+                widths: Vec::new(),
+                bank_angles: Vec::new(),
             },
+            physics: TrackPhysics::default(),
             control_points: Vec::new(),
         }
     }
 
-    pub fn load_builtin() -> Result<Self, String> {
-        let text = include_str!("../assets/track1.toml");
-        toml::from_str(&text).map_err(|e| format!("Failed to parse track1.toml: {}", e))
+    /// A small oval used when no track file can be loaded.
+    pub fn fallback() -> Self {
+        let control_points = (0..12)
+            .map(|i| {
+                let angle = i as f32 / 12.0 * std::f32::consts::TAU;
+                [angle.cos() * 90.0, angle.sin() * 50.0]
+            })
+            .collect();
+        Self {
+            metadata: TrackMetadata {
+                name: "Fallback Oval".to_string(),
+                ..TrackMetadata::default()
+            },
+            physics: TrackPhysics::default(),
+            control_points,
+        }
+    }
+
+    pub fn load_builtin() -> Result<Self, TrackError> {
+        Self::parse(include_str!("../assets/track1.toml"))
     }
 
     /// Load a track from a TOML file.
-    pub fn load(path: &Path) -> Result<Self, String> {
-        let text = std::fs::read_to_string(path)
-            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
-        toml::from_str(&text).map_err(|e| format!("Failed to parse {}: {}", path.display(), e))
+    pub fn load(path: &Path) -> Result<Self, TrackError> {
+        let text = std::fs::read_to_string(path).map_err(|e| TrackError::Io {
+            path: path.display().to_string(),
+            message: e.to_string(),
+        })?;
+        Self::parse(&text)
+    }
+
+    /// Parse and validate track TOML.
+    pub fn parse(text: &str) -> Result<Self, TrackError> {
+        let track: Self = toml::from_str(text).map_err(|e| TrackError::Parse(e.to_string()))?;
+        track.validate()?;
+        Ok(track)
+    }
+
+    /// Build a track from SVG path data (the `d` attribute), sampling each
+    /// cubic segment into `samples` control points. Coordinates are taken as
+    /// metres with y flipped; metadata is the `new_empty` default.
+    pub fn from_svg_path(d: &str, samples: usize) -> Result<Self, TrackError> {
+        let mut track = Self::new_empty("Imported SVG");
+        track.control_points = svg::sample_path(d, samples)?;
+        track.validate()?;
+        Ok(track)
+    }
+
+    /// Import the first `<path>` of an SVG file, naming the track after the
+    /// file stem.
+    pub fn load_svg(path: &Path, samples: usize) -> Result<Self, TrackError> {
+        let text = std::fs::read_to_string(path).map_err(|e| TrackError::Io {
+            path: path.display().to_string(),
+            message: e.to_string(),
+        })?;
+        let d = svg::first_path_data(&text).ok_or_else(|| {
+            TrackError::InvalidSvgPath("no <path> element with a d attribute".to_string())
+        })?;
+        let mut track = Self::from_svg_path(d, samples)?;
+        if let Some(stem) = path.file_stem() {
+            track.metadata.name = stem.to_string_lossy().into_owned();
+        }
+        Ok(track)
+    }
+
+    /// Check that the track can be built into a closed spline with sane widths
+    /// and physics overrides.
+    pub fn validate(&self) -> Result<(), TrackError> {
+        if self.control_points.len() < MIN_CONTROL_POINTS {
+            return Err(TrackError::TooFewControlPoints(self.control_points.len()));
+        }
+        let metadata = &self.metadata;
+        if !metadata.widths.is_empty() && metadata.widths.len() != self.control_points.len() {
+            return Err(TrackError::WidthCountMismatch {
+                widths: metadata.widths.len(),
+                control_points: self.control_points.len(),
+            });
+        }
+        for &width in std::iter::once(&metadata.track_width).chain(&metadata.widths) {
+            if width.is_nan() || width <= 0.0 {
+                return Err(TrackError::InvalidTrackWidth(width));
+            }
+        }
+        let narrowest = if metadata.widths.is_empty() {
+            metadata.track_width
+        } else {
+            metadata
+                .widths
+                .iter()
+                .copied()
+                .fold(f32::INFINITY, f32::min)
+        };
+        if metadata.kerb_width > narrowest / 2.0 {
+            return Err(TrackError::KerbTooWide {
+                kerb_width: metadata.kerb_width,
+                track_width: narrowest,
+            });
+        }
+        if !metadata.bank_angles.is_empty()
+            && metadata.bank_angles.len() != self.control_points.len()
+        {
+            return Err(TrackError::BankCountMismatch {
+                bank_angles: metadata.bank_angles.len(),
+                control_points: self.control_points.len(),
+            });
+        }
+        if let Some(&angle) = metadata.bank_angles.iter().find(|angle| !angle.is_finite()) {
+            return Err(TrackError::InvalidBankAngle(angle));
+        }
+        for (field, value) in self.physics.overrides() {
+            if let Some(value) = value.filter(|value| !(value.is_finite() && *value > 0.0)) {
+                return Err(TrackError::InvalidPhysics { field, value });
+            }
+        }
+        Ok(())
+    }
+
+    /// Insert a control point, giving it the mean width and bank angle of its
+    /// neighbours when the track has per-point `widths` / `bank_angles`.
+    pub fn insert_control_point(&mut self, index: usize, point: [f32; 2]) {
+        let count = self.control_points.len();
+        for values in [&mut self.metadata.widths, &mut self.metadata.bank_angles] {
+            if values.len() == count && !values.is_empty() {
+                let before = values[(index + values.len() - 1) % values.len()];
+                let after = values[index % values.len()];
+                values.insert(index, (before + after) / 2.0);
+            }
+        }
+        self.control_points.insert(index, point);
+    }
+
+    /// Remove a control point together with its per-point width and bank angle.
+    pub fn remove_control_point(&mut self, index: usize) {
+        let count = self.control_points.len();
+        for values in [&mut self.metadata.widths, &mut self.metadata.bank_angles] {
+            if values.len() == count {
+                values.remove(index);
+            }
+        }
+        self.control_points.remove(index);
     }
 
     /// Save this track to a TOML file.
@@ -85,3 +362,214 @@ impl TrackFile {
             .collect()
     }
 }
+
+/// Track files (`*.toml`) directly inside `dir`, sorted by path.
+pub fn list_track_files(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut tracks = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_file() && path.extension().is_some_and(|ext| ext == "toml") {
+            tracks.push(path);
+        }
+    }
+    tracks.sort();
+    Ok(tracks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TRIANGLE: &str = "control_points = [[0.0, 0.0], [10.0, 0.0], [0.0, 10.0]]\n";
+
+    #[test]
+    fn builtin_and_fallback_tracks_are_valid() {
+        assert!(TrackFile::load_builtin().is_ok());
+        assert_eq!(TrackFile::fallback().validate(), Ok(()));
+    }
+
+    #[test]
+    fn list_track_files_finds_sorted_toml_files_only() {
+        let dir = std::env::temp_dir().join(format!("botracers_tracks_{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("nested.toml")).unwrap();
+        for name in ["b.toml", "a.toml", "notes.txt", "track.csv"] {
+            std::fs::write(dir.join(name), TRIANGLE).unwrap();
+        }
+
+        let tracks = list_track_files(&dir).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(tracks, vec![dir.join("a.toml"), dir.join("b.toml")]);
+        assert!(list_track_files(&dir).is_err());
+    }
+
+    #[test]
+    fn svg_lines_and_cubics_become_control_points() {
+        let track =
+            TrackFile::from_svg_path("M 0 0 L 100 0 C 130 0 130 60 100 60 L 0,60 Z", 4).unwrap();
+        // moveto + lineto + 4 cubic samples + lineto; the closing Z adds nothing.
+        assert_eq!(track.control_points.len(), 7);
+        assert_eq!(track.control_points[3], [122.5, -30.0]);
+        assert_eq!(track.control_points[6], [0.0, -60.0]);
+        assert_eq!(
+            TrackFile::from_svg_path("M0 0 L10 0 L10 10 L0 0 Z", 4)
+                .unwrap()
+                .control_points
+                .len(),
+            3
+        );
+    }
+
+    #[test]
+    fn svg_arcs_and_short_paths_are_rejected() {
+        let error = TrackFile::from_svg_path("M0 0 A 5 5 0 0 1 10 0 Z", 4).unwrap_err();
+        assert_eq!(error, TrackError::UnsupportedSvgCommand('A'));
+        assert!(error.to_string().contains("'A'"), "{error}");
+        assert!(matches!(
+            TrackFile::from_svg_path("L 10 0", 4),
+            Err(TrackError::InvalidSvgPath(_))
+        ));
+        assert_eq!(
+            TrackFile::from_svg_path("M0 0 L10 0", 4).unwrap_err(),
+            TrackError::TooFewControlPoints(2)
+        );
+    }
+
+    #[test]
+    fn missing_file_is_an_io_error() {
+        let error = TrackFile::load(Path::new("/nonexistent/track.toml")).unwrap_err();
+        assert!(matches!(error, TrackError::Io { .. }), "{error}");
+    }
+
+    #[test]
+    fn unparseable_toml_is_a_parse_error() {
+        assert!(matches!(
+            TrackFile::parse("control_points = \"nope\""),
+            Err(TrackError::Parse(_))
+        ));
+    }
+
+    #[test]
+    fn fewer_than_three_points_are_rejected() {
+        assert_eq!(
+            TrackFile::parse("control_points = [[0.0, 0.0], [1.0, 1.0]]").unwrap_err(),
+            TrackError::TooFewControlPoints(2)
+        );
+    }
+
+    #[test]
+    fn negative_track_width_is_rejected() {
+        let text = format!("{TRIANGLE}[metadata]\ntrack_width = -4.0\n");
+        assert_eq!(
+            TrackFile::parse(&text).unwrap_err(),
+            TrackError::InvalidTrackWidth(-4.0)
+        );
+    }
+
+    #[test]
+    fn kerbs_wider_than_half_the_track_are_rejected() {
+        let text = format!("{TRIANGLE}[metadata]\ntrack_width = 6.0\nkerb_width = 3.5\n");
+        assert_eq!(
+            TrackFile::parse(&text).unwrap_err(),
+            TrackError::KerbTooWide {
+                kerb_width: 3.5,
+                track_width: 6.0
+            }
+        );
+        let text = format!("{TRIANGLE}[metadata]\ntrack_width = 6.0\nkerb_width = 3.0\n");
+        assert!(TrackFile::parse(&text).is_ok());
+    }
+
+    #[test]
+    fn widths_must_match_control_points_and_be_positive() {
+        let text = format!("{TRIANGLE}[metadata]\nwidths = [10.0, 12.0]\n");
+        assert_eq!(
+            TrackFile::parse(&text).unwrap_err(),
+            TrackError::WidthCountMismatch {
+                widths: 2,
+                control_points: 3
+            }
+        );
+        let text = format!("{TRIANGLE}[metadata]\nwidths = [10.0, 0.0, 12.0]\n");
+        assert_eq!(
+            TrackFile::parse(&text).unwrap_err(),
+            TrackError::InvalidTrackWidth(0.0)
+        );
+        let text = format!("{TRIANGLE}[metadata]\nwidths = [10.0, 4.0, 12.0]\nkerb_width = 2.5\n");
+        assert!(matches!(
+            TrackFile::parse(&text),
+            Err(TrackError::KerbTooWide { .. })
+        ));
+    }
+
+    #[test]
+    fn bank_angles_parse_and_default_to_flat() {
+        let text = format!("{TRIANGLE}[metadata]\nbank_angles = [0.0, 0.1, -0.2]\n");
+        let track = TrackFile::parse(&text).unwrap();
+        assert_eq!(track.metadata.bank_angles, vec![0.0, 0.1, -0.2]);
+        assert!(
+            TrackFile::parse(TRIANGLE)
+                .unwrap()
+                .metadata
+                .bank_angles
+                .is_empty()
+        );
+
+        let text = format!("{TRIANGLE}[metadata]\nbank_angles = [0.1]\n");
+        assert_eq!(
+            TrackFile::parse(&text).unwrap_err(),
+            TrackError::BankCountMismatch {
+                bank_angles: 1,
+                control_points: 3
+            }
+        );
+        let text = format!("{TRIANGLE}[metadata]\nbank_angles = [0.0, nan, 0.0]\n");
+        assert!(matches!(
+            TrackFile::parse(&text),
+            Err(TrackError::InvalidBankAngle(_))
+        ));
+    }
+
+    #[test]
+    fn inserted_points_blend_neighbouring_bank_angles() {
+        let text = format!("{TRIANGLE}[metadata]\nbank_angles = [0.0, 0.2, 0.4]\n");
+        let mut track = TrackFile::parse(&text).unwrap();
+        track.insert_control_point(1, [5.0, 0.0]);
+        assert_eq!(track.metadata.bank_angles, vec![0.0, 0.1, 0.2, 0.4]);
+        track.remove_control_point(3);
+        assert_eq!(track.metadata.bank_angles, vec![0.0, 0.1, 0.2]);
+    }
+
+    #[test]
+    fn physics_table_parses_and_defaults_to_no_overrides() {
+        let text = format!(
+            "{TRIANGLE}[physics]
+brake_max_axle_nm = 600.0
+wheel_base = 1.3
+"
+        );
+        let track = TrackFile::parse(&text).unwrap();
+        assert_eq!(
+            track.physics,
+            TrackPhysics {
+                brake_max_axle_nm: Some(600.0),
+                wheel_base: Some(1.3),
+                ..TrackPhysics::default()
+            }
+        );
+        assert!(TrackFile::parse(TRIANGLE).unwrap().physics.is_empty());
+
+        let text = format!(
+            "{TRIANGLE}[physics]
+lateral_grip = 0.0
+"
+        );
+        assert_eq!(
+            TrackFile::parse(&text).unwrap_err(),
+            TrackError::InvalidPhysics {
+                field: "lateral_grip",
+                value: 0.0
+            }
+        );
+    }
+}
@@ -0,0 +1,247 @@
+//! Minimal SVG path reader for importing hand-drawn tracks. Only the commands
+//! needed to trace a loop are understood: moveto, lineto, cubic Bézier and
+//! closepath, in absolute and relative form.
+
+use bevy::math::Vec2;
+
+use super::TrackError;
+
+/// Points closer than this to the first point close the loop and are dropped.
+const CLOSE_EPSILON: f32 = 1e-3;
+
+enum Token {
+    Command(char),
+    Number(f32),
+}
+
+struct Tokens<'a> {
+    text: &'a str,
+    pos: usize,
+}
+
+impl<'a> Tokens<'a> {
+    fn new(text: &'a str) -> Self {
+        Self { text, pos: 0 }
+    }
+
+    fn skip_separators(&mut self) {
+        let rest = &self.text[self.pos..];
+        let trimmed = rest.trim_start_matches(|c: char| c.is_ascii_whitespace() || c == ',');
+        self.pos += rest.len() - trimmed.len();
+    }
+
+    fn peek(&mut self) -> Result<Option<Token>, TrackError> {
+        let start = self.pos;
+        let token = self.next();
+        self.pos = start;
+        token
+    }
+
+    fn next(&mut self) -> Result<Option<Token>, TrackError> {
+        self.skip_separators();
+        let rest = &self.text[self.pos..];
+        let Some(first) = rest.chars().next() else {
+            return Ok(None);
+        };
+        if first.is_ascii_alphabetic() {
+            self.pos += 1;
+            return Ok(Some(Token::Command(first)));
+        }
+        if !(first.is_ascii_digit() || matches!(first, '+' | '-' | '.')) {
+            return Err(TrackError::InvalidSvgPath(format!(
+                "unexpected character {first:?}"
+            )));
+        }
+
+        // sign? digits? ('.' digits?)? (('e'|'E') sign? digits)?
+        let bytes = rest.as_bytes();
+        let mut len = usize::from(matches!(first, '+' | '-'));
+        let digits = |from: usize| {
+            bytes[from..]
+                .iter()
+                .take_while(|b| b.is_ascii_digit())
+                .count()
+        };
+        let mut mantissa_digits = digits(len);
+        len += mantissa_digits;
+        if bytes.get(len) == Some(&b'.') {
+            let fraction = digits(len + 1);
+            mantissa_digits += fraction;
+            len += 1 + fraction;
+        }
+        if mantissa_digits == 0 {
+            return Err(TrackError::InvalidSvgPath(format!(
+                "expected a number at {:?}",
+                &rest[..rest.len().min(12)]
+            )));
+        }
+        if matches!(bytes.get(len), Some(b'e' | b'E')) {
+            let sign = usize::from(matches!(bytes.get(len + 1), Some(b'+' | b'-')));
+            let exponent = digits(len + 1 + sign);
+            if exponent > 0 {
+                len += 1 + sign + exponent;
+            }
+        }
+        let value = rest[..len]
+            .parse::<f32>()
+            .map_err(|error| TrackError::InvalidSvgPath(format!("{}: {error}", &rest[..len])))?;
+        self.pos += len;
+        Ok(Some(Token::Number(value)))
+    }
+
+    fn number(&mut self, command: char) -> Result<f32, TrackError> {
+        match self.next()? {
+            Some(Token::Number(value)) => Ok(value),
+            _ => Err(TrackError::InvalidSvgPath(format!(
+                "'{command}' is missing coordinates"
+            ))),
+        }
+    }
+
+    fn point(&mut self, command: char, origin: Vec2) -> Result<Vec2, TrackError> {
+        let x = self.number(command)?;
+        let y = self.number(command)?;
+        Ok(origin + Vec2::new(x, y))
+    }
+}
+
+fn cubic(p0: Vec2, p1: Vec2, p2: Vec2, p3: Vec2, t: f32) -> Vec2 {
+    let u = 1.0 - t;
+    p0 * (u * u * u) + p1 * (3.0 * u * u * t) + p2 * (3.0 * u * t * t) + p3 * (t * t * t)
+}
+
+/// Samples a single-subpath SVG path into track control points, in SVG user
+/// units. Line segments contribute their end point; cubic segments contribute
+/// `samples` points evenly spaced in `t`, ending on the segment's end point. A
+/// final point back on the start is dropped, since tracks close implicitly.
+///
+/// SVG's y axis points down, so y is negated to keep the drawing's orientation
+/// in the game's y-up world.
+pub(super) fn sample_path(d: &str, samples: usize) -> Result<Vec<[f32; 2]>, TrackError> {
+    let samples = samples.max(1);
+    let mut tokens = Tokens::new(d);
+    let mut points: Vec<Vec2> = Vec::new();
+    let mut current = Vec2::ZERO;
+    let mut command = None;
+
+    while let Some(token) = tokens.peek()? {
+        if let Token::Command(next) = token {
+            tokens.next()?;
+            match next {
+                'M' | 'm' | 'L' | 'l' | 'C' | 'c' => command = Some(next),
+                'Z' | 'z' => {
+                    if let Some(&start) = points.first() {
+                        current = start;
+                    }
+                    command = None;
+                    continue;
+                }
+                other => return Err(TrackError::UnsupportedSvgCommand(other)),
+            }
+        }
+        let active = match command {
+            Some(active) if !points.is_empty() || matches!(active, 'M' | 'm') => active,
+            _ => {
+                return Err(TrackError::InvalidSvgPath(
+                    "path data must start with a moveto ('M')".to_string(),
+                ));
+            }
+        };
+        let origin = if active.is_ascii_lowercase() {
+            current
+        } else {
+            Vec2::ZERO
+        };
+
+        match active {
+            'M' | 'm' => {
+                if !points.is_empty() {
+                    return Err(TrackError::InvalidSvgPath(
+                        "only a single subpath is supported".to_string(),
+                    ));
+                }
+                current = tokens.point(active, origin)?;
+                points.push(current);
+                // Further coordinate pairs after a moveto are implicit linetos.
+                command = Some(if active == 'M' { 'L' } else { 'l' });
+            }
+            'L' | 'l' => {
+                current = tokens.point(active, origin)?;
+                points.push(current);
+            }
+            'C' | 'c' => {
+                let control1 = tokens.point(active, origin)?;
+                let control2 = tokens.point(active, origin)?;
+                let end = tokens.point(active, origin)?;
+                for step in 1..=samples {
+                    let t = step as f32 / samples as f32;
+                    points.push(cubic(current, control1, control2, end, t));
+                }
+                current = end;
+            }
+            _ => unreachable!("only supported commands are stored"),
+        }
+    }
+
+    if points.len() > 1
+        && points
+            .last()
+            .is_some_and(|last| last.distance(points[0]) < CLOSE_EPSILON)
+    {
+        points.pop();
+    }
+    Ok(points.into_iter().map(|p| [p.x, -p.y]).collect())
+}
+
+/// The `d` attribute of the first `<path>` element in an SVG document.
+pub(super) fn first_path_data(svg: &str) -> Option<&str> {
+    let mut rest = svg;
+    while let Some(start) = rest.find("<path") {
+        let tag_start = &rest[start + "<path".len()..];
+        let tag = &tag_start[..tag_start.find('>').unwrap_or(tag_start.len())];
+        // `d=` must be a whole attribute name, not the end of e.g. `id=`.
+        let mut search = 0;
+        while let Some(offset) = tag[search..].find("d=") {
+            let at = search + offset;
+            let boundary = tag[..at]
+                .chars()
+                .next_back()
+                .is_none_or(|c| c.is_ascii_whitespace());
+            let value = &tag[at + 2..];
+            if boundary && let Some(quote @ ('"' | '\'')) = value.chars().next() {
+                let value = &value[1..];
+                return value.find(quote).map(|end| &value[..end]);
+            }
+            search = at + 2;
+        }
+        rest = tag_start;
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn relative_commands_and_implicit_linetos_follow_the_pen() {
+        let points = sample_path("m10-5 20,0 l0 10c0 5 -20 5 -20 0z", 2).expect("path");
+        assert_eq!(
+            points,
+            vec![
+                [10.0, 5.0],
+                [30.0, 5.0],
+                [30.0, -5.0],
+                [20.0, -8.75],
+                [10.0, -5.0],
+            ]
+        );
+    }
+
+    #[test]
+    fn path_data_is_read_from_the_d_attribute() {
+        let svg = r#"<svg><rect id="x"/><path id='loop' d="M0 0 L1 0" fill="none"/></svg>"#;
+        assert_eq!(first_path_data(svg), Some("M0 0 L1 0"));
+        assert_eq!(first_path_data("<svg><path id=\"a\"/></svg>"), None);
+    }
+}
@@ -1,10 +1,13 @@
+use std::path::PathBuf;
+
 use bevy::prelude::*;
 
 use crate::bootstrap::WebPortalState;
-use crate::game_api::{DriverType, SpawnCarRequest, WebApiCommand};
+use crate::game_api::{DriverType, LoadTrackRequest, SpawnCarRequest, WebApiCommand};
 use crate::race_runtime::{
-    CarLabel, CpuFrequencySetting, DebugGizmos, FollowCar, LongitudinalDebugData, RaceManager,
-    SimState,
+    ActiveTrack, CarEntry, CarLabel, CpuFrequencySetting, DebugGizmos, FollowCar,
+    LongitudinalDebugData, RaceClock, RaceCountdown, RaceManager, SimState, SimTickHz,
+    available_tracks, color_for_car,
 };
 
 pub struct BootstrapUiPlugin;
@@ -20,6 +23,9 @@ impl Plugin for BootstrapUiPlugin {
                 handle_artifact_spawn_button,
                 handle_artifact_delete_button,
                 handle_artifact_visibility_button,
+                update_race_record_list_ui,
+                handle_watch_replay_button,
+                handle_ghost_replay_button,
             ),
         );
     }
@@ -29,22 +35,33 @@ pub struct RaceRuntimeUiPlugin;
 
 impl Plugin for RaceRuntimeUiPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(
-            Update,
-            (
-                update_car_list_ui,
-                handle_remove_car_button,
-                handle_toggle_gizmos_button,
-                handle_follow_car_button,
-                handle_cpu_frequency_buttons,
-                handle_start_button,
-                handle_reset_button,
-                update_console_output,
-                update_debug_telemetry_ui,
-                update_cpu_frequency_text,
-                update_start_button_text,
-            ),
-        );
+        app.init_resource::<SelectedConsole>()
+            .add_systems(Startup, setup_countdown_overlay)
+            .add_systems(
+                Update,
+                (
+                    update_car_list_ui,
+                    handle_remove_car_button,
+                    handle_toggle_gizmos_button,
+                    handle_follow_car_button,
+                    handle_console_focus_row,
+                    handle_clear_console_button,
+                    handle_cpu_frequency_buttons,
+                    handle_start_button,
+                    handle_reset_button,
+                    handle_manual_car_button,
+                    handle_publish_result_button,
+                    update_publish_result_button,
+                    handle_track_dropdown_button,
+                    handle_track_option_button,
+                    update_track_dropdown_text,
+                    update_console_output,
+                    update_debug_telemetry_ui,
+                    update_cpu_frequency_text,
+                    update_start_button_text,
+                    update_countdown_overlay,
+                ),
+            );
     }
 }
 
@@ -67,6 +84,14 @@ struct DeleteArtifactButton(i64);
 #[derive(Component)]
 struct ToggleArtifactVisibilityButton(i64, bool);
 #[derive(Component)]
+struct RaceRecordListContainer;
+#[derive(Component)]
+struct RaceRecordListRow;
+#[derive(Component)]
+struct WatchReplayButton(i64);
+#[derive(Component)]
+struct GhostReplayButton(i64);
+#[derive(Component)]
 struct CpuFrequencyMinusButton;
 #[derive(Component)]
 struct CpuFrequencyPlusButton;
@@ -77,6 +102,18 @@ struct StartButton;
 #[derive(Component)]
 struct ResetButton;
 #[derive(Component)]
+struct ManualCarButton;
+#[derive(Component)]
+struct PublishResultButton;
+#[derive(Component)]
+struct TrackDropdownButton;
+#[derive(Component)]
+struct TrackDropdownText;
+#[derive(Component)]
+struct TrackListContainer;
+#[derive(Component)]
+struct TrackOptionButton(PathBuf);
+#[derive(Component)]
 struct CarListContainer;
 #[derive(Component)]
 struct RemoveCarButton(Entity);
@@ -85,20 +122,50 @@ struct ToggleGizmosButton(Entity);
 #[derive(Component)]
 struct FollowCarButton(Entity);
 #[derive(Component)]
-struct CarListRow(#[allow(dead_code)] Entity);
+struct CarListRow(Entity);
+#[derive(Component)]
+struct ClearConsoleButton;
 #[derive(Component)]
 struct ConsoleTextContainer;
 #[derive(Component)]
 struct ConsoleText;
 #[derive(Component)]
 struct DebugTelemetryText;
+#[derive(Component)]
+struct CountdownText;
+
+/// The car whose console is shown; `None` shows every car's output.
+#[derive(Resource, Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SelectedConsole(pub Option<Entity>);
+
+impl SelectedConsole {
+    /// Clicking a car row focuses its console; clicking it again shows all cars.
+    fn toggle(&mut self, car: Entity) {
+        self.0 = if self.0 == Some(car) { None } else { Some(car) };
+    }
+
+    fn shows(&self, car: Entity) -> bool {
+        self.0.is_none_or(|selected| selected == car)
+    }
+
+    /// Empties the console on display: the focused car's, or every car's when
+    /// none is focused.
+    fn clear(&self, cars: &mut [CarEntry]) {
+        for entry in cars.iter_mut().filter(|entry| self.shows(entry.entity)) {
+            entry.console_output.clear();
+        }
+    }
+}
 
 const PANEL_BG: Color = Color::srgba(0.08, 0.08, 0.12, 0.92);
 const BTN_BG: Color = Color::srgb(0.25, 0.25, 0.35);
 const START_BG: Color = Color::srgb(0.15, 0.55, 0.2);
 const RESET_BG: Color = Color::srgb(0.55, 0.15, 0.15);
+const DISABLED_BG: Color = Color::srgb(0.16, 0.16, 0.2);
 const TEXT_COLOR: Color = Color::srgb(0.9, 0.9, 0.9);
 const LABEL_COLOR: Color = Color::srgb(0.7, 0.7, 0.7);
+/// How long "GO!" stays on screen after the countdown elapses.
+const GO_BANNER_SECS: f32 = 1.0;
 
 fn px(val: f32) -> Val {
     Val::Px(val)
@@ -121,7 +188,17 @@ fn button_style() -> Node {
     }
 }
 
-fn setup_ui(mut commands: Commands, cpu_frequency: Res<CpuFrequencySetting>) {
+fn track_dropdown_label(track: &ActiveTrack) -> String {
+    format!("Track: {}", track.file.metadata.name)
+}
+
+fn setup_ui(
+    mut commands: Commands,
+    cpu_frequency: Res<CpuFrequencySetting>,
+    active_track: Res<ActiveTrack>,
+) {
+    let tracks = available_tracks();
+
     commands
         .spawn((
             UiRoot,
@@ -216,6 +293,25 @@ fn setup_ui(mut commands: Commands, cpu_frequency: Res<CpuFrequencySetting>) {
                 ))
                 .with_children(|_| {});
 
+            panel.spawn((
+                Text::new("Replays"),
+                text_font(16.0),
+                TextColor(LABEL_COLOR),
+            ));
+
+            panel
+                .spawn((
+                    Node {
+                        flex_direction: FlexDirection::Column,
+                        row_gap: px(4.0),
+                        overflow: Overflow::scroll_y(),
+                        max_height: px(120.0),
+                        ..default()
+                    },
+                    RaceRecordListContainer,
+                ))
+                .with_children(|_| {});
+
             panel.spawn((Text::new("Race"), text_font(16.0), TextColor(LABEL_COLOR)));
 
             panel
@@ -280,6 +376,78 @@ fn setup_ui(mut commands: Commands, cpu_frequency: Res<CpuFrequencySetting>) {
                     .with_children(|btn| {
                         btn.spawn((Text::new("Reset"), text_font(14.0), TextColor(TEXT_COLOR)));
                     });
+
+                    row.spawn((
+                        Button,
+                        ManualCarButton,
+                        button_style(),
+                        BackgroundColor(BTN_BG),
+                    ))
+                    .with_children(|btn| {
+                        btn.spawn((Text::new("Manual"), text_font(14.0), TextColor(TEXT_COLOR)));
+                    });
+
+                    row.spawn((
+                        Button,
+                        PublishResultButton,
+                        button_style(),
+                        BackgroundColor(DISABLED_BG),
+                    ))
+                    .with_children(|btn| {
+                        btn.spawn((Text::new("Publish"), text_font(14.0), TextColor(TEXT_COLOR)));
+                    });
+                });
+
+            panel
+                .spawn((
+                    Button,
+                    TrackDropdownButton,
+                    button_style(),
+                    BackgroundColor(BTN_BG),
+                ))
+                .with_children(|btn| {
+                    btn.spawn((
+                        Text::new(track_dropdown_label(&active_track)),
+                        TrackDropdownText,
+                        text_font(14.0),
+                        TextColor(TEXT_COLOR),
+                    ));
+                });
+
+            panel
+                .spawn((
+                    Node {
+                        display: Display::None,
+                        flex_direction: FlexDirection::Column,
+                        row_gap: px(2.0),
+                        overflow: Overflow::scroll_y(),
+                        max_height: px(160.0),
+                        ..default()
+                    },
+                    TrackListContainer,
+                ))
+                .with_children(|list| {
+                    if tracks.is_empty() {
+                        list.spawn((
+                            Text::new("No track files found"),
+                            text_font(12.0),
+                            TextColor(LABEL_COLOR),
+                        ));
+                    }
+                    for path in tracks {
+                        let label = path
+                            .file_stem()
+                            .map_or_else(String::new, |stem| stem.to_string_lossy().into_owned());
+                        list.spawn((
+                            Button,
+                            TrackOptionButton(path),
+                            button_style(),
+                            BackgroundColor(BTN_BG),
+                        ))
+                        .with_children(|btn| {
+                            btn.spawn((Text::new(label), text_font(13.0), TextColor(TEXT_COLOR)));
+                        });
+                    }
                 });
 
             panel.spawn((Text::new("Cars"), text_font(16.0), TextColor(LABEL_COLOR)));
@@ -322,11 +490,36 @@ fn setup_ui(mut commands: Commands, cpu_frequency: Res<CpuFrequencySetting>) {
                     ));
                 });
 
-            panel.spawn((
-                Text::new("Console"),
-                text_font(16.0),
-                TextColor(LABEL_COLOR),
-            ));
+            panel
+                .spawn(Node {
+                    flex_direction: FlexDirection::Row,
+                    align_items: AlignItems::Center,
+                    ..default()
+                })
+                .with_children(|header| {
+                    header.spawn((
+                        Text::new("Console"),
+                        text_font(16.0),
+                        TextColor(LABEL_COLOR),
+                        Node {
+                            flex_grow: 1.0,
+                            ..default()
+                        },
+                    ));
+                    header
+                        .spawn((
+                            Button,
+                            ClearConsoleButton,
+                            Node {
+                                padding: UiRect::axes(px(6.0), px(2.0)),
+                                ..default()
+                            },
+                            BackgroundColor(BTN_BG),
+                        ))
+                        .with_children(|btn| {
+                            btn.spawn((Text::new("Clear"), text_font(12.0), TextColor(TEXT_COLOR)));
+                        });
+                });
 
             panel.spawn((
                 Node {
@@ -350,6 +543,7 @@ fn handle_web_buttons(
     for interaction in &refresh_query {
         if *interaction == Interaction::Pressed {
             web_commands.write(WebApiCommand::LoadArtifacts);
+            web_commands.write(WebApiCommand::LoadRaceRecords);
         }
     }
 
@@ -404,8 +598,12 @@ fn update_artifact_list_ui(
             "private"
         };
         let label = format!(
-            "{} [#{}] by {} ({})",
-            artifact.name, artifact.id, artifact.owner_username, visibility
+            "{} [#{}] by {} ({}, {} downloads)",
+            artifact.name,
+            artifact.id,
+            artifact.owner_username,
+            visibility,
+            artifact.download_count
         );
 
         commands.entity(container).with_children(|list| {
@@ -481,6 +679,123 @@ fn update_artifact_list_ui(
     }
 }
 
+fn update_race_record_list_ui(
+    web_state: Res<WebPortalState>,
+    mut commands: Commands,
+    container_query: Query<Entity, With<RaceRecordListContainer>>,
+    existing_rows: Query<Entity, With<RaceRecordListRow>>,
+) {
+    if !web_state.is_changed() {
+        return;
+    }
+
+    let Ok(container) = container_query.single() else {
+        return;
+    };
+
+    for row_entity in &existing_rows {
+        commands.entity(row_entity).despawn();
+    }
+
+    for record in web_state.race_records.iter().filter(|r| r.has_replay) {
+        let record_id = record.id;
+        let label = format!(
+            "Race #{} on {} by {}",
+            record.id, record.track_id, record.owner_username
+        );
+
+        commands.entity(container).with_children(|list| {
+            list.spawn((
+                RaceRecordListRow,
+                Node {
+                    flex_direction: FlexDirection::Row,
+                    align_items: AlignItems::Center,
+                    column_gap: px(4.0),
+                    padding: UiRect::axes(px(4.0), px(2.0)),
+                    ..default()
+                },
+                BackgroundColor(Color::srgba(0.15, 0.15, 0.2, 0.8)),
+            ))
+            .with_children(|row| {
+                row.spawn((
+                    Text::new(label),
+                    text_font(13.0),
+                    TextColor(TEXT_COLOR),
+                    Node {
+                        flex_grow: 1.0,
+                        ..default()
+                    },
+                ));
+
+                row.spawn((
+                    Button,
+                    WatchReplayButton(record_id),
+                    Node {
+                        padding: UiRect::axes(px(6.0), px(2.0)),
+                        ..default()
+                    },
+                    BackgroundColor(BTN_BG),
+                ))
+                .with_children(|btn| {
+                    btn.spawn((Text::new("Watch"), text_font(12.0), TextColor(TEXT_COLOR)));
+                });
+
+                row.spawn((
+                    Button,
+                    GhostReplayButton(record_id),
+                    Node {
+                        padding: UiRect::axes(px(6.0), px(2.0)),
+                        ..default()
+                    },
+                    BackgroundColor(BTN_BG),
+                ))
+                .with_children(|btn| {
+                    btn.spawn((Text::new("Ghost"), text_font(12.0), TextColor(TEXT_COLOR)));
+                });
+            });
+        });
+    }
+}
+
+fn handle_watch_replay_button(
+    query: Query<(&Interaction, &WatchReplayButton), Changed<Interaction>>,
+    mut web_commands: MessageWriter<WebApiCommand>,
+    state: Res<State<SimState>>,
+) {
+    if *state.get() != SimState::PreRace {
+        return;
+    }
+
+    for (interaction, watch_btn) in &query {
+        if *interaction == Interaction::Pressed {
+            web_commands.write(WebApiCommand::LoadReplay {
+                record_id: watch_btn.0,
+                as_ghost: false,
+            });
+        }
+    }
+}
+
+/// Race against the first car of a stored replay.
+fn handle_ghost_replay_button(
+    query: Query<(&Interaction, &GhostReplayButton), Changed<Interaction>>,
+    mut web_commands: MessageWriter<WebApiCommand>,
+    state: Res<State<SimState>>,
+) {
+    if *state.get() != SimState::PreRace {
+        return;
+    }
+
+    for (interaction, ghost_btn) in &query {
+        if *interaction == Interaction::Pressed {
+            web_commands.write(WebApiCommand::LoadReplay {
+                record_id: ghost_btn.0,
+                as_ghost: true,
+            });
+        }
+    }
+}
+
 fn handle_artifact_spawn_button(
     query: Query<(&Interaction, &SpawnArtifactButton), Changed<Interaction>>,
     mut spawn_events: MessageWriter<SpawnCarRequest>,
@@ -499,6 +814,85 @@ fn handle_artifact_spawn_button(
     }
 }
 
+/// Adds the keyboard-driven car; `spawn_car_entry` ignores it if one is already on the grid.
+fn handle_manual_car_button(
+    query: Query<&Interaction, (Changed<Interaction>, With<ManualCarButton>)>,
+    mut spawn_events: MessageWriter<SpawnCarRequest>,
+    state: Res<State<SimState>>,
+) {
+    if *state.get() != SimState::PreRace {
+        return;
+    }
+
+    for interaction in &query {
+        if *interaction == Interaction::Pressed {
+            spawn_events.write(SpawnCarRequest {
+                driver: DriverType::Manual,
+            });
+        }
+    }
+}
+
+/// Opens or closes the track list; tracks can only be changed before a race.
+fn handle_track_dropdown_button(
+    query: Query<&Interaction, (Changed<Interaction>, With<TrackDropdownButton>)>,
+    mut list_query: Query<&mut Node, With<TrackListContainer>>,
+    state: Res<State<SimState>>,
+) {
+    let Ok(mut list) = list_query.single_mut() else {
+        return;
+    };
+    if *state.get() != SimState::PreRace {
+        if list.display != Display::None {
+            list.display = Display::None;
+        }
+        return;
+    }
+
+    for interaction in &query {
+        if *interaction == Interaction::Pressed {
+            list.display = match list.display {
+                Display::None => Display::Flex,
+                _ => Display::None,
+            };
+        }
+    }
+}
+
+fn handle_track_option_button(
+    query: Query<(&Interaction, &TrackOptionButton), Changed<Interaction>>,
+    mut list_query: Query<&mut Node, With<TrackListContainer>>,
+    mut track_events: MessageWriter<LoadTrackRequest>,
+    state: Res<State<SimState>>,
+) {
+    if *state.get() != SimState::PreRace {
+        return;
+    }
+
+    for (interaction, option) in &query {
+        if *interaction == Interaction::Pressed {
+            track_events.write(LoadTrackRequest {
+                path: option.0.clone(),
+            });
+            if let Ok(mut list) = list_query.single_mut() {
+                list.display = Display::None;
+            }
+        }
+    }
+}
+
+fn update_track_dropdown_text(
+    active_track: Res<ActiveTrack>,
+    mut text_query: Query<&mut Text, With<TrackDropdownText>>,
+) {
+    if !active_track.is_changed() {
+        return;
+    }
+    for mut text in &mut text_query {
+        text.0 = track_dropdown_label(&active_track);
+    }
+}
+
 fn handle_artifact_delete_button(
     query: Query<(&Interaction, &DeleteArtifactButton), Changed<Interaction>>,
     mut web_commands: MessageWriter<WebApiCommand>,
@@ -538,19 +932,25 @@ fn handle_start_button(
     query: Query<&Interaction, (Changed<Interaction>, With<StartButton>)>,
     current_state: Res<State<SimState>>,
     mut next_state: ResMut<NextState<SimState>>,
+    countdown: Res<RaceCountdown>,
+    tick_hz: Res<SimTickHz>,
 ) {
     for interaction in &query {
         if *interaction == Interaction::Pressed {
             match current_state.get() {
                 SimState::PreRace => {
-                    next_state.set(SimState::Racing);
+                    next_state.set(countdown.start_state(*tick_hz));
                 }
+                SimState::Countdown => {}
                 SimState::Racing => {
                     next_state.set(SimState::Paused);
                 }
                 SimState::Paused => {
                     next_state.set(SimState::Racing);
                 }
+                SimState::Spectating => {
+                    next_state.set(SimState::PreRace);
+                }
             }
         }
     }
@@ -569,14 +969,68 @@ fn update_start_button_text(
             if let Ok(mut text) = text_query.get_mut(child) {
                 text.0 = match state.get() {
                     SimState::PreRace => "Start".into(),
+                    SimState::Countdown => "Get Ready".into(),
                     SimState::Racing => "Pause".into(),
                     SimState::Paused => "Resume".into(),
+                    SimState::Spectating => "Exit Replay".into(),
                 };
             }
         }
     }
 }
 
+fn setup_countdown_overlay(mut commands: Commands) {
+    commands.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Percent(35.0),
+            left: px(0.0),
+            right: px(0.0),
+            justify_content: JustifyContent::Center,
+            ..default()
+        },
+        Text::new(""),
+        text_font(72.0),
+        TextColor(TEXT_COLOR),
+        Visibility::Hidden,
+        CountdownText,
+    ));
+}
+
+fn update_countdown_overlay(
+    state: Res<State<SimState>>,
+    clock: Res<RaceClock>,
+    countdown: Res<RaceCountdown>,
+    tick_hz: Res<SimTickHz>,
+    mut query: Query<(&mut Text, &mut Visibility), With<CountdownText>>,
+) {
+    let Ok((mut text, mut visibility)) = query.single_mut() else {
+        return;
+    };
+
+    let label = match state.get() {
+        SimState::Countdown => {
+            let remaining = clock.countdown_remaining_secs(&countdown, *tick_hz);
+            Some(format!("{}", remaining.ceil().max(1.0) as u32))
+        }
+        SimState::Racing if countdown.ticks(*tick_hz) > 0 => clock
+            .race_time_secs(*tick_hz)
+            .filter(|t| *t < GO_BANNER_SECS)
+            .map(|_| "GO!".to_string()),
+        _ => None,
+    };
+
+    match label {
+        Some(label) => {
+            if text.0 != label {
+                text.0 = label;
+            }
+            *visibility = Visibility::Inherited;
+        }
+        None => *visibility = Visibility::Hidden,
+    }
+}
+
 fn handle_reset_button(
     query: Query<&Interaction, (Changed<Interaction>, With<ResetButton>)>,
     mut next_state: ResMut<NextState<SimState>>,
@@ -596,6 +1050,39 @@ fn handle_reset_button(
     }
 }
 
+/// Publishing is only possible once the race has finished.
+fn handle_publish_result_button(
+    query: Query<&Interaction, (Changed<Interaction>, With<PublishResultButton>)>,
+    manager: Res<RaceManager>,
+    mut web_commands: MessageWriter<WebApiCommand>,
+) {
+    if !manager.race_finished() {
+        return;
+    }
+    for interaction in &query {
+        if *interaction == Interaction::Pressed {
+            web_commands.write(WebApiCommand::PublishResult);
+        }
+    }
+}
+
+fn update_publish_result_button(
+    manager: Res<RaceManager>,
+    mut query: Query<&mut BackgroundColor, With<PublishResultButton>>,
+) {
+    if !manager.is_changed() {
+        return;
+    }
+    let color = if manager.race_finished() {
+        START_BG
+    } else {
+        DISABLED_BG
+    };
+    for mut background in &mut query {
+        background.0 = color;
+    }
+}
+
 fn handle_remove_car_button(
     query: Query<(&Interaction, &RemoveCarButton), Changed<Interaction>>,
     mut manager: ResMut<RaceManager>,
@@ -646,6 +1133,33 @@ fn handle_follow_car_button(
     }
 }
 
+/// Car rows are buttons themselves; their inner buttons block the click, so
+/// only presses on the row background reach here.
+fn handle_console_focus_row(
+    query: Query<(&Interaction, &CarListRow), Changed<Interaction>>,
+    mut selected: ResMut<SelectedConsole>,
+) {
+    for (interaction, row) in &query {
+        if *interaction == Interaction::Pressed {
+            selected.toggle(row.0);
+        }
+    }
+}
+
+fn handle_clear_console_button(
+    query: Query<&Interaction, (Changed<Interaction>, With<ClearConsoleButton>)>,
+    mut manager: ResMut<RaceManager>,
+    mut selected: ResMut<SelectedConsole>,
+) {
+    for interaction in &query {
+        if *interaction == Interaction::Pressed {
+            selected.clear(&mut manager.cars);
+            // Re-render the console even though the selection did not change.
+            selected.set_changed();
+        }
+    }
+}
+
 fn handle_cpu_frequency_buttons(
     minus_query: Query<&Interaction, (Changed<Interaction>, With<CpuFrequencyMinusButton>)>,
     plus_query: Query<&Interaction, (Changed<Interaction>, With<CpuFrequencyPlusButton>)>,
@@ -686,9 +1200,16 @@ fn update_car_list_ui(
     added_gizmos: Query<(), Added<DebugGizmos>>,
     mut removed_gizmos: RemovedComponents<DebugGizmos>,
     follow: Res<FollowCar>,
+    selected: Res<SelectedConsole>,
+    web_state: Res<WebPortalState>,
 ) {
     let gizmos_changed = !added_gizmos.is_empty() || removed_gizmos.read().next().is_some();
-    if !manager.is_changed() && !follow.is_changed() && !gizmos_changed {
+    if !manager.is_changed()
+        && !follow.is_changed()
+        && !selected.is_changed()
+        && !web_state.is_changed()
+        && !gizmos_changed
+    {
         return;
     }
 
@@ -704,19 +1225,40 @@ fn update_car_list_ui(
         let entity = entry.entity;
         let has_gizmos = gizmo_query.get(entity).is_ok();
         let is_followed = follow.target == Some(entity);
-        let driver_label = entry.driver.label();
+        let driver_label = entry.driver.label_with_artifacts(&web_state.artifacts);
+        let format_lap = |secs: Option<f32>| secs.map_or("--".to_string(), |s| format!("{s:.2}s"));
+        let mut lap_label = format!(
+            "L{} last {} best {}",
+            entry.laps_completed + 1,
+            format_lap(entry.last_lap_secs),
+            format_lap(entry.best_lap_secs)
+        );
+        if entry.dnf {
+            lap_label = format!("DNF | {lap_label}");
+        }
+
+        // The row whose console is focused is outlined.
+        let row_border = if selected.0 == Some(entity) {
+            BorderColor::all(TEXT_COLOR)
+        } else {
+            BorderColor::all(Color::NONE)
+        };
 
         commands.entity(container).with_children(|list| {
             list.spawn((
+                Button,
                 CarListRow(entity),
                 Node {
                     flex_direction: FlexDirection::Row,
                     align_items: AlignItems::Center,
                     column_gap: px(4.0),
                     padding: UiRect::axes(px(4.0), px(2.0)),
+                    border: UiRect::all(px(1.0)),
                     ..default()
                 },
-                BackgroundColor(Color::srgba(0.15, 0.15, 0.2, 0.8)),
+                row_border,
+                // Dimmed so the white row text stays readable over the car tint.
+                BackgroundColor(color_for_car(entry.id).darker(0.35).with_alpha(0.8)),
             ))
             .with_children(|row| {
                 row.spawn((
@@ -728,6 +1270,7 @@ fn update_car_list_ui(
                         ..default()
                     },
                 ));
+                row.spawn((Text::new(lap_label), text_font(12.0), TextColor(TEXT_COLOR)));
 
                 let follow_bg = if is_followed {
                     Color::srgb(0.2, 0.5, 0.7)
@@ -784,7 +1327,14 @@ fn update_car_list_ui(
 
 fn update_debug_telemetry_ui(
     follow: Res<FollowCar>,
-    telemetry_query: Query<(&CarLabel, &LongitudinalDebugData), With<DebugGizmos>>,
+    telemetry_query: Query<
+        (
+            &CarLabel,
+            &LongitudinalDebugData,
+            Option<&emulator::bevy::CpuComponent>,
+        ),
+        With<DebugGizmos>,
+    >,
     mut text_query: Query<&mut Text, With<DebugTelemetryText>>,
 ) {
     let Ok(mut text) = text_query.single_mut() else {
@@ -793,8 +1343,8 @@ fn update_debug_telemetry_ui(
 
     let message = match follow.target {
         Some(entity) => {
-            if let Ok((label, telemetry)) = telemetry_query.get(entity) {
-                format!(
+            if let Ok((label, telemetry, cpu)) = telemetry_query.get(entity) {
+                let mut message = format!(
                     concat!(
                         "{}\n",
                         "v: {:.2} m/s ({:.1} km/h)\n",
@@ -824,7 +1374,17 @@ fn update_debug_telemetry_ui(
                     telemetry.f_clamped,
                     telemetry.traction_limit,
                     telemetry.a_mps2,
-                )
+                );
+                if let Some(cpu) = cpu {
+                    message.push_str(&format!(
+                        "\ncpu: {} / {} instr per tick\nCPU cycles: {} ({} instr retired)",
+                        cpu.instructions_last_update(),
+                        cpu.instructions_per_update(),
+                        cpu.cycles(),
+                        cpu.instructions_retired()
+                    ));
+                }
+                message
             } else {
                 "Follow a gizmo-enabled car to view telemetry".to_string()
             }
@@ -837,6 +1397,7 @@ fn update_debug_telemetry_ui(
 
 fn update_console_output(
     mut manager: ResMut<RaceManager>,
+    mut selected: ResMut<SelectedConsole>,
     mut cpu_query: Query<(&CarLabel, &mut emulator::cpu::LogDevice)>,
     container_query: Query<Entity, With<ConsoleTextContainer>>,
     mut commands: Commands,
@@ -845,9 +1406,12 @@ fn update_console_output(
     let mut any_new = false;
     for (label, mut log_dev) in &mut cpu_query {
         let output = log_dev.drain_output();
-        if !output.is_empty() {
+        if !output.text.is_empty() {
             if let Some(entry) = manager.cars.iter_mut().find(|c| c.name == label.name) {
-                entry.console_output.push_str(&output);
+                if output.truncated {
+                    entry.console_output.push_str("\n[older output dropped]\n");
+                }
+                entry.console_output.push_str(&output.text);
                 if entry.console_output.len() > 8192 {
                     let start = entry.console_output.len() - 4096;
                     let trimmed = entry.console_output[start..].to_string();
@@ -858,7 +1422,14 @@ fn update_console_output(
         }
     }
 
-    if !any_new {
+    // A removed car's console can't stay focused.
+    if let Some(car) = selected.0
+        && !manager.cars.iter().any(|entry| entry.entity == car)
+    {
+        selected.0 = None;
+    }
+
+    if !any_new && !selected.is_changed() {
         return;
     }
 
@@ -871,7 +1442,11 @@ fn update_console_output(
     }
 
     commands.entity(container).with_children(|console| {
-        for entry in &manager.cars {
+        for entry in manager
+            .cars
+            .iter()
+            .filter(|entry| selected.shows(entry.entity))
+        {
             if entry.console_output.is_empty() {
                 continue;
             }
@@ -893,3 +1468,57 @@ fn update_console_output(
         }
     });
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game_api::DriverType;
+
+    fn car_with_output(entity: Entity, output: &str) -> CarEntry {
+        CarEntry {
+            entity,
+            id: 1,
+            name: format!("car {entity}"),
+            driver: DriverType::Manual,
+            console_output: output.to_string(),
+            laps_completed: 0,
+            last_lap_secs: None,
+            best_lap_secs: None,
+            dnf: false,
+            finish_secs: None,
+        }
+    }
+
+    #[test]
+    fn clicking_a_row_toggles_its_console_focus() {
+        let mut world = World::new();
+        let (a, b) = (world.spawn_empty().id(), world.spawn_empty().id());
+        let mut selected = SelectedConsole::default();
+        assert!(selected.shows(a) && selected.shows(b));
+
+        selected.toggle(a);
+        assert_eq!(selected, SelectedConsole(Some(a)));
+        assert!(selected.shows(a) && !selected.shows(b));
+
+        selected.toggle(b);
+        assert_eq!(selected, SelectedConsole(Some(b)));
+
+        selected.toggle(b);
+        assert_eq!(selected, SelectedConsole(None));
+    }
+
+    #[test]
+    fn clear_empties_only_the_focused_console() {
+        let mut world = World::new();
+        let (a, b) = (world.spawn_empty().id(), world.spawn_empty().id());
+        let mut cars = vec![car_with_output(a, "lap 1\n"), car_with_output(b, "hello\n")];
+
+        SelectedConsole(Some(b)).clear(&mut cars);
+        assert_eq!(cars[0].console_output, "lap 1\n");
+        assert!(cars[1].console_output.is_empty());
+
+        cars[1].console_output.push_str("again\n");
+        SelectedConsole(None).clear(&mut cars);
+        assert!(cars.iter().all(|car| car.console_output.is_empty()));
+    }
+}
@@ -2,6 +2,22 @@ use serde::{Deserialize, Serialize};
 
 pub const API_VERSION: &str = "v1";
 
+/// `ServerCapabilities::features` entries: the server serves `GET /api/v1/leaderboard`.
+pub const FEATURE_LEADERBOARD: &str = "leaderboard";
+/// Race results can be published and listed under `/api/v1/race-records`.
+pub const FEATURE_RACE_RECORDS: &str = "race_records";
+/// Race records may carry a replay, served by `GET /api/v1/race-records/{id}/replay`.
+pub const FEATURE_REPLAYS: &str = "replays";
+/// Security-relevant actions are recorded and listed for admins at `GET /api/v1/admin/audit`.
+pub const FEATURE_AUDIT_LOG: &str = "audit_log";
+/// Prometheus counters are served at `GET /metrics`.
+pub const FEATURE_METRICS: &str = "metrics";
+/// `GET /api/v1/ws` pushes an `ArtifactChangeEvent` whenever the caller's artifacts change.
+pub const FEATURE_ARTIFACT_EVENTS: &str = "artifact_events";
+
+/// Target triple bots are built for and the emulator runs.
+pub const DEFAULT_ARTIFACT_TARGET: &str = "riscv32imafc-unknown-none-elf";
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ErrorResponse {
     pub error: String,
@@ -17,6 +33,22 @@ pub struct UserInfo {
 pub struct RegisterRequest {
     pub username: String,
     pub password: String,
+    /// Challenge from `GET /api/v1/auth/pow-challenge`; required when the
+    /// server advertises `ServerCapabilities::pow_required`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pow_challenge: Option<String>,
+    /// Solution for `pow_challenge`, see `PowChallengeResponse`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pow_nonce: Option<String>,
+}
+
+/// `GET /api/v1/auth/pow-challenge`: a single-use registration challenge.
+/// Solve it by finding a `nonce` string for which
+/// `sha256(challenge + nonce)` starts with at least `difficulty` zero bits.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PowChallengeResponse {
+    pub challenge: String,
+    pub difficulty: u8,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -31,18 +63,49 @@ pub struct LoginResponse {
     pub user: UserInfo,
 }
 
+/// Body of `DELETE /api/v1/me`; the current password confirms the deletion.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeleteAccountRequest {
+    pub password: String,
+}
+
+/// Result of `POST /api/v1/auth/logout-all`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogoutAllResponse {
+    pub removed_sessions: u64,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ServerCapabilities {
     pub auth_required: bool,
     pub mode: String,
     #[serde(default = "default_registration_enabled")]
     pub registration_enabled: bool,
+    /// Artifact targets the server accepts on upload.
+    #[serde(default = "default_supported_targets")]
+    pub supported_targets: Vec<String>,
+    /// `API_VERSION` of the server; empty for servers that predate it.
+    #[serde(default)]
+    pub api_version: String,
+    /// Largest accepted ELF upload in bytes; 0 if the server did not say.
+    #[serde(default)]
+    pub max_artifact_bytes: u64,
+    /// Enabled optional capabilities, e.g. `FEATURE_LEADERBOARD`.
+    #[serde(default)]
+    pub features: Vec<String>,
+    /// Registration needs a solved `PowChallengeResponse`.
+    #[serde(default)]
+    pub pow_required: bool,
 }
 
 fn default_registration_enabled() -> bool {
     true
 }
 
+fn default_supported_targets() -> Vec<String> {
+    vec![DEFAULT_ARTIFACT_TARGET.to_string()]
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ArtifactSummary {
     pub id: i64,
@@ -54,6 +117,38 @@ pub struct ArtifactSummary {
     pub is_public: bool,
     pub owned_by_me: bool,
     pub created_at: String,
+    /// Hex sha256 of the ELF; `None` for artifacts uploaded before checksums
+    /// were recorded.
+    #[serde(default)]
+    pub sha256: Option<String>,
+    /// JSON object the uploader recorded about the build (compiler version,
+    /// commit, flags); `None` when the upload did not include one.
+    #[serde(default)]
+    pub build_meta_json: Option<String>,
+    /// Successful downloads of the ELF (not counting `304 Not Modified`).
+    #[serde(default)]
+    pub download_count: u64,
+    /// Owner-assigned labels, sorted; filter with `GET /api/v1/artifacts?tag=...`.
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+/// What happened to an artifact in an `ArtifactChangeEvent`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ArtifactChangeKind {
+    Uploaded,
+    Deleted,
+    VisibilityChanged,
+    TagsChanged,
+}
+
+/// Text message sent over `GET /api/v1/ws` when one of the connected user's
+/// artifacts changes; clients re-fetch `GET /api/v1/artifacts` on receipt.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArtifactChangeEvent {
+    pub kind: ArtifactChangeKind,
+    pub artifact_id: i64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -62,6 +157,11 @@ pub struct UploadArtifactRequest {
     pub note: Option<String>,
     pub target: String,
     pub elf_base64: String,
+    /// Optional JSON object describing the build, e.g.
+    /// `{"rustc":"rustc 1.90.0 (…)","commit":"3f2c…","profile":"release"}`.
+    /// The server stores it verbatim and returns it in `ArtifactSummary`.
+    #[serde(default)]
+    pub build_meta_json: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -69,7 +169,159 @@ pub struct UploadArtifactResponse {
     pub artifact_id: i64,
 }
 
+/// `GET /api/v1/artifacts/{id}/checksum`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArtifactChecksumResponse {
+    pub artifact_id: i64,
+    pub sha256: String,
+}
+
+/// Partial artifact metadata update; absent fields are left unchanged and an
+/// empty `note` clears it.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UpdateArtifactRequest {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UpdateArtifactVisibilityRequest {
     pub is_public: bool,
 }
+
+/// Body of `POST` and `DELETE /api/v1/artifacts/{id}/tags`: the tag to add or
+/// remove. Tags are case-insensitive and stored lowercase.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArtifactTagRequest {
+    pub tag: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RaceRecordSummary {
+    pub id: i64,
+    pub owner_user_id: i64,
+    pub owner_username: String,
+    pub track_id: String,
+    pub result_json: String,
+    pub has_replay: bool,
+    pub created_at: String,
+}
+
+/// `POST /api/v1/race-records`: store a finished race for the caller.
+/// `result_json` must be a JSON object; a numeric `time_ms` field puts the
+/// record on that track's leaderboard.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PublishRaceRecordRequest {
+    pub track_id: String,
+    pub result_json: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PublishRaceRecordResponse {
+    pub record_id: i64,
+}
+
+/// A ranked race time on one track (`GET /api/v1/leaderboard?track_id=...`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LeaderboardEntry {
+    pub rank: u32,
+    pub user_id: i64,
+    pub username: String,
+    pub time_ms: u64,
+}
+
+/// One row of the server's security audit trail (`GET /api/v1/admin/audit`).
+/// `user_id`/`username` are absent for events without a known user, such as
+/// failed logins for unknown accounts.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditLogEntry {
+    pub id: i64,
+    pub event: String,
+    pub user_id: Option<i64>,
+    pub username: Option<String>,
+    pub ip: Option<String>,
+    pub detail: String,
+    pub created_at: String,
+}
+
+/// One account as listed by `GET /api/v1/admin/users`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AdminUserEntry {
+    pub id: i64,
+    pub username: String,
+    pub created_at: String,
+    pub is_admin: bool,
+}
+
+/// Recorded car trajectories of a finished race, sampled once per fixed tick.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RaceReplay {
+    pub tick_hz: u32,
+    pub cars: Vec<ReplayCar>,
+    pub frames: Vec<ReplayFrame>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplayCar {
+    pub name: String,
+}
+
+/// One pose per entry in `RaceReplay::cars`, in the same order. `controls`
+/// follows the same order; replays recorded without inputs leave it empty.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplayFrame {
+    pub poses: Vec<ReplayCarPose>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub controls: Vec<ReplayCarControls>,
+}
+
+/// World position and rotation around Z (radians) of a car.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct ReplayCarPose {
+    pub x: f32,
+    pub y: f32,
+    pub heading: f32,
+}
+
+/// Driver inputs applied to a car during a tick.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct ReplayCarControls {
+    pub steer: f32,
+    pub accelerator: f32,
+    pub brake: f32,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ArtifactSummary;
+
+    #[test]
+    fn artifact_summary_round_trips_the_server_list_shape() {
+        // The one `ArtifactSummary` shape: what `GET /api/v1/artifacts` returns and what the
+        // game and the VSCode extension (`src/types.ts`) read.
+        let json = serde_json::json!({
+            "id": 12,
+            "owner_user_id": 3,
+            "owner_username": "alice",
+            "name": "fast bot",
+            "note": null,
+            "target": "riscv32imafc-unknown-none-elf",
+            "is_public": true,
+            "owned_by_me": false,
+            "created_at": "2026-01-01T00:00:00Z",
+            "sha256": "9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08",
+            "build_meta_json": null,
+            "download_count": 0,
+            "tags": ["aggressive", "v2"]
+        });
+
+        let summary: ArtifactSummary = serde_json::from_value(json.clone()).expect("parse");
+        assert_eq!(summary.id, 12);
+        assert_eq!(summary.owner_username, "alice");
+        assert!(summary.note.is_none());
+        assert_eq!(summary.tags, ["aggressive", "v2"]);
+        assert_eq!(serde_json::to_value(&summary).expect("serialize"), json);
+    }
+}
@@ -1,37 +1,88 @@
 use std::{
+    collections::HashMap,
     net::SocketAddr,
-    path::{Path, PathBuf},
-    sync::Arc,
+    path::{Component, Path, PathBuf},
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::{Duration, Instant},
 };
 
 use argon2::{
-    Argon2,
+    Argon2, Params as Argon2Params,
     password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
 };
 use axum::{
     Form, Json, Router,
-    extract::{OriginalUri, Path as AxumPath, Query, State},
-    http::{HeaderMap, HeaderValue, StatusCode, header},
+    body::Body,
+    extract::{
+        ConnectInfo, DefaultBodyLimit, FromRequestParts, OriginalUri, Path as AxumPath, Query,
+        State,
+        ws::{Message, WebSocket, WebSocketUpgrade},
+    },
+    http::{HeaderMap, HeaderValue, Method, StatusCode, header, request::Parts},
+    middleware,
     response::{Html, IntoResponse, Redirect, Response},
     routing::{get, patch, post},
 };
 use base64::Engine;
 use botracers_protocol::{
-    ArtifactSummary, ErrorResponse, LoginRequest, LoginResponse, RegisterRequest,
-    ServerCapabilities, UpdateArtifactVisibilityRequest, UploadArtifactRequest,
+    API_VERSION, AdminUserEntry, ArtifactChangeEvent, ArtifactChangeKind, ArtifactChecksumResponse,
+    ArtifactSummary, ArtifactTagRequest, AuditLogEntry, DEFAULT_ARTIFACT_TARGET,
+    DeleteAccountRequest, ErrorResponse, FEATURE_ARTIFACT_EVENTS, FEATURE_AUDIT_LOG,
+    FEATURE_LEADERBOARD, FEATURE_METRICS, FEATURE_RACE_RECORDS, FEATURE_REPLAYS, LeaderboardEntry,
+    LoginRequest, LoginResponse, LogoutAllResponse, PowChallengeResponse, PublishRaceRecordRequest,
+    PublishRaceRecordResponse, RaceRecordSummary, RegisterRequest, ServerCapabilities,
+    UpdateArtifactRequest, UpdateArtifactVisibilityRequest, UploadArtifactRequest,
     UploadArtifactResponse, UserInfo,
 };
-use chrono::Utc;
+use chrono::{DateTime, TimeDelta, Utc};
+use r2d2::PooledConnection;
+use r2d2_sqlite::SqliteConnectionManager;
 use rand::Rng;
-use rusqlite::{Connection, OptionalExtension, params};
+use rusqlite::{Connection, OptionalExtension, TransactionBehavior, params};
 use serde::Deserialize;
-use tokio::sync::Mutex;
-use tower_http::{cors::CorsLayer, services::ServeDir, trace::TraceLayer};
+use sha2::{Digest, Sha256};
+use tokio::sync::{broadcast, mpsc};
+use tokio_util::io::ReaderStream;
+use tower_http::{
+    compression::CompressionLayer,
+    cors::{AllowOrigin, CorsLayer},
+    limit::RequestBodyLimitLayer,
+    services::ServeDir,
+    trace::TraceLayer,
+};
 use tracing::{debug, info, warn};
 
 const LOCAL_USER_ID: i64 = 1;
 const LOCAL_USERNAME: &str = "local";
 const COOKIE_NAME: &str = "botracers_session";
+/// Headroom above the base64-encoded artifact for the rest of the JSON payload.
+const REQUEST_BODY_OVERHEAD_BYTES: usize = 64 * 1024;
+const ELF_MAGIC: [u8; 4] = [0x7f, b'E', b'L', b'F'];
+const ELF32_HEADER_LEN: usize = 52;
+const ELF64_HEADER_LEN: usize = 64;
+const ELFCLASS32: u8 = 1;
+const ELFCLASS64: u8 = 2;
+const ELFDATA2LSB: u8 = 1;
+const EM_RISCV: u16 = 243;
+const ARTIFACT_PAGE_DEFAULT: u32 = 50;
+const ARTIFACT_PAGE_MAX: u32 = 200;
+/// Upper bound on `UploadArtifactRequest::build_meta_json`.
+const BUILD_META_MAX_BYTES: usize = 4 * 1024;
+/// Most tags one artifact can carry.
+const ARTIFACT_TAGS_MAX: usize = 10;
+const ARTIFACT_TAG_MAX_LEN: usize = 32;
+const AUDIT_PAGE_DEFAULT: u32 = 50;
+const AUDIT_PAGE_MAX: u32 = 500;
+/// How long a proof-of-work challenge can be redeemed after it was issued.
+const POW_CHALLENGE_TTL: Duration = Duration::from_secs(10 * 60);
+/// Unredeemed, unexpired challenges kept at once; more requests get 429.
+const POW_MAX_OUTSTANDING: usize = 10_000;
+/// Artifact changes buffered per websocket subscriber; one that falls further
+/// behind skips the oldest and catches up on the next event.
+const ARTIFACT_EVENTS_CAPACITY: usize = 64;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum AuthMode {
@@ -67,7 +118,48 @@ pub struct ServerConfig {
     pub static_dir: Option<PathBuf>,
     pub auth_mode: AuthMode,
     pub cookie_secure: bool,
+    /// How long a session stays valid; also sent as the session cookie's `Max-Age`.
+    pub session_ttl: Duration,
     pub registration_enabled: bool,
+    pub max_artifact_bytes: usize,
+    /// Artifacts one non-admin user may store; 0 means no limit.
+    pub max_artifacts_per_user: u32,
+    pub supported_targets: Vec<String>,
+    pub audit_log_enabled: bool,
+    pub admin_usernames: Vec<String>,
+    /// Flag the first account registered on an empty database as admin.
+    pub first_user_admin: bool,
+    pub trust_forwarded_for: bool,
+    /// Consecutive failed logins per username before further attempts get 429.
+    pub login_max_failures: u32,
+    /// How long the failure count is kept, and how long a locked-out username waits.
+    pub login_failure_window: Duration,
+    /// Browser origins allowed to make credentialed cross-origin requests.
+    /// Must not be empty unless auth is disabled.
+    pub allowed_origins: Vec<String>,
+    /// Argon2id memory cost in KiB for new password hashes.
+    pub argon2_m_cost: u32,
+    /// Argon2id iterations for new password hashes.
+    pub argon2_t_cost: u32,
+    /// Argon2id parallelism for new password hashes.
+    pub argon2_p_cost: u32,
+    /// Registration (API and web form) needs a solved proof-of-work challenge.
+    pub require_pow: bool,
+    /// Leading zero bits the proof-of-work hash must have.
+    pub pow_difficulty_bits: u8,
+}
+
+impl ServerConfig {
+    /// The configured Argon2 cost parameters; `run_server` refuses to start
+    /// when they are out of range.
+    pub fn argon2_params(&self) -> Result<Argon2Params, argon2::Error> {
+        Argon2Params::new(
+            self.argon2_m_cost,
+            self.argon2_t_cost,
+            self.argon2_p_cost,
+            None,
+        )
+    }
 }
 
 impl Default for ServerConfig {
@@ -79,19 +171,402 @@ impl Default for ServerConfig {
             static_dir: Some(PathBuf::from("web-dist")),
             auth_mode: AuthMode::Required,
             cookie_secure: false,
+            session_ttl: Duration::from_secs(30 * 24 * 60 * 60),
             registration_enabled: true,
+            max_artifact_bytes: 16 * 1024 * 1024,
+            max_artifacts_per_user: 100,
+            supported_targets: vec![DEFAULT_ARTIFACT_TARGET.to_string()],
+            audit_log_enabled: true,
+            admin_usernames: Vec::new(),
+            first_user_admin: false,
+            trust_forwarded_for: false,
+            login_max_failures: 5,
+            login_failure_window: Duration::from_secs(60),
+            allowed_origins: vec![
+                "http://127.0.0.1:8787".to_string(),
+                "http://localhost:8787".to_string(),
+            ],
+            argon2_m_cost: Argon2Params::DEFAULT_M_COST,
+            argon2_t_cost: Argon2Params::DEFAULT_T_COST,
+            argon2_p_cost: Argon2Params::DEFAULT_P_COST,
+            require_pow: false,
+            pow_difficulty_bits: 16,
         }
     }
 }
 
+/// Upper bound on concurrently open SQLite connections.
+const DB_POOL_SIZE: u32 = 8;
+
+/// How long `/healthz` waits for a pooled connection before reporting 503.
+const HEALTHZ_DB_TIMEOUT: Duration = Duration::from_secs(2);
+
+type DbPool = r2d2::Pool<SqliteConnectionManager>;
+
+/// Builds the connection pool; every connection enforces foreign keys and
+/// waits on a locked database instead of failing immediately.
+fn open_db_pool(manager: SqliteConnectionManager) -> Result<DbPool, r2d2::Error> {
+    let manager = manager.with_init(|conn| {
+        conn.execute_batch("PRAGMA foreign_keys = ON; PRAGMA busy_timeout = 5000;")
+    });
+    r2d2::Pool::builder().max_size(DB_POOL_SIZE).build(manager)
+}
+
 #[derive(Clone)]
 struct AppState {
-    db: Arc<Mutex<Connection>>,
+    db: DbPool,
     artifacts_dir: PathBuf,
     static_dir: Option<PathBuf>,
     auth_mode: AuthMode,
     cookie_secure: bool,
+    session_ttl: Duration,
     registration_enabled: bool,
+    max_artifact_bytes: usize,
+    max_artifacts_per_user: u32,
+    supported_targets: Arc<[String]>,
+    audit: Option<AuditLog>,
+    admin_usernames: Arc<[String]>,
+    first_user_admin: bool,
+    trust_forwarded_for: bool,
+    login_limiter: LoginLimiter,
+    allowed_origins: Arc<[String]>,
+    argon2_params: Argon2Params,
+    metrics: Arc<Metrics>,
+    artifact_events: broadcast::Sender<ArtifactChange>,
+    /// `Some` when registration requires proof of work.
+    pow: Option<PowChallenges>,
+}
+
+impl AppState {
+    fn db(&self) -> Result<PooledConnection<SqliteConnectionManager>, ApiError> {
+        self.db
+            .get()
+            .map_err(|e| ApiError::internal(format!("failed to get db connection: {e}")))
+    }
+}
+
+/// An `ArtifactChangeEvent` for the websockets of `owner_user_id`.
+#[derive(Debug, Clone)]
+struct ArtifactChange {
+    owner_user_id: i64,
+    event: ArtifactChangeEvent,
+}
+
+/// Tell `owner_user_id`'s open `GET /api/v1/ws` connections that one of their
+/// artifacts changed.
+fn publish_artifact_change(
+    state: &AppState,
+    owner_user_id: i64,
+    kind: ArtifactChangeKind,
+    artifact_id: i64,
+) {
+    // Sending only fails when nobody is subscribed.
+    let _ = state.artifact_events.send(ArtifactChange {
+        owner_user_id,
+        event: ArtifactChangeEvent { kind, artifact_id },
+    });
+}
+
+/// Process-lifetime request counters reported by `GET /metrics`; they reset
+/// when the server restarts.
+#[derive(Default)]
+struct Metrics {
+    uploads: AtomicU64,
+    downloads: AtomicU64,
+    logins_succeeded: AtomicU64,
+    logins_failed: AtomicU64,
+}
+
+impl Metrics {
+    fn increment(counter: &AtomicU64) {
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Counts consecutive failed logins per username, keyed with the time of the
+/// first failure in the current window. Only existing users are recorded, and
+/// expired windows are dropped on every failure, so the map stays bounded by
+/// the users who failed recently.
+#[derive(Clone)]
+struct LoginLimiter {
+    failures: Arc<Mutex<HashMap<String, (u32, Instant)>>>,
+    max_failures: u32,
+    window: Duration,
+}
+
+impl LoginLimiter {
+    fn new(max_failures: u32, window: Duration) -> Self {
+        Self {
+            failures: Arc::default(),
+            max_failures,
+            window,
+        }
+    }
+
+    fn check(&self, username: &str) -> Result<(), ApiError> {
+        let mut failures = self.failures.lock().expect("login limiter lock");
+        let Some(&(count, since)) = failures.get(username) else {
+            return Ok(());
+        };
+        let elapsed = since.elapsed();
+        if elapsed >= self.window {
+            failures.remove(username);
+            return Ok(());
+        }
+        if count >= self.max_failures {
+            let retry_after = (self.window - elapsed).as_secs().max(1);
+            return Err(ApiError::too_many_requests(format!(
+                "too many failed login attempts; retry in {retry_after}s"
+            )));
+        }
+        Ok(())
+    }
+
+    fn record_failure(&self, username: &str) {
+        let mut failures = self.failures.lock().expect("login limiter lock");
+        let now = Instant::now();
+        failures.retain(|_, &mut (_, since)| now.duration_since(since) < self.window);
+        failures.entry(username.to_string()).or_insert((0, now)).0 += 1;
+    }
+
+    fn reset(&self, username: &str) {
+        self.failures
+            .lock()
+            .expect("login limiter lock")
+            .remove(username);
+    }
+}
+
+/// Proof-of-work challenges handed out by `GET /api/v1/auth/pow-challenge`;
+/// each can be redeemed once within `POW_CHALLENGE_TTL`. State is lost on
+/// restart.
+#[derive(Clone)]
+struct PowChallenges {
+    issued: Arc<Mutex<HashMap<String, Instant>>>,
+    difficulty_bits: u8,
+}
+
+impl PowChallenges {
+    fn new(difficulty_bits: u8) -> Self {
+        Self {
+            issued: Arc::default(),
+            difficulty_bits,
+        }
+    }
+
+    fn issue(&self) -> Result<String, ApiError> {
+        let mut issued = self.issued.lock().expect("pow challenges lock");
+        issued.retain(|_, issued_at| issued_at.elapsed() < POW_CHALLENGE_TTL);
+        if issued.len() >= POW_MAX_OUTSTANDING {
+            return Err(ApiError::too_many_requests(
+                "too many outstanding proof-of-work challenges; retry later",
+            ));
+        }
+        let challenge = generate_token();
+        issued.insert(challenge.clone(), Instant::now());
+        Ok(challenge)
+    }
+
+    /// Consumes `challenge` and checks that `sha256(challenge + nonce)` has
+    /// `difficulty_bits` leading zero bits.
+    fn redeem(&self, challenge: Option<&str>, nonce: Option<&str>) -> Result<(), ApiError> {
+        let (Some(challenge), Some(nonce)) = (challenge, nonce) else {
+            return Err(ApiError::bad_request(
+                "registration requires a solved proof-of-work challenge from /api/v1/auth/pow-challenge",
+            ));
+        };
+        let issued_at = self
+            .issued
+            .lock()
+            .expect("pow challenges lock")
+            .remove(challenge);
+        if issued_at.is_none_or(|issued_at| issued_at.elapsed() >= POW_CHALLENGE_TTL) {
+            return Err(ApiError::bad_request(
+                "unknown or expired proof-of-work challenge",
+            ));
+        }
+        let digest = Sha256::digest(format!("{challenge}{nonce}"));
+        if leading_zero_bits(&digest) < u32::from(self.difficulty_bits) {
+            return Err(ApiError::bad_request(
+                "proof-of-work nonce does not meet the required difficulty",
+            ));
+        }
+        Ok(())
+    }
+}
+
+fn leading_zero_bits(bytes: &[u8]) -> u32 {
+    let mut bits = 0;
+    for &byte in bytes {
+        if byte != 0 {
+            return bits + byte.leading_zeros();
+        }
+        bits += 8;
+    }
+    bits
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AuditEvent {
+    Login,
+    LoginFailed,
+    Logout,
+    LogoutAll,
+    Register,
+    AccountDelete,
+    ArtifactUpload,
+    ArtifactDelete,
+    ArtifactUpdate,
+    ArtifactVisibility,
+    RacePublish,
+}
+
+impl AuditEvent {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Login => "login",
+            Self::LoginFailed => "login_failed",
+            Self::Logout => "logout",
+            Self::LogoutAll => "logout_all",
+            Self::Register => "register",
+            Self::AccountDelete => "account_delete",
+            Self::ArtifactUpload => "artifact_upload",
+            Self::ArtifactDelete => "artifact_delete",
+            Self::ArtifactUpdate => "artifact_update",
+            Self::ArtifactVisibility => "artifact_visibility",
+            Self::RacePublish => "race_publish",
+        }
+    }
+}
+
+#[derive(Debug)]
+struct AuditRecord {
+    event: AuditEvent,
+    user_id: Option<i64>,
+    ip: Option<String>,
+    detail: String,
+    created_at: String,
+}
+
+/// Queue feeding a background task that writes `audit_log` rows, so handlers
+/// never wait on the audit insert.
+#[derive(Clone)]
+struct AuditLog {
+    tx: mpsc::UnboundedSender<AuditRecord>,
+}
+
+impl AuditLog {
+    fn spawn(db: DbPool) -> Self {
+        let (tx, mut rx) = mpsc::unbounded_channel::<AuditRecord>();
+        tokio::spawn(async move {
+            while let Some(record) = rx.recv().await {
+                let db = match db.get() {
+                    Ok(db) => db,
+                    Err(error) => {
+                        warn!(%error, event = record.event.as_str(), "no db connection for audit log row");
+                        continue;
+                    }
+                };
+                if let Err(error) = db.execute(
+                    "INSERT INTO audit_log (event, user_id, ip, detail, created_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+                    params![
+                        record.event.as_str(),
+                        record.user_id,
+                        record.ip,
+                        record.detail,
+                        record.created_at
+                    ],
+                ) {
+                    warn!(%error, event = record.event.as_str(), "failed to write audit log row");
+                }
+            }
+        });
+        Self { tx }
+    }
+}
+
+/// Client address for the audit log: the first `X-Forwarded-For` hop when the
+/// server is configured to trust it, otherwise the TCP peer.
+struct ClientIp(Option<String>);
+
+impl FromRequestParts<AppState> for ClientIp {
+    type Rejection = std::convert::Infallible;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        if state.trust_forwarded_for
+            && let Some(forwarded) = parts
+                .headers
+                .get("x-forwarded-for")
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.split(',').next())
+                .map(str::trim)
+                .filter(|value| !value.is_empty())
+        {
+            return Ok(Self(Some(forwarded.to_string())));
+        }
+        Ok(Self(
+            parts
+                .extensions
+                .get::<ConnectInfo<SocketAddr>>()
+                .map(|ConnectInfo(addr)| addr.ip().to_string()),
+        ))
+    }
+}
+
+fn audit(
+    state: &AppState,
+    client: &ClientIp,
+    event: AuditEvent,
+    user_id: Option<i64>,
+    detail: impl Into<String>,
+) {
+    let Some(audit) = &state.audit else {
+        return;
+    };
+    let _ = audit.tx.send(AuditRecord {
+        event,
+        user_id,
+        ip: client.0.clone(),
+        detail: detail.into(),
+        created_at: now_utc(),
+    });
+}
+
+#[derive(Debug, Deserialize)]
+struct ArtifactListQuery {
+    limit: Option<u32>,
+    offset: Option<u32>,
+    #[serde(default)]
+    sort: ArtifactSort,
+    /// Only artifacts carrying this tag.
+    tag: Option<String>,
+}
+
+/// Order of `GET /api/v1/artifacts` (`?sort=recent|popular`).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum ArtifactSort {
+    /// Newest first.
+    #[default]
+    Recent,
+    /// Most downloaded first, newest first among equals.
+    Popular,
+}
+
+#[derive(Debug, Deserialize)]
+struct LeaderboardQuery {
+    track_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AuditQuery {
+    user_id: Option<i64>,
+    event: Option<String>,
+    limit: Option<u32>,
+    offset: Option<u32>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -117,6 +592,9 @@ struct WebRegisterForm {
     username: String,
     password: String,
     next: Option<String>,
+    /// Filled in by the register page's script when proof of work is required.
+    pow_challenge: Option<String>,
+    pow_nonce: Option<String>,
 }
 
 #[derive(Debug)]
@@ -154,6 +632,27 @@ impl ApiError {
         }
     }
 
+    fn payload_too_large(message: impl Into<String>) -> Self {
+        Self {
+            status: StatusCode::PAYLOAD_TOO_LARGE,
+            message: message.into(),
+        }
+    }
+
+    fn too_many_requests(message: impl Into<String>) -> Self {
+        Self {
+            status: StatusCode::TOO_MANY_REQUESTS,
+            message: message.into(),
+        }
+    }
+
+    fn service_unavailable(message: impl Into<String>) -> Self {
+        Self {
+            status: StatusCode::SERVICE_UNAVAILABLE,
+            message: message.into(),
+        }
+    }
+
     fn internal(message: impl Into<String>) -> Self {
         Self {
             status: StatusCode::INTERNAL_SERVER_ERROR,
@@ -182,21 +681,62 @@ pub async fn run_server(config: ServerConfig) -> Result<(), Box<dyn std::error::
         artifacts_dir = %config.artifacts_dir.display(),
         static_dir = ?config.static_dir.as_ref().map(|p| p.display().to_string()),
         registration_enabled = config.registration_enabled,
+        session_ttl_secs = config.session_ttl.as_secs(),
+        max_artifact_bytes = config.max_artifact_bytes,
+        max_artifacts_per_user = config.max_artifacts_per_user,
+        supported_targets = ?config.supported_targets,
+        audit_log_enabled = config.audit_log_enabled,
+        login_max_failures = config.login_max_failures,
+        login_failure_window_secs = config.login_failure_window.as_secs(),
+        allowed_origins = ?config.allowed_origins,
+        argon2_m_cost = config.argon2_m_cost,
+        argon2_t_cost = config.argon2_t_cost,
+        argon2_p_cost = config.argon2_p_cost,
         "starting botracers server"
     );
+    let argon2_params = config
+        .argon2_params()
+        .map_err(|error| format!("invalid Argon2 parameters: {error}"))?;
+    // Refuse to start rather than serve without the CORS policy the config asks for.
+    if let Err(error) = cors_layer(config.auth_mode, &config.allowed_origins) {
+        return Err(error.into());
+    }
 
     std::fs::create_dir_all(&config.artifacts_dir)?;
-    let conn = Connection::open(&config.db_path)?;
-    run_migrations(&conn)?;
-    ensure_local_user(&conn)?;
+    let db = open_db_pool(SqliteConnectionManager::file(&config.db_path))?;
+    {
+        let conn = db.get()?;
+        conn.pragma_update(None, "journal_mode", "WAL")?;
+        run_migrations(&conn)?;
+        ensure_local_user(&conn)?;
+    }
 
+    let audit = config
+        .audit_log_enabled
+        .then(|| AuditLog::spawn(db.clone()));
     let state = AppState {
-        db: Arc::new(Mutex::new(conn)),
+        db,
         artifacts_dir: config.artifacts_dir,
         static_dir: config.static_dir.clone(),
         auth_mode: config.auth_mode,
         cookie_secure: config.cookie_secure,
+        session_ttl: config.session_ttl,
         registration_enabled: config.registration_enabled,
+        max_artifact_bytes: config.max_artifact_bytes,
+        max_artifacts_per_user: config.max_artifacts_per_user,
+        supported_targets: config.supported_targets.into(),
+        audit,
+        admin_usernames: config.admin_usernames.into(),
+        first_user_admin: config.first_user_admin,
+        trust_forwarded_for: config.trust_forwarded_for,
+        login_limiter: LoginLimiter::new(config.login_max_failures, config.login_failure_window),
+        allowed_origins: config.allowed_origins.into(),
+        argon2_params,
+        metrics: Arc::default(),
+        artifact_events: broadcast::channel(ARTIFACT_EVENTS_CAPACITY).0,
+        pow: config
+            .require_pow
+            .then(|| PowChallenges::new(config.pow_difficulty_bits)),
     };
 
     let app = build_app(state, config.static_dir);
@@ -204,9 +744,12 @@ pub async fn run_server(config: ServerConfig) -> Result<(), Box<dyn std::error::
     let addr: SocketAddr = config.bind.parse()?;
     let listener = tokio::net::TcpListener::bind(addr).await?;
     info!(%addr, "botracers listening");
-    axum::serve(listener, app)
-        .with_graceful_shutdown(shutdown_signal())
-        .await?;
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .with_graceful_shutdown(shutdown_signal())
+    .await?;
     info!("botracers server shutdown complete");
     Ok(())
 }
@@ -233,37 +776,115 @@ async fn shutdown_signal() {
     }
 }
 
+/// Largest accepted request body: an upload of `max_artifact_bytes` after
+/// base64 expansion plus room for the surrounding JSON fields.
+fn request_body_limit(max_artifact_bytes: usize) -> usize {
+    max_artifact_bytes.div_ceil(3) * 4 + REQUEST_BODY_OVERHEAD_BYTES
+}
+
+/// Replaces the plain-text 413 produced by the body limit with a JSON error.
+async fn json_payload_too_large(response: Response) -> Response {
+    if response.status() != StatusCode::PAYLOAD_TOO_LARGE {
+        return response;
+    }
+    ApiError::payload_too_large("request body too large").into_response()
+}
+
+/// CORS for browser clients on other origins. Credentialed requests are only
+/// allowed from the configured origins; an empty list means any origin, which
+/// is only acceptable when there are no sessions to protect.
+fn cors_layer(auth_mode: AuthMode, allowed_origins: &[String]) -> Result<CorsLayer, String> {
+    if allowed_origins.is_empty() {
+        return match auth_mode {
+            AuthMode::Disabled => Ok(CorsLayer::permissive()),
+            AuthMode::Required => Err(
+                "BOTRACERS_ALLOWED_ORIGINS must list at least one origin when auth is required"
+                    .to_string(),
+            ),
+        };
+    }
+
+    let origins = allowed_origins
+        .iter()
+        .map(|origin| {
+            HeaderValue::from_str(origin)
+                .map_err(|_| format!("invalid origin in BOTRACERS_ALLOWED_ORIGINS: {origin:?}"))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(CorsLayer::new()
+        .allow_origin(AllowOrigin::list(origins))
+        .allow_credentials(true)
+        .allow_methods([Method::GET, Method::POST, Method::PATCH, Method::DELETE])
+        .allow_headers([header::CONTENT_TYPE, header::AUTHORIZATION]))
+}
+
 fn build_app(state: AppState, static_dir: Option<PathBuf>) -> Router {
+    let body_limit = request_body_limit(state.max_artifact_bytes);
+    // `run_server` refuses to start with an invalid list; anything else gets
+    // no CORS headers, so browsers keep requests same-origin.
+    let cors = cors_layer(state.auth_mode, &state.allowed_origins).unwrap_or_else(|error| {
+        warn!(%error, "cross-origin requests disabled");
+        CorsLayer::new()
+    });
     let mut app = Router::new()
         .route("/", get(web_game_entry))
         .route("/index.html", get(web_game_entry))
         .route("/login", get(web_login_get).post(web_login_post))
         .route("/register", get(web_register_get).post(web_register_post))
         .route("/healthz", get(healthz))
+        .route("/metrics", get(metrics))
         .route("/api/v1/capabilities", get(capabilities))
         .route("/api/v1/auth/register", post(register))
+        .route("/api/v1/auth/pow-challenge", get(pow_challenge))
         .route("/api/v1/auth/login", post(login))
         .route("/api/v1/auth/logout", post(logout))
-        .route("/api/v1/me", get(me))
+        .route("/api/v1/auth/logout-all", post(logout_all))
+        .route("/api/v1/me", get(me).delete(delete_account))
+        .route("/api/v1/ws", get(artifact_events_ws))
         .route(
             "/api/v1/artifacts",
             get(list_artifacts).post(upload_artifact),
         )
         .route(
             "/api/v1/artifacts/{id}",
-            get(download_artifact).delete(delete_artifact),
+            get(download_artifact)
+                .patch(update_artifact)
+                .delete(delete_artifact),
         )
+        .route("/api/v1/artifacts/{id}/checksum", get(artifact_checksum))
         .route(
             "/api/v1/artifacts/{id}/visibility",
             patch(update_artifact_visibility),
         )
-        .layer(CorsLayer::permissive())
+        .route(
+            "/api/v1/artifacts/{id}/tags",
+            post(add_artifact_tag).delete(remove_artifact_tag),
+        )
+        .route(
+            "/api/v1/race-records",
+            get(list_race_records).post(publish_race_record),
+        )
+        .route("/api/v1/leaderboard", get(leaderboard))
+        .route("/api/v1/admin/audit", get(list_audit_log))
+        .route("/api/v1/admin/users", get(list_users))
+        .route(
+            "/api/v1/race-records/{id}/replay",
+            get(download_race_replay),
+        )
+        .layer(DefaultBodyLimit::max(body_limit))
+        .layer(RequestBodyLimitLayer::new(body_limit))
+        .layer(middleware::map_response(json_payload_too_large))
+        // Negotiates gzip/deflate from `Accept-Encoding`; streamed artifact
+        // bodies are compressed on the fly and lose their `Content-Length`.
+        .layer(CompressionLayer::new().gzip(true).deflate(true))
+        .layer(cors)
         .layer(TraceLayer::new_for_http())
-        .with_state(state);
+        .with_state(state.clone());
 
     if let Some(dir) = static_dir {
         info!(static_dir = %dir.display(), "serving static files");
-        app = app.fallback_service(ServeDir::new(dir));
+        let client_routes = get(web_client_route).with_state(state);
+        app = app.fallback_service(ServeDir::new(dir).fallback(client_routes));
     } else {
         warn!("static file serving disabled (BOTRACERS_STATIC_DIR empty)");
     }
@@ -271,8 +892,64 @@ fn build_app(state: AppState, static_dir: Option<PathBuf>) -> Router {
     app
 }
 
-async fn healthz() -> &'static str {
-    "ok"
+/// Plain `ok` once a pooled connection answers `SELECT 1`; otherwise 503
+/// with an `ErrorResponse` body.
+async fn healthz(State(state): State<AppState>) -> Result<&'static str, ApiError> {
+    let db = state
+        .db
+        .get_timeout(HEALTHZ_DB_TIMEOUT)
+        .map_err(|e| ApiError::service_unavailable(format!("database unavailable: {e}")))?;
+    db.query_row("SELECT 1", [], |row| row.get::<_, i64>(0))
+        .map_err(|e| ApiError::service_unavailable(format!("database check failed: {e}")))?;
+    Ok("ok")
+}
+
+/// Prometheus text exposition of the request counters plus live artifact and
+/// user counts. Unauthenticated, so deployments should keep it off the public
+/// listener (e.g. block `/metrics` at the reverse proxy).
+async fn metrics(State(state): State<AppState>) -> Result<Response, ApiError> {
+    let db = state.db()?;
+    let count = |table: &str| {
+        db.query_row(&format!("SELECT COUNT(*) FROM {table}"), [], |row| {
+            row.get::<_, i64>(0)
+        })
+        .map_err(|e| ApiError::internal(format!("failed to count {table}: {e}")))
+    };
+    let artifacts = count("artifacts")?;
+    let users = count("users")?;
+    let counters = &state.metrics;
+    let load = |counter: &AtomicU64| counter.load(Ordering::Relaxed);
+
+    let body = format!(
+        "# HELP botracers_artifact_uploads_total Artifacts uploaded since startup.\n\
+         # TYPE botracers_artifact_uploads_total counter\n\
+         botracers_artifact_uploads_total {}\n\
+         # HELP botracers_artifact_downloads_total Artifact bodies served since startup.\n\
+         # TYPE botracers_artifact_downloads_total counter\n\
+         botracers_artifact_downloads_total {}\n\
+         # HELP botracers_logins_total Login attempts since startup by outcome.\n\
+         # TYPE botracers_logins_total counter\n\
+         botracers_logins_total{{result=\"success\"}} {}\n\
+         botracers_logins_total{{result=\"failure\"}} {}\n\
+         # HELP botracers_artifacts Artifacts currently stored.\n\
+         # TYPE botracers_artifacts gauge\n\
+         botracers_artifacts {artifacts}\n\
+         # HELP botracers_users Registered user accounts.\n\
+         # TYPE botracers_users gauge\n\
+         botracers_users {users}\n",
+        load(&counters.uploads),
+        load(&counters.downloads),
+        load(&counters.logins_succeeded),
+        load(&counters.logins_failed),
+    );
+    Ok((
+        [(
+            header::CONTENT_TYPE,
+            HeaderValue::from_static("text/plain; version=0.0.4; charset=utf-8"),
+        )],
+        body,
+    )
+        .into_response())
 }
 
 async fn capabilities(State(state): State<AppState>) -> Json<ServerCapabilities> {
@@ -280,9 +957,29 @@ async fn capabilities(State(state): State<AppState>) -> Json<ServerCapabilities>
         auth_required: state.auth_mode.auth_required(),
         mode: state.auth_mode.as_str().to_string(),
         registration_enabled: state.registration_enabled,
+        supported_targets: state.supported_targets.to_vec(),
+        api_version: API_VERSION.to_string(),
+        max_artifact_bytes: state.max_artifact_bytes as u64,
+        features: enabled_features(&state),
+        pow_required: state.pow.is_some(),
     })
 }
 
+/// Feature names advertised in `ServerCapabilities::features`.
+fn enabled_features(state: &AppState) -> Vec<String> {
+    let mut features = vec![
+        FEATURE_LEADERBOARD,
+        FEATURE_RACE_RECORDS,
+        FEATURE_REPLAYS,
+        FEATURE_METRICS,
+        FEATURE_ARTIFACT_EVENTS,
+    ];
+    if state.audit.is_some() {
+        features.push(FEATURE_AUDIT_LOG);
+    }
+    features.into_iter().map(str::to_string).collect()
+}
+
 async fn web_game_entry(
     State(state): State<AppState>,
     headers: HeaderMap,
@@ -303,6 +1000,22 @@ async fn web_game_entry(
     }
 }
 
+/// Fallback for paths the static dir has no file for. Client-side routes of the
+/// web game get the same entry page (and login gate) as `/`; unknown API
+/// paths and missing assets (anything with a file extension) stay 404.
+async fn web_client_route(
+    state: State<AppState>,
+    headers: HeaderMap,
+    OriginalUri(uri): OriginalUri,
+) -> Response {
+    let path = uri.path();
+    let file_name = path.rsplit('/').next().unwrap_or_default();
+    if path.starts_with("/api/") || file_name.contains('.') {
+        return ApiError::not_found("not found").into_response();
+    }
+    web_game_entry(state, headers, OriginalUri(uri)).await
+}
+
 async fn web_login_get(
     State(state): State<AppState>,
     Query(query): Query<WebLoginQuery>,
@@ -317,6 +1030,7 @@ async fn web_login_get(
 
 async fn web_login_post(
     State(state): State<AppState>,
+    client: ClientIp,
     Form(payload): Form<WebLoginForm>,
 ) -> Response {
     if state.auth_mode == AuthMode::Disabled {
@@ -328,8 +1042,9 @@ async fn web_login_post(
     let next = sanitize_next(payload.next.as_deref().unwrap_or("/"));
 
     match create_session_for_credentials(&state, username, &payload.password).await {
-        Ok((_user, token)) => {
-            let cookie = session_cookie(&token, state.cookie_secure);
+        Ok((user, token)) => {
+            audit(&state, &client, AuditEvent::Login, Some(user.id), "web");
+            let cookie = session_cookie(&token, state.cookie_secure, state.session_ttl);
             (
                 StatusCode::SEE_OTHER,
                 [
@@ -342,8 +1057,28 @@ async fn web_login_post(
             )
                 .into_response()
         }
+        Err(error) if error.status == StatusCode::TOO_MANY_REQUESTS => {
+            warn!(username, "web login rate limited");
+            (
+                StatusCode::TOO_MANY_REQUESTS,
+                render_login_page(
+                    next,
+                    Some(username),
+                    Some("Too many failed attempts, try again later"),
+                    state.registration_enabled,
+                ),
+            )
+                .into_response()
+        }
         Err(_) => {
             warn!(username, "web login failed");
+            audit(
+                &state,
+                &client,
+                AuditEvent::LoginFailed,
+                None,
+                format!("web username={username}"),
+            );
             (
                 StatusCode::UNAUTHORIZED,
                 render_login_page(
@@ -367,11 +1102,12 @@ async fn web_register_get(
         return Redirect::to(next_login_with_error(next, Some("registration_disabled")).as_str())
             .into_response();
     }
-    render_register_page(next, None, None).into_response()
+    render_register_page(next, None, None, state.pow.is_some()).into_response()
 }
 
 async fn web_register_post(
     State(state): State<AppState>,
+    client: ClientIp,
     Form(payload): Form<WebRegisterForm>,
 ) -> Response {
     let next = sanitize_next(payload.next.as_deref().unwrap_or("/"));
@@ -381,11 +1117,29 @@ async fn web_register_post(
     }
 
     let username = payload.username.trim();
-    match create_user_with_password(&state, username, &payload.password).await {
-        Ok(_user) => {
-            match create_session_for_credentials(&state, username, &payload.password).await {
+    let registered = match &state.pow {
+        Some(pow) => pow.redeem(
+            payload
+                .pow_challenge
+                .as_deref()
+                .filter(|value| !value.is_empty()),
+            payload
+                .pow_nonce
+                .as_deref()
+                .filter(|value| !value.is_empty()),
+        ),
+        None => Ok(()),
+    };
+    let registered = match registered {
+        Ok(()) => create_user_with_password(&state, username, &payload.password).await,
+        Err(err) => Err(err),
+    };
+    match registered {
+        Ok(user) => {
+            audit(&state, &client, AuditEvent::Register, Some(user.id), "web");
+            match create_session_for_credentials(&state, &user.username, &payload.password).await {
                 Ok((_user, token)) => {
-                    let cookie = session_cookie(&token, state.cookie_secure);
+                    let cookie = session_cookie(&token, state.cookie_secure, state.session_ttl);
                     (
                         StatusCode::SEE_OTHER,
                         [
@@ -409,7 +1163,12 @@ async fn web_register_post(
             };
             (
                 status,
-                render_register_page(next, Some(username), Some(&err.message)),
+                render_register_page(
+                    next,
+                    Some(username),
+                    Some(&err.message),
+                    state.pow.is_some(),
+                ),
             )
                 .into_response()
         }
@@ -418,6 +1177,7 @@ async fn web_register_post(
 
 async fn register(
     State(state): State<AppState>,
+    client: ClientIp,
     Json(payload): Json<RegisterRequest>,
 ) -> Result<Json<UserInfo>, ApiError> {
     if state.auth_mode == AuthMode::Disabled {
@@ -426,13 +1186,36 @@ async fn register(
     if !state.registration_enabled {
         return Err(ApiError::forbidden("registration is disabled"));
     }
+    if let Some(pow) = &state.pow {
+        pow.redeem(
+            payload.pow_challenge.as_deref(),
+            payload.pow_nonce.as_deref(),
+        )?;
+    }
     let user =
         create_user_with_password(&state, payload.username.trim(), &payload.password).await?;
+    audit(&state, &client, AuditEvent::Register, Some(user.id), "api");
     Ok(Json(user))
 }
 
+/// `GET /api/v1/auth/pow-challenge`: 404 unless `ServerConfig::require_pow`.
+async fn pow_challenge(
+    State(state): State<AppState>,
+) -> Result<Json<PowChallengeResponse>, ApiError> {
+    let Some(pow) = &state.pow else {
+        return Err(ApiError::not_found(
+            "registration does not require proof of work",
+        ));
+    };
+    Ok(Json(PowChallengeResponse {
+        challenge: pow.issue()?,
+        difficulty: pow.difficulty_bits,
+    }))
+}
+
 async fn login(
     State(state): State<AppState>,
+    client: ClientIp,
     Json(payload): Json<LoginRequest>,
 ) -> Result<Response, ApiError> {
     if state.auth_mode == AuthMode::Disabled {
@@ -440,31 +1223,56 @@ async fn login(
     }
 
     let username = payload.username.trim();
-    let (user, token) = create_session_for_credentials(&state, username, &payload.password).await?;
+    let (user, token) =
+        match create_session_for_credentials(&state, username, &payload.password).await {
+            Ok(session) => session,
+            Err(error) => {
+                if error.status == StatusCode::UNAUTHORIZED {
+                    audit(
+                        &state,
+                        &client,
+                        AuditEvent::LoginFailed,
+                        None,
+                        format!("api username={username}"),
+                    );
+                }
+                return Err(error);
+            }
+        };
+    audit(&state, &client, AuditEvent::Login, Some(user.id), "api");
     let login = LoginResponse {
         token: token.clone(),
         user,
     };
 
-    let cookie = session_cookie(&token, state.cookie_secure);
+    let cookie = session_cookie(&token, state.cookie_secure, state.session_ttl);
     Ok((StatusCode::OK, [(header::SET_COOKIE, cookie)], Json(login)).into_response())
 }
 
-async fn logout(State(state): State<AppState>, headers: HeaderMap) -> Result<Response, ApiError> {
+async fn logout(
+    State(state): State<AppState>,
+    client: ClientIp,
+    headers: HeaderMap,
+) -> Result<Response, ApiError> {
     if state.auth_mode == AuthMode::Disabled {
         return Ok(StatusCode::NO_CONTENT.into_response());
     }
 
+    let user_id = authenticate(&state, &headers)
+        .await
+        .ok()
+        .map(|user| user.id);
+
     let mut removed = false;
     if let Some(token) = bearer_token_opt(&headers) {
-        let db = state.db.lock().await;
+        let db = state.db()?;
         db.execute("DELETE FROM sessions WHERE token = ?1", params![token])
             .map_err(|e| ApiError::internal(format!("failed to logout bearer token: {e}")))?;
         removed = true;
     }
 
     if let Some(token) = session_cookie_token(&headers) {
-        let db = state.db.lock().await;
+        let db = state.db()?;
         db.execute("DELETE FROM sessions WHERE token = ?1", params![token])
             .map_err(|e| ApiError::internal(format!("failed to logout cookie token: {e}")))?;
         removed = true;
@@ -473,28 +1281,191 @@ async fn logout(State(state): State<AppState>, headers: HeaderMap) -> Result<Res
     if !removed {
         return Err(ApiError::unauthorized("missing auth token/session cookie"));
     }
+    if user_id.is_some() {
+        audit(&state, &client, AuditEvent::Logout, user_id, "");
+    }
 
     let clear_cookie = expired_session_cookie(state.cookie_secure);
     Ok((StatusCode::NO_CONTENT, [(header::SET_COOKIE, clear_cookie)]).into_response())
 }
 
+/// Ends every session of the caller, not just the presented one.
+async fn logout_all(
+    State(state): State<AppState>,
+    client: ClientIp,
+    headers: HeaderMap,
+) -> Result<Response, ApiError> {
+    if state.auth_mode == AuthMode::Disabled {
+        return Ok(Json(LogoutAllResponse {
+            removed_sessions: 0,
+        })
+        .into_response());
+    }
+
+    let user = authenticate(&state, &headers).await?;
+    let db = state.db()?;
+    let removed = db
+        .execute("DELETE FROM sessions WHERE user_id = ?1", params![user.id])
+        .map_err(|e| ApiError::internal(format!("failed to remove sessions: {e}")))?;
+    drop(db);
+
+    audit(
+        &state,
+        &client,
+        AuditEvent::LogoutAll,
+        Some(user.id),
+        format!("removed_sessions={removed}"),
+    );
+
+    let clear_cookie = expired_session_cookie(state.cookie_secure);
+    Ok((
+        [(header::SET_COOKIE, clear_cookie)],
+        Json(LogoutAllResponse {
+            removed_sessions: removed as u64,
+        }),
+    )
+        .into_response())
+}
+
 async fn me(State(state): State<AppState>, headers: HeaderMap) -> Result<Json<UserInfo>, ApiError> {
     let user = authenticate(&state, &headers).await?;
     Ok(Json(user))
 }
 
+/// Deletes the caller's account after checking their password. Deleting the
+/// `users` row cascades to sessions, artifacts and race records; the artifact
+/// files are removed from disk, best effort, once that has committed.
+async fn delete_account(
+    State(state): State<AppState>,
+    client: ClientIp,
+    headers: HeaderMap,
+    Json(payload): Json<DeleteAccountRequest>,
+) -> Result<Response, ApiError> {
+    if state.auth_mode == AuthMode::Disabled {
+        return Err(ApiError::bad_request("auth is disabled in standalone mode"));
+    }
+
+    let user = authenticate(&state, &headers).await?;
+    let mut db = state.db()?;
+    let password_hash: String = db
+        .query_row(
+            "SELECT password_hash FROM users WHERE id = ?1",
+            params![user.id],
+            |row| row.get(0),
+        )
+        .map_err(|e| ApiError::internal(format!("failed to query user: {e}")))?;
+    // A wrong password is not a failed login: the session stays valid.
+    verify_password(&state.argon2_params, &payload.password, &password_hash).map_err(|error| {
+        if error.status == StatusCode::UNAUTHORIZED {
+            ApiError::forbidden("incorrect password")
+        } else {
+            error
+        }
+    })?;
+
+    // Rows go first, under the write lock so no upload lands between reading
+    // the paths and deleting the user; files are only unlinked after commit.
+    let tx = db
+        .transaction_with_behavior(TransactionBehavior::Immediate)
+        .map_err(|e| ApiError::internal(format!("failed to begin transaction: {e}")))?;
+    let elf_paths = {
+        let mut stmt = tx
+            .prepare("SELECT elf_path FROM artifacts WHERE owner_user_id = ?1")
+            .map_err(|e| ApiError::internal(format!("failed to prepare artifact query: {e}")))?;
+        stmt.query_map(params![user.id], |row| row.get::<_, String>(0))
+            .and_then(Iterator::collect::<Result<Vec<_>, _>>)
+            .map_err(|e| ApiError::internal(format!("failed to query artifacts: {e}")))?
+    };
+    tx.execute("DELETE FROM users WHERE id = ?1", params![user.id])
+        .map_err(|e| ApiError::internal(format!("failed to delete user: {e}")))?;
+    tx.commit()
+        .map_err(|e| ApiError::internal(format!("failed to commit account deletion: {e}")))?;
+    drop(db);
+
+    for rel_path in &elf_paths {
+        let removed = resolve_artifact_path(&state.artifacts_dir, rel_path)
+            .map_err(|error| error.message)
+            .and_then(|full_path| match std::fs::remove_file(full_path) {
+                Err(error) if error.kind() != std::io::ErrorKind::NotFound => {
+                    Err(error.to_string())
+                }
+                _ => Ok(()),
+            });
+        if let Err(error) = removed {
+            warn!(user_id = user.id, rel_path, %error, "failed to delete artifact file");
+        }
+    }
+
+    info!(
+        user_id = user.id,
+        artifacts = elf_paths.len(),
+        "account deleted"
+    );
+    audit(
+        &state,
+        &client,
+        AuditEvent::AccountDelete,
+        Some(user.id),
+        format!("username={} artifacts={}", user.username, elf_paths.len()),
+    );
+
+    let clear_cookie = expired_session_cookie(state.cookie_secure);
+    Ok((StatusCode::NO_CONTENT, [(header::SET_COOKIE, clear_cookie)]).into_response())
+}
+
 async fn list_artifacts(
     State(state): State<AppState>,
     headers: HeaderMap,
+    Query(query): Query<ArtifactListQuery>,
 ) -> Result<Json<Vec<ArtifactSummary>>, ApiError> {
     let user = authenticate(&state, &headers).await?;
-    let db = state.db.lock().await;
-
-    let mut sql = "SELECT a.id, a.owner_user_id, u.username, a.name, a.note, a.target, a.is_public, a.created_at FROM artifacts a JOIN users u ON u.id = a.owner_user_id".to_string();
+    let limit = query
+        .limit
+        .unwrap_or(ARTIFACT_PAGE_DEFAULT)
+        .clamp(1, ARTIFACT_PAGE_MAX);
+    let offset = query.offset.unwrap_or(0);
+    let db = state.db()?;
+
+    let tag = query
+        .tag
+        .as_deref()
+        .map(normalize_artifact_tag)
+        .transpose()?;
+
+    // Tags are restricted to characters without commas, so group_concat
+    // round-trips them.
+    let mut sql = "SELECT a.id, a.owner_user_id, u.username, a.name, a.note, a.target, a.is_public, a.created_at, a.sha256, a.build_meta_json, a.download_count, (SELECT group_concat(tag, ',') FROM (SELECT tag FROM artifact_tags t WHERE t.artifact_id = a.id ORDER BY tag)) FROM artifacts a JOIN users u ON u.id = a.owner_user_id".to_string();
+    let mut conditions = Vec::new();
+    let mut values: Vec<rusqlite::types::Value> = Vec::new();
     if state.auth_mode == AuthMode::Required {
-        sql.push_str(" WHERE a.owner_user_id = ?1 OR a.is_public = 1");
+        values.push(user.id.into());
+        conditions.push(format!(
+            "(a.owner_user_id = ?{} OR a.is_public = 1)",
+            values.len()
+        ));
+    }
+    if let Some(tag) = tag {
+        values.push(tag.into());
+        conditions.push(format!(
+            "a.id IN (SELECT artifact_id FROM artifact_tags WHERE tag = ?{})",
+            values.len()
+        ));
+    }
+    if !conditions.is_empty() {
+        sql.push_str(" WHERE ");
+        sql.push_str(&conditions.join(" AND "));
     }
-    sql.push_str(" ORDER BY a.created_at DESC");
+    sql.push_str(match query.sort {
+        ArtifactSort::Recent => " ORDER BY a.created_at DESC",
+        ArtifactSort::Popular => " ORDER BY a.download_count DESC, a.created_at DESC",
+    });
+    values.push(i64::from(limit).into());
+    values.push(i64::from(offset).into());
+    sql.push_str(&format!(
+        " LIMIT ?{} OFFSET ?{}",
+        values.len() - 1,
+        values.len()
+    ));
 
     let mut stmt = db
         .prepare(&sql)
@@ -512,15 +1483,19 @@ async fn list_artifacts(
             is_public: row.get::<_, i64>(6)? != 0,
             owned_by_me: owner_user_id == user.id,
             created_at: row.get(7)?,
+            sha256: row.get(8)?,
+            build_meta_json: row.get(9)?,
+            download_count: row.get::<_, i64>(10)? as u64,
+            tags: row
+                .get::<_, Option<String>>(11)?
+                .map(|tags| tags.split(',').map(str::to_string).collect())
+                .unwrap_or_default(),
         })
     };
 
-    let rows = if state.auth_mode == AuthMode::Required {
-        stmt.query_map(params![user.id], mapper)
-    } else {
-        stmt.query_map([], mapper)
-    }
-    .map_err(|e| ApiError::internal(format!("failed to query artifacts: {e}")))?;
+    let rows = stmt
+        .query_map(rusqlite::params_from_iter(values), mapper)
+        .map_err(|e| ApiError::internal(format!("failed to query artifacts: {e}")))?;
 
     let mut out = Vec::new();
     for item in rows {
@@ -534,6 +1509,7 @@ async fn list_artifacts(
 
 async fn upload_artifact(
     State(state): State<AppState>,
+    client: ClientIp,
     headers: HeaderMap,
     Json(payload): Json<UploadArtifactRequest>,
 ) -> Result<Json<UploadArtifactResponse>, ApiError> {
@@ -545,6 +1521,17 @@ async fn upload_artifact(
     if payload.target.trim().is_empty() {
         return Err(ApiError::bad_request("artifact target must not be empty"));
     }
+    if !state
+        .supported_targets
+        .iter()
+        .any(|target| target == payload.target.trim())
+    {
+        return Err(ApiError::bad_request(format!(
+            "unsupported artifact target '{}'; supported targets: {}",
+            payload.target.trim(),
+            state.supported_targets.join(", ")
+        )));
+    }
 
     let elf_bytes = base64::engine::general_purpose::STANDARD
         .decode(payload.elf_base64.as_bytes())
@@ -552,38 +1539,61 @@ async fn upload_artifact(
     if elf_bytes.is_empty() {
         return Err(ApiError::bad_request("elf payload must not be empty"));
     }
-
-    let db = state.db.lock().await;
+    if elf_bytes.len() > state.max_artifact_bytes {
+        return Err(ApiError::bad_request(format!(
+            "artifact is {} bytes, above the {} byte limit",
+            elf_bytes.len(),
+            state.max_artifact_bytes
+        )));
+    }
+    validate_elf(&elf_bytes, payload.target.trim())?;
+    if let Some(build_meta_json) = &payload.build_meta_json {
+        validate_build_meta(build_meta_json)?;
+    }
+    let sha256 = sha256_hex(&elf_bytes);
+
+    // The row only becomes visible once the file is on disk: if the write or
+    // the commit fails, the transaction rolls back when dropped.
+    let mut db = state.db()?;
+    let tx = db
+        .transaction()
+        .map_err(|e| ApiError::internal(format!("failed to begin transaction: {e}")))?;
+    check_artifact_quota(&state, &tx, &user)?;
     let now = now_utc();
-    db.execute(
-        "INSERT INTO artifacts (owner_user_id, name, note, target, elf_path, is_public, created_at) VALUES (?1, ?2, ?3, ?4, '', 0, ?5)",
+    tx.execute(
+        "INSERT INTO artifacts (owner_user_id, name, note, target, elf_path, is_public, created_at, sha256, build_meta_json) VALUES (?1, ?2, ?3, ?4, '', 0, ?5, ?6, ?7)",
         params![
             user.id,
             payload.name.trim(),
             payload.note,
             payload.target.trim(),
-            now
+            now,
+            sha256,
+            payload.build_meta_json
         ],
     )
     .map_err(|e| ApiError::internal(format!("failed to create artifact row: {e}")))?;
 
-    let artifact_id = db.last_insert_rowid();
+    let artifact_id = tx.last_insert_rowid();
     let artifact_name = format!("artifact_{artifact_id}.elf");
     let artifact_path = state.artifacts_dir.join(&artifact_name);
 
-    if let Err(error) = std::fs::write(&artifact_path, elf_bytes) {
-        let _ = db.execute("DELETE FROM artifacts WHERE id = ?1", params![artifact_id]);
-        return Err(ApiError::internal(format!(
-            "failed to write artifact file: {error}"
-        )));
-    }
-
-    db.execute(
+    tx.execute(
         "UPDATE artifacts SET elf_path = ?1 WHERE id = ?2",
         params![artifact_name, artifact_id],
     )
     .map_err(|e| ApiError::internal(format!("failed to update artifact path: {e}")))?;
 
+    std::fs::write(&artifact_path, elf_bytes)
+        .map_err(|e| ApiError::internal(format!("failed to write artifact file: {e}")))?;
+
+    if let Err(error) = tx.commit() {
+        let _ = std::fs::remove_file(&artifact_path);
+        return Err(ApiError::internal(format!(
+            "failed to commit artifact row: {error}"
+        )));
+    }
+
     info!(
         artifact_id,
         owner_user_id = user.id,
@@ -592,58 +1602,270 @@ async fn upload_artifact(
         is_public = false,
         "artifact uploaded"
     );
-    Ok(Json(UploadArtifactResponse { artifact_id }))
+    audit(
+        &state,
+        &client,
+        AuditEvent::ArtifactUpload,
+        Some(user.id),
+        format!(
+            "artifact_id={artifact_id} name={} target={}",
+            payload.name.trim(),
+            payload.target.trim()
+        ),
+    );
+    Metrics::increment(&state.metrics.uploads);
+    publish_artifact_change(&state, user.id, ArtifactChangeKind::Uploaded, artifact_id);
+    Ok(Json(UploadArtifactResponse { artifact_id }))
 }
 
-async fn download_artifact(
-    State(state): State<AppState>,
-    headers: HeaderMap,
-    AxumPath(artifact_id): AxumPath<i64>,
-) -> Result<Response, ApiError> {
-    let user = authenticate(&state, &headers).await?;
-    let db = state.db.lock().await;
+/// 403 once `user` stores `max_artifacts_per_user` artifacts. Admins, and
+/// everyone with auth disabled, have no quota.
+fn check_artifact_quota(
+    state: &AppState,
+    db: &Connection,
+    user: &UserInfo,
+) -> Result<(), ApiError> {
+    let quota = state.max_artifacts_per_user;
+    if quota == 0 || state.auth_mode == AuthMode::Disabled {
+        return Ok(());
+    }
+    let count: u32 = db
+        .query_row(
+            "SELECT COUNT(*) FROM artifacts WHERE owner_user_id = ?1",
+            params![user.id],
+            |row| row.get(0),
+        )
+        .map_err(|e| ApiError::internal(format!("failed to count artifacts: {e}")))?;
+    if count < quota || user_is_admin(state, db, user)? {
+        return Ok(());
+    }
+    Err(ApiError::forbidden(format!(
+        "artifact quota reached: you have {count} of {quota} allowed artifacts; delete one to upload another"
+    )))
+}
+
+/// Build metadata must be a JSON object of at most `BUILD_META_MAX_BYTES`;
+/// its fields are up to the uploader.
+fn validate_build_meta(build_meta_json: &str) -> Result<(), ApiError> {
+    if build_meta_json.len() > BUILD_META_MAX_BYTES {
+        return Err(ApiError::bad_request(format!(
+            "build metadata is {} bytes, above the {BUILD_META_MAX_BYTES} byte limit",
+            build_meta_json.len()
+        )));
+    }
+    match serde_json::from_str::<serde_json::Value>(build_meta_json) {
+        Ok(serde_json::Value::Object(_)) => Ok(()),
+        Ok(_) => Err(ApiError::bad_request(
+            "build metadata must be a JSON object",
+        )),
+        Err(e) => Err(ApiError::bad_request(format!(
+            "invalid build metadata json: {e}"
+        ))),
+    }
+}
+
+/// Checks that `bytes` starts with a little-endian RISC-V ELF header whose
+/// class matches the pointer width of `expected_target` (`riscv32*`/`riscv64*`).
+fn validate_elf(bytes: &[u8], expected_target: &str) -> Result<(), ApiError> {
+    let (expected_class, header_len) = if expected_target.starts_with("riscv32") {
+        (ELFCLASS32, ELF32_HEADER_LEN)
+    } else if expected_target.starts_with("riscv64") {
+        (ELFCLASS64, ELF64_HEADER_LEN)
+    } else {
+        return Err(ApiError::bad_request(format!(
+            "cannot validate elf for non-RISC-V target '{expected_target}'"
+        )));
+    };
+
+    if bytes.len() < ELF_MAGIC.len() || bytes[..ELF_MAGIC.len()] != ELF_MAGIC {
+        return Err(ApiError::bad_request("artifact is not an ELF file"));
+    }
+    if bytes.len() < header_len {
+        return Err(ApiError::bad_request("elf header is truncated"));
+    }
+    if bytes[4] != expected_class {
+        return Err(ApiError::bad_request(format!(
+            "elf class does not match target '{expected_target}'"
+        )));
+    }
+    if bytes[5] != ELFDATA2LSB {
+        return Err(ApiError::bad_request("elf must be little-endian"));
+    }
+    let machine = u16::from_le_bytes([bytes[18], bytes[19]]);
+    if machine != EM_RISCV {
+        return Err(ApiError::bad_request(format!(
+            "elf machine {machine} is not RISC-V ({EM_RISCV})"
+        )));
+    }
+    Ok(())
+}
+
+/// Hex-encoded sha256 of `bytes`.
+fn sha256_hex(bytes: &[u8]) -> String {
+    hex::encode(Sha256::digest(bytes))
+}
+
+/// Full path of the artifact file stored as `rel` (the `elf_path` column).
+///
+/// Uploads only ever store a bare file name, so anything else (absolute
+/// paths, `..`, nested directories) means a tampered row and is refused
+/// rather than read or deleted outside `root`.
+fn resolve_artifact_path(root: &Path, rel: &str) -> Result<PathBuf, ApiError> {
+    let mut components = Path::new(rel).components();
+    let (Some(Component::Normal(name)), None) = (components.next(), components.next()) else {
+        return Err(ApiError::internal("invalid artifact file path"));
+    };
+    let full_path = root.join(name);
+    if full_path.parent() != Some(root) {
+        return Err(ApiError::internal("artifact path escaped storage root"));
+    }
+    Ok(full_path)
+}
+
+/// Storage path and checksum of an artifact `user` may read (own or public).
+struct ReadableArtifact {
+    path: PathBuf,
+    sha256: Option<String>,
+}
 
-    let row: Option<(i64, String, i64)> = db
+fn readable_artifact(
+    state: &AppState,
+    user: &UserInfo,
+    artifact_id: i64,
+) -> Result<ReadableArtifact, ApiError> {
+    let db = state.db()?;
+    let row: Option<(i64, String, i64, Option<String>)> = db
         .query_row(
-            "SELECT owner_user_id, elf_path, is_public FROM artifacts WHERE id = ?1",
+            "SELECT owner_user_id, elf_path, is_public, sha256 FROM artifacts WHERE id = ?1",
             params![artifact_id],
-            |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?)),
+            |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?, r.get(3)?)),
         )
         .optional()
         .map_err(|e| ApiError::internal(format!("failed to query artifact: {e}")))?;
 
-    let Some((owner_user_id, rel_path, is_public)) = row else {
+    let Some((owner_user_id, rel_path, is_public, sha256)) = row else {
         return Err(ApiError::not_found("artifact not found"));
     };
 
+    // The caller is signed in, just not allowed to see it: 403, so clients
+    // do not treat it as an expired session.
     if state.auth_mode == AuthMode::Required && owner_user_id != user.id && is_public == 0 {
-        return Err(ApiError::unauthorized(
-            "artifact is not owned by current user",
-        ));
+        return Err(ApiError::forbidden("artifact is not owned by current user"));
     }
 
-    let full_path = state.artifacts_dir.join(rel_path);
-    let bytes = std::fs::read(&full_path)
+    Ok(ReadableArtifact {
+        path: resolve_artifact_path(&state.artifacts_dir, &rel_path)?,
+        sha256,
+    })
+}
+
+/// The artifact's stored checksum; artifacts uploaded before checksums were
+/// recorded are hashed from disk once and backfilled.
+async fn artifact_sha256(
+    state: &AppState,
+    artifact_id: i64,
+    artifact: &ReadableArtifact,
+) -> Result<String, ApiError> {
+    if let Some(sha256) = &artifact.sha256 {
+        return Ok(sha256.clone());
+    }
+    let bytes = tokio::fs::read(&artifact.path)
+        .await
         .map_err(|e| ApiError::internal(format!("failed to read artifact file: {e}")))?;
+    let sha256 = sha256_hex(&bytes);
+    state
+        .db()?
+        .execute(
+            "UPDATE artifacts SET sha256 = ?1 WHERE id = ?2",
+            params![sha256, artifact_id],
+        )
+        .map_err(|e| ApiError::internal(format!("failed to store artifact checksum: {e}")))?;
+    Ok(sha256)
+}
+
+/// Whether the request's `If-None-Match` names the artifact with checksum
+/// `sha256` (quoted or bare, weak or strong, or `*`).
+fn if_none_match_hits(headers: &HeaderMap, sha256: &str) -> bool {
+    headers
+        .get_all(header::IF_NONE_MATCH)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(|tag| {
+            let tag = tag.trim();
+            tag.strip_prefix("W/").unwrap_or(tag).trim_matches('"')
+        })
+        .any(|tag| tag == "*" || tag.eq_ignore_ascii_case(sha256))
+}
+
+async fn artifact_checksum(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    AxumPath(artifact_id): AxumPath<i64>,
+) -> Result<Json<ArtifactChecksumResponse>, ApiError> {
+    let user = authenticate(&state, &headers).await?;
+    let artifact = readable_artifact(&state, &user, artifact_id)?;
+    let sha256 = artifact_sha256(&state, artifact_id, &artifact).await?;
+    Ok(Json(ArtifactChecksumResponse {
+        artifact_id,
+        sha256,
+    }))
+}
+
+async fn download_artifact(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    AxumPath(artifact_id): AxumPath<i64>,
+) -> Result<Response, ApiError> {
+    let user = authenticate(&state, &headers).await?;
+    let artifact = readable_artifact(&state, &user, artifact_id)?;
+    let sha256 = artifact_sha256(&state, artifact_id, &artifact).await?;
+    let etag = HeaderValue::try_from(format!("\"{sha256}\""))
+        .map_err(|e| ApiError::internal(format!("invalid artifact checksum: {e}")))?;
+    if if_none_match_hits(&headers, &sha256) {
+        return Ok((StatusCode::NOT_MODIFIED, [(header::ETAG, etag)]).into_response());
+    }
 
+    let file = tokio::fs::File::open(&artifact.path)
+        .await
+        .map_err(|e| ApiError::internal(format!("failed to open artifact file: {e}")))?;
+    let len = file
+        .metadata()
+        .await
+        .map_err(|e| ApiError::internal(format!("failed to stat artifact file: {e}")))?
+        .len();
+
+    state
+        .db()?
+        .execute(
+            "UPDATE artifacts SET download_count = download_count + 1 WHERE id = ?1",
+            params![artifact_id],
+        )
+        .map_err(|e| ApiError::internal(format!("failed to count artifact download: {e}")))?;
+    Metrics::increment(&state.metrics.downloads);
     Ok((
         StatusCode::OK,
-        [(
-            header::CONTENT_TYPE,
-            HeaderValue::from_static("application/octet-stream"),
-        )],
-        bytes,
+        [
+            (
+                header::CONTENT_TYPE,
+                HeaderValue::from_static("application/octet-stream"),
+            ),
+            (header::CONTENT_LENGTH, HeaderValue::from(len)),
+            (header::ETAG, etag),
+        ],
+        Body::from_stream(ReaderStream::new(file)),
     )
         .into_response())
 }
 
 async fn delete_artifact(
     State(state): State<AppState>,
+    client: ClientIp,
     headers: HeaderMap,
     AxumPath(artifact_id): AxumPath<i64>,
 ) -> Result<Response, ApiError> {
     let user = authenticate(&state, &headers).await?;
-    let db = state.db.lock().await;
+    let db = state.db()?;
 
     let row: Option<(i64, String)> = db
         .query_row(
@@ -658,22 +1880,16 @@ async fn delete_artifact(
         return Err(ApiError::not_found("artifact not found"));
     };
 
-    if state.auth_mode == AuthMode::Required && owner_user_id != user.id {
+    if state.auth_mode == AuthMode::Required
+        && owner_user_id != user.id
+        && !user_is_admin(&state, &db, &user)?
+    {
         return Err(ApiError::unauthorized(
             "artifact is not owned by current user",
         ));
     }
 
-    let relative = Path::new(&rel_path);
-    if relative.is_absolute() || relative.components().count() != 1 {
-        return Err(ApiError::internal("invalid artifact file path"));
-    }
-
-    let full_path = state.artifacts_dir.join(relative);
-    if !full_path.starts_with(&state.artifacts_dir) {
-        return Err(ApiError::internal("artifact path escaped storage root"));
-    }
-
+    let full_path = resolve_artifact_path(&state.artifacts_dir, &rel_path)?;
     match std::fs::remove_file(&full_path) {
         Ok(()) => {}
         Err(error) if error.kind() == std::io::ErrorKind::NotFound => {}
@@ -688,18 +1904,45 @@ async fn delete_artifact(
         .map_err(|e| ApiError::internal(format!("failed to delete artifact row: {e}")))?;
 
     info!(artifact_id, owner_user_id = user.id, "artifact deleted");
+    publish_artifact_change(
+        &state,
+        owner_user_id,
+        ArtifactChangeKind::Deleted,
+        artifact_id,
+    );
+    audit(
+        &state,
+        &client,
+        AuditEvent::ArtifactDelete,
+        Some(user.id),
+        if owner_user_id == user.id {
+            format!("artifact_id={artifact_id}")
+        } else {
+            format!("artifact_id={artifact_id} owner_user_id={owner_user_id}")
+        },
+    );
     Ok(StatusCode::NO_CONTENT.into_response())
 }
 
-async fn update_artifact_visibility(
+async fn update_artifact(
     State(state): State<AppState>,
+    client: ClientIp,
     headers: HeaderMap,
     AxumPath(artifact_id): AxumPath<i64>,
-    Json(payload): Json<UpdateArtifactVisibilityRequest>,
+    Json(payload): Json<UpdateArtifactRequest>,
 ) -> Result<Response, ApiError> {
     let user = authenticate(&state, &headers).await?;
-    let db = state.db.lock().await;
 
+    let name = payload.name.as_deref().map(str::trim);
+    if name.is_some_and(str::is_empty) {
+        return Err(ApiError::bad_request("artifact name must not be empty"));
+    }
+    let note = payload
+        .note
+        .as_deref()
+        .map(|note| Some(note).filter(|note| !note.trim().is_empty()));
+
+    let db = state.db()?;
     let owner_user_id: Option<i64> = db
         .query_row(
             "SELECT owner_user_id FROM artifacts WHERE id = ?1",
@@ -719,6 +1962,53 @@ async fn update_artifact_visibility(
         ));
     }
 
+    if let Some(name) = name {
+        db.execute(
+            "UPDATE artifacts SET name = ?1 WHERE id = ?2",
+            params![name, artifact_id],
+        )
+        .map_err(|e| ApiError::internal(format!("failed to update artifact name: {e}")))?;
+    }
+    if let Some(note) = note {
+        db.execute(
+            "UPDATE artifacts SET note = ?1 WHERE id = ?2",
+            params![note, artifact_id],
+        )
+        .map_err(|e| ApiError::internal(format!("failed to update artifact note: {e}")))?;
+    }
+
+    info!(
+        artifact_id,
+        owner_user_id = user.id,
+        name_changed = name.is_some(),
+        note_changed = note.is_some(),
+        "artifact updated"
+    );
+    audit(
+        &state,
+        &client,
+        AuditEvent::ArtifactUpdate,
+        Some(user.id),
+        match name {
+            Some(name) => format!("artifact_id={artifact_id} name={name}"),
+            None => format!("artifact_id={artifact_id}"),
+        },
+    );
+
+    Ok(StatusCode::NO_CONTENT.into_response())
+}
+
+async fn update_artifact_visibility(
+    State(state): State<AppState>,
+    client: ClientIp,
+    headers: HeaderMap,
+    AxumPath(artifact_id): AxumPath<i64>,
+    Json(payload): Json<UpdateArtifactVisibilityRequest>,
+) -> Result<Response, ApiError> {
+    let user = authenticate(&state, &headers).await?;
+    let db = state.db()?;
+    let owner_user_id = owned_artifact(&state, &db, &user, artifact_id)?;
+
     let is_public_i64 = if payload.is_public { 1 } else { 0 };
     db.execute(
         "UPDATE artifacts SET is_public = ?1 WHERE id = ?2",
@@ -732,128 +2022,700 @@ async fn update_artifact_visibility(
         is_public = payload.is_public,
         "artifact visibility updated"
     );
+    audit(
+        &state,
+        &client,
+        AuditEvent::ArtifactVisibility,
+        Some(user.id),
+        format!("artifact_id={artifact_id} is_public={}", payload.is_public),
+    );
+    publish_artifact_change(
+        &state,
+        owner_user_id,
+        ArtifactChangeKind::VisibilityChanged,
+        artifact_id,
+    );
 
     Ok(StatusCode::NO_CONTENT.into_response())
 }
 
-async fn authenticate(state: &AppState, headers: &HeaderMap) -> Result<UserInfo, ApiError> {
-    if state.auth_mode == AuthMode::Disabled {
-        return Ok(UserInfo {
-            id: LOCAL_USER_ID,
-            username: LOCAL_USERNAME.to_string(),
-        });
+/// Lowercases and trims `tag`, which must then be 1 to `ARTIFACT_TAG_MAX_LEN`
+/// ASCII letters, digits, `-` or `_`.
+fn normalize_artifact_tag(tag: &str) -> Result<String, ApiError> {
+    let tag = tag.trim().to_ascii_lowercase();
+    if tag.is_empty() || tag.len() > ARTIFACT_TAG_MAX_LEN {
+        return Err(ApiError::bad_request(format!(
+            "tag must be 1 to {ARTIFACT_TAG_MAX_LEN} characters long"
+        )));
     }
-
-    let token = if let Some(token) = bearer_token_opt(headers) {
-        token
-    } else if let Some(token) = session_cookie_token(headers) {
-        token
-    } else {
-        debug!("authentication failed: no bearer token or session cookie");
-        return Err(ApiError::unauthorized(
-            "missing Authorization bearer token or session cookie",
+    if !tag
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_'))
+    {
+        return Err(ApiError::bad_request(
+            "tag may only contain letters, digits, '-' and '_'",
         ));
-    };
-
-    let db = state.db.lock().await;
-    let user: Option<UserInfo> = db
-        .query_row(
-            "SELECT u.id, u.username FROM sessions s JOIN users u ON s.user_id = u.id WHERE s.token = ?1",
-            params![token],
-            |row| {
-                Ok(UserInfo {
-                    id: row.get(0)?,
-                    username: row.get(1)?,
-                })
-            },
-        )
-        .optional()
-        .map_err(|e| ApiError::internal(format!("failed to lookup session: {e}")))?;
-
-    if user.is_none() {
-        debug!("authentication failed: invalid or expired session");
     }
-    user.ok_or_else(|| ApiError::unauthorized("invalid or expired session"))
+    Ok(tag)
 }
 
-async fn create_session_for_credentials(
+/// Looks up the owner of `artifact_id` and checks that `user` may change it.
+fn owned_artifact(
     state: &AppState,
-    username: &str,
-    password: &str,
-) -> Result<(UserInfo, String), ApiError> {
-    if username.is_empty() {
-        return Err(ApiError::bad_request("username must not be empty"));
-    }
-
-    let db = state.db.lock().await;
-    let user_row: Option<(i64, String)> = db
+    db: &Connection,
+    user: &UserInfo,
+    artifact_id: i64,
+) -> Result<i64, ApiError> {
+    let owner_user_id: Option<i64> = db
         .query_row(
-            "SELECT id, password_hash FROM users WHERE username = ?1",
-            params![username],
-            |row| Ok((row.get(0)?, row.get(1)?)),
+            "SELECT owner_user_id FROM artifacts WHERE id = ?1",
+            params![artifact_id],
+            |r| r.get(0),
         )
         .optional()
-        .map_err(|e| ApiError::internal(format!("failed to query user: {e}")))?;
+        .map_err(|e| ApiError::internal(format!("failed to query artifact: {e}")))?;
 
-    let Some((user_id, password_hash)) = user_row else {
-        return Err(ApiError::unauthorized("invalid credentials"));
+    let Some(owner_user_id) = owner_user_id else {
+        return Err(ApiError::not_found("artifact not found"));
     };
 
-    verify_password(password, &password_hash)?;
+    if state.auth_mode == AuthMode::Required && owner_user_id != user.id {
+        return Err(ApiError::unauthorized(
+            "artifact is not owned by current user",
+        ));
+    }
+    Ok(owner_user_id)
+}
+
+/// `POST /api/v1/artifacts/{id}/tags`: adds a tag; adding one the artifact
+/// already has is a no-op.
+async fn add_artifact_tag(
+    State(state): State<AppState>,
+    client: ClientIp,
+    headers: HeaderMap,
+    AxumPath(artifact_id): AxumPath<i64>,
+    Json(payload): Json<ArtifactTagRequest>,
+) -> Result<Response, ApiError> {
+    let user = authenticate(&state, &headers).await?;
+    let tag = normalize_artifact_tag(&payload.tag)?;
+    let db = state.db()?;
+    let owner_user_id = owned_artifact(&state, &db, &user, artifact_id)?;
+
+    let existing: Vec<String> = {
+        let mut stmt = db
+            .prepare("SELECT tag FROM artifact_tags WHERE artifact_id = ?1")
+            .map_err(|e| ApiError::internal(format!("failed to prepare tag query: {e}")))?;
+        stmt.query_map(params![artifact_id], |row| row.get(0))
+            .and_then(Iterator::collect::<Result<Vec<_>, _>>)
+            .map_err(|e| ApiError::internal(format!("failed to query tags: {e}")))?
+    };
+    if existing.contains(&tag) {
+        return Ok(StatusCode::NO_CONTENT.into_response());
+    }
+    if existing.len() >= ARTIFACT_TAGS_MAX {
+        return Err(ApiError::bad_request(format!(
+            "an artifact can have at most {ARTIFACT_TAGS_MAX} tags"
+        )));
+    }
 
-    let token = generate_token();
     db.execute(
-        "INSERT INTO sessions (token, user_id, created_at) VALUES (?1, ?2, ?3)",
-        params![token, user_id, now_utc()],
+        "INSERT OR IGNORE INTO artifact_tags (artifact_id, tag) VALUES (?1, ?2)",
+        params![artifact_id, tag],
     )
-    .map_err(|e| ApiError::internal(format!("failed to create session: {e}")))?;
+    .map_err(|e| ApiError::internal(format!("failed to add tag: {e}")))?;
 
-    Ok((
-        UserInfo {
-            id: user_id,
-            username: username.to_string(),
-        },
-        token,
-    ))
+    info!(
+        artifact_id,
+        owner_user_id = user.id,
+        tag,
+        "artifact tag added"
+    );
+    audit(
+        &state,
+        &client,
+        AuditEvent::ArtifactUpdate,
+        Some(user.id),
+        format!("artifact_id={artifact_id} tag_added={tag}"),
+    );
+    publish_artifact_change(
+        &state,
+        owner_user_id,
+        ArtifactChangeKind::TagsChanged,
+        artifact_id,
+    );
+
+    Ok(StatusCode::NO_CONTENT.into_response())
 }
 
-async fn create_user_with_password(
-    state: &AppState,
-    username: &str,
-    password: &str,
-) -> Result<UserInfo, ApiError> {
-    if username.is_empty() {
-        return Err(ApiError::bad_request("username must not be empty"));
-    }
-    if password.len() < 8 {
-        return Err(ApiError::bad_request("password must be at least 8 chars"));
+/// `DELETE /api/v1/artifacts/{id}/tags`: removes a tag; removing one the
+/// artifact does not have is a no-op.
+async fn remove_artifact_tag(
+    State(state): State<AppState>,
+    client: ClientIp,
+    headers: HeaderMap,
+    AxumPath(artifact_id): AxumPath<i64>,
+    Json(payload): Json<ArtifactTagRequest>,
+) -> Result<Response, ApiError> {
+    let user = authenticate(&state, &headers).await?;
+    let tag = normalize_artifact_tag(&payload.tag)?;
+    let db = state.db()?;
+    let owner_user_id = owned_artifact(&state, &db, &user, artifact_id)?;
+
+    let removed = db
+        .execute(
+            "DELETE FROM artifact_tags WHERE artifact_id = ?1 AND tag = ?2",
+            params![artifact_id, tag],
+        )
+        .map_err(|e| ApiError::internal(format!("failed to remove tag: {e}")))?;
+    if removed == 0 {
+        return Ok(StatusCode::NO_CONTENT.into_response());
     }
 
-    let hash = hash_password(password)?;
-    let db = state.db.lock().await;
-    let inserted = db.execute(
-        "INSERT INTO users (username, password_hash, created_at) VALUES (?1, ?2, ?3)",
-        params![username, hash, now_utc()],
+    info!(
+        artifact_id,
+        owner_user_id = user.id,
+        tag,
+        "artifact tag removed"
+    );
+    audit(
+        &state,
+        &client,
+        AuditEvent::ArtifactUpdate,
+        Some(user.id),
+        format!("artifact_id={artifact_id} tag_removed={tag}"),
+    );
+    publish_artifact_change(
+        &state,
+        owner_user_id,
+        ArtifactChangeKind::TagsChanged,
+        artifact_id,
     );
 
-    if let Err(err) = inserted {
-        if err.to_string().contains("UNIQUE") {
-            return Err(ApiError::bad_request("username already exists"));
-        }
-        return Err(ApiError::internal(format!("failed to create user: {err}")));
-    }
+    Ok(StatusCode::NO_CONTENT.into_response())
+}
 
-    Ok(UserInfo {
-        id: db.last_insert_rowid(),
-        username: username.to_string(),
-    })
+/// `GET /api/v1/ws`: after authenticating the upgrade request, push an
+/// `ArtifactChangeEvent` text message whenever one of the caller's artifacts
+/// is uploaded, deleted, or changes visibility or tags.
+async fn artifact_events_ws(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    ws: WebSocketUpgrade,
+) -> Result<Response, ApiError> {
+    let user = authenticate(&state, &headers).await?;
+    // Subscribe before upgrading so nothing published after the handshake is missed.
+    let events = state.artifact_events.subscribe();
+    Ok(ws.on_upgrade(move |socket| forward_artifact_changes(socket, events, user.id)))
 }
 
-fn sanitize_next(next: &str) -> &str {
-    if next.starts_with('/') && !next.starts_with("//") {
-        next
-    } else {
-        "/"
+async fn forward_artifact_changes(
+    mut socket: WebSocket,
+    mut events: broadcast::Receiver<ArtifactChange>,
+    user_id: i64,
+) {
+    loop {
+        tokio::select! {
+            change = events.recv() => match change {
+                Ok(change) if change.owner_user_id == user_id => {
+                    let text = match serde_json::to_string(&change.event) {
+                        Ok(text) => text,
+                        Err(error) => {
+                            warn!(%error, "failed to encode artifact change");
+                            continue;
+                        }
+                    };
+                    if socket.send(Message::Text(text.into())).await.is_err() {
+                        break;
+                    }
+                }
+                Ok(_) => {}
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    debug!(user_id, skipped, "artifact event subscriber lagged");
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            },
+            incoming = socket.recv() => match incoming {
+                // Pings are answered by axum; anything else from the client is ignored.
+                Some(Ok(Message::Close(_)) | Err(_)) | None => break,
+                Some(Ok(_)) => {}
+            },
+        }
+    }
+}
+
+async fn list_race_records(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<RaceRecordSummary>>, ApiError> {
+    authenticate(&state, &headers).await?;
+    let db = state.db()?;
+
+    let mut stmt = db
+        .prepare(
+            "SELECT r.id, r.owner_user_id, u.username, r.track_id, r.result_json, r.replay_json IS NOT NULL, r.created_at FROM race_records r JOIN users u ON u.id = r.owner_user_id ORDER BY r.created_at DESC",
+        )
+        .map_err(|e| ApiError::internal(format!("failed to prepare race record query: {e}")))?;
+
+    let rows = stmt
+        .query_map([], |row| {
+            Ok(RaceRecordSummary {
+                id: row.get(0)?,
+                owner_user_id: row.get(1)?,
+                owner_username: row.get(2)?,
+                track_id: row.get(3)?,
+                result_json: row.get(4)?,
+                has_replay: row.get::<_, i64>(5)? != 0,
+                created_at: row.get(6)?,
+            })
+        })
+        .map_err(|e| ApiError::internal(format!("failed to query race records: {e}")))?;
+
+    let mut out = Vec::new();
+    for item in rows {
+        out.push(
+            item.map_err(|e| ApiError::internal(format!("failed to read race record row: {e}")))?,
+        );
+    }
+
+    Ok(Json(out))
+}
+
+/// Store a race result for the caller. `result_json` is kept verbatim but must
+/// parse as a JSON object so the leaderboard can read it.
+async fn publish_race_record(
+    State(state): State<AppState>,
+    client: ClientIp,
+    headers: HeaderMap,
+    Json(payload): Json<PublishRaceRecordRequest>,
+) -> Result<Json<PublishRaceRecordResponse>, ApiError> {
+    let user = authenticate(&state, &headers).await?;
+
+    let track_id = payload.track_id.trim();
+    if track_id.is_empty() {
+        return Err(ApiError::bad_request("track_id must not be empty"));
+    }
+    let is_object = serde_json::from_str::<serde_json::Value>(&payload.result_json)
+        .is_ok_and(|value| value.is_object());
+    if !is_object {
+        return Err(ApiError::bad_request("result_json must be a JSON object"));
+    }
+
+    let db = state.db()?;
+    db.execute(
+        "INSERT INTO race_records (owner_user_id, track_id, result_json, created_at) VALUES (?1, ?2, ?3, ?4)",
+        params![user.id, track_id, payload.result_json, now_utc()],
+    )
+    .map_err(|e| ApiError::internal(format!("failed to create race record: {e}")))?;
+    let record_id = db.last_insert_rowid();
+
+    info!(
+        record_id,
+        owner_user_id = user.id,
+        track_id,
+        "race record published"
+    );
+    audit(
+        &state,
+        &client,
+        AuditEvent::RacePublish,
+        Some(user.id),
+        format!("record_id={record_id} track_id={track_id}"),
+    );
+    Ok(Json(PublishRaceRecordResponse { record_id }))
+}
+
+/// Race times on one track, fastest first. Times come from the numeric
+/// `time_ms` field of each record's `result_json`; records without one are
+/// skipped. Equal times keep submission order.
+async fn leaderboard(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<LeaderboardQuery>,
+) -> Result<Json<Vec<LeaderboardEntry>>, ApiError> {
+    authenticate(&state, &headers).await?;
+    let Some(track_id) = query.track_id.filter(|id| !id.is_empty()) else {
+        return Err(ApiError::bad_request(
+            "track_id query parameter is required",
+        ));
+    };
+    let db = state.db()?;
+
+    let mut stmt = db
+        .prepare(
+            "SELECT r.owner_user_id, u.username, r.result_json FROM race_records r JOIN users u ON u.id = r.owner_user_id WHERE r.track_id = ?1 ORDER BY r.id",
+        )
+        .map_err(|e| ApiError::internal(format!("failed to prepare leaderboard query: {e}")))?;
+    let rows = stmt
+        .query_map(params![track_id], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+            ))
+        })
+        .map_err(|e| ApiError::internal(format!("failed to query leaderboard: {e}")))?;
+
+    let mut times = Vec::new();
+    for item in rows {
+        let (user_id, username, result_json) =
+            item.map_err(|e| ApiError::internal(format!("failed to read race record row: {e}")))?;
+        if let Some(time_ms) = result_time_ms(&result_json) {
+            times.push((time_ms, user_id, username));
+        }
+    }
+    // Stable sort: records with equal times stay in submission order.
+    times.sort_by_key(|(time_ms, _, _)| *time_ms);
+
+    Ok(Json(
+        times
+            .into_iter()
+            .enumerate()
+            .map(|(index, (time_ms, user_id, username))| LeaderboardEntry {
+                rank: index as u32 + 1,
+                user_id,
+                username,
+                time_ms,
+            })
+            .collect(),
+    ))
+}
+
+fn result_time_ms(result_json: &str) -> Option<u64> {
+    serde_json::from_str::<serde_json::Value>(result_json)
+        .ok()?
+        .get("time_ms")?
+        .as_u64()
+}
+
+async fn download_race_replay(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    AxumPath(record_id): AxumPath<i64>,
+) -> Result<Response, ApiError> {
+    authenticate(&state, &headers).await?;
+    let db = state.db()?;
+
+    let replay_json: Option<Option<String>> = db
+        .query_row(
+            "SELECT replay_json FROM race_records WHERE id = ?1",
+            params![record_id],
+            |r| r.get(0),
+        )
+        .optional()
+        .map_err(|e| ApiError::internal(format!("failed to query race record: {e}")))?;
+
+    let Some(replay_json) = replay_json else {
+        return Err(ApiError::not_found("race record not found"));
+    };
+    let Some(replay_json) = replay_json else {
+        return Err(ApiError::not_found("race record has no replay"));
+    };
+
+    Ok((
+        StatusCode::OK,
+        [(
+            header::CONTENT_TYPE,
+            HeaderValue::from_static("application/json"),
+        )],
+        replay_json,
+    )
+        .into_response())
+}
+
+/// Admins are accounts flagged `is_admin` or named in `admin_usernames`.
+fn user_is_admin(state: &AppState, db: &Connection, user: &UserInfo) -> Result<bool, ApiError> {
+    if state.admin_usernames.contains(&user.username) {
+        return Ok(true);
+    }
+    db.query_row(
+        "SELECT is_admin FROM users WHERE id = ?1",
+        params![user.id],
+        |row| row.get::<_, bool>(0),
+    )
+    .optional()
+    .map(|flag| flag.unwrap_or(false))
+    .map_err(|e| ApiError::internal(format!("failed to query admin flag: {e}")))
+}
+
+/// 403 unless `user` is an admin; with auth disabled everyone is.
+fn require_admin(state: &AppState, user: &UserInfo) -> Result<(), ApiError> {
+    if state.auth_mode == AuthMode::Disabled || user_is_admin(state, &*state.db()?, user)? {
+        return Ok(());
+    }
+    Err(ApiError::forbidden("admin access required"))
+}
+
+/// Every account except the built-in local user, oldest first.
+async fn list_users(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<AdminUserEntry>>, ApiError> {
+    let user = authenticate(&state, &headers).await?;
+    require_admin(&state, &user)?;
+
+    let db = state.db()?;
+    let mut stmt = db
+        .prepare("SELECT id, username, created_at, is_admin FROM users WHERE id != ?1 ORDER BY id")
+        .map_err(|e| ApiError::internal(format!("failed to prepare user query: {e}")))?;
+    let rows = stmt
+        .query_map(params![LOCAL_USER_ID], |row| {
+            Ok(AdminUserEntry {
+                id: row.get(0)?,
+                username: row.get(1)?,
+                created_at: row.get(2)?,
+                is_admin: row.get(3)?,
+            })
+        })
+        .map_err(|e| ApiError::internal(format!("failed to query users: {e}")))?;
+
+    let mut users = Vec::new();
+    for row in rows {
+        let mut entry =
+            row.map_err(|e| ApiError::internal(format!("failed to decode user row: {e}")))?;
+        entry.is_admin |= state.admin_usernames.contains(&entry.username);
+        users.push(entry);
+    }
+    Ok(Json(users))
+}
+
+async fn list_audit_log(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<AuditQuery>,
+) -> Result<Json<Vec<AuditLogEntry>>, ApiError> {
+    let user = authenticate(&state, &headers).await?;
+    require_admin(&state, &user)?;
+
+    let limit = query
+        .limit
+        .unwrap_or(AUDIT_PAGE_DEFAULT)
+        .clamp(1, AUDIT_PAGE_MAX);
+    let offset = query.offset.unwrap_or(0);
+
+    let db = state.db()?;
+    let mut stmt = db
+        .prepare(
+            "SELECT l.id, l.event, l.user_id, u.username, l.ip, l.detail, l.created_at FROM audit_log l LEFT JOIN users u ON u.id = l.user_id WHERE (?1 IS NULL OR l.user_id = ?1) AND (?2 IS NULL OR l.event = ?2) ORDER BY l.id DESC LIMIT ?3 OFFSET ?4",
+        )
+        .map_err(|e| ApiError::internal(format!("failed to prepare audit query: {e}")))?;
+
+    let rows = stmt
+        .query_map(params![query.user_id, query.event, limit, offset], |row| {
+            Ok(AuditLogEntry {
+                id: row.get(0)?,
+                event: row.get(1)?,
+                user_id: row.get(2)?,
+                username: row.get(3)?,
+                ip: row.get(4)?,
+                detail: row.get(5)?,
+                created_at: row.get(6)?,
+            })
+        })
+        .map_err(|e| ApiError::internal(format!("failed to query audit log: {e}")))?;
+
+    let mut out = Vec::new();
+    for item in rows {
+        out.push(
+            item.map_err(|e| ApiError::internal(format!("failed to read audit log row: {e}")))?,
+        );
+    }
+
+    Ok(Json(out))
+}
+
+async fn authenticate(state: &AppState, headers: &HeaderMap) -> Result<UserInfo, ApiError> {
+    if state.auth_mode == AuthMode::Disabled {
+        return Ok(UserInfo {
+            id: LOCAL_USER_ID,
+            username: LOCAL_USERNAME.to_string(),
+        });
+    }
+
+    let token = if let Some(token) = bearer_token_opt(headers) {
+        token
+    } else if let Some(token) = session_cookie_token(headers) {
+        token
+    } else {
+        debug!("authentication failed: no bearer token or session cookie");
+        return Err(ApiError::unauthorized(
+            "missing Authorization bearer token or session cookie",
+        ));
+    };
+
+    let db = state.db()?;
+    let session: Option<(UserInfo, String)> = db
+        .query_row(
+            "SELECT u.id, u.username, s.created_at FROM sessions s JOIN users u ON s.user_id = u.id WHERE s.token = ?1",
+            params![token],
+            |row| {
+                Ok((
+                    UserInfo {
+                        id: row.get(0)?,
+                        username: row.get(1)?,
+                    },
+                    row.get(2)?,
+                ))
+            },
+        )
+        .optional()
+        .map_err(|e| ApiError::internal(format!("failed to lookup session: {e}")))?;
+
+    let user = match session {
+        Some((user, created_at)) if session_is_live(&created_at, state.session_ttl) => Some(user),
+        Some(_) => {
+            db.execute("DELETE FROM sessions WHERE token = ?1", params![token])
+                .map_err(|e| ApiError::internal(format!("failed to remove session: {e}")))?;
+            None
+        }
+        None => None,
+    };
+    if user.is_none() {
+        debug!("authentication failed: invalid or expired session");
+    }
+    user.ok_or_else(|| ApiError::unauthorized("invalid or expired session"))
+}
+
+/// Whether a session created at `created_at` (RFC 3339) is still within `ttl`.
+fn session_is_live(created_at: &str, ttl: Duration) -> bool {
+    DateTime::parse_from_rfc3339(created_at)
+        .is_ok_and(|created_at| Utc::now() < session_expiry(created_at.to_utc(), ttl))
+}
+
+async fn create_session_for_credentials(
+    state: &AppState,
+    username: &str,
+    password: &str,
+) -> Result<(UserInfo, String), ApiError> {
+    if username.is_empty() {
+        return Err(ApiError::bad_request("username must not be empty"));
+    }
+    // Lookups ignore case, so the lockout must too or each spelling gets its own budget.
+    let limiter_key = username.to_lowercase();
+    state.login_limiter.check(&limiter_key)?;
+
+    let db = state.db()?;
+    // New usernames are stored lowercase; older accounts may still have capitals,
+    // so an exact match wins over the lowercased one.
+    let user_row: Option<(i64, String, String)> = db
+        .query_row(
+            "SELECT id, password_hash, username FROM users WHERE username = ?1 OR username = lower(?1) ORDER BY username = ?1 DESC LIMIT 1",
+            params![username],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .optional()
+        .map_err(|e| ApiError::internal(format!("failed to query user: {e}")))?;
+
+    let Some((user_id, password_hash, stored_username)) = user_row else {
+        Metrics::increment(&state.metrics.logins_failed);
+        return Err(ApiError::unauthorized("invalid credentials"));
+    };
+
+    if let Err(error) = verify_password(&state.argon2_params, password, &password_hash) {
+        if error.status == StatusCode::UNAUTHORIZED {
+            state.login_limiter.record_failure(&limiter_key);
+            Metrics::increment(&state.metrics.logins_failed);
+        }
+        return Err(error);
+    }
+    state.login_limiter.reset(&limiter_key);
+    Metrics::increment(&state.metrics.logins_succeeded);
+
+    let token = generate_token();
+    db.execute(
+        "INSERT INTO sessions (token, user_id, created_at) VALUES (?1, ?2, ?3)",
+        params![token, user_id, now_utc()],
+    )
+    .map_err(|e| ApiError::internal(format!("failed to create session: {e}")))?;
+
+    Ok((
+        UserInfo {
+            id: user_id,
+            username: stored_username,
+        },
+        token,
+    ))
+}
+
+async fn create_user_with_password(
+    state: &AppState,
+    username: &str,
+    password: &str,
+) -> Result<UserInfo, ApiError> {
+    validate_username(username)?;
+    let username = username.to_ascii_lowercase();
+    if password.len() < 8 {
+        return Err(ApiError::bad_request("password must be at least 8 chars"));
+    }
+
+    let hash = hash_password(&state.argon2_params, password)?;
+    let db = state.db()?;
+    // The emptiness check runs inside the insert so two concurrent first
+    // registrations cannot both become admin.
+    let inserted = db.execute(
+        "INSERT INTO users (username, password_hash, created_at, is_admin) SELECT ?1, ?2, ?3, ?4 AND NOT EXISTS (SELECT 1 FROM users WHERE id != ?5)",
+        params![username, hash, now_utc(), state.first_user_admin, LOCAL_USER_ID],
+    );
+
+    if let Err(err) = inserted {
+        // Taken names are caught by the UNIQUE index rather than a prior
+        // lookup, so two concurrent registrations cannot both succeed.
+        if is_unique_violation(&err) {
+            return Err(ApiError::bad_request("username already exists"));
+        }
+        return Err(ApiError::internal(format!("failed to create user: {err}")));
+    }
+
+    Ok(UserInfo {
+        id: db.last_insert_rowid(),
+        username,
+    })
+}
+
+/// Whether `err` is SQLite rejecting a row that breaks a `UNIQUE` constraint.
+fn is_unique_violation(err: &rusqlite::Error) -> bool {
+    matches!(
+        err,
+        rusqlite::Error::SqliteFailure(failure, _)
+            if failure.code == rusqlite::ErrorCode::ConstraintViolation
+                && failure.extended_code == rusqlite::ffi::SQLITE_CONSTRAINT_UNIQUE
+    )
+}
+
+const USERNAME_MIN_LEN: usize = 3;
+const USERNAME_MAX_LEN: usize = 32;
+
+/// Registration rules for usernames: 3 to 32 of `[a-z0-9_-]` (capitals are
+/// accepted and stored lowercase), not starting or ending with `_` or `-`.
+fn validate_username(name: &str) -> Result<(), ApiError> {
+    if name.is_empty() {
+        return Err(ApiError::bad_request("username must not be empty"));
+    }
+    if let Some(invalid) = name
+        .chars()
+        .find(|c| !(c.is_ascii_alphanumeric() || *c == '_' || *c == '-'))
+    {
+        return Err(ApiError::bad_request(format!(
+            "username must only contain letters, digits, '_' and '-' (found {invalid:?})"
+        )));
+    }
+    if !(USERNAME_MIN_LEN..=USERNAME_MAX_LEN).contains(&name.len()) {
+        return Err(ApiError::bad_request(format!(
+            "username must be {USERNAME_MIN_LEN} to {USERNAME_MAX_LEN} characters long"
+        )));
+    }
+    if name.starts_with(['_', '-']) || name.ends_with(['_', '-']) {
+        return Err(ApiError::bad_request(
+            "username must start and end with a letter or digit",
+        ));
+    }
+    Ok(())
+}
+
+fn sanitize_next(next: &str) -> &str {
+    if next.starts_with('/') && !next.starts_with("//") {
+        next
+    } else {
+        "/"
     }
 }
 
@@ -925,7 +2787,53 @@ fn render_login_page(
     ))
 }
 
-fn render_register_page(next: &str, username: Option<&str>, error: Option<&str>) -> Html<String> {
+/// Solves a proof-of-work challenge in the browser before the register form
+/// is submitted. `crypto.subtle` needs a secure context (HTTPS or localhost).
+const REGISTER_POW_SCRIPT: &str = r#"<script>
+document.querySelector('form').addEventListener('submit', async (event) => {
+  const form = event.target;
+  if (form.elements.pow_nonce.value) return;
+  event.preventDefault();
+  const button = form.querySelector('button');
+  button.disabled = true;
+  button.textContent = 'Checking...';
+  const { challenge, difficulty } = await (await fetch('/api/v1/auth/pow-challenge')).json();
+  const encoder = new TextEncoder();
+  const zeroBits = (bytes) => {
+    let bits = 0;
+    for (const byte of bytes) {
+      if (byte !== 0) return bits + Math.clz32(byte) - 24;
+      bits += 8;
+    }
+    return bits;
+  };
+  for (let nonce = 0; ; nonce++) {
+    const digest = await crypto.subtle.digest('SHA-256', encoder.encode(challenge + nonce));
+    if (zeroBits(new Uint8Array(digest)) >= difficulty) {
+      form.elements.pow_challenge.value = challenge;
+      form.elements.pow_nonce.value = String(nonce);
+      form.submit();
+      return;
+    }
+  }
+});
+</script>"#;
+
+fn render_register_page(
+    next: &str,
+    username: Option<&str>,
+    error: Option<&str>,
+    require_pow: bool,
+) -> Html<String> {
+    let (pow_fields, pow_script) = if require_pow {
+        (
+            "<input type=\"hidden\" name=\"pow_challenge\" />\
+             <input type=\"hidden\" name=\"pow_nonce\" />",
+            REGISTER_POW_SCRIPT,
+        )
+    } else {
+        ("", "")
+    };
     let escaped_next = escape_html(next);
     let escaped_username = escape_html(username.unwrap_or(""));
     let error_html = match error {
@@ -957,6 +2865,7 @@ fn render_register_page(next: &str, username: Option<&str>, error: Option<&str>)
          {error_html}\
          <form method=\"post\" action=\"/register\">\
          <input type=\"hidden\" name=\"next\" value=\"{escaped_next}\" />\
+         {pow_fields}\
          <label for=\"username\">Username</label>\
          <input id=\"username\" name=\"username\" autocomplete=\"username\" required value=\"{escaped_username}\" />\
          <label for=\"password\">Password</label>\
@@ -965,6 +2874,7 @@ fn render_register_page(next: &str, username: Option<&str>, error: Option<&str>)
          </form>\
          <p class=\"hint\">Already have an account? <a href=\"/login?next={}\">Sign in</a></p>\
          </main>\
+         {pow_script}\
          </body>\
          </html>",
         urlencoding::encode(next)
@@ -1023,14 +2933,26 @@ fn session_cookie_token(headers: &HeaderMap) -> Option<String> {
     None
 }
 
-fn session_cookie(token: &str, secure: bool) -> HeaderValue {
+/// Session cookie that expires together with the server-side session;
+/// `Expires` is for clients that ignore `Max-Age`.
+fn session_cookie(token: &str, secure: bool, ttl: Duration) -> HeaderValue {
     let secure_part = if secure { "; Secure" } else { "" };
+    let max_age = ttl.as_secs();
+    let expires = session_expiry(Utc::now(), ttl).format("%a, %d %b %Y %H:%M:%S GMT");
     HeaderValue::from_str(&format!(
-        "{COOKIE_NAME}={token}; HttpOnly; Path=/; SameSite=Lax{secure_part}"
+        "{COOKIE_NAME}={token}; HttpOnly; Path=/; SameSite=Lax; Max-Age={max_age}; Expires={expires}{secure_part}"
     ))
     .expect("valid session cookie")
 }
 
+/// When a session created at `created_at` stops being valid.
+fn session_expiry(created_at: DateTime<Utc>, ttl: Duration) -> DateTime<Utc> {
+    TimeDelta::from_std(ttl)
+        .ok()
+        .and_then(|ttl| created_at.checked_add_signed(ttl))
+        .unwrap_or(DateTime::<Utc>::MAX_UTC)
+}
+
 fn expired_session_cookie(secure: bool) -> HeaderValue {
     let secure_part = if secure { "; Secure" } else { "" };
     HeaderValue::from_str(&format!(
@@ -1045,21 +2967,33 @@ fn generate_token() -> String {
     hex::encode(bytes)
 }
 
-fn hash_password(password: &str) -> Result<String, ApiError> {
+fn hash_password(params: &Argon2Params, password: &str) -> Result<String, ApiError> {
     let mut rng = rand::rng();
     let salt_bytes: [u8; 16] = rng.random();
     let salt = SaltString::encode_b64(&salt_bytes)
         .map_err(|e| ApiError::internal(format!("failed to encode password salt: {e}")))?;
-    Argon2::default()
+    Argon2::from(params)
         .hash_password(password.as_bytes(), &salt)
         .map(|hash| hash.to_string())
         .map_err(|e| ApiError::internal(format!("password hash failed: {e}")))
 }
 
-fn verify_password(password: &str, hash: &str) -> Result<(), ApiError> {
+/// Checks `password` against a stored hash. Hashes made with the configured
+/// `params` are verified with them; hashes from before a parameter change fall
+/// back to `Argon2::default()`, which takes the costs embedded in the hash.
+fn verify_password(params: &Argon2Params, password: &str, hash: &str) -> Result<(), ApiError> {
     let parsed = PasswordHash::new(hash)
         .map_err(|e| ApiError::internal(format!("invalid password hash in database: {e}")))?;
-    Argon2::default()
+    let configured = Argon2Params::try_from(&parsed).is_ok_and(|stored| {
+        (stored.m_cost(), stored.t_cost(), stored.p_cost())
+            == (params.m_cost(), params.t_cost(), params.p_cost())
+    });
+    let hasher = if configured {
+        Argon2::from(params)
+    } else {
+        Argon2::default()
+    };
+    hasher
         .verify_password(password.as_bytes(), &parsed)
         .map_err(|_| ApiError::unauthorized("invalid credentials"))
 }
@@ -1086,7 +3020,8 @@ fn run_migrations(conn: &Connection) -> Result<(), rusqlite::Error> {
             id INTEGER PRIMARY KEY AUTOINCREMENT,
             username TEXT NOT NULL UNIQUE,
             password_hash TEXT NOT NULL,
-            created_at TEXT NOT NULL
+            created_at TEXT NOT NULL,
+            is_admin INTEGER NOT NULL DEFAULT 0
         );
 
         CREATE TABLE IF NOT EXISTS sessions (
@@ -1105,28 +3040,81 @@ fn run_migrations(conn: &Connection) -> Result<(), rusqlite::Error> {
             elf_path TEXT NOT NULL,
             is_public INTEGER NOT NULL DEFAULT 0,
             created_at TEXT NOT NULL,
+            sha256 TEXT,
+            build_meta_json TEXT,
+            download_count INTEGER NOT NULL DEFAULT 0,
             FOREIGN KEY(owner_user_id) REFERENCES users(id) ON DELETE CASCADE
         );
-        ",
-    )?;
 
-    let mut has_is_public = false;
-    let mut stmt = conn.prepare("PRAGMA table_info(artifacts)")?;
-    let rows = stmt.query_map([], |row| row.get::<_, String>(1))?;
-    for row in rows {
-        if row? == "is_public" {
-            has_is_public = true;
-            break;
-        }
+        CREATE TABLE IF NOT EXISTS race_records (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            owner_user_id INTEGER NOT NULL,
+            track_id TEXT NOT NULL,
+            result_json TEXT NOT NULL,
+            replay_json TEXT,
+            created_at TEXT NOT NULL,
+            FOREIGN KEY(owner_user_id) REFERENCES users(id) ON DELETE CASCADE
+        );
+
+        CREATE TABLE IF NOT EXISTS audit_log (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            event TEXT NOT NULL,
+            user_id INTEGER,
+            ip TEXT,
+            detail TEXT NOT NULL,
+            created_at TEXT NOT NULL
+        );
+
+        CREATE TABLE IF NOT EXISTS artifact_tags (
+            artifact_id INTEGER NOT NULL,
+            tag TEXT NOT NULL,
+            PRIMARY KEY(artifact_id, tag),
+            FOREIGN KEY(artifact_id) REFERENCES artifacts(id) ON DELETE CASCADE
+        );
+
+        CREATE INDEX IF NOT EXISTS artifact_tags_tag ON artifact_tags(tag);
+        CREATE INDEX IF NOT EXISTS audit_log_user_id ON audit_log(user_id);
+        CREATE INDEX IF NOT EXISTS audit_log_event ON audit_log(event);
+        ",
+    )?;
+
+    add_column_if_missing(conn, "artifacts", "is_public", "INTEGER NOT NULL DEFAULT 0")?;
+    add_column_if_missing(conn, "artifacts", "sha256", "TEXT")?;
+    add_column_if_missing(conn, "artifacts", "build_meta_json", "TEXT")?;
+    add_column_if_missing(
+        conn,
+        "artifacts",
+        "download_count",
+        "INTEGER NOT NULL DEFAULT 0",
+    )?;
+    add_column_if_missing(conn, "users", "is_admin", "INTEGER NOT NULL DEFAULT 0")?;
+
+    Ok(())
+}
+
+/// Adds `column` to `table` in databases created before it existed.
+fn add_column_if_missing(
+    conn: &Connection,
+    table: &str,
+    column: &str,
+    definition: &str,
+) -> Result<(), rusqlite::Error> {
+    let mut has_column = false;
+    let mut stmt = conn.prepare(&format!("PRAGMA table_info({table})"))?;
+    let rows = stmt.query_map([], |row| row.get::<_, String>(1))?;
+    for row in rows {
+        if row? == column {
+            has_column = true;
+            break;
+        }
     }
 
-    if !has_is_public {
+    if !has_column {
         conn.execute(
-            "ALTER TABLE artifacts ADD COLUMN is_public INTEGER NOT NULL DEFAULT 0",
+            &format!("ALTER TABLE {table} ADD COLUMN {column} {definition}"),
             [],
         )?;
     }
-
     Ok(())
 }
 
@@ -1143,10 +3131,23 @@ mod tests {
         http::Request,
     };
     use botracers_protocol::{
-        ArtifactSummary, LoginResponse, UpdateArtifactVisibilityRequest, UploadArtifactRequest,
+        ArtifactSummary, LeaderboardEntry, LoginResponse, LogoutAllResponse, PowChallengeResponse,
+        UpdateArtifactRequest, UpdateArtifactVisibilityRequest, UploadArtifactRequest,
     };
     use tower::ServiceExt;
 
+    /// Smallest payload `validate_elf` accepts: a bare ELF32 RISC-V header.
+    fn minimal_riscv_elf() -> Vec<u8> {
+        let mut elf = vec![0u8; ELF32_HEADER_LEN];
+        elf[..4].copy_from_slice(&ELF_MAGIC);
+        elf[4] = ELFCLASS32;
+        elf[5] = ELFDATA2LSB;
+        elf[6] = 1;
+        elf[16..18].copy_from_slice(&2u16.to_le_bytes());
+        elf[18..20].copy_from_slice(&EM_RISCV.to_le_bytes());
+        elf
+    }
+
     fn unique_temp_dir(prefix: &str) -> PathBuf {
         let mut rng = rand::rng();
         let suffix: u64 = rng.random();
@@ -1167,23 +3168,45 @@ mod tests {
         )
         .expect("write index");
 
-        let conn = Connection::open_in_memory().expect("open in-memory sqlite");
-        run_migrations(&conn).expect("run migrations");
-        ensure_local_user(&conn).expect("ensure local user");
+        // Pooled connections each see their own `:memory:` database, so tests
+        // share a throwaway file instead.
+        let db = open_db_pool(SqliteConnectionManager::file(
+            artifacts_dir.join("botracers-test.db"),
+        ))
+        .expect("open sqlite pool");
+        {
+            let conn = db.get().expect("db connection");
+            run_migrations(&conn).expect("run migrations");
+            ensure_local_user(&conn).expect("ensure local user");
+        }
         let state = AppState {
-            db: Arc::new(Mutex::new(conn)),
+            audit: Some(AuditLog::spawn(db.clone())),
+            db,
             artifacts_dir: artifacts_dir.clone(),
             static_dir: Some(static_dir.clone()),
             auth_mode,
             cookie_secure: false,
+            session_ttl: Duration::from_secs(3600),
             registration_enabled,
+            max_artifact_bytes: 1024,
+            max_artifacts_per_user: 0,
+            supported_targets: vec![DEFAULT_ARTIFACT_TARGET.to_string()].into(),
+            admin_usernames: vec!["admin".to_string()].into(),
+            first_user_admin: false,
+            trust_forwarded_for: true,
+            login_limiter: LoginLimiter::new(5, Duration::from_secs(60)),
+            allowed_origins: vec!["https://game.example".to_string()].into(),
+            argon2_params: Argon2Params::DEFAULT,
+            metrics: Arc::default(),
+            artifact_events: broadcast::channel(ARTIFACT_EVENTS_CAPACITY).0,
+            pow: None,
         };
         (state, static_dir, artifacts_dir)
     }
 
     async fn create_user(state: &AppState, username: &str, password: &str) {
-        let hash = hash_password(password).expect("hash password");
-        let db = state.db.lock().await;
+        let hash = hash_password(&state.argon2_params, password).expect("hash password");
+        let db = state.db().expect("db connection");
         db.execute(
             "INSERT INTO users (username, password_hash, created_at) VALUES (?1, ?2, ?3)",
             params![username, hash, now_utc()],
@@ -1191,6 +3214,34 @@ mod tests {
         .expect("insert user");
     }
 
+    #[tokio::test]
+    async fn unique_violations_on_usernames_map_to_already_exists() {
+        let (state, static_dir, artifacts_dir) = setup_test_state(AuthMode::Required, true);
+        create_user_with_password(&state, "alice", "pw-alice-1")
+            .await
+            .expect("first registration");
+        let error = create_user_with_password(&state, "ALICE", "pw-alice-2")
+            .await
+            .expect_err("duplicate registration");
+        assert_eq!(error.status, StatusCode::BAD_REQUEST);
+        assert_eq!(error.message, "username already exists");
+
+        let db = state.db().expect("db connection");
+        let insert = |username: Option<&str>| {
+            db.execute(
+                "INSERT INTO users (username, password_hash, created_at) VALUES (?1, 'x', ?2)",
+                params![username, now_utc()],
+            )
+            .expect_err("constraint violation")
+        };
+        assert!(is_unique_violation(&insert(Some("alice"))));
+        // Other constraint failures are not mistaken for a taken name.
+        assert!(!is_unique_violation(&insert(None)));
+
+        let _ = std::fs::remove_dir_all(static_dir);
+        let _ = std::fs::remove_dir_all(artifacts_dir);
+    }
+
     async fn make_session_cookie(state: &AppState, username: &str, password: &str) -> String {
         let (_, token) = create_session_for_credentials(state, username, password)
             .await
@@ -1207,7 +3258,8 @@ mod tests {
             name: name.to_string(),
             note: None,
             target: "riscv32imafc-unknown-none-elf".to_string(),
-            elf_base64: base64::engine::general_purpose::STANDARD.encode([0x7f, b'E', b'L', b'F']),
+            elf_base64: base64::engine::general_purpose::STANDARD.encode(minimal_riscv_elf()),
+            build_meta_json: None,
         };
         let resp = app
             .clone()
@@ -1272,18 +3324,27 @@ mod tests {
         resp.status()
     }
 
-    async fn download_artifact_with_cookie(
+    async fn tag_artifact_with_cookie(
         app: &Router,
         cookie: &str,
+        method: Method,
         artifact_id: i64,
+        tag: &str,
     ) -> StatusCode {
+        let payload = ArtifactTagRequest {
+            tag: tag.to_string(),
+        };
         let resp = app
             .clone()
             .oneshot(
                 Request::builder()
-                    .uri(format!("/api/v1/artifacts/{artifact_id}"))
+                    .method(method)
+                    .uri(format!("/api/v1/artifacts/{artifact_id}/tags"))
                     .header(header::COOKIE, cookie)
-                    .body(Body::empty())
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(
+                        serde_json::to_vec(&payload).expect("serialize payload"),
+                    ))
                     .expect("request"),
             )
             .await
@@ -1291,482 +3352,2909 @@ mod tests {
         resp.status()
     }
 
-    #[tokio::test]
-    async fn unauthenticated_game_entry_shows_login_in_required_mode() {
-        let (state, static_dir, artifacts_dir) = setup_test_state(AuthMode::Required, true);
-        let app = build_app(state, Some(static_dir.clone()));
-
+    async fn list_artifacts_tagged(app: &Router, cookie: &str, tag: &str) -> Vec<i64> {
         let resp = app
+            .clone()
             .oneshot(
                 Request::builder()
-                    .uri("/")
+                    .uri(format!("/api/v1/artifacts?tag={tag}"))
+                    .header(header::COOKIE, cookie)
                     .body(Body::empty())
                     .expect("request"),
             )
             .await
             .expect("response");
-
         assert_eq!(resp.status(), StatusCode::OK);
         let body = to_bytes(resp.into_body(), usize::MAX).await.expect("body");
-        let text = String::from_utf8(body.to_vec()).expect("utf8");
-        assert!(text.contains("Sign in to BotRacers"));
-        assert!(text.contains("name=\"next\" value=\"/\""));
-
-        let _ = std::fs::remove_dir_all(static_dir);
-        let _ = std::fs::remove_dir_all(artifacts_dir);
+        let artifacts: Vec<ArtifactSummary> =
+            serde_json::from_slice(&body).expect("artifact list json");
+        artifacts.into_iter().map(|a| a.id).collect()
     }
 
-    #[tokio::test]
-    async fn authenticated_game_entry_serves_index() {
-        let (state, static_dir, artifacts_dir) = setup_test_state(AuthMode::Required, true);
-        create_user(&state, "alice", "password123").await;
-        let cookie = make_session_cookie(&state, "alice", "password123").await;
-        let app = build_app(state, Some(static_dir.clone()));
+    async fn update_artifact_with_cookie(
+        app: &Router,
+        cookie: &str,
+        artifact_id: i64,
+        payload: &UpdateArtifactRequest,
+    ) -> StatusCode {
+        let resp = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("PATCH")
+                    .uri(format!("/api/v1/artifacts/{artifact_id}"))
+                    .header(header::COOKIE, cookie)
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(
+                        serde_json::to_vec(payload).expect("serialize payload"),
+                    ))
+                    .expect("request"),
+            )
+            .await
+            .expect("response");
+        resp.status()
+    }
 
+    async fn download_artifact_with_cookie(
+        app: &Router,
+        cookie: &str,
+        artifact_id: i64,
+    ) -> StatusCode {
         let resp = app
+            .clone()
             .oneshot(
                 Request::builder()
-                    .uri("/")
+                    .uri(format!("/api/v1/artifacts/{artifact_id}"))
                     .header(header::COOKIE, cookie)
                     .body(Body::empty())
                     .expect("request"),
             )
             .await
             .expect("response");
-
-        assert_eq!(resp.status(), StatusCode::OK);
-        let body = to_bytes(resp.into_body(), usize::MAX).await.expect("body");
-        let text = String::from_utf8(body.to_vec()).expect("utf8");
-        assert!(text.contains("game entry"));
-
-        let _ = std::fs::remove_dir_all(static_dir);
-        let _ = std::fs::remove_dir_all(artifacts_dir);
+        resp.status()
     }
 
     #[tokio::test]
-    async fn web_login_success_sets_cookie_and_redirects() {
-        let (state, static_dir, artifacts_dir) = setup_test_state(AuthMode::Required, true);
-        create_user(&state, "alice", "password123").await;
+    async fn download_streams_large_artifact_byte_for_byte() {
+        let (mut state, static_dir, artifacts_dir) = setup_test_state(AuthMode::Required, true);
+        state.max_artifact_bytes = 4 * 1024 * 1024;
+        create_user(&state, "alice", "pw-alice").await;
+        let cookie = make_session_cookie(&state, "alice", "pw-alice").await;
         let app = build_app(state, Some(static_dir.clone()));
 
+        let mut elf = minimal_riscv_elf();
+        let mut seed: u32 = 0x1234_5678;
+        elf.extend((0..3 * 1024 * 1024).map(|_| {
+            seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+            (seed >> 24) as u8
+        }));
+
+        let payload = UploadArtifactRequest {
+            name: "big".to_string(),
+            note: None,
+            target: DEFAULT_ARTIFACT_TARGET.to_string(),
+            elf_base64: base64::engine::general_purpose::STANDARD.encode(&elf),
+            build_meta_json: None,
+        };
         let resp = app
+            .clone()
             .oneshot(
                 Request::builder()
                     .method("POST")
-                    .uri("/login")
-                    .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+                    .uri("/api/v1/artifacts")
+                    .header(header::COOKIE, &cookie)
+                    .header(header::CONTENT_TYPE, "application/json")
                     .body(Body::from(
-                        "username=alice&password=password123&next=%2Findex.html",
+                        serde_json::to_vec(&payload).expect("serialize payload"),
                     ))
                     .expect("request"),
             )
             .await
             .expect("response");
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body = to_bytes(resp.into_body(), usize::MAX).await.expect("body");
+        let uploaded: UploadArtifactResponse = serde_json::from_slice(&body).expect("upload json");
 
-        assert_eq!(resp.status(), StatusCode::SEE_OTHER);
+        let resp = app
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/api/v1/artifacts/{}", uploaded.artifact_id))
+                    .header(header::COOKIE, &cookie)
+                    .body(Body::empty())
+                    .expect("request"),
+            )
+            .await
+            .expect("response");
+        assert_eq!(resp.status(), StatusCode::OK);
         assert_eq!(
-            resp.headers()
-                .get(header::LOCATION)
-                .expect("location")
-                .to_str()
-                .expect("location str"),
-            "/index.html"
+            resp.headers().get(header::CONTENT_LENGTH),
+            Some(&HeaderValue::from(elf.len()))
         );
-        let cookie = resp
-            .headers()
-            .get(header::SET_COOKIE)
-            .expect("set-cookie")
-            .to_str()
-            .expect("cookie str");
-        assert!(cookie.contains("botracers_session="));
+        let body = to_bytes(resp.into_body(), usize::MAX).await.expect("body");
+        assert!(body.as_ref() == elf.as_slice());
 
         let _ = std::fs::remove_dir_all(static_dir);
         let _ = std::fs::remove_dir_all(artifacts_dir);
     }
 
     #[tokio::test]
-    async fn web_login_failure_renders_error() {
+    async fn downloads_are_counted_and_popular_sort_orders_by_count() {
         let (state, static_dir, artifacts_dir) = setup_test_state(AuthMode::Required, true);
-        create_user(&state, "alice", "password123").await;
+        create_user(&state, "alice", "pw-alice").await;
+        let cookie = make_session_cookie(&state, "alice", "pw-alice").await;
         let app = build_app(state, Some(static_dir.clone()));
 
-        let resp = app
-            .oneshot(
+        let mut ids = Vec::new();
+        for name in ["quiet", "popular", "middling"] {
+            let (status, artifact_id) = upload_artifact_with_cookie(&app, &cookie, name).await;
+            assert_eq!(status, StatusCode::OK);
+            ids.push(artifact_id);
+        }
+        for artifact_id in [ids[1], ids[1], ids[2]] {
+            assert_eq!(
+                download_artifact_with_cookie(&app, &cookie, artifact_id).await,
+                StatusCode::OK
+            );
+        }
+        // A revalidation that hits the cache is not a download.
+        let etag = format!("\"{}\"", sha256_hex(&minimal_riscv_elf()));
+        let (status, _) = download_with_if_none_match(&app, &cookie, ids[2], &etag).await;
+        assert_eq!(status, StatusCode::NOT_MODIFIED);
+
+        let list = |query: &'static str| {
+            app.clone().oneshot(
                 Request::builder()
-                    .method("POST")
-                    .uri("/login")
-                    .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
-                    .body(Body::from(
-                        "username=alice&password=wrongpassword&next=%2Findex.html",
-                    ))
+                    .uri(format!("/api/v1/artifacts{query}"))
+                    .header(header::COOKIE, &cookie)
+                    .body(Body::empty())
                     .expect("request"),
             )
-            .await
-            .expect("response");
-
-        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
-        assert!(resp.headers().get(header::SET_COOKIE).is_none());
+        };
+        let resp = list("?sort=popular").await.expect("response");
+        assert_eq!(resp.status(), StatusCode::OK);
         let body = to_bytes(resp.into_body(), usize::MAX).await.expect("body");
-        let text = String::from_utf8(body.to_vec()).expect("utf8");
-        assert!(text.contains("Invalid username or password"));
+        let listed: Vec<ArtifactSummary> = serde_json::from_slice(&body).expect("list json");
+        let ranking: Vec<_> = listed
+            .iter()
+            .map(|artifact| (artifact.name.as_str(), artifact.download_count))
+            .collect();
+        assert_eq!(ranking, vec![("popular", 2), ("middling", 1), ("quiet", 0)]);
+
+        let resp = list("?sort=loudest").await.expect("response");
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
 
         let _ = std::fs::remove_dir_all(static_dir);
         let _ = std::fs::remove_dir_all(artifacts_dir);
     }
 
-    #[test]
-    fn sanitize_next_rejects_external_targets() {
-        assert_eq!(sanitize_next("https://evil.com"), "/");
-        assert_eq!(sanitize_next("//evil.com"), "/");
-        assert_eq!(sanitize_next("/assets/foo"), "/assets/foo");
+    type EventSocket = tokio_tungstenite::WebSocketStream<
+        tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>,
+    >;
+
+    /// Serve `app` on an ephemeral local port; websocket upgrades need a real
+    /// connection rather than `oneshot`.
+    async fn serve_on_localhost(app: Router) -> SocketAddr {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("bind");
+        let addr = listener.local_addr().expect("local addr");
+        tokio::spawn(async move {
+            axum::serve(
+                listener,
+                app.into_make_service_with_connect_info::<SocketAddr>(),
+            )
+            .await
+            .expect("serve");
+        });
+        addr
+    }
+
+    async fn connect_artifact_events(
+        addr: SocketAddr,
+        cookie: Option<&str>,
+    ) -> Result<EventSocket, tokio_tungstenite::tungstenite::Error> {
+        use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+
+        let mut request = format!("ws://{addr}/api/v1/ws")
+            .into_client_request()
+            .expect("ws request");
+        if let Some(cookie) = cookie {
+            request.headers_mut().insert(
+                header::COOKIE,
+                HeaderValue::from_str(cookie).expect("cookie"),
+            );
+        }
+        tokio_tungstenite::connect_async(request)
+            .await
+            .map(|(socket, _)| socket)
+    }
+
+    async fn next_artifact_change(socket: &mut EventSocket) -> ArtifactChangeEvent {
+        use futures_util::StreamExt;
+        use tokio_tungstenite::tungstenite::Message;
+
+        let message = tokio::time::timeout(Duration::from_secs(5), socket.next())
+            .await
+            .expect("artifact change within 5s")
+            .expect("socket open")
+            .expect("websocket message");
+        let Message::Text(text) = message else {
+            panic!("expected a text message, got {message:?}");
+        };
+        serde_json::from_str(&text).expect("artifact change json")
     }
 
     #[tokio::test]
-    async fn api_login_still_returns_json_and_cookie() {
+    async fn websocket_pushes_changes_to_the_owners_artifacts_only() {
         let (state, static_dir, artifacts_dir) = setup_test_state(AuthMode::Required, true);
         create_user(&state, "alice", "password123").await;
+        create_user(&state, "bob", "password123").await;
+        let alice_cookie = make_session_cookie(&state, "alice", "password123").await;
+        let bob_cookie = make_session_cookie(&state, "bob", "password123").await;
+        let app = build_app(state, Some(static_dir.clone()));
+        let addr = serve_on_localhost(app.clone()).await;
+
+        match connect_artifact_events(addr, None).await {
+            Err(tokio_tungstenite::tungstenite::Error::Http(response)) => {
+                assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+            }
+            other => panic!("expected 401 without a session, got {other:?}"),
+        }
+
+        let mut alice_socket = connect_artifact_events(addr, Some(&alice_cookie))
+            .await
+            .expect("alice connects");
+        // Bob's upload goes first; Alice's socket must skip it.
+        let (status, _) = upload_artifact_with_cookie(&app, &bob_cookie, "bob.elf").await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, artifact_id) =
+            upload_artifact_with_cookie(&app, &alice_cookie, "alice.elf").await;
+        assert_eq!(status, StatusCode::OK);
+
+        let change = next_artifact_change(&mut alice_socket).await;
+        assert_eq!(change.kind, ArtifactChangeKind::Uploaded);
+        assert_eq!(change.artifact_id, artifact_id);
+
+        assert_eq!(
+            update_visibility_with_cookie(&app, &alice_cookie, artifact_id, true).await,
+            StatusCode::NO_CONTENT
+        );
+        let change = next_artifact_change(&mut alice_socket).await;
+        assert_eq!(change.kind, ArtifactChangeKind::VisibilityChanged);
+        assert_eq!(change.artifact_id, artifact_id);
+
+        let _ = std::fs::remove_dir_all(static_dir);
+        let _ = std::fs::remove_dir_all(artifacts_dir);
+    }
+
+    #[tokio::test]
+    async fn download_is_gzip_compressed_when_accepted() {
+        let (mut state, static_dir, artifacts_dir) = setup_test_state(AuthMode::Required, true);
+        state.max_artifact_bytes = 256 * 1024;
+        create_user(&state, "alice", "pw-alice").await;
+        let cookie = make_session_cookie(&state, "alice", "pw-alice").await;
         let app = build_app(state, Some(static_dir.clone()));
 
+        let mut elf = minimal_riscv_elf();
+        elf.extend((0..64 * 1024).map(|i| (i % 16) as u8));
+        let payload = UploadArtifactRequest {
+            name: "padded".to_string(),
+            note: None,
+            target: DEFAULT_ARTIFACT_TARGET.to_string(),
+            elf_base64: base64::engine::general_purpose::STANDARD.encode(&elf),
+            build_meta_json: None,
+        };
         let resp = app
+            .clone()
             .oneshot(
                 Request::builder()
                     .method("POST")
-                    .uri("/api/v1/auth/login")
+                    .uri("/api/v1/artifacts")
+                    .header(header::COOKIE, &cookie)
                     .header(header::CONTENT_TYPE, "application/json")
                     .body(Body::from(
-                        "{\"username\":\"alice\",\"password\":\"password123\"}",
+                        serde_json::to_vec(&payload).expect("serialize payload"),
                     ))
                     .expect("request"),
             )
             .await
             .expect("response");
-
         assert_eq!(resp.status(), StatusCode::OK);
-        assert!(resp.headers().contains_key(header::SET_COOKIE));
         let body = to_bytes(resp.into_body(), usize::MAX).await.expect("body");
-        let parsed: LoginResponse = serde_json::from_slice(&body).expect("login response json");
-        assert_eq!(parsed.user.username, "alice");
-        assert!(!parsed.token.is_empty());
-
-        let _ = std::fs::remove_dir_all(static_dir);
-        let _ = std::fs::remove_dir_all(artifacts_dir);
-    }
+        let uploaded: UploadArtifactResponse = serde_json::from_slice(&body).expect("upload json");
 
-    #[tokio::test]
-    async fn capabilities_include_registration_enabled() {
-        let (state, static_dir, artifacts_dir) = setup_test_state(AuthMode::Required, false);
-        let app = build_app(state, Some(static_dir.clone()));
         let resp = app
             .oneshot(
                 Request::builder()
-                    .uri("/api/v1/capabilities")
+                    .uri(format!("/api/v1/artifacts/{}", uploaded.artifact_id))
+                    .header(header::COOKIE, &cookie)
+                    .header(header::ACCEPT_ENCODING, "gzip")
                     .body(Body::empty())
                     .expect("request"),
             )
             .await
             .expect("response");
-
         assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(
+            resp.headers().get(header::CONTENT_ENCODING),
+            Some(&HeaderValue::from_static("gzip"))
+        );
         let body = to_bytes(resp.into_body(), usize::MAX).await.expect("body");
-        let caps: ServerCapabilities = serde_json::from_slice(&body).expect("caps json");
-        assert!(caps.auth_required);
-        assert!(!caps.registration_enabled);
+        assert!(
+            body.len() < elf.len() / 4,
+            "{} compressed bytes",
+            body.len()
+        );
+        let mut decoded = Vec::new();
+        std::io::Read::read_to_end(&mut flate2::read::GzDecoder::new(&body[..]), &mut decoded)
+            .expect("gunzip");
+        assert!(decoded == elf);
 
         let _ = std::fs::remove_dir_all(static_dir);
         let _ = std::fs::remove_dir_all(artifacts_dir);
     }
 
     #[tokio::test]
-    async fn api_register_blocked_when_registration_disabled() {
-        let (state, static_dir, artifacts_dir) = setup_test_state(AuthMode::Required, false);
-        let app = build_app(state, Some(static_dir.clone()));
+    async fn upload_records_checksum_and_download_sends_etag() {
+        let (state, static_dir, artifacts_dir) = setup_test_state(AuthMode::Required, true);
+        create_user(&state, "alice", "pw-alice").await;
+        let cookie = make_session_cookie(&state, "alice", "pw-alice").await;
+        let app = build_app(state.clone(), Some(static_dir.clone()));
+        // sha256 of `minimal_riscv_elf()`, computed outside the server.
+        let expected = "062476adf5ea2d4bcb64d0423c20aa5a650c3c378d1315822861548838caf093";
+
+        let (status, artifact_id) = upload_artifact_with_cookie(&app, &cookie, "bot").await;
+        assert_eq!(status, StatusCode::OK);
+
+        let stored: Option<String> = state
+            .db()
+            .expect("db connection")
+            .query_row(
+                "SELECT sha256 FROM artifacts WHERE id = ?1",
+                params![artifact_id],
+                |r| r.get(0),
+            )
+            .expect("query checksum");
+        assert_eq!(stored.as_deref(), Some(expected));
+        let listed = list_artifacts_with_cookie(&app, &cookie).await;
+        assert_eq!(listed[0].sha256.as_deref(), Some(expected));
+
+        let resp = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/api/v1/artifacts/{artifact_id}/checksum"))
+                    .header(header::COOKIE, &cookie)
+                    .body(Body::empty())
+                    .expect("request"),
+            )
+            .await
+            .expect("response");
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body = to_bytes(resp.into_body(), usize::MAX).await.expect("body");
+        let checksum: ArtifactChecksumResponse =
+            serde_json::from_slice(&body).expect("checksum json");
+        assert_eq!(checksum.artifact_id, artifact_id);
+        assert_eq!(checksum.sha256, expected);
+
+        let resp = app
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/api/v1/artifacts/{artifact_id}"))
+                    .header(header::COOKIE, &cookie)
+                    .body(Body::empty())
+                    .expect("request"),
+            )
+            .await
+            .expect("response");
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(
+            resp.headers().get(header::ETAG),
+            Some(&HeaderValue::from_str(&format!("\"{expected}\"")).expect("etag"))
+        );
+
+        let _ = std::fs::remove_dir_all(static_dir);
+        let _ = std::fs::remove_dir_all(artifacts_dir);
+    }
+
+    #[tokio::test]
+    async fn build_metadata_round_trips_through_upload_and_list() {
+        let (state, static_dir, artifacts_dir) = setup_test_state(AuthMode::Required, true);
+        create_user(&state, "alice", "pw-alice").await;
+        let cookie = make_session_cookie(&state, "alice", "pw-alice").await;
+        let app = build_app(state, Some(static_dir.clone()));
+
+        let upload = |build_meta_json: Option<&str>| {
+            let payload = UploadArtifactRequest {
+                name: "bot".to_string(),
+                note: None,
+                target: DEFAULT_ARTIFACT_TARGET.to_string(),
+                elf_base64: base64::engine::general_purpose::STANDARD.encode(minimal_riscv_elf()),
+                build_meta_json: build_meta_json.map(str::to_string),
+            };
+            app.clone().oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/api/v1/artifacts")
+                    .header(header::COOKIE, &cookie)
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(
+                        serde_json::to_vec(&payload).expect("serialize payload"),
+                    ))
+                    .expect("request"),
+            )
+        };
+
+        let meta = r#"{"rustc":"rustc 1.90.0 (1159e78c4 2025-09-14)","commit":"3f2c9a1","profile":"release"}"#;
+        let resp = upload(Some(meta)).await.expect("response");
+        assert_eq!(resp.status(), StatusCode::OK);
+        let resp = upload(None).await.expect("response");
+        assert_eq!(resp.status(), StatusCode::OK);
+        for rejected in [r#"["not", "an", "object"]"#, "{broken", &"x".repeat(5000)] {
+            let resp = upload(Some(rejected)).await.expect("response");
+            assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+        }
+
+        let mut listed = list_artifacts_with_cookie(&app, &cookie).await;
+        listed.sort_by_key(|artifact| artifact.id);
+        let metas: Vec<_> = listed
+            .iter()
+            .map(|artifact| artifact.build_meta_json.as_deref())
+            .collect();
+        assert_eq!(metas, vec![Some(meta), None]);
+
+        let _ = std::fs::remove_dir_all(static_dir);
+        let _ = std::fs::remove_dir_all(artifacts_dir);
+    }
+
+    #[test]
+    fn artifact_paths_must_be_a_single_file_name_under_the_root() {
+        let root = Path::new("/srv/botracers_artifacts");
+        assert_eq!(
+            resolve_artifact_path(root, "artifact_1.elf").expect("plain name"),
+            root.join("artifact_1.elf")
+        );
+        for rel in [
+            "../../etc/passwd",
+            "..",
+            ".",
+            "",
+            "/etc/passwd",
+            "nested/artifact.elf",
+            "artifact.elf/../../secret",
+        ] {
+            let error = resolve_artifact_path(root, rel).expect_err(rel);
+            assert_eq!(error.status, StatusCode::INTERNAL_SERVER_ERROR, "{rel}");
+        }
+    }
+
+    #[tokio::test]
+    async fn tampered_artifact_paths_are_not_downloaded_or_deleted() {
+        let (state, static_dir, artifacts_dir) = setup_test_state(AuthMode::Required, true);
+        create_user(&state, "alice", "pw-alice").await;
+        let cookie = make_session_cookie(&state, "alice", "pw-alice").await;
+        let app = build_app(state.clone(), Some(static_dir.clone()));
+
+        let (status, artifact_id) = upload_artifact_with_cookie(&app, &cookie, "bot").await;
+        assert_eq!(status, StatusCode::OK);
+        // A file next to the storage root stands in for anything outside it.
+        let outside = artifacts_dir.with_extension("secret");
+        std::fs::write(&outside, b"secret").expect("write outside file");
+        let rel = format!("../{}", outside.file_name().unwrap().to_string_lossy());
+        state
+            .db()
+            .expect("db connection")
+            .execute(
+                "UPDATE artifacts SET elf_path = ?1, sha256 = NULL WHERE id = ?2",
+                params![rel, artifact_id],
+            )
+            .expect("tamper artifact row");
+
+        assert_eq!(
+            download_artifact_with_cookie(&app, &cookie, artifact_id).await,
+            StatusCode::INTERNAL_SERVER_ERROR
+        );
+        let resp = app
+            .oneshot(
+                Request::builder()
+                    .method("DELETE")
+                    .uri(format!("/api/v1/artifacts/{artifact_id}"))
+                    .header(header::COOKIE, &cookie)
+                    .body(Body::empty())
+                    .expect("request"),
+            )
+            .await
+            .expect("response");
+        assert_eq!(resp.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert!(outside.exists());
+
+        let _ = std::fs::remove_file(outside);
+        let _ = std::fs::remove_dir_all(static_dir);
+        let _ = std::fs::remove_dir_all(artifacts_dir);
+    }
+
+    async fn download_with_if_none_match(
+        app: &Router,
+        cookie: &str,
+        artifact_id: i64,
+        etag: &str,
+    ) -> (StatusCode, Vec<u8>) {
+        let resp = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/api/v1/artifacts/{artifact_id}"))
+                    .header(header::COOKIE, cookie)
+                    .header(header::IF_NONE_MATCH, etag)
+                    .body(Body::empty())
+                    .expect("request"),
+            )
+            .await
+            .expect("response");
+        let status = resp.status();
+        let body = to_bytes(resp.into_body(), usize::MAX).await.expect("body");
+        (status, body.to_vec())
+    }
+
+    #[tokio::test]
+    async fn matching_if_none_match_returns_304_without_body() {
+        let (state, static_dir, artifacts_dir) = setup_test_state(AuthMode::Required, true);
+        create_user(&state, "alice", "pw-alice").await;
+        create_user(&state, "bob", "pw-bob").await;
+        let alice_cookie = make_session_cookie(&state, "alice", "pw-alice").await;
+        let bob_cookie = make_session_cookie(&state, "bob", "pw-bob").await;
+        let app = build_app(state, Some(static_dir.clone()));
+        let etag = format!("\"{}\"", sha256_hex(&minimal_riscv_elf()));
+
+        let (_, artifact_id) = upload_artifact_with_cookie(&app, &bob_cookie, "bot").await;
+
+        let (status, body) =
+            download_with_if_none_match(&app, &bob_cookie, artifact_id, &etag).await;
+        assert_eq!(status, StatusCode::NOT_MODIFIED);
+        assert!(body.is_empty());
+
+        // Access checks run before the 304 short-circuit.
+        let (status, _) =
+            download_with_if_none_match(&app, &alice_cookie, artifact_id, &etag).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+
+        let _ = std::fs::remove_dir_all(static_dir);
+        let _ = std::fs::remove_dir_all(artifacts_dir);
+    }
+
+    #[tokio::test]
+    async fn stale_if_none_match_returns_the_artifact() {
+        let (state, static_dir, artifacts_dir) = setup_test_state(AuthMode::Required, true);
+        create_user(&state, "alice", "pw-alice").await;
+        let cookie = make_session_cookie(&state, "alice", "pw-alice").await;
+        let app = build_app(state, Some(static_dir.clone()));
+
+        let (_, artifact_id) = upload_artifact_with_cookie(&app, &cookie, "bot").await;
+        let stale = format!("\"{}\"", sha256_hex(b"an older build"));
+        let (status, body) = download_with_if_none_match(&app, &cookie, artifact_id, &stale).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, minimal_riscv_elf());
+
+        let _ = std::fs::remove_dir_all(static_dir);
+        let _ = std::fs::remove_dir_all(artifacts_dir);
+    }
+
+    #[tokio::test]
+    async fn unauthenticated_game_entry_shows_login_in_required_mode() {
+        let (state, static_dir, artifacts_dir) = setup_test_state(AuthMode::Required, true);
+        let app = build_app(state, Some(static_dir.clone()));
+
+        let resp = app
+            .oneshot(
+                Request::builder()
+                    .uri("/")
+                    .body(Body::empty())
+                    .expect("request"),
+            )
+            .await
+            .expect("response");
+
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body = to_bytes(resp.into_body(), usize::MAX).await.expect("body");
+        let text = String::from_utf8(body.to_vec()).expect("utf8");
+        assert!(text.contains("Sign in to BotRacers"));
+        assert!(text.contains("name=\"next\" value=\"/\""));
+
+        let _ = std::fs::remove_dir_all(static_dir);
+        let _ = std::fs::remove_dir_all(artifacts_dir);
+    }
+
+    #[tokio::test]
+    async fn authenticated_game_entry_serves_index() {
+        let (state, static_dir, artifacts_dir) = setup_test_state(AuthMode::Required, true);
+        create_user(&state, "alice", "password123").await;
+        let cookie = make_session_cookie(&state, "alice", "password123").await;
+        let app = build_app(state, Some(static_dir.clone()));
+
+        let resp = app
+            .oneshot(
+                Request::builder()
+                    .uri("/")
+                    .header(header::COOKIE, cookie)
+                    .body(Body::empty())
+                    .expect("request"),
+            )
+            .await
+            .expect("response");
+
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body = to_bytes(resp.into_body(), usize::MAX).await.expect("body");
+        let text = String::from_utf8(body.to_vec()).expect("utf8");
+        assert!(text.contains("game entry"));
+
+        let _ = std::fs::remove_dir_all(static_dir);
+        let _ = std::fs::remove_dir_all(artifacts_dir);
+    }
+
+    #[tokio::test]
+    async fn client_routes_fall_back_to_the_game_entry() {
+        let (state, static_dir, artifacts_dir) = setup_test_state(AuthMode::Required, true);
+        create_user(&state, "alice", "password123").await;
+        let cookie = make_session_cookie(&state, "alice", "password123").await;
+        std::fs::write(static_dir.join("app.js"), "console.log('app');").expect("write asset");
+        let app = build_app(state, Some(static_dir.clone()));
+
+        let get = |uri: &str, cookie: Option<&str>| {
+            let mut request = Request::builder().uri(uri);
+            if let Some(cookie) = cookie {
+                request = request.header(header::COOKIE, cookie);
+            }
+            let request = request.body(Body::empty()).expect("request");
+            let app = app.clone();
+            async move {
+                let resp = app.oneshot(request).await.expect("response");
+                let status = resp.status();
+                let body = to_bytes(resp.into_body(), usize::MAX).await.expect("body");
+                (status, String::from_utf8(body.to_vec()).expect("utf8"))
+            }
+        };
+
+        let (status, text) = get("/race/42/replay?speed=2", Some(&cookie)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(text.contains("game entry"), "{text}");
+
+        // Same login gate as `/`, returning to the requested route.
+        let (status, text) = get("/race/42", None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(text.contains("Sign in to BotRacers"));
+        assert!(text.contains("name=\"next\" value=\"/race/42\""));
+
+        // Real files are still served as-is.
+        let (status, text) = get("/app.js", Some(&cookie)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(text, "console.log('app');");
+
+        for missing in ["/missing.js", "/api/v1/nope"] {
+            let (status, _) = get(missing, Some(&cookie)).await;
+            assert_eq!(status, StatusCode::NOT_FOUND, "{missing}");
+        }
+
+        let _ = std::fs::remove_dir_all(static_dir);
+        let _ = std::fs::remove_dir_all(artifacts_dir);
+    }
+
+    #[tokio::test]
+    async fn web_login_success_sets_cookie_and_redirects() {
+        let (state, static_dir, artifacts_dir) = setup_test_state(AuthMode::Required, true);
+        create_user(&state, "alice", "password123").await;
+        let app = build_app(state, Some(static_dir.clone()));
+
+        let resp = app
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/login")
+                    .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+                    .body(Body::from(
+                        "username=alice&password=password123&next=%2Findex.html",
+                    ))
+                    .expect("request"),
+            )
+            .await
+            .expect("response");
+
+        assert_eq!(resp.status(), StatusCode::SEE_OTHER);
+        assert_eq!(
+            resp.headers()
+                .get(header::LOCATION)
+                .expect("location")
+                .to_str()
+                .expect("location str"),
+            "/index.html"
+        );
+        let cookie = resp
+            .headers()
+            .get(header::SET_COOKIE)
+            .expect("set-cookie")
+            .to_str()
+            .expect("cookie str");
+        assert!(cookie.contains("botracers_session="));
+
+        let _ = std::fs::remove_dir_all(static_dir);
+        let _ = std::fs::remove_dir_all(artifacts_dir);
+    }
+
+    #[tokio::test]
+    async fn web_login_failure_renders_error() {
+        let (state, static_dir, artifacts_dir) = setup_test_state(AuthMode::Required, true);
+        create_user(&state, "alice", "password123").await;
+        let app = build_app(state, Some(static_dir.clone()));
+
+        let resp = app
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/login")
+                    .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+                    .body(Body::from(
+                        "username=alice&password=wrongpassword&next=%2Findex.html",
+                    ))
+                    .expect("request"),
+            )
+            .await
+            .expect("response");
+
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+        assert!(resp.headers().get(header::SET_COOKIE).is_none());
+        let body = to_bytes(resp.into_body(), usize::MAX).await.expect("body");
+        let text = String::from_utf8(body.to_vec()).expect("utf8");
+        assert!(text.contains("Invalid username or password"));
+
+        let _ = std::fs::remove_dir_all(static_dir);
+        let _ = std::fs::remove_dir_all(artifacts_dir);
+    }
+
+    #[test]
+    fn validate_elf_accepts_riscv32_header_and_rejects_others() {
+        let target = DEFAULT_ARTIFACT_TARGET;
+        assert!(validate_elf(&minimal_riscv_elf(), target).is_ok());
+
+        let truncated = &minimal_riscv_elf()[..20];
+        let err = validate_elf(truncated, target).expect_err("truncated");
+        assert_eq!(err.status, StatusCode::BAD_REQUEST);
+        assert_eq!(err.message, "elf header is truncated");
+
+        let mut x86 = minimal_riscv_elf();
+        x86[18..20].copy_from_slice(&62u16.to_le_bytes());
+        let err = validate_elf(&x86, target).expect_err("x86-64 machine");
+        assert_eq!(err.status, StatusCode::BAD_REQUEST);
+        assert!(err.message.contains("not RISC-V"));
+
+        let mut elf64 = minimal_riscv_elf();
+        elf64[4] = ELFCLASS64;
+        assert!(validate_elf(&elf64, target).is_err());
+        assert!(validate_elf(b"#!/bin/sh\necho hi\n", target).is_err());
+    }
+
+    #[tokio::test]
+    async fn upload_rejects_non_elf_payload() {
+        let (state, static_dir, artifacts_dir) = setup_test_state(AuthMode::Required, true);
+        create_user(&state, "alice", "pw-alice").await;
+        let cookie = make_session_cookie(&state, "alice", "pw-alice").await;
+        let app = build_app(state, Some(static_dir.clone()));
+
+        let payload = UploadArtifactRequest {
+            name: "notes".to_string(),
+            note: None,
+            target: DEFAULT_ARTIFACT_TARGET.to_string(),
+            elf_base64: base64::engine::general_purpose::STANDARD.encode(b"not an elf"),
+            build_meta_json: None,
+        };
+        let resp = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/api/v1/artifacts")
+                    .header(header::COOKIE, &cookie)
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(
+                        serde_json::to_vec(&payload).expect("serialize payload"),
+                    ))
+                    .expect("request"),
+            )
+            .await
+            .expect("response");
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+        assert!(list_artifacts_with_cookie(&app, &cookie).await.is_empty());
+
+        let _ = std::fs::remove_dir_all(static_dir);
+        let _ = std::fs::remove_dir_all(artifacts_dir);
+    }
+
+    #[tokio::test]
+    async fn upload_is_refused_once_the_artifact_quota_is_reached() {
+        let (mut state, static_dir, artifacts_dir) = setup_test_state(AuthMode::Required, true);
+        state.max_artifacts_per_user = 3;
+        create_user(&state, "alice", "pw-alice").await;
+        create_user(&state, "admin", "pw-admin").await;
+        let alice_cookie = make_session_cookie(&state, "alice", "pw-alice").await;
+        let admin_cookie = make_session_cookie(&state, "admin", "pw-admin").await;
+        let app = build_app(state, Some(static_dir.clone()));
+
+        // The last of these uploads happens at count N - 1 and still fits.
+        for i in 0..3 {
+            let (status, _) =
+                upload_artifact_with_cookie(&app, &alice_cookie, &format!("bot{i}")).await;
+            assert_eq!(status, StatusCode::OK);
+        }
+
+        let payload = UploadArtifactRequest {
+            name: "one too many".to_string(),
+            note: None,
+            target: DEFAULT_ARTIFACT_TARGET.to_string(),
+            elf_base64: base64::engine::general_purpose::STANDARD.encode(minimal_riscv_elf()),
+            build_meta_json: None,
+        };
+        let resp = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/api/v1/artifacts")
+                    .header(header::COOKIE, &alice_cookie)
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(
+                        serde_json::to_vec(&payload).expect("serialize payload"),
+                    ))
+                    .expect("request"),
+            )
+            .await
+            .expect("response");
+        assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+        let body = to_bytes(resp.into_body(), usize::MAX).await.expect("body");
+        let error: ErrorResponse = serde_json::from_slice(&body).expect("error json");
+        assert!(
+            error.error.contains("3 of 3 allowed artifacts"),
+            "{}",
+            error.error
+        );
+        assert_eq!(
+            list_artifacts_with_cookie(&app, &alice_cookie).await.len(),
+            3
+        );
+
+        // Admins have no quota.
+        for i in 0..4 {
+            let (status, _) =
+                upload_artifact_with_cookie(&app, &admin_cookie, &format!("admin{i}")).await;
+            assert_eq!(status, StatusCode::OK);
+        }
+
+        let _ = std::fs::remove_dir_all(static_dir);
+        let _ = std::fs::remove_dir_all(artifacts_dir);
+    }
+
+    #[test]
+    fn sanitize_next_rejects_external_targets() {
+        assert_eq!(sanitize_next("https://evil.com"), "/");
+        assert_eq!(sanitize_next("//evil.com"), "/");
+        assert_eq!(sanitize_next("/assets/foo"), "/assets/foo");
+    }
+
+    #[tokio::test]
+    async fn api_login_still_returns_json_and_cookie() {
+        let (state, static_dir, artifacts_dir) = setup_test_state(AuthMode::Required, true);
+        create_user(&state, "alice", "password123").await;
+        let app = build_app(state, Some(static_dir.clone()));
+
+        let resp = app
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/api/v1/auth/login")
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(
+                        "{\"username\":\"alice\",\"password\":\"password123\"}",
+                    ))
+                    .expect("request"),
+            )
+            .await
+            .expect("response");
+
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert!(resp.headers().contains_key(header::SET_COOKIE));
+        let body = to_bytes(resp.into_body(), usize::MAX).await.expect("body");
+        let parsed: LoginResponse = serde_json::from_slice(&body).expect("login response json");
+        assert_eq!(parsed.user.username, "alice");
+        assert!(!parsed.token.is_empty());
+
+        let _ = std::fs::remove_dir_all(static_dir);
+        let _ = std::fs::remove_dir_all(artifacts_dir);
+    }
+
+    #[tokio::test]
+    async fn session_cookie_max_age_matches_session_ttl() {
+        let (state, static_dir, artifacts_dir) = setup_test_state(AuthMode::Required, true);
+        create_user(&state, "alice", "password123").await;
+        let ttl_secs = state.session_ttl.as_secs();
+        let app = build_app(state, Some(static_dir.clone()));
+
+        let resp = app
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/api/v1/auth/login")
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(
+                        "{\"username\":\"alice\",\"password\":\"password123\"}",
+                    ))
+                    .expect("request"),
+            )
+            .await
+            .expect("response");
+
+        assert_eq!(resp.status(), StatusCode::OK);
+        let set_cookie = resp.headers()[header::SET_COOKIE].to_str().expect("ascii");
+        assert!(
+            set_cookie.contains(&format!("; Max-Age={ttl_secs};")),
+            "{set_cookie}"
+        );
+        assert!(set_cookie.contains("; Expires="), "{set_cookie}");
+        assert!(set_cookie.ends_with(" GMT"), "{set_cookie}");
+        assert!(
+            set_cookie.contains("HttpOnly; Path=/; SameSite=Lax"),
+            "{set_cookie}"
+        );
+
+        let _ = std::fs::remove_dir_all(static_dir);
+        let _ = std::fs::remove_dir_all(artifacts_dir);
+    }
+
+    #[tokio::test]
+    async fn sessions_older_than_the_ttl_are_rejected() {
+        let (state, static_dir, artifacts_dir) = setup_test_state(AuthMode::Required, true);
+        create_user(&state, "alice", "password123").await;
+        let cookie = make_session_cookie(&state, "alice", "password123").await;
+        let stale =
+            (Utc::now() - TimeDelta::seconds(state.session_ttl.as_secs() as i64 + 1)).to_rfc3339();
+        state
+            .db()
+            .expect("db connection")
+            .execute("UPDATE sessions SET created_at = ?1", params![stale])
+            .expect("age session");
+        let app = build_app(state.clone(), Some(static_dir.clone()));
+
+        let resp = app
+            .oneshot(
+                Request::builder()
+                    .uri("/api/v1/me")
+                    .header(header::COOKIE, cookie)
+                    .body(Body::empty())
+                    .expect("request"),
+            )
+            .await
+            .expect("response");
+
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+        let remaining: i64 = state
+            .db()
+            .expect("db connection")
+            .query_row("SELECT COUNT(*) FROM sessions", [], |row| row.get(0))
+            .expect("count sessions");
+        assert_eq!(remaining, 0);
+
+        let _ = std::fs::remove_dir_all(static_dir);
+        let _ = std::fs::remove_dir_all(artifacts_dir);
+    }
+
+    #[tokio::test]
+    async fn logout_all_invalidates_every_session_of_the_caller() {
+        let (state, static_dir, artifacts_dir) = setup_test_state(AuthMode::Required, true);
+        create_user(&state, "alice", "password123").await;
+        create_user(&state, "bob", "password123").await;
+        let mut alice_cookies = Vec::new();
+        for _ in 0..3 {
+            alice_cookies.push(make_session_cookie(&state, "alice", "password123").await);
+        }
+        let bob_cookie = make_session_cookie(&state, "bob", "password123").await;
+        let app = build_app(state, Some(static_dir.clone()));
+
+        let resp = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/api/v1/auth/logout-all")
+                    .header(header::COOKIE, &alice_cookies[0])
+                    .body(Body::empty())
+                    .expect("request"),
+            )
+            .await
+            .expect("response");
+        assert_eq!(resp.status(), StatusCode::OK);
+        let set_cookie = resp
+            .headers()
+            .get(header::SET_COOKIE)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default()
+            .to_string();
+        assert!(set_cookie.contains("Max-Age=0"), "{set_cookie}");
+        let body = to_bytes(resp.into_body(), usize::MAX).await.expect("body");
+        let parsed: LogoutAllResponse = serde_json::from_slice(&body).expect("logout-all json");
+        assert_eq!(parsed.removed_sessions, 3);
+
+        for cookie in &alice_cookies {
+            let resp = app
+                .clone()
+                .oneshot(
+                    Request::builder()
+                        .uri("/api/v1/me")
+                        .header(header::COOKIE, cookie)
+                        .body(Body::empty())
+                        .expect("request"),
+                )
+                .await
+                .expect("response");
+            assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+        }
+        assert!(
+            list_artifacts_with_cookie(&app, &bob_cookie)
+                .await
+                .is_empty()
+        );
+
+        let _ = std::fs::remove_dir_all(static_dir);
+        let _ = std::fs::remove_dir_all(artifacts_dir);
+    }
+
+    fn delete_account_request(cookie: &str, password: &str) -> Request<Body> {
+        Request::builder()
+            .method("DELETE")
+            .uri("/api/v1/me")
+            .header(header::COOKIE, cookie)
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(
+                serde_json::to_vec(&DeleteAccountRequest {
+                    password: password.to_string(),
+                })
+                .expect("serialize payload"),
+            ))
+            .expect("request")
+    }
+
+    #[tokio::test]
+    async fn account_deletion_removes_artifact_files_user_and_sessions() {
+        let (state, static_dir, artifacts_dir) = setup_test_state(AuthMode::Required, true);
+        create_user(&state, "alice", "password123").await;
+        create_user(&state, "bob", "password123").await;
+        let alice_cookie = make_session_cookie(&state, "alice", "password123").await;
+        let second_alice_cookie = make_session_cookie(&state, "alice", "password123").await;
+        let bob_cookie = make_session_cookie(&state, "bob", "password123").await;
+        let db_state = state.clone();
+        let app = build_app(state, Some(static_dir.clone()));
+
+        upload_artifact_with_cookie(&app, &alice_cookie, "alice-bot-1").await;
+        upload_artifact_with_cookie(&app, &alice_cookie, "alice-bot-2").await;
+        upload_artifact_with_cookie(&app, &bob_cookie, "bob-bot").await;
+        let db = db_state.db().expect("db connection");
+        let elf_paths_of = |username: &str| {
+            let mut stmt = db
+                .prepare(
+                    "SELECT a.elf_path FROM artifacts a JOIN users u ON a.owner_user_id = u.id WHERE u.username = ?1",
+                )
+                .expect("prepare");
+            stmt.query_map(params![username], |row| row.get::<_, String>(0))
+                .expect("query")
+                .collect::<Result<Vec<_>, _>>()
+                .expect("rows")
+        };
+        let alice_files = elf_paths_of("alice");
+        let bob_files = elf_paths_of("bob");
+        assert_eq!(alice_files.len(), 2);
+        assert!(
+            alice_files
+                .iter()
+                .all(|path| artifacts_dir.join(path).exists())
+        );
+
+        let resp = app
+            .clone()
+            .oneshot(delete_account_request(&alice_cookie, "password123"))
+            .await
+            .expect("response");
+        assert_eq!(resp.status(), StatusCode::NO_CONTENT);
+        let set_cookie = resp
+            .headers()
+            .get(header::SET_COOKIE)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default()
+            .to_string();
+        assert!(set_cookie.contains("Max-Age=0"), "{set_cookie}");
+
+        assert!(
+            alice_files
+                .iter()
+                .all(|path| !artifacts_dir.join(path).exists())
+        );
+        assert!(artifacts_dir.join(&bob_files[0]).exists());
+        let count =
+            |sql: &str| -> i64 { db.query_row(sql, [], |row| row.get(0)).expect("count rows") };
+        assert_eq!(
+            count("SELECT COUNT(*) FROM users WHERE username = 'alice'"),
+            0
+        );
+        assert_eq!(count("SELECT COUNT(*) FROM artifacts"), 1);
+        // Only bob's session is left.
+        assert_eq!(count("SELECT COUNT(*) FROM sessions"), 1);
+
+        let resp = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri("/api/v1/me")
+                    .header(header::COOKIE, &second_alice_cookie)
+                    .body(Body::empty())
+                    .expect("request"),
+            )
+            .await
+            .expect("response");
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(list_artifacts_with_cookie(&app, &bob_cookie).await.len(), 1);
+
+        let _ = std::fs::remove_dir_all(static_dir);
+        let _ = std::fs::remove_dir_all(artifacts_dir);
+    }
+
+    #[tokio::test]
+    async fn account_deletion_requires_the_current_password() {
+        let (state, static_dir, artifacts_dir) = setup_test_state(AuthMode::Required, true);
+        create_user(&state, "alice", "password123").await;
+        let cookie = make_session_cookie(&state, "alice", "password123").await;
+        let app = build_app(state, Some(static_dir.clone()));
+        upload_artifact_with_cookie(&app, &cookie, "alice-bot").await;
+
+        let resp = app
+            .clone()
+            .oneshot(delete_account_request(&cookie, "wrong-password"))
+            .await
+            .expect("response");
+        assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+        assert_eq!(list_artifacts_with_cookie(&app, &cookie).await.len(), 1);
+
+        let resp = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("DELETE")
+                    .uri("/api/v1/me")
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(r#"{"password":"password123"}"#))
+                    .expect("request"),
+            )
+            .await
+            .expect("response");
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+
+        let _ = std::fs::remove_dir_all(static_dir);
+        let _ = std::fs::remove_dir_all(artifacts_dir);
+    }
+
+    #[tokio::test]
+    async fn repeated_failed_logins_are_rate_limited() {
+        let (state, static_dir, artifacts_dir) = setup_test_state(AuthMode::Required, true);
+        create_user(&state, "alice", "password123").await;
+        create_user(&state, "bob", "password123").await;
+        let app = build_app(state, Some(static_dir.clone()));
+
+        let login = |username: &str, password: &str| {
+            let app = app.clone();
+            let body = serde_json::json!({ "username": username, "password": password });
+            async move {
+                app.oneshot(
+                    Request::builder()
+                        .method("POST")
+                        .uri("/api/v1/auth/login")
+                        .header(header::CONTENT_TYPE, "application/json")
+                        .body(Body::from(body.to_string()))
+                        .expect("request"),
+                )
+                .await
+                .expect("response")
+                .status()
+            }
+        };
+
+        for _ in 0..5 {
+            assert_eq!(login("alice", "wrong").await, StatusCode::UNAUTHORIZED);
+        }
+        assert_eq!(
+            login("alice", "password123").await,
+            StatusCode::TOO_MANY_REQUESTS
+        );
+        assert_eq!(login("bob", "password123").await, StatusCode::OK);
+
+        let _ = std::fs::remove_dir_all(static_dir);
+        let _ = std::fs::remove_dir_all(artifacts_dir);
+    }
+
+    #[tokio::test]
+    async fn failed_logins_share_one_lockout_across_username_case() {
+        let (state, static_dir, artifacts_dir) = setup_test_state(AuthMode::Required, true);
+        create_user(&state, "alice", "password123").await;
+        let app = build_app(state, Some(static_dir.clone()));
+
+        let login = |username: &str, password: &str| {
+            let app = app.clone();
+            let body = serde_json::json!({ "username": username, "password": password });
+            async move {
+                app.oneshot(
+                    Request::builder()
+                        .method("POST")
+                        .uri("/api/v1/auth/login")
+                        .header(header::CONTENT_TYPE, "application/json")
+                        .body(Body::from(body.to_string()))
+                        .expect("request"),
+                )
+                .await
+                .expect("response")
+                .status()
+            }
+        };
+
+        for username in ["alice", "Alice", "ALICE", "aLiCe", "alicE"] {
+            assert_eq!(login(username, "wrong").await, StatusCode::UNAUTHORIZED);
+        }
+        assert_eq!(
+            login("ALICE", "password123").await,
+            StatusCode::TOO_MANY_REQUESTS
+        );
+        assert_eq!(
+            login("alice", "password123").await,
+            StatusCode::TOO_MANY_REQUESTS
+        );
+
+        let _ = std::fs::remove_dir_all(static_dir);
+        let _ = std::fs::remove_dir_all(artifacts_dir);
+    }
+
+    #[test]
+    fn login_limiter_resets_on_success_and_after_window() {
+        let limiter = LoginLimiter::new(2, Duration::from_secs(60));
+        limiter.record_failure("alice");
+        limiter.reset("alice");
+        limiter.record_failure("alice");
+        assert!(limiter.check("alice").is_ok());
+        limiter.record_failure("alice");
+        assert_eq!(
+            limiter.check("alice").expect_err("locked out").status,
+            StatusCode::TOO_MANY_REQUESTS
+        );
+
+        let expired = LoginLimiter::new(1, Duration::ZERO);
+        expired.record_failure("alice");
+        assert!(expired.check("alice").is_ok());
+    }
+
+    #[test]
+    fn login_limiter_prunes_expired_failures() {
+        let limiter = LoginLimiter::new(1, Duration::ZERO);
+        for username in ["a", "b", "c"] {
+            limiter.record_failure(username);
+        }
+        assert_eq!(limiter.failures.lock().expect("lock").len(), 1);
+    }
+
+    #[tokio::test]
+    async fn failed_logins_for_unknown_users_are_not_tracked() {
+        let (state, static_dir, artifacts_dir) = setup_test_state(AuthMode::Required, true);
+        for username in ["ghost1", "ghost2", "ghost3"] {
+            let error = create_session_for_credentials(&state, username, "wrong")
+                .await
+                .expect_err("unknown user");
+            assert_eq!(error.status, StatusCode::UNAUTHORIZED);
+        }
+        assert!(
+            state
+                .login_limiter
+                .failures
+                .lock()
+                .expect("lock")
+                .is_empty()
+        );
+
+        let _ = std::fs::remove_dir_all(static_dir);
+        let _ = std::fs::remove_dir_all(artifacts_dir);
+    }
+
+    async fn capabilities_allow_origin(app: &Router, origin: &str) -> Option<String> {
+        let resp = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri("/api/v1/capabilities")
+                    .header(header::ORIGIN, origin)
+                    .body(Body::empty())
+                    .expect("request"),
+            )
+            .await
+            .expect("response");
+        assert_eq!(resp.status(), StatusCode::OK);
+        resp.headers()
+            .get(header::ACCESS_CONTROL_ALLOW_ORIGIN)
+            .map(|value| value.to_str().expect("ascii origin").to_string())
+    }
+
+    #[tokio::test]
+    async fn cors_reflects_configured_origins_only() {
+        let (state, static_dir, artifacts_dir) = setup_test_state(AuthMode::Required, true);
+        let app = build_app(state, Some(static_dir.clone()));
+
+        assert_eq!(
+            capabilities_allow_origin(&app, "https://game.example").await,
+            Some("https://game.example".to_string())
+        );
+        assert_eq!(
+            capabilities_allow_origin(&app, "https://evil.example").await,
+            None
+        );
+
+        let _ = std::fs::remove_dir_all(static_dir);
+        let _ = std::fs::remove_dir_all(artifacts_dir);
+    }
+
+    #[test]
+    fn empty_cors_origins_are_only_allowed_without_auth() {
+        assert!(cors_layer(AuthMode::Disabled, &[]).is_ok());
+        let error = cors_layer(AuthMode::Required, &[]).expect_err("must refuse");
+        assert!(error.contains("BOTRACERS_ALLOWED_ORIGINS"), "{error}");
+        assert!(cors_layer(AuthMode::Required, &["bad\norigin".to_string()]).is_err());
+    }
+
+    #[tokio::test]
+    async fn capabilities_include_registration_enabled() {
+        let (state, static_dir, artifacts_dir) = setup_test_state(AuthMode::Required, false);
+        let app = build_app(state, Some(static_dir.clone()));
+        let resp = app
+            .oneshot(
+                Request::builder()
+                    .uri("/api/v1/capabilities")
+                    .body(Body::empty())
+                    .expect("request"),
+            )
+            .await
+            .expect("response");
+
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body = to_bytes(resp.into_body(), usize::MAX).await.expect("body");
+        let caps: ServerCapabilities = serde_json::from_slice(&body).expect("caps json");
+        assert!(caps.auth_required);
+        assert!(!caps.registration_enabled);
+        assert_eq!(caps.supported_targets, vec![DEFAULT_ARTIFACT_TARGET]);
+
+        let _ = std::fs::remove_dir_all(static_dir);
+        let _ = std::fs::remove_dir_all(artifacts_dir);
+    }
+
+    #[tokio::test]
+    async fn capabilities_advertise_version_upload_limit_and_features() {
+        let (state, static_dir, artifacts_dir) = setup_test_state(AuthMode::Required, true);
+        let app = build_app(state, Some(static_dir.clone()));
+        let resp = app
+            .oneshot(
+                Request::builder()
+                    .uri("/api/v1/capabilities")
+                    .body(Body::empty())
+                    .expect("request"),
+            )
+            .await
+            .expect("response");
+
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body = to_bytes(resp.into_body(), usize::MAX).await.expect("body");
+        let json: serde_json::Value = serde_json::from_slice(&body).expect("caps json");
+        assert_eq!(json["api_version"], "v1");
+        assert_eq!(json["max_artifact_bytes"], 1024);
+        assert_eq!(
+            json["features"],
+            serde_json::json!([
+                "leaderboard",
+                "race_records",
+                "replays",
+                "metrics",
+                "artifact_events",
+                "audit_log"
+            ])
+        );
+
+        let _ = std::fs::remove_dir_all(static_dir);
+        let _ = std::fs::remove_dir_all(artifacts_dir);
+    }
+
+    #[tokio::test]
+    async fn api_register_blocked_when_registration_disabled() {
+        let (state, static_dir, artifacts_dir) = setup_test_state(AuthMode::Required, false);
+        let app = build_app(state, Some(static_dir.clone()));
+        let resp = app
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/api/v1/auth/register")
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(
+                        "{\"username\":\"alice\",\"password\":\"password123\"}",
+                    ))
+                    .expect("request"),
+            )
+            .await
+            .expect("response");
+
+        assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+        let body = to_bytes(resp.into_body(), usize::MAX).await.expect("body");
+        let error: ErrorResponse = serde_json::from_slice(&body).expect("error json");
+        assert_eq!(error.error, "registration is disabled");
+
+        let _ = std::fs::remove_dir_all(static_dir);
+        let _ = std::fs::remove_dir_all(artifacts_dir);
+    }
+
+    #[tokio::test]
+    async fn register_flow_creates_session_and_redirects() {
+        let (state, static_dir, artifacts_dir) = setup_test_state(AuthMode::Required, true);
+        let app = build_app(state, Some(static_dir.clone()));
+        let resp = app
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/register")
+                    .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+                    .body(Body::from(
+                        "username=bob&password=password123&next=%2Findex.html",
+                    ))
+                    .expect("request"),
+            )
+            .await
+            .expect("response");
+
+        assert_eq!(resp.status(), StatusCode::SEE_OTHER);
+        assert_eq!(
+            resp.headers()
+                .get(header::LOCATION)
+                .expect("location")
+                .to_str()
+                .expect("location str"),
+            "/index.html"
+        );
+        assert!(resp.headers().contains_key(header::SET_COOKIE));
+
+        let _ = std::fs::remove_dir_all(static_dir);
+        let _ = std::fs::remove_dir_all(artifacts_dir);
+    }
+
+    #[test]
+    fn username_rules_report_each_violation() {
+        let message = |name: &str| validate_username(name).expect_err(name).message;
+        assert!(message("ab").contains("3 to 32"));
+        assert!(message(&"a".repeat(33)).contains("3 to 32"));
+        assert!(message("bob smith").contains("only contain"));
+        assert!(message("bøb").contains("only contain"));
+        assert!(message("-bob").contains("start and end"));
+        assert!(message("bob_").contains("start and end"));
+        validate_username("bob-the_3rd").expect("valid name");
+    }
+
+    #[tokio::test]
+    async fn api_register_stores_username_lowercase() {
+        let (state, static_dir, artifacts_dir) = setup_test_state(AuthMode::Required, true);
+        let app = build_app(state.clone(), Some(static_dir.clone()));
+        let payload = RegisterRequest {
+            username: "Alice_99".to_string(),
+            password: "password123".to_string(),
+            pow_challenge: None,
+            pow_nonce: None,
+        };
+        let resp = app
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/api/v1/auth/register")
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(
+                        serde_json::to_vec(&payload).expect("serialize payload"),
+                    ))
+                    .expect("request"),
+            )
+            .await
+            .expect("response");
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body = to_bytes(resp.into_body(), usize::MAX).await.expect("body");
+        let user: UserInfo = serde_json::from_slice(&body).expect("user json");
+        assert_eq!(user.username, "alice_99");
+
+        // Logging in with the original spelling still finds the account.
+        let (session_user, _) = create_session_for_credentials(&state, "Alice_99", "password123")
+            .await
+            .expect("login");
+        assert_eq!(session_user.id, user.id);
+
+        let _ = std::fs::remove_dir_all(static_dir);
+        let _ = std::fs::remove_dir_all(artifacts_dir);
+    }
+
+    async fn fetch_pow_challenge(app: &Router) -> PowChallengeResponse {
+        let resp = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri("/api/v1/auth/pow-challenge")
+                    .body(Body::empty())
+                    .expect("request"),
+            )
+            .await
+            .expect("response");
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body = to_bytes(resp.into_body(), usize::MAX).await.expect("body");
+        serde_json::from_slice(&body).expect("challenge json")
+    }
+
+    /// The first nonce whose hash has (`solved`) or lacks (`!solved`) the
+    /// required leading zero bits.
+    fn pow_nonce(challenge: &PowChallengeResponse, solved: bool) -> String {
+        (0u64..)
+            .map(|nonce| nonce.to_string())
+            .find(|nonce| {
+                let digest = Sha256::digest(format!("{}{nonce}", challenge.challenge));
+                (leading_zero_bits(&digest) >= u32::from(challenge.difficulty)) == solved
+            })
+            .expect("nonce")
+    }
+
+    async fn register_with_pow(
+        app: &Router,
+        username: &str,
+        pow: Option<(&str, &str)>,
+    ) -> StatusCode {
+        let payload = RegisterRequest {
+            username: username.to_string(),
+            password: "password123".to_string(),
+            pow_challenge: pow.map(|(challenge, _)| challenge.to_string()),
+            pow_nonce: pow.map(|(_, nonce)| nonce.to_string()),
+        };
+        app.clone()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/api/v1/auth/register")
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(
+                        serde_json::to_vec(&payload).expect("serialize payload"),
+                    ))
+                    .expect("request"),
+            )
+            .await
+            .expect("response")
+            .status()
+    }
+
+    #[tokio::test]
+    async fn registration_accepts_a_solved_pow_challenge_once() {
+        let (mut state, static_dir, artifacts_dir) = setup_test_state(AuthMode::Required, true);
+        state.pow = Some(PowChallenges::new(8));
+        let app = build_app(state, Some(static_dir.clone()));
+
+        let challenge = fetch_pow_challenge(&app).await;
+        assert_eq!(challenge.difficulty, 8);
+        let nonce = pow_nonce(&challenge, true);
+        let pow = Some((challenge.challenge.as_str(), nonce.as_str()));
+        assert_eq!(register_with_pow(&app, "alice", pow).await, StatusCode::OK);
+        // Challenges are single use.
+        assert_eq!(
+            register_with_pow(&app, "bob", pow).await,
+            StatusCode::BAD_REQUEST
+        );
+
+        let _ = std::fs::remove_dir_all(static_dir);
+        let _ = std::fs::remove_dir_all(artifacts_dir);
+    }
+
+    #[tokio::test]
+    async fn registration_rejects_missing_or_unsolved_pow() {
+        let (mut state, static_dir, artifacts_dir) = setup_test_state(AuthMode::Required, true);
+        state.pow = Some(PowChallenges::new(8));
+        let app = build_app(state.clone(), Some(static_dir.clone()));
+
+        assert_eq!(
+            register_with_pow(&app, "alice", None).await,
+            StatusCode::BAD_REQUEST
+        );
+        let challenge = fetch_pow_challenge(&app).await;
+        let nonce = pow_nonce(&challenge, false);
+        assert_eq!(
+            register_with_pow(&app, "alice", Some((&challenge.challenge, &nonce))).await,
+            StatusCode::BAD_REQUEST
+        );
+        assert_eq!(
+            register_with_pow(&app, "alice", Some(("not-issued", "0"))).await,
+            StatusCode::BAD_REQUEST
+        );
+        let users: i64 = state
+            .db()
+            .expect("db")
+            .query_row(
+                "SELECT COUNT(*) FROM users WHERE username = 'alice'",
+                [],
+                |r| r.get(0),
+            )
+            .expect("count users");
+        assert_eq!(users, 0);
+
+        let _ = std::fs::remove_dir_all(static_dir);
+        let _ = std::fs::remove_dir_all(artifacts_dir);
+    }
+
+    #[tokio::test]
+    async fn register_routes_redirect_when_disabled() {
+        let (state, static_dir, artifacts_dir) = setup_test_state(AuthMode::Required, false);
+        let app = build_app(state, Some(static_dir.clone()));
+        let resp = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri("/register?next=%2Findex.html")
+                    .body(Body::empty())
+                    .expect("request"),
+            )
+            .await
+            .expect("response");
+        assert_eq!(resp.status(), StatusCode::SEE_OTHER);
+        assert_eq!(
+            resp.headers()
+                .get(header::LOCATION)
+                .expect("location")
+                .to_str()
+                .expect("location str"),
+            "/login?next=%2Findex.html&error=registration_disabled"
+        );
+
+        let resp = app
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/register")
+                    .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+                    .body(Body::from(
+                        "username=bob&password=password123&next=%2Findex.html",
+                    ))
+                    .expect("request"),
+            )
+            .await
+            .expect("response");
+        assert_eq!(resp.status(), StatusCode::SEE_OTHER);
+        assert_eq!(
+            resp.headers()
+                .get(header::LOCATION)
+                .expect("location")
+                .to_str()
+                .expect("location str"),
+            "/login?next=%2Findex.html&error=registration_disabled"
+        );
+
+        let _ = std::fs::remove_dir_all(static_dir);
+        let _ = std::fs::remove_dir_all(artifacts_dir);
+    }
+
+    #[tokio::test]
+    async fn login_page_hides_register_link_when_disabled() {
+        let (state, static_dir, artifacts_dir) = setup_test_state(AuthMode::Required, false);
+        let app = build_app(state, Some(static_dir.clone()));
+        let resp = app
+            .oneshot(
+                Request::builder()
+                    .uri("/login?next=%2F")
+                    .body(Body::empty())
+                    .expect("request"),
+            )
+            .await
+            .expect("response");
+        let body = to_bytes(resp.into_body(), usize::MAX).await.expect("body");
+        let text = String::from_utf8(body.to_vec()).expect("utf8");
+        assert!(!text.contains("Create account"));
+
+        let _ = std::fs::remove_dir_all(static_dir);
+        let _ = std::fs::remove_dir_all(artifacts_dir);
+    }
+
+    #[tokio::test]
+    async fn uploads_are_private_by_default() {
+        let (state, static_dir, artifacts_dir) = setup_test_state(AuthMode::Required, true);
+        create_user(&state, "alice", "password123").await;
+        let alice_cookie = make_session_cookie(&state, "alice", "password123").await;
+        let app = build_app(state, Some(static_dir.clone()));
+
+        let (status, artifact_id) = upload_artifact_with_cookie(&app, &alice_cookie, "a.elf").await;
+        assert_eq!(status, StatusCode::OK);
+
+        let artifacts = list_artifacts_with_cookie(&app, &alice_cookie).await;
+        let artifact = artifacts
+            .into_iter()
+            .find(|a| a.id == artifact_id)
+            .expect("artifact exists");
+        assert!(!artifact.is_public);
+        assert!(artifact.owned_by_me);
+
+        let _ = std::fs::remove_dir_all(static_dir);
+        let _ = std::fs::remove_dir_all(artifacts_dir);
+    }
+
+    #[tokio::test]
+    async fn list_artifacts_in_required_mode_shows_own_and_public_others() {
+        let (state, static_dir, artifacts_dir) = setup_test_state(AuthMode::Required, true);
+        create_user(&state, "alice", "password123").await;
+        create_user(&state, "bob", "password123").await;
+        let alice_cookie = make_session_cookie(&state, "alice", "password123").await;
+        let bob_cookie = make_session_cookie(&state, "bob", "password123").await;
+        let app = build_app(state, Some(static_dir.clone()));
+
+        let (_, alice_artifact_id) =
+            upload_artifact_with_cookie(&app, &alice_cookie, "alice.elf").await;
+        let (_, bob_artifact_id) = upload_artifact_with_cookie(&app, &bob_cookie, "bob.elf").await;
+        assert_eq!(
+            update_visibility_with_cookie(&app, &bob_cookie, bob_artifact_id, true).await,
+            StatusCode::NO_CONTENT
+        );
+
+        let alice_view = list_artifacts_with_cookie(&app, &alice_cookie).await;
+        assert!(
+            alice_view
+                .iter()
+                .any(|a| a.id == alice_artifact_id && a.owned_by_me)
+        );
+        assert!(
+            alice_view
+                .iter()
+                .any(|a| a.id == bob_artifact_id && !a.owned_by_me && a.is_public)
+        );
+
+        let _ = std::fs::remove_dir_all(static_dir);
+        let _ = std::fs::remove_dir_all(artifacts_dir);
+    }
+
+    #[tokio::test]
+    async fn list_artifacts_hides_private_others() {
+        let (state, static_dir, artifacts_dir) = setup_test_state(AuthMode::Required, true);
+        create_user(&state, "alice", "password123").await;
+        create_user(&state, "bob", "password123").await;
+        let alice_cookie = make_session_cookie(&state, "alice", "password123").await;
+        let bob_cookie = make_session_cookie(&state, "bob", "password123").await;
+        let app = build_app(state, Some(static_dir.clone()));
+
+        let (_, bob_artifact_id) = upload_artifact_with_cookie(&app, &bob_cookie, "bob.elf").await;
+        let alice_view = list_artifacts_with_cookie(&app, &alice_cookie).await;
+        assert!(!alice_view.iter().any(|a| a.id == bob_artifact_id));
+
+        let _ = std::fs::remove_dir_all(static_dir);
+        let _ = std::fs::remove_dir_all(artifacts_dir);
+    }
+
+    #[tokio::test]
+    async fn owners_add_and_remove_artifact_tags() {
+        let (state, static_dir, artifacts_dir) = setup_test_state(AuthMode::Required, true);
+        create_user(&state, "alice", "password123").await;
+        create_user(&state, "bob", "password123").await;
+        let alice_cookie = make_session_cookie(&state, "alice", "password123").await;
+        let bob_cookie = make_session_cookie(&state, "bob", "password123").await;
+        let app = build_app(state, Some(static_dir.clone()));
+
+        let (_, artifact_id) = upload_artifact_with_cookie(&app, &alice_cookie, "alice.elf").await;
+        assert_eq!(
+            update_visibility_with_cookie(&app, &alice_cookie, artifact_id, true).await,
+            StatusCode::NO_CONTENT
+        );
+        for tag in ["Aggressive", "v2", "aggressive"] {
+            assert_eq!(
+                tag_artifact_with_cookie(&app, &alice_cookie, Method::POST, artifact_id, tag).await,
+                StatusCode::NO_CONTENT
+            );
+        }
+        let tags_seen_by = |artifacts: Vec<ArtifactSummary>| {
+            artifacts
+                .into_iter()
+                .find(|a| a.id == artifact_id)
+                .expect("artifact listed")
+                .tags
+        };
+        assert_eq!(
+            tags_seen_by(list_artifacts_with_cookie(&app, &bob_cookie).await),
+            ["aggressive", "v2"]
+        );
+
+        assert_eq!(
+            tag_artifact_with_cookie(&app, &bob_cookie, Method::POST, artifact_id, "mine").await,
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(
+            tag_artifact_with_cookie(&app, &bob_cookie, Method::DELETE, artifact_id, "v2").await,
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(
+            tag_artifact_with_cookie(&app, &alice_cookie, Method::POST, artifact_id, "no spaces")
+                .await,
+            StatusCode::BAD_REQUEST
+        );
+
+        assert_eq!(
+            tag_artifact_with_cookie(&app, &alice_cookie, Method::DELETE, artifact_id, "V2").await,
+            StatusCode::NO_CONTENT
+        );
+        assert_eq!(
+            tags_seen_by(list_artifacts_with_cookie(&app, &alice_cookie).await),
+            ["aggressive"]
+        );
+
+        let _ = std::fs::remove_dir_all(static_dir);
+        let _ = std::fs::remove_dir_all(artifacts_dir);
+    }
+
+    #[tokio::test]
+    async fn artifacts_hold_at_most_ten_tags() {
+        let (state, static_dir, artifacts_dir) = setup_test_state(AuthMode::Required, true);
+        create_user(&state, "alice", "password123").await;
+        let cookie = make_session_cookie(&state, "alice", "password123").await;
+        let app = build_app(state, Some(static_dir.clone()));
+
+        let (_, artifact_id) = upload_artifact_with_cookie(&app, &cookie, "alice.elf").await;
+        for i in 0..ARTIFACT_TAGS_MAX {
+            let tag = format!("tag{i}");
+            assert_eq!(
+                tag_artifact_with_cookie(&app, &cookie, Method::POST, artifact_id, &tag).await,
+                StatusCode::NO_CONTENT
+            );
+        }
+        assert_eq!(
+            tag_artifact_with_cookie(&app, &cookie, Method::POST, artifact_id, "one-more").await,
+            StatusCode::BAD_REQUEST
+        );
+        // Re-adding an existing tag does not count against the limit.
+        assert_eq!(
+            tag_artifact_with_cookie(&app, &cookie, Method::POST, artifact_id, "tag0").await,
+            StatusCode::NO_CONTENT
+        );
+        assert_eq!(
+            tag_artifact_with_cookie(&app, &cookie, Method::DELETE, artifact_id, "tag0").await,
+            StatusCode::NO_CONTENT
+        );
+        assert_eq!(
+            tag_artifact_with_cookie(&app, &cookie, Method::POST, artifact_id, "one-more").await,
+            StatusCode::NO_CONTENT
+        );
+
+        let _ = std::fs::remove_dir_all(static_dir);
+        let _ = std::fs::remove_dir_all(artifacts_dir);
+    }
+
+    #[tokio::test]
+    async fn list_artifacts_filters_by_tag() {
+        let (state, static_dir, artifacts_dir) = setup_test_state(AuthMode::Required, true);
+        create_user(&state, "alice", "password123").await;
+        create_user(&state, "bob", "password123").await;
+        let alice_cookie = make_session_cookie(&state, "alice", "password123").await;
+        let bob_cookie = make_session_cookie(&state, "bob", "password123").await;
+        let app = build_app(state, Some(static_dir.clone()));
+
+        let (_, fast) = upload_artifact_with_cookie(&app, &alice_cookie, "fast.elf").await;
+        let (_, slow) = upload_artifact_with_cookie(&app, &alice_cookie, "slow.elf").await;
+        let (_, hidden) = upload_artifact_with_cookie(&app, &bob_cookie, "hidden.elf").await;
+        for (cookie, id) in [(&alice_cookie, fast), (&bob_cookie, hidden)] {
+            assert_eq!(
+                tag_artifact_with_cookie(&app, cookie, Method::POST, id, "aggressive").await,
+                StatusCode::NO_CONTENT
+            );
+        }
+        assert_eq!(
+            tag_artifact_with_cookie(&app, &alice_cookie, Method::POST, slow, "careful").await,
+            StatusCode::NO_CONTENT
+        );
+
+        // Bob's private artifact stays hidden from alice even when it matches.
+        assert_eq!(
+            list_artifacts_tagged(&app, &alice_cookie, "aggressive").await,
+            [fast]
+        );
+        assert_eq!(
+            list_artifacts_tagged(&app, &alice_cookie, "Careful").await,
+            [slow]
+        );
+        assert!(
+            list_artifacts_tagged(&app, &alice_cookie, "unused")
+                .await
+                .is_empty()
+        );
+
+        let _ = std::fs::remove_dir_all(static_dir);
+        let _ = std::fs::remove_dir_all(artifacts_dir);
+    }
+
+    #[tokio::test]
+    async fn download_public_artifact_allowed_for_non_owner() {
+        let (state, static_dir, artifacts_dir) = setup_test_state(AuthMode::Required, true);
+        create_user(&state, "alice", "password123").await;
+        create_user(&state, "bob", "password123").await;
+        let alice_cookie = make_session_cookie(&state, "alice", "password123").await;
+        let bob_cookie = make_session_cookie(&state, "bob", "password123").await;
+        let app = build_app(state, Some(static_dir.clone()));
+
+        let (_, artifact_id) = upload_artifact_with_cookie(&app, &bob_cookie, "bob.elf").await;
+        assert_eq!(
+            update_visibility_with_cookie(&app, &bob_cookie, artifact_id, true).await,
+            StatusCode::NO_CONTENT
+        );
+        assert_eq!(
+            download_artifact_with_cookie(&app, &alice_cookie, artifact_id).await,
+            StatusCode::OK
+        );
+
+        let _ = std::fs::remove_dir_all(static_dir);
+        let _ = std::fs::remove_dir_all(artifacts_dir);
+    }
+
+    #[tokio::test]
+    async fn download_private_artifact_denied_for_non_owner() {
+        let (state, static_dir, artifacts_dir) = setup_test_state(AuthMode::Required, true);
+        create_user(&state, "alice", "password123").await;
+        create_user(&state, "bob", "password123").await;
+        let alice_cookie = make_session_cookie(&state, "alice", "password123").await;
+        let bob_cookie = make_session_cookie(&state, "bob", "password123").await;
+        let app = build_app(state, Some(static_dir.clone()));
+
+        let (_, artifact_id) = upload_artifact_with_cookie(&app, &bob_cookie, "bob.elf").await;
+        assert_eq!(
+            download_artifact_with_cookie(&app, &alice_cookie, artifact_id).await,
+            StatusCode::FORBIDDEN
+        );
+
+        let _ = std::fs::remove_dir_all(static_dir);
+        let _ = std::fs::remove_dir_all(artifacts_dir);
+    }
+
+    #[tokio::test]
+    async fn download_without_session_is_unauthorized() {
+        let (state, static_dir, artifacts_dir) = setup_test_state(AuthMode::Required, true);
+        create_user(&state, "bob", "password123").await;
+        let bob_cookie = make_session_cookie(&state, "bob", "password123").await;
+        let app = build_app(state, Some(static_dir.clone()));
+
+        let (_, artifact_id) = upload_artifact_with_cookie(&app, &bob_cookie, "bob.elf").await;
+        let resp = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/api/v1/artifacts/{artifact_id}"))
+                    .body(Body::empty())
+                    .expect("request"),
+            )
+            .await
+            .expect("response");
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+
+        let _ = std::fs::remove_dir_all(static_dir);
+        let _ = std::fs::remove_dir_all(artifacts_dir);
+    }
+
+    #[tokio::test]
+    async fn owner_can_toggle_visibility() {
+        let (state, static_dir, artifacts_dir) = setup_test_state(AuthMode::Required, true);
+        create_user(&state, "alice", "password123").await;
+        let alice_cookie = make_session_cookie(&state, "alice", "password123").await;
+        let app = build_app(state, Some(static_dir.clone()));
+
+        let (_, artifact_id) = upload_artifact_with_cookie(&app, &alice_cookie, "alice.elf").await;
+        assert_eq!(
+            update_visibility_with_cookie(&app, &alice_cookie, artifact_id, true).await,
+            StatusCode::NO_CONTENT
+        );
+
+        let artifacts = list_artifacts_with_cookie(&app, &alice_cookie).await;
+        let artifact = artifacts
+            .into_iter()
+            .find(|a| a.id == artifact_id)
+            .expect("artifact exists");
+        assert!(artifact.is_public);
+
+        let _ = std::fs::remove_dir_all(static_dir);
+        let _ = std::fs::remove_dir_all(artifacts_dir);
+    }
+
+    #[tokio::test]
+    async fn non_owner_cannot_toggle_visibility() {
+        let (state, static_dir, artifacts_dir) = setup_test_state(AuthMode::Required, true);
+        create_user(&state, "alice", "password123").await;
+        create_user(&state, "bob", "password123").await;
+        let alice_cookie = make_session_cookie(&state, "alice", "password123").await;
+        let bob_cookie = make_session_cookie(&state, "bob", "password123").await;
+        let app = build_app(state, Some(static_dir.clone()));
+
+        let (_, artifact_id) = upload_artifact_with_cookie(&app, &bob_cookie, "bob.elf").await;
+        assert_eq!(
+            update_visibility_with_cookie(&app, &alice_cookie, artifact_id, true).await,
+            StatusCode::UNAUTHORIZED
+        );
+
+        let _ = std::fs::remove_dir_all(static_dir);
+        let _ = std::fs::remove_dir_all(artifacts_dir);
+    }
+
+    #[tokio::test]
+    async fn owner_can_rename_artifact_and_partial_update_keeps_note() {
+        let (state, static_dir, artifacts_dir) = setup_test_state(AuthMode::Required, true);
+        create_user(&state, "alice", "password123").await;
+        let alice_cookie = make_session_cookie(&state, "alice", "password123").await;
+        let app = build_app(state, Some(static_dir.clone()));
+
+        let (_, artifact_id) = upload_artifact_with_cookie(&app, &alice_cookie, "alice.elf").await;
+        let both = UpdateArtifactRequest {
+            name: Some("  fast line  ".to_string()),
+            note: Some("apex tweaks".to_string()),
+        };
+        assert_eq!(
+            update_artifact_with_cookie(&app, &alice_cookie, artifact_id, &both).await,
+            StatusCode::NO_CONTENT
+        );
+
+        let name_only = UpdateArtifactRequest {
+            name: Some("faster line".to_string()),
+            note: None,
+        };
+        assert_eq!(
+            update_artifact_with_cookie(&app, &alice_cookie, artifact_id, &name_only).await,
+            StatusCode::NO_CONTENT
+        );
+
+        let artifact = list_artifacts_with_cookie(&app, &alice_cookie)
+            .await
+            .into_iter()
+            .find(|a| a.id == artifact_id)
+            .expect("artifact exists");
+        assert_eq!(artifact.name, "faster line");
+        assert_eq!(artifact.note.as_deref(), Some("apex tweaks"));
+
+        let empty_name = UpdateArtifactRequest {
+            name: Some("   ".to_string()),
+            note: None,
+        };
+        assert_eq!(
+            update_artifact_with_cookie(&app, &alice_cookie, artifact_id, &empty_name).await,
+            StatusCode::BAD_REQUEST
+        );
+
+        let _ = std::fs::remove_dir_all(static_dir);
+        let _ = std::fs::remove_dir_all(artifacts_dir);
+    }
+
+    #[tokio::test]
+    async fn non_owner_cannot_rename_artifact() {
+        let (state, static_dir, artifacts_dir) = setup_test_state(AuthMode::Required, true);
+        create_user(&state, "alice", "password123").await;
+        create_user(&state, "bob", "password123").await;
+        let alice_cookie = make_session_cookie(&state, "alice", "password123").await;
+        let bob_cookie = make_session_cookie(&state, "bob", "password123").await;
+        let app = build_app(state, Some(static_dir.clone()));
+
+        let (_, artifact_id) = upload_artifact_with_cookie(&app, &bob_cookie, "bob.elf").await;
+        let rename = UpdateArtifactRequest {
+            name: Some("stolen".to_string()),
+            note: None,
+        };
+        assert_eq!(
+            update_artifact_with_cookie(&app, &alice_cookie, artifact_id, &rename).await,
+            StatusCode::UNAUTHORIZED
+        );
+
+        let artifact = list_artifacts_with_cookie(&app, &bob_cookie)
+            .await
+            .into_iter()
+            .find(|a| a.id == artifact_id)
+            .expect("artifact exists");
+        assert_eq!(artifact.name, "bob.elf");
+
+        let _ = std::fs::remove_dir_all(static_dir);
+        let _ = std::fs::remove_dir_all(artifacts_dir);
+    }
+
+    async fn insert_race_record(
+        state: &AppState,
+        username: &str,
+        track_id: &str,
+        replay_json: Option<&str>,
+    ) -> i64 {
+        let db = state.db().expect("db connection");
+        let owner_user_id: i64 = db
+            .query_row(
+                "SELECT id FROM users WHERE username = ?1",
+                params![username],
+                |r| r.get(0),
+            )
+            .expect("lookup user");
+        db.execute(
+            "INSERT INTO race_records (owner_user_id, track_id, result_json, replay_json, created_at) VALUES (?1, ?2, '{}', ?3, ?4)",
+            params![owner_user_id, track_id, replay_json, now_utc()],
+        )
+        .expect("insert race record");
+        db.last_insert_rowid()
+    }
+
+    async fn insert_race_result(state: &AppState, username: &str, track_id: &str, result: &str) {
+        let db = state.db().expect("db connection");
+        db.execute(
+            "INSERT INTO race_records (owner_user_id, track_id, result_json, created_at) SELECT id, ?2, ?3, ?4 FROM users WHERE username = ?1",
+            params![username, track_id, result, now_utc()],
+        )
+        .expect("insert race result");
+    }
+
+    #[tokio::test]
+    async fn leaderboard_ranks_times_and_skips_records_without_one() {
+        let (state, static_dir, artifacts_dir) = setup_test_state(AuthMode::Required, true);
+        create_user(&state, "alice", "password123").await;
+        create_user(&state, "bob", "password123").await;
+        insert_race_result(&state, "alice", "oval", r#"{"time_ms":61000}"#).await;
+        insert_race_result(&state, "bob", "oval", r#"{"time_ms":59500}"#).await;
+        insert_race_result(&state, "bob", "oval", r#"{"laps":3}"#).await;
+        insert_race_result(&state, "alice", "oval", "not json").await;
+        insert_race_result(&state, "alice", "oval", r#"{"time_ms":61000}"#).await;
+        insert_race_result(&state, "alice", "figure8", r#"{"time_ms":1000}"#).await;
+        let cookie = make_session_cookie(&state, "alice", "password123").await;
+        let app = build_app(state, Some(static_dir.clone()));
+
+        let resp = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri("/api/v1/leaderboard?track_id=oval")
+                    .header(header::COOKIE, &cookie)
+                    .body(Body::empty())
+                    .expect("request"),
+            )
+            .await
+            .expect("response");
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body = to_bytes(resp.into_body(), usize::MAX).await.expect("body");
+        let entries: Vec<LeaderboardEntry> =
+            serde_json::from_slice(&body).expect("leaderboard json");
+        let ranked: Vec<(u32, &str, u64)> = entries
+            .iter()
+            .map(|entry| (entry.rank, entry.username.as_str(), entry.time_ms))
+            .collect();
+        assert_eq!(
+            ranked,
+            vec![(1, "bob", 59500), (2, "alice", 61000), (3, "alice", 61000)]
+        );
+
+        let resp = app
+            .oneshot(
+                Request::builder()
+                    .uri("/api/v1/leaderboard")
+                    .header(header::COOKIE, &cookie)
+                    .body(Body::empty())
+                    .expect("request"),
+            )
+            .await
+            .expect("response");
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+        let _ = std::fs::remove_dir_all(static_dir);
+        let _ = std::fs::remove_dir_all(artifacts_dir);
+    }
+
+    #[tokio::test]
+    async fn published_race_records_reach_the_leaderboard() {
+        let (state, static_dir, artifacts_dir) = setup_test_state(AuthMode::Required, true);
+        create_user(&state, "alice", "password123").await;
+        let cookie = make_session_cookie(&state, "alice", "password123").await;
+        let app = build_app(state, Some(static_dir.clone()));
+
+        let publish = |result_json: &str| {
+            let payload = PublishRaceRecordRequest {
+                track_id: "oval".to_string(),
+                result_json: result_json.to_string(),
+            };
+            Request::builder()
+                .method("POST")
+                .uri("/api/v1/race-records")
+                .header(header::COOKIE, &cookie)
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(
+                    serde_json::to_vec(&payload).expect("serialize payload"),
+                ))
+                .expect("request")
+        };
+
+        let resp = app
+            .clone()
+            .oneshot(publish(r#"{"time_ms":42000,"cars":[]}"#))
+            .await
+            .expect("response");
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body = to_bytes(resp.into_body(), usize::MAX).await.expect("body");
+        let published: PublishRaceRecordResponse =
+            serde_json::from_slice(&body).expect("publish json");
+
+        let resp = app
+            .clone()
+            .oneshot(publish("[1, 2]"))
+            .await
+            .expect("response");
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+        let resp = app
+            .oneshot(
+                Request::builder()
+                    .uri("/api/v1/leaderboard?track_id=oval")
+                    .header(header::COOKIE, &cookie)
+                    .body(Body::empty())
+                    .expect("request"),
+            )
+            .await
+            .expect("response");
+        let body = to_bytes(resp.into_body(), usize::MAX).await.expect("body");
+        let entries: Vec<LeaderboardEntry> =
+            serde_json::from_slice(&body).expect("leaderboard json");
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].username, "alice");
+        assert_eq!(entries[0].time_ms, 42000);
+        assert!(published.record_id > 0);
+
+        let _ = std::fs::remove_dir_all(static_dir);
+        let _ = std::fs::remove_dir_all(artifacts_dir);
+    }
+
+    #[tokio::test]
+    async fn failed_artifact_write_leaves_no_row() {
+        let (mut state, static_dir, artifacts_dir) = setup_test_state(AuthMode::Required, true);
+        create_user(&state, "alice", "pw-alice").await;
+        let cookie = make_session_cookie(&state, "alice", "pw-alice").await;
+        // Writes into a directory that does not exist fail even when running as root.
+        state.artifacts_dir = artifacts_dir.join("missing");
+        let db = state.db.clone();
+        let app = build_app(state, Some(static_dir.clone()));
+
+        let payload = UploadArtifactRequest {
+            name: "bot".to_string(),
+            note: None,
+            target: DEFAULT_ARTIFACT_TARGET.to_string(),
+            elf_base64: base64::engine::general_purpose::STANDARD.encode(minimal_riscv_elf()),
+            build_meta_json: None,
+        };
         let resp = app
             .oneshot(
                 Request::builder()
                     .method("POST")
-                    .uri("/api/v1/auth/register")
+                    .uri("/api/v1/artifacts")
+                    .header(header::COOKIE, &cookie)
                     .header(header::CONTENT_TYPE, "application/json")
                     .body(Body::from(
-                        "{\"username\":\"alice\",\"password\":\"password123\"}",
+                        serde_json::to_vec(&payload).expect("serialize payload"),
+                    ))
+                    .expect("request"),
+            )
+            .await
+            .expect("response");
+        assert_eq!(resp.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        let rows: i64 = db
+            .get()
+            .expect("db connection")
+            .query_row("SELECT COUNT(*) FROM artifacts", [], |row| row.get(0))
+            .expect("count artifacts");
+        assert_eq!(rows, 0);
+
+        let _ = std::fs::remove_dir_all(static_dir);
+        let _ = std::fs::remove_dir_all(artifacts_dir);
+    }
+
+    #[tokio::test]
+    async fn metrics_reflect_uploads_downloads_and_logins() {
+        let (state, static_dir, artifacts_dir) = setup_test_state(AuthMode::Required, true);
+        create_user(&state, "alice", "pw-alice").await;
+        let cookie = make_session_cookie(&state, "alice", "pw-alice").await;
+        assert!(
+            create_session_for_credentials(&state, "alice", "wrong")
+                .await
+                .is_err()
+        );
+        let app = build_app(state, Some(static_dir.clone()));
+        let (status, artifact_id) = upload_artifact_with_cookie(&app, &cookie, "bot").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(
+            download_artifact_with_cookie(&app, &cookie, artifact_id).await,
+            StatusCode::OK
+        );
+
+        let resp = app
+            .oneshot(
+                Request::builder()
+                    .uri("/metrics")
+                    .body(Body::empty())
+                    .expect("request"),
+            )
+            .await
+            .expect("response");
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert!(
+            resp.headers()[header::CONTENT_TYPE]
+                .to_str()
+                .expect("content type")
+                .starts_with("text/plain; version=0.0.4")
+        );
+        let body = to_bytes(resp.into_body(), usize::MAX).await.expect("body");
+        let body = String::from_utf8(body.to_vec()).expect("utf8");
+        let lines: Vec<&str> = body.lines().collect();
+        for expected in [
+            "botracers_artifact_uploads_total 1",
+            "botracers_artifact_downloads_total 1",
+            "botracers_logins_total{result=\"success\"} 1",
+            "botracers_logins_total{result=\"failure\"} 1",
+            "botracers_artifacts 1",
+            // alice plus the built-in local user.
+            "botracers_users 2",
+        ] {
+            assert!(
+                lines.contains(&expected),
+                "missing {expected:?} in:\n{body}"
+            );
+        }
+
+        let _ = std::fs::remove_dir_all(static_dir);
+        let _ = std::fs::remove_dir_all(artifacts_dir);
+    }
+
+    #[tokio::test]
+    async fn healthz_reports_database_reachability() {
+        let (mut state, static_dir, artifacts_dir) = setup_test_state(AuthMode::Required, true);
+        let app = build_app(state.clone(), Some(static_dir.clone()));
+        let probe = || {
+            Request::builder()
+                .uri("/healthz")
+                .body(Body::empty())
+                .expect("request")
+        };
+
+        let resp = app.oneshot(probe()).await.expect("response");
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body = to_bytes(resp.into_body(), usize::MAX).await.expect("body");
+        assert_eq!(&body[..], b"ok");
+
+        // A database file in a missing directory can never be opened.
+        state.db = r2d2::Pool::builder().build_unchecked(SqliteConnectionManager::file(
+            artifacts_dir.join("missing").join("botracers.db"),
+        ));
+        let app = build_app(state, Some(static_dir.clone()));
+        let resp = app.oneshot(probe()).await.expect("response");
+        assert_eq!(resp.status(), StatusCode::SERVICE_UNAVAILABLE);
+        let body = to_bytes(resp.into_body(), usize::MAX).await.expect("body");
+        let error: ErrorResponse = serde_json::from_slice(&body).expect("error json");
+        assert!(
+            error.error.starts_with("database unavailable"),
+            "{}",
+            error.error
+        );
+
+        let _ = std::fs::remove_dir_all(static_dir);
+        let _ = std::fs::remove_dir_all(artifacts_dir);
+    }
+
+    #[tokio::test]
+    async fn race_records_list_and_serve_replays() {
+        let (state, static_dir, artifacts_dir) = setup_test_state(AuthMode::Required, true);
+        create_user(&state, "alice", "password123").await;
+        let cookie = make_session_cookie(&state, "alice", "password123").await;
+        let replay = r#"{"tick_hz":200,"cars":[{"name":"Car 1"}],"frames":[{"poses":[{"x":1.0,"y":2.0,"heading":0.5}]}]}"#;
+        let with_replay = insert_race_record(&state, "alice", "track1", Some(replay)).await;
+        let without_replay = insert_race_record(&state, "alice", "track1", None).await;
+        let app = build_app(state, Some(static_dir.clone()));
+
+        let resp = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri("/api/v1/race-records")
+                    .header(header::COOKIE, &cookie)
+                    .body(Body::empty())
+                    .expect("request"),
+            )
+            .await
+            .expect("response");
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body = to_bytes(resp.into_body(), usize::MAX).await.expect("body");
+        let records: Vec<RaceRecordSummary> =
+            serde_json::from_slice(&body).expect("race record list json");
+        assert_eq!(records.len(), 2);
+        let record = records
+            .iter()
+            .find(|r| r.id == with_replay)
+            .expect("record exists");
+        assert!(record.has_replay);
+        assert_eq!(record.owner_username, "alice");
+
+        let resp = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/api/v1/race-records/{with_replay}/replay"))
+                    .header(header::COOKIE, &cookie)
+                    .body(Body::empty())
+                    .expect("request"),
+            )
+            .await
+            .expect("response");
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body = to_bytes(resp.into_body(), usize::MAX).await.expect("body");
+        let parsed: botracers_protocol::RaceReplay =
+            serde_json::from_slice(&body).expect("replay json");
+        assert_eq!(parsed.frames.len(), 1);
+
+        let resp = app
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/api/v1/race-records/{without_replay}/replay"))
+                    .header(header::COOKIE, &cookie)
+                    .body(Body::empty())
+                    .expect("request"),
+            )
+            .await
+            .expect("response");
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+
+        let _ = std::fs::remove_dir_all(static_dir);
+        let _ = std::fs::remove_dir_all(artifacts_dir);
+    }
+
+    #[tokio::test]
+    async fn oversized_request_body_is_rejected_with_json_413() {
+        let (state, static_dir, artifacts_dir) = setup_test_state(AuthMode::Required, true);
+        create_user(&state, "alice", "pw-alice").await;
+        let cookie = make_session_cookie(&state, "alice", "pw-alice").await;
+        let limit = request_body_limit(state.max_artifact_bytes);
+        let app = build_app(state, Some(static_dir.clone()));
+
+        let payload = UploadArtifactRequest {
+            name: "huge".to_string(),
+            note: None,
+            target: "riscv32imafc-unknown-none-elf".to_string(),
+            elf_base64: "A".repeat(limit + 4),
+            build_meta_json: None,
+        };
+        let resp = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/api/v1/artifacts")
+                    .header(header::COOKIE, &cookie)
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(
+                        serde_json::to_vec(&payload).expect("serialize payload"),
+                    ))
+                    .expect("request"),
+            )
+            .await
+            .expect("response");
+        assert_eq!(resp.status(), StatusCode::PAYLOAD_TOO_LARGE);
+        let body = to_bytes(resp.into_body(), usize::MAX).await.expect("body");
+        let parsed: ErrorResponse = serde_json::from_slice(&body).expect("error json");
+        assert_eq!(parsed.error, "request body too large");
+
+        let (status, _) = upload_artifact_with_cookie(&app, &cookie, "small").await;
+        assert_eq!(status, StatusCode::OK);
+
+        let _ = std::fs::remove_dir_all(static_dir);
+        let _ = std::fs::remove_dir_all(artifacts_dir);
+    }
+
+    #[tokio::test]
+    async fn upload_with_unsupported_target_is_rejected() {
+        let (state, static_dir, artifacts_dir) = setup_test_state(AuthMode::Required, true);
+        create_user(&state, "alice", "pw-alice").await;
+        let cookie = make_session_cookie(&state, "alice", "pw-alice").await;
+        let app = build_app(state, Some(static_dir.clone()));
+
+        let payload = UploadArtifactRequest {
+            name: "x86 bot".to_string(),
+            note: None,
+            target: "x86_64-unknown-linux-gnu".to_string(),
+            elf_base64: base64::engine::general_purpose::STANDARD.encode(minimal_riscv_elf()),
+            build_meta_json: None,
+        };
+        let resp = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/api/v1/artifacts")
+                    .header(header::COOKIE, &cookie)
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(
+                        serde_json::to_vec(&payload).expect("serialize payload"),
                     ))
                     .expect("request"),
             )
             .await
             .expect("response");
-
-        assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
         let body = to_bytes(resp.into_body(), usize::MAX).await.expect("body");
-        let error: ErrorResponse = serde_json::from_slice(&body).expect("error json");
-        assert_eq!(error.error, "registration is disabled");
+        let parsed: ErrorResponse = serde_json::from_slice(&body).expect("error json");
+        assert!(parsed.error.contains("x86_64-unknown-linux-gnu"));
+        assert!(parsed.error.contains(DEFAULT_ARTIFACT_TARGET));
+
+        let listed = list_artifacts_with_cookie(&app, &cookie).await;
+        assert!(listed.is_empty());
 
         let _ = std::fs::remove_dir_all(static_dir);
         let _ = std::fs::remove_dir_all(artifacts_dir);
     }
 
+    /// Audit rows are written by a background task; wait until `count` exist.
+    async fn wait_for_audit_rows(state: &AppState, count: i64) {
+        for _ in 0..1000 {
+            let written: i64 = state
+                .db()
+                .expect("db connection")
+                .query_row("SELECT COUNT(*) FROM audit_log", [], |r| r.get(0))
+                .expect("count audit rows");
+            if written >= count {
+                return;
+            }
+            tokio::task::yield_now().await;
+        }
+        panic!("expected {count} audit rows");
+    }
+
     #[tokio::test]
-    async fn register_flow_creates_session_and_redirects() {
+    async fn login_and_upload_are_audited_and_listed_for_admins() {
         let (state, static_dir, artifacts_dir) = setup_test_state(AuthMode::Required, true);
-        let app = build_app(state, Some(static_dir.clone()));
+        create_user(&state, "alice", "password123").await;
+        create_user(&state, "admin", "password123").await;
+        let admin_cookie = make_session_cookie(&state, "admin", "password123").await;
+        let app = build_app(state.clone(), Some(static_dir.clone()));
+
         let resp = app
+            .clone()
             .oneshot(
                 Request::builder()
                     .method("POST")
-                    .uri("/register")
-                    .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+                    .uri("/api/v1/auth/login")
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .header("x-forwarded-for", "203.0.113.7, 10.0.0.1")
                     .body(Body::from(
-                        "username=bob&password=password123&next=%2Findex.html",
+                        r#"{"username":"alice","password":"password123"}"#,
                     ))
                     .expect("request"),
             )
             .await
             .expect("response");
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body = to_bytes(resp.into_body(), usize::MAX).await.expect("body");
+        let login: LoginResponse = serde_json::from_slice(&body).expect("login json");
+        let alice_id = login.user.id;
+        let alice_cookie = format!("{COOKIE_NAME}={}", login.token);
 
-        assert_eq!(resp.status(), StatusCode::SEE_OTHER);
-        assert_eq!(
-            resp.headers()
-                .get(header::LOCATION)
-                .expect("location")
-                .to_str()
-                .expect("location str"),
-            "/index.html"
-        );
-        assert!(resp.headers().contains_key(header::SET_COOKIE));
-
-        let _ = std::fs::remove_dir_all(static_dir);
-        let _ = std::fs::remove_dir_all(artifacts_dir);
-    }
+        let (status, artifact_id) = upload_artifact_with_cookie(&app, &alice_cookie, "bot").await;
+        assert_eq!(status, StatusCode::OK);
+        wait_for_audit_rows(&state, 2).await;
+
+        {
+            let db = state.db().expect("db connection");
+            let mut stmt = db
+                .prepare("SELECT event, user_id, ip, detail FROM audit_log ORDER BY id")
+                .expect("prepare");
+            let rows: Vec<(String, Option<i64>, Option<String>, String)> = stmt
+                .query_map([], |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?, r.get(3)?)))
+                .expect("query")
+                .map(|row| row.expect("row"))
+                .collect();
+            assert_eq!(rows.len(), 2);
+            assert_eq!(rows[0].0, "login");
+            assert_eq!(rows[0].1, Some(alice_id));
+            assert_eq!(rows[0].2.as_deref(), Some("203.0.113.7"));
+            assert_eq!(rows[1].0, "artifact_upload");
+            assert_eq!(rows[1].1, Some(alice_id));
+            assert!(rows[1].3.contains(&format!("artifact_id={artifact_id}")));
+        }
 
-    #[tokio::test]
-    async fn register_routes_redirect_when_disabled() {
-        let (state, static_dir, artifacts_dir) = setup_test_state(AuthMode::Required, false);
-        let app = build_app(state, Some(static_dir.clone()));
-        let resp = app
+        let forbidden = app
             .clone()
             .oneshot(
                 Request::builder()
-                    .uri("/register?next=%2Findex.html")
+                    .uri("/api/v1/admin/audit")
+                    .header(header::COOKIE, &alice_cookie)
                     .body(Body::empty())
                     .expect("request"),
             )
             .await
             .expect("response");
-        assert_eq!(resp.status(), StatusCode::SEE_OTHER);
-        assert_eq!(
-            resp.headers()
-                .get(header::LOCATION)
-                .expect("location")
-                .to_str()
-                .expect("location str"),
-            "/login?next=%2Findex.html&error=registration_disabled"
-        );
+        assert_eq!(forbidden.status(), StatusCode::FORBIDDEN);
 
         let resp = app
+            .clone()
             .oneshot(
                 Request::builder()
-                    .method("POST")
-                    .uri("/register")
-                    .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
-                    .body(Body::from(
-                        "username=bob&password=password123&next=%2Findex.html",
+                    .uri(format!(
+                        "/api/v1/admin/audit?user_id={alice_id}&event=artifact_upload&limit=10"
                     ))
+                    .header(header::COOKIE, &admin_cookie)
+                    .body(Body::empty())
                     .expect("request"),
             )
             .await
             .expect("response");
-        assert_eq!(resp.status(), StatusCode::SEE_OTHER);
-        assert_eq!(
-            resp.headers()
-                .get(header::LOCATION)
-                .expect("location")
-                .to_str()
-                .expect("location str"),
-            "/login?next=%2Findex.html&error=registration_disabled"
-        );
-
-        let _ = std::fs::remove_dir_all(static_dir);
-        let _ = std::fs::remove_dir_all(artifacts_dir);
-    }
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body = to_bytes(resp.into_body(), usize::MAX).await.expect("body");
+        let entries: Vec<AuditLogEntry> = serde_json::from_slice(&body).expect("audit json");
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].event, "artifact_upload");
+        assert_eq!(entries[0].username.as_deref(), Some("alice"));
 
-    #[tokio::test]
-    async fn login_page_hides_register_link_when_disabled() {
-        let (state, static_dir, artifacts_dir) = setup_test_state(AuthMode::Required, false);
-        let app = build_app(state, Some(static_dir.clone()));
         let resp = app
             .oneshot(
                 Request::builder()
-                    .uri("/login?next=%2F")
+                    .uri("/api/v1/admin/audit?limit=1&offset=1")
+                    .header(header::COOKIE, &admin_cookie)
                     .body(Body::empty())
                     .expect("request"),
             )
             .await
             .expect("response");
         let body = to_bytes(resp.into_body(), usize::MAX).await.expect("body");
-        let text = String::from_utf8(body.to_vec()).expect("utf8");
-        assert!(!text.contains("Create account"));
+        let entries: Vec<AuditLogEntry> = serde_json::from_slice(&body).expect("audit json");
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].event, "login");
 
         let _ = std::fs::remove_dir_all(static_dir);
         let _ = std::fs::remove_dir_all(artifacts_dir);
     }
 
-    #[tokio::test]
-    async fn uploads_are_private_by_default() {
-        let (state, static_dir, artifacts_dir) = setup_test_state(AuthMode::Required, true);
-        create_user(&state, "alice", "password123").await;
-        let alice_cookie = make_session_cookie(&state, "alice", "password123").await;
-        let app = build_app(state, Some(static_dir.clone()));
-
-        let (status, artifact_id) = upload_artifact_with_cookie(&app, &alice_cookie, "a.elf").await;
-        assert_eq!(status, StatusCode::OK);
-
-        let artifacts = list_artifacts_with_cookie(&app, &alice_cookie).await;
-        let artifact = artifacts
-            .into_iter()
-            .find(|a| a.id == artifact_id)
-            .expect("artifact exists");
-        assert!(!artifact.is_public);
-        assert!(artifact.owned_by_me);
-
-        let _ = std::fs::remove_dir_all(static_dir);
-        let _ = std::fs::remove_dir_all(artifacts_dir);
+    async fn get_admin_users(app: &Router, cookie: &str) -> (StatusCode, Vec<AdminUserEntry>) {
+        let resp = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri("/api/v1/admin/users")
+                    .header(header::COOKIE, cookie)
+                    .body(Body::empty())
+                    .expect("request"),
+            )
+            .await
+            .expect("response");
+        let status = resp.status();
+        let body = to_bytes(resp.into_body(), usize::MAX).await.expect("body");
+        (status, serde_json::from_slice(&body).unwrap_or_default())
     }
 
     #[tokio::test]
-    async fn list_artifacts_in_required_mode_shows_own_and_public_others() {
-        let (state, static_dir, artifacts_dir) = setup_test_state(AuthMode::Required, true);
-        create_user(&state, "alice", "password123").await;
-        create_user(&state, "bob", "password123").await;
-        let alice_cookie = make_session_cookie(&state, "alice", "password123").await;
+    async fn user_listing_is_admin_only_and_first_user_is_promoted() {
+        let (mut state, static_dir, artifacts_dir) = setup_test_state(AuthMode::Required, true);
+        state.first_user_admin = true;
+        create_user_with_password(&state, "bob", "password123")
+            .await
+            .expect("register bob");
+        create_user_with_password(&state, "carol", "password123")
+            .await
+            .expect("register carol");
+        create_user(&state, "admin", "password123").await;
         let bob_cookie = make_session_cookie(&state, "bob", "password123").await;
+        let carol_cookie = make_session_cookie(&state, "carol", "password123").await;
+        let admin_cookie = make_session_cookie(&state, "admin", "password123").await;
         let app = build_app(state, Some(static_dir.clone()));
 
-        let (_, alice_artifact_id) =
-            upload_artifact_with_cookie(&app, &alice_cookie, "alice.elf").await;
-        let (_, bob_artifact_id) = upload_artifact_with_cookie(&app, &bob_cookie, "bob.elf").await;
-        assert_eq!(
-            update_visibility_with_cookie(&app, &bob_cookie, bob_artifact_id, true).await,
-            StatusCode::NO_CONTENT
-        );
+        let (status, _) = get_admin_users(&app, &carol_cookie).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
 
-        let alice_view = list_artifacts_with_cookie(&app, &alice_cookie).await;
-        assert!(
-            alice_view
-                .iter()
-                .any(|a| a.id == alice_artifact_id && a.owned_by_me)
-        );
-        assert!(
-            alice_view
+        for cookie in [&bob_cookie, &admin_cookie] {
+            let (status, users) = get_admin_users(&app, cookie).await;
+            assert_eq!(status, StatusCode::OK);
+            let flags: Vec<(&str, bool)> = users
                 .iter()
-                .any(|a| a.id == bob_artifact_id && !a.owned_by_me && a.is_public)
-        );
+                .map(|user| (user.username.as_str(), user.is_admin))
+                .collect();
+            // bob registered first; admin is named in `admin_usernames`.
+            assert_eq!(flags, [("bob", true), ("carol", false), ("admin", true)]);
+        }
 
         let _ = std::fs::remove_dir_all(static_dir);
         let _ = std::fs::remove_dir_all(artifacts_dir);
     }
 
     #[tokio::test]
-    async fn list_artifacts_hides_private_others() {
+    async fn admins_can_delete_any_artifact() {
         let (state, static_dir, artifacts_dir) = setup_test_state(AuthMode::Required, true);
         create_user(&state, "alice", "password123").await;
-        create_user(&state, "bob", "password123").await;
+        create_user(&state, "carol", "password123").await;
+        create_user(&state, "admin", "password123").await;
         let alice_cookie = make_session_cookie(&state, "alice", "password123").await;
-        let bob_cookie = make_session_cookie(&state, "bob", "password123").await;
+        let carol_cookie = make_session_cookie(&state, "carol", "password123").await;
+        let admin_cookie = make_session_cookie(&state, "admin", "password123").await;
         let app = build_app(state, Some(static_dir.clone()));
+        let (_, artifact_id) = upload_artifact_with_cookie(&app, &alice_cookie, "bot").await;
 
-        let (_, bob_artifact_id) = upload_artifact_with_cookie(&app, &bob_cookie, "bob.elf").await;
-        let alice_view = list_artifacts_with_cookie(&app, &alice_cookie).await;
-        assert!(!alice_view.iter().any(|a| a.id == bob_artifact_id));
+        let delete = |cookie: &str| {
+            app.clone().oneshot(
+                Request::builder()
+                    .method("DELETE")
+                    .uri(format!("/api/v1/artifacts/{artifact_id}"))
+                    .header(header::COOKIE, cookie)
+                    .body(Body::empty())
+                    .expect("request"),
+            )
+        };
+        let resp = delete(&carol_cookie).await.expect("response");
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+        let resp = delete(&admin_cookie).await.expect("response");
+        assert_eq!(resp.status(), StatusCode::NO_CONTENT);
+        assert!(
+            list_artifacts_with_cookie(&app, &alice_cookie)
+                .await
+                .is_empty()
+        );
 
         let _ = std::fs::remove_dir_all(static_dir);
         let _ = std::fs::remove_dir_all(artifacts_dir);
     }
 
     #[tokio::test]
-    async fn download_public_artifact_allowed_for_non_owner() {
+    async fn decoded_artifact_above_max_is_rejected_with_400() {
         let (state, static_dir, artifacts_dir) = setup_test_state(AuthMode::Required, true);
-        create_user(&state, "alice", "password123").await;
-        create_user(&state, "bob", "password123").await;
-        let alice_cookie = make_session_cookie(&state, "alice", "password123").await;
-        let bob_cookie = make_session_cookie(&state, "bob", "password123").await;
+        create_user(&state, "alice", "pw-alice").await;
+        let cookie = make_session_cookie(&state, "alice", "pw-alice").await;
+        let max = state.max_artifact_bytes;
         let app = build_app(state, Some(static_dir.clone()));
 
-        let (_, artifact_id) = upload_artifact_with_cookie(&app, &bob_cookie, "bob.elf").await;
-        assert_eq!(
-            update_visibility_with_cookie(&app, &bob_cookie, artifact_id, true).await,
-            StatusCode::NO_CONTENT
-        );
+        // Fits under the request body limit, but decodes to more than `max`.
+        let mut elf = minimal_riscv_elf();
+        elf.resize(max + 1, 0);
+        let payload = UploadArtifactRequest {
+            name: "slightly too big".to_string(),
+            note: None,
+            target: DEFAULT_ARTIFACT_TARGET.to_string(),
+            elf_base64: base64::engine::general_purpose::STANDARD.encode(&elf),
+            build_meta_json: None,
+        };
+        let resp = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/api/v1/artifacts")
+                    .header(header::COOKIE, &cookie)
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(
+                        serde_json::to_vec(&payload).expect("serialize payload"),
+                    ))
+                    .expect("request"),
+            )
+            .await
+            .expect("response");
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+        let body = to_bytes(resp.into_body(), usize::MAX).await.expect("body");
+        let parsed: ErrorResponse = serde_json::from_slice(&body).expect("error json");
         assert_eq!(
-            download_artifact_with_cookie(&app, &alice_cookie, artifact_id).await,
-            StatusCode::OK
+            parsed.error,
+            format!("artifact is {} bytes, above the {max} byte limit", max + 1)
         );
+        assert!(list_artifacts_with_cookie(&app, &cookie).await.is_empty());
 
         let _ = std::fs::remove_dir_all(static_dir);
         let _ = std::fs::remove_dir_all(artifacts_dir);
     }
 
-    #[tokio::test]
-    async fn download_private_artifact_denied_for_non_owner() {
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn concurrent_artifact_lists_share_the_pool() {
         let (state, static_dir, artifacts_dir) = setup_test_state(AuthMode::Required, true);
         create_user(&state, "alice", "password123").await;
-        create_user(&state, "bob", "password123").await;
-        let alice_cookie = make_session_cookie(&state, "alice", "password123").await;
-        let bob_cookie = make_session_cookie(&state, "bob", "password123").await;
+        let cookie = make_session_cookie(&state, "alice", "password123").await;
         let app = build_app(state, Some(static_dir.clone()));
-
-        let (_, artifact_id) = upload_artifact_with_cookie(&app, &bob_cookie, "bob.elf").await;
-        assert_eq!(
-            download_artifact_with_cookie(&app, &alice_cookie, artifact_id).await,
-            StatusCode::UNAUTHORIZED
-        );
+        upload_artifact_with_cookie(&app, &cookie, "alice.elf").await;
+
+        let requests: Vec<_> = (0..50)
+            .map(|_| {
+                let app = app.clone();
+                let cookie = cookie.clone();
+                tokio::spawn(async move { list_artifacts_with_cookie(&app, &cookie).await })
+            })
+            .collect();
+        for request in requests {
+            assert_eq!(request.await.expect("list task").len(), 1);
+        }
 
         let _ = std::fs::remove_dir_all(static_dir);
         let _ = std::fs::remove_dir_all(artifacts_dir);
     }
 
     #[tokio::test]
-    async fn owner_can_toggle_visibility() {
+    async fn list_artifacts_is_paginated() {
         let (state, static_dir, artifacts_dir) = setup_test_state(AuthMode::Required, true);
-        create_user(&state, "alice", "password123").await;
-        let alice_cookie = make_session_cookie(&state, "alice", "password123").await;
+        create_user(&state, "alice", "pw-alice").await;
+        let cookie = make_session_cookie(&state, "alice", "pw-alice").await;
+        {
+            let db = state.db().expect("db connection");
+            let alice_id: i64 = db
+                .query_row("SELECT id FROM users WHERE username = 'alice'", [], |r| {
+                    r.get(0)
+                })
+                .expect("alice id");
+            for i in 0..60 {
+                db.execute(
+                    "INSERT INTO artifacts (owner_user_id, name, note, target, elf_path, is_public, created_at) VALUES (?1, ?2, NULL, ?3, '', 0, ?4)",
+                    params![
+                        alice_id,
+                        format!("bot-{i:02}"),
+                        DEFAULT_ARTIFACT_TARGET,
+                        format!("2026-01-01T00:00:{i:02}Z")
+                    ],
+                )
+                .expect("insert artifact");
+            }
+        }
         let app = build_app(state, Some(static_dir.clone()));
 
-        let (_, artifact_id) = upload_artifact_with_cookie(&app, &alice_cookie, "alice.elf").await;
-        assert_eq!(
-            update_visibility_with_cookie(&app, &alice_cookie, artifact_id, true).await,
-            StatusCode::NO_CONTENT
-        );
+        let page = |query: &'static str| {
+            let app = app.clone();
+            let cookie = cookie.clone();
+            async move {
+                let resp = app
+                    .oneshot(
+                        Request::builder()
+                            .uri(format!("/api/v1/artifacts{query}"))
+                            .header(header::COOKIE, cookie)
+                            .body(Body::empty())
+                            .expect("request"),
+                    )
+                    .await
+                    .expect("response");
+                assert_eq!(resp.status(), StatusCode::OK);
+                let body = to_bytes(resp.into_body(), usize::MAX).await.expect("body");
+                serde_json::from_slice::<Vec<ArtifactSummary>>(&body).expect("artifact list")
+            }
+        };
 
-        let artifacts = list_artifacts_with_cookie(&app, &alice_cookie).await;
-        let artifact = artifacts
-            .into_iter()
-            .find(|a| a.id == artifact_id)
-            .expect("artifact exists");
-        assert!(artifact.is_public);
+        let first = page("").await;
+        assert_eq!(first.len(), 50);
+        assert_eq!(first[0].name, "bot-59");
+        let second = page("?offset=50").await;
+        assert_eq!(second.len(), 10);
+        assert_eq!(second[9].name, "bot-00");
+        assert_eq!(page("?limit=1000").await.len(), 60);
+        assert_eq!(page("?limit=5&offset=10").await[0].name, "bot-49");
 
         let _ = std::fs::remove_dir_all(static_dir);
         let _ = std::fs::remove_dir_all(artifacts_dir);
     }
 
-    #[tokio::test]
-    async fn non_owner_cannot_toggle_visibility() {
-        let (state, static_dir, artifacts_dir) = setup_test_state(AuthMode::Required, true);
-        create_user(&state, "alice", "password123").await;
-        create_user(&state, "bob", "password123").await;
-        let alice_cookie = make_session_cookie(&state, "alice", "password123").await;
-        let bob_cookie = make_session_cookie(&state, "bob", "password123").await;
-        let app = build_app(state, Some(static_dir.clone()));
+    #[test]
+    fn custom_argon2_params_hash_and_verify() {
+        let config = ServerConfig {
+            argon2_m_cost: 8 * 1024,
+            argon2_t_cost: 1,
+            argon2_p_cost: 2,
+            ..ServerConfig::default()
+        };
+        let params = config.argon2_params().expect("valid params");
 
-        let (_, artifact_id) = upload_artifact_with_cookie(&app, &bob_cookie, "bob.elf").await;
-        assert_eq!(
-            update_visibility_with_cookie(&app, &alice_cookie, artifact_id, true).await,
-            StatusCode::UNAUTHORIZED
-        );
+        let hash = hash_password(&params, "correct horse").expect("hash password");
+        assert!(hash.contains("m=8192,t=1,p=2"), "{hash}");
+        verify_password(&params, "correct horse", &hash).expect("verify");
+        let wrong = verify_password(&params, "wrong horse", &hash).expect_err("wrong password");
+        assert_eq!(wrong.status, StatusCode::UNAUTHORIZED);
 
-        let _ = std::fs::remove_dir_all(static_dir);
-        let _ = std::fs::remove_dir_all(artifacts_dir);
+        // Hashes stored before the parameters changed still verify.
+        let old = hash_password(&Argon2Params::DEFAULT, "correct horse").expect("hash password");
+        verify_password(&params, "correct horse", &old).expect("verify default-cost hash");
+    }
+
+    #[tokio::test]
+    async fn invalid_argon2_params_stop_startup() {
+        let config = ServerConfig {
+            argon2_t_cost: 0,
+            ..ServerConfig::default()
+        };
+        let error = run_server(config).await.expect_err("startup must fail");
+        assert!(error.to_string().contains("Argon2"), "{error}");
     }
 
     #[test]
-    fn migration_adds_is_public_column() {
+    fn migration_adds_missing_artifact_columns() {
         let conn = Connection::open_in_memory().expect("open in-memory sqlite");
         conn.execute_batch(
             "
@@ -1791,13 +6279,38 @@ mod tests {
         let rows = stmt
             .query_map([], |row| row.get::<_, String>(1))
             .expect("query columns");
-        let mut has_is_public = false;
-        for row in rows {
-            if row.expect("column") == "is_public" {
-                has_is_public = true;
-                break;
-            }
-        }
-        assert!(has_is_public);
+        let columns: Vec<String> = rows.map(|row| row.expect("column")).collect();
+        assert!(columns.iter().any(|c| c == "is_public"));
+        assert!(columns.iter().any(|c| c == "sha256"));
+        assert!(columns.iter().any(|c| c == "build_meta_json"));
+        assert!(columns.iter().any(|c| c == "download_count"));
+    }
+
+    #[test]
+    fn migration_adds_admin_flag_to_legacy_users() {
+        let conn = Connection::open_in_memory().expect("open in-memory sqlite");
+        conn.execute_batch(
+            "
+            CREATE TABLE users (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                username TEXT NOT NULL UNIQUE,
+                password_hash TEXT NOT NULL,
+                created_at TEXT NOT NULL
+            );
+            INSERT INTO users (username, password_hash, created_at) VALUES ('old', '', 'then');
+            ",
+        )
+        .expect("create legacy users table");
+
+        run_migrations(&conn).expect("run migrations");
+
+        let is_admin: bool = conn
+            .query_row(
+                "SELECT is_admin FROM users WHERE username = 'old'",
+                [],
+                |row| row.get(0),
+            )
+            .expect("is_admin column");
+        assert!(!is_admin);
     }
 }
@@ -25,12 +25,121 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     if let Ok(cookie_secure) = std::env::var("BOTRACERS_COOKIE_SECURE") {
         config.cookie_secure = matches!(cookie_secure.as_str(), "1" | "true" | "TRUE" | "True");
     }
+    if let Ok(ttl_secs) = std::env::var("BOTRACERS_SESSION_TTL_SECS") {
+        match ttl_secs.parse() {
+            Ok(value) => config.session_ttl = std::time::Duration::from_secs(value),
+            Err(_) => tracing::warn!(
+                value = %ttl_secs,
+                "ignoring invalid BOTRACERS_SESSION_TTL_SECS"
+            ),
+        }
+    }
     if let Ok(registration_enabled) = std::env::var("BOTRACERS_REGISTRATION_ENABLED") {
         config.registration_enabled = matches!(
             registration_enabled.as_str(),
             "1" | "true" | "TRUE" | "True"
         );
     }
+    if let Ok(max_artifact_bytes) = std::env::var("BOTRACERS_MAX_ARTIFACT_BYTES") {
+        match max_artifact_bytes.parse() {
+            Ok(value) => config.max_artifact_bytes = value,
+            Err(_) => tracing::warn!(
+                value = %max_artifact_bytes,
+                "ignoring invalid BOTRACERS_MAX_ARTIFACT_BYTES"
+            ),
+        }
+    }
+    if let Ok(max_artifacts) = std::env::var("BOTRACERS_MAX_ARTIFACTS_PER_USER") {
+        match max_artifacts.parse() {
+            Ok(value) => config.max_artifacts_per_user = value,
+            Err(_) => tracing::warn!(
+                value = %max_artifacts,
+                "ignoring invalid BOTRACERS_MAX_ARTIFACTS_PER_USER"
+            ),
+        }
+    }
+    if let Ok(supported_targets) = std::env::var("BOTRACERS_SUPPORTED_TARGETS") {
+        let targets: Vec<String> = supported_targets
+            .split(',')
+            .map(str::trim)
+            .filter(|target| !target.is_empty())
+            .map(str::to_string)
+            .collect();
+        if targets.is_empty() {
+            tracing::warn!("ignoring empty BOTRACERS_SUPPORTED_TARGETS");
+        } else {
+            config.supported_targets = targets;
+        }
+    }
+    if let Ok(audit_log) = std::env::var("BOTRACERS_AUDIT_LOG") {
+        config.audit_log_enabled = matches!(audit_log.as_str(), "1" | "true" | "TRUE" | "True");
+    }
+    if let Ok(admin_usernames) = std::env::var("BOTRACERS_ADMIN_USERNAMES") {
+        config.admin_usernames = admin_usernames
+            .split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(str::to_string)
+            .collect();
+    }
+    if let Ok(first_user_admin) = std::env::var("BOTRACERS_FIRST_USER_ADMIN") {
+        config.first_user_admin =
+            matches!(first_user_admin.as_str(), "1" | "true" | "TRUE" | "True");
+    }
+    if let Ok(trust_forwarded_for) = std::env::var("BOTRACERS_TRUST_FORWARDED_FOR") {
+        config.trust_forwarded_for =
+            matches!(trust_forwarded_for.as_str(), "1" | "true" | "TRUE" | "True");
+    }
+    if let Ok(max_failures) = std::env::var("BOTRACERS_LOGIN_MAX_FAILURES") {
+        match max_failures.parse() {
+            Ok(value) => config.login_max_failures = value,
+            Err(_) => tracing::warn!(
+                value = %max_failures,
+                "ignoring invalid BOTRACERS_LOGIN_MAX_FAILURES"
+            ),
+        }
+    }
+    if let Ok(window_secs) = std::env::var("BOTRACERS_LOGIN_FAILURE_WINDOW_SECS") {
+        match window_secs.parse() {
+            Ok(value) => config.login_failure_window = std::time::Duration::from_secs(value),
+            Err(_) => tracing::warn!(
+                value = %window_secs,
+                "ignoring invalid BOTRACERS_LOGIN_FAILURE_WINDOW_SECS"
+            ),
+        }
+    }
+    if let Ok(allowed_origins) = std::env::var("BOTRACERS_ALLOWED_ORIGINS") {
+        config.allowed_origins = allowed_origins
+            .split(',')
+            .map(str::trim)
+            .filter(|origin| !origin.is_empty())
+            .map(str::to_string)
+            .collect();
+    }
+    for (name, cost) in [
+        ("BOTRACERS_ARGON2_M_COST", &mut config.argon2_m_cost),
+        ("BOTRACERS_ARGON2_T_COST", &mut config.argon2_t_cost),
+        ("BOTRACERS_ARGON2_P_COST", &mut config.argon2_p_cost),
+    ] {
+        if let Ok(value) = std::env::var(name) {
+            match value.parse() {
+                Ok(value) => *cost = value,
+                Err(_) => tracing::warn!(%value, "ignoring invalid {name}"),
+            }
+        }
+    }
+    if let Ok(require_pow) = std::env::var("BOTRACERS_REQUIRE_POW") {
+        config.require_pow = matches!(require_pow.as_str(), "1" | "true" | "TRUE" | "True");
+    }
+    if let Ok(difficulty) = std::env::var("BOTRACERS_POW_DIFFICULTY") {
+        match difficulty.parse() {
+            Ok(value) => config.pow_difficulty_bits = value,
+            Err(_) => tracing::warn!(
+                value = %difficulty,
+                "ignoring invalid BOTRACERS_POW_DIFFICULTY"
+            ),
+        }
+    }
     if let Ok(static_dir) = std::env::var("BOTRACERS_STATIC_DIR") {
         if static_dir.trim().is_empty() {
             config.static_dir = None;
@@ -0,0 +1,55 @@
+//! Instruction dispatch throughput with and without the decode cache.
+//!
+//! Run with `cargo bench -p emulator --bench dispatch`.
+
+use criterion::{Criterion, criterion_group, criterion_main};
+use emulator::cpu::{Dram, Hart, RamLike};
+
+/// Sums `i * i` over 1000 iterations through a 64-word scratch buffer, then
+/// hits `ebreak`. Same program as the cache equivalence test in `cpu.rs`.
+const LOOP_PROGRAM: [(u32, u32); 13] = [
+    (0x00, 0x0000_0293), // addi t0, zero, 0
+    (0x04, 0x3e80_0313), // addi t1, zero, 1000
+    (0x08, 0x0000_0393), // addi t2, zero, 0
+    (0x0c, 0x0252_8e33), // loop: mul t3, t0, t0
+    (0x10, 0x01c3_83b3), // add t2, t2, t3
+    (0x14, 0x0022_9e93), // slli t4, t0, 2
+    (0x18, 0x0fce_fe93), // andi t4, t4, 0xfc
+    (0x1c, 0x407e_a023), // sw t2, 0x400(t4)
+    (0x20, 0x400e_af03), // lw t5, 0x400(t4)
+    (0x24, 0x01e3_83b3), // add t2, t2, t5
+    (0x28, 0x0285),      // c.addi t0, 1
+    (0x2a, 0xfe62_c1e3), // blt t0, t1, loop
+    (0x2e, 0x0010_0073), // ebreak
+];
+
+fn loop_program() -> Dram {
+    let mut dram = Dram {
+        dram: vec![0; 0x800],
+        allow_misaligned: true,
+    };
+    for &(addr, encoding) in &LOOP_PROGRAM {
+        let size = if encoding & 0x3 == 0x3 { 32 } else { 16 };
+        dram.store(addr, size, encoding).expect("store");
+    }
+    dram
+}
+
+fn dispatch(c: &mut Criterion) {
+    let mut group = c.benchmark_group("loop_9k_instructions");
+    for (name, cached) in [("uncached", false), ("decode_cache", true)] {
+        group.bench_function(name, |b| {
+            let mut hart = Hart::new(0);
+            hart.set_decode_cache(cached);
+            let mut dram = loop_program();
+            b.iter(|| {
+                hart.pc = 0;
+                hart.run(&mut dram, 100_000)
+            });
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, dispatch);
+criterion_main!(benches);
@@ -3,8 +3,10 @@ use bevy::{
     prelude::*,
 };
 
-use crate::CpuBuilder;
-use crate::cpu::{Device, Instruction, Mmu};
+use tracing::warn;
+
+use crate::cpu::{Device, Mmu};
+use crate::{CpuBuilder, CpuSnapshot, EmulatorError};
 
 #[macro_export]
 macro_rules! define_cpu_config {
@@ -89,22 +91,59 @@ pub trait CpuConfig: Send + Sync + 'static {
     ) -> R;
 }
 
+/// Marks a CPU the host has retired (e.g. its car finished the race):
+/// `cpu_system` no longer steps it, and its counters keep their last values.
+/// Remove the marker to resume the program where it left off.
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct CpuHalted;
+
 #[derive(Component)]
 pub struct CpuComponent {
     hart: crate::cpu::Hart,
     dram: crate::cpu::Dram,
     instructions_per_update: u32,
+    stop_reason: Option<EmulatorError>,
+    stop_reported: bool,
+    instructions_last_update: u32,
 }
 
 impl CpuComponent {
     /// Create a new CpuComponent from an ELF binary.
-    pub fn new(elf: &[u8], instructions_per_update: u32) -> Self {
-        let (hart, dram) = CpuBuilder::default().build(elf);
-        Self {
+    pub fn new(elf: &[u8], instructions_per_update: u32) -> Result<Self, EmulatorError> {
+        Self::from_builder(CpuBuilder::default(), elf, instructions_per_update)
+    }
+
+    /// Like `new`, but with a configured builder (e.g. one with a syscall handler).
+    pub fn from_builder(
+        builder: CpuBuilder,
+        elf: &[u8],
+        instructions_per_update: u32,
+    ) -> Result<Self, EmulatorError> {
+        let (hart, dram) = builder.build(elf)?;
+        Ok(Self {
             hart,
             dram,
             instructions_per_update,
+            stop_reason: None,
+            stop_reported: false,
+            instructions_last_update: 0,
+        })
+    }
+
+    /// Why the program stopped running, if it has. A stopped CPU is skipped by
+    /// `cpu_system`, like one marked `CpuHalted`.
+    pub fn stop_reason(&self) -> Option<&EmulatorError> {
+        self.stop_reason.as_ref()
+    }
+
+    /// The stop reason, returned only on the first call after the CPU stops so
+    /// the host can report it once (e.g. to the bot's log).
+    pub fn take_unreported_stop(&mut self) -> Option<&EmulatorError> {
+        if self.stop_reported {
+            return None;
         }
+        self.stop_reported = self.stop_reason.is_some();
+        self.stop_reason.as_ref()
     }
 
     pub fn instructions_per_update(&self) -> u32 {
@@ -114,27 +153,62 @@ impl CpuComponent {
     pub fn set_instructions_per_update(&mut self, value: u32) {
         self.instructions_per_update = value.max(1);
     }
-}
 
-fn run_one_instruction(cpu: &mut CpuComponent, device_refs: &mut [&mut dyn Device]) {
-    let mut mmu = Mmu::new(&mut cpu.dram, device_refs);
+    /// Instructions retired during the most recent `cpu_system` run (0 once stopped).
+    pub fn instructions_last_update(&self) -> u32 {
+        self.instructions_last_update
+    }
+
+    /// Instructions retired since the CPU was created or its counters reset.
+    pub fn instructions_retired(&self) -> u64 {
+        self.hart.instructions_retired()
+    }
+
+    /// Estimated cycles since the CPU was created or its counters reset.
+    pub fn cycles(&self) -> u64 {
+        self.hart.cycles()
+    }
 
-    // 1. Fetch.
-    let inst = cpu.hart.fetch(&mmu);
+    pub fn reset_counters(&mut self) {
+        self.hart.reset_counters();
+    }
 
-    // 2. Decode.
-    let (decoded, len) = Instruction::parse_with_len(inst);
-    // 3. Execute.
-    cpu.hart.execute(decoded, len, &mut mmu);
+    pub fn snapshot(&self) -> CpuSnapshot {
+        CpuSnapshot::capture(&self.hart, &self.dram)
+    }
+
+    /// Rewind to `snap`; the CPU runs again even if it had stopped.
+    pub fn restore(&mut self, snap: &CpuSnapshot) {
+        snap.restore(&mut self.hart, &mut self.dram);
+        self.stop_reason = None;
+        self.stop_reported = false;
+        self.instructions_last_update = 0;
+    }
 }
 
 fn run_cpu(cpu: &mut CpuComponent, device_refs: &mut [&mut dyn Device]) {
-    for _ in 0..cpu.instructions_per_update {
-        run_one_instruction(cpu, device_refs);
+    if cpu.stop_reason.is_some() {
+        cpu.instructions_last_update = 0;
+        return;
+    }
+    let mut mmu = Mmu::new(&mut cpu.dram, device_refs);
+    let retired_before = cpu.hart.instret;
+    let result = cpu
+        .hart
+        .run(&mut mmu, u64::from(cpu.instructions_per_update));
+    cpu.instructions_last_update = (cpu.hart.instret - retired_before) as u32;
+    match result {
+        EmulatorError::BudgetExhausted | EmulatorError::Yielded => {}
+        error => {
+            warn!("cpu stopped: {error}");
+            cpu.stop_reason = Some(error);
+        }
     }
 }
 
-pub fn cpu_system<C: CpuConfig>(mut cpu_query: Query<(&mut CpuComponent, C::Devices)>) {
+pub fn cpu_system<C: CpuConfig>(
+    mut cpu_query: Query<(&mut CpuComponent, C::Devices), Without<CpuHalted>>,
+) {
     for (mut cpu, devices) in cpu_query.iter_mut() {
         C::with_slotted_devices(devices, |slotted| {
             slotted.sort_by_key(|entry| entry.0);
@@ -156,3 +230,179 @@ pub fn cpu_system<C: CpuConfig>(mut cpu_query: Query<(&mut CpuComponent, C::Devi
         });
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AccessKind;
+    use crate::HaltReason;
+    use crate::cpu::{Dram, Hart, LogDevice, RamLike, YieldDevice};
+    use bevy::ecs::system::RunSystemOnce;
+
+    crate::define_cpu_config! {
+        LogOnlyConfig {
+            1 => LogDevice,
+        }
+    }
+
+    fn cpu_with_words(words: &[u32], instructions_per_update: u32) -> CpuComponent {
+        // Addresses below 0x1000 are MMIO, so code lives above it.
+        let mut dram = Dram {
+            dram: vec![0; 0x1100],
+            allow_misaligned: true,
+        };
+        for (i, &word) in words.iter().enumerate() {
+            dram.store(0x1000 + i as u32 * 4, 32, word).expect("store");
+        }
+        CpuComponent {
+            hart: Hart::new(0x1000),
+            dram,
+            instructions_per_update,
+            stop_reason: None,
+            stop_reported: false,
+            instructions_last_update: 0,
+        }
+    }
+
+    #[test]
+    fn budget_splits_program_across_updates_and_resumes() {
+        // 10 × addi x1, x1, 1; ebreak
+        let mut words = vec![0x0010_8093; 10];
+        words.push(0x0010_0073);
+        let mut cpu = cpu_with_words(&words, 4);
+
+        let mut per_update = Vec::new();
+        for _ in 0..4 {
+            run_cpu(&mut cpu, &mut []);
+            per_update.push(cpu.instructions_last_update());
+        }
+
+        assert_eq!(per_update, vec![4, 4, 2, 0]);
+        assert_eq!(per_update.iter().sum::<u32>(), 10);
+        assert_eq!(cpu.hart.regs[1], 10);
+        assert_eq!(cpu.hart.instret, 10);
+        assert_eq!(
+            cpu.stop_reason(),
+            Some(&EmulatorError::Halt(HaltReason::Ebreak))
+        );
+    }
+
+    #[test]
+    fn yield_ends_the_update_early_and_resumes_after_the_store() {
+        // addi x1, x1, 1; sw x0, 0x100(x0) (yield); addi x1, x1, 1;
+        // addi x1, x1, 1; ebreak
+        let words = [
+            0x0010_8093,
+            0x1000_2023,
+            0x0010_8093,
+            0x0010_8093,
+            0x0010_0073,
+        ];
+        let mut cpu = cpu_with_words(&words, 10);
+        let mut yield_device = YieldDevice::default();
+
+        run_cpu(&mut cpu, &mut [&mut yield_device]);
+        assert_eq!(cpu.instructions_last_update(), 2);
+        assert_eq!(cpu.hart.regs[1], 1);
+        assert_eq!(cpu.stop_reason(), None);
+
+        run_cpu(&mut cpu, &mut [&mut yield_device]);
+        assert_eq!(cpu.instructions_last_update(), 2);
+        assert_eq!(cpu.hart.regs[1], 3);
+        assert_eq!(
+            cpu.stop_reason(),
+            Some(&EmulatorError::Halt(HaltReason::Ebreak))
+        );
+    }
+
+    #[test]
+    fn restored_snapshot_replays_identically() {
+        // Loop pushing a counter to the stack:
+        // addi x1, x1, 1; sw x1, -4(sp); addi sp, sp, -4; jal x0, -12
+        let words = [0x0010_8093, 0xfe11_2e23, 0xffc1_0113, 0xff5f_f06f];
+        let mut cpu = cpu_with_words(&words, 5);
+        cpu.hart.regs[2] = 0x10f0;
+
+        run_cpu(&mut cpu, &mut []);
+        run_cpu(&mut cpu, &mut []);
+        let snap = cpu.snapshot();
+
+        run_cpu(&mut cpu, &mut []);
+        run_cpu(&mut cpu, &mut []);
+        let first = cpu.snapshot();
+
+        cpu.restore(&snap);
+        assert_eq!(cpu.snapshot(), snap);
+        run_cpu(&mut cpu, &mut []);
+        run_cpu(&mut cpu, &mut []);
+        assert_eq!(cpu.snapshot(), first);
+        assert_ne!(first, snap);
+        assert!(cpu.stop_reason().is_none());
+    }
+
+    #[test]
+    fn out_of_bounds_store_traps_into_the_log() {
+        // lui x1, 0x2; sw x0, 0(x1) — 0x2000 is past the end of DRAM.
+        let mut cpu = cpu_with_words(&[0x0000_20b7, 0x0000_a023], 10);
+        let mut log = LogDevice::default();
+
+        run_cpu(&mut cpu, &mut [&mut log]);
+        assert_eq!(
+            cpu.stop_reason(),
+            Some(&EmulatorError::MemoryFault {
+                pc: 0x1004,
+                addr: 0x2000,
+                access: AccessKind::Store,
+            })
+        );
+
+        // What the host's stop reporting writes to the bot console.
+        let reason = cpu.take_unreported_stop().expect("stopped");
+        assert!(reason.is_trap());
+        log.push_str(&format!("{reason}\n"));
+        let output = log.output();
+        assert!(output.contains("access fault"), "{output}");
+        assert_eq!(output, "trap: store access fault at 0x2000, pc=0x1004\n");
+    }
+
+    #[test]
+    fn halted_cpus_are_skipped_by_cpu_system() {
+        // addi x1, x1, 1; jal x0, -4
+        let words = [0x0010_8093, 0xffdf_f06f];
+        let mut world = World::new();
+        let running = world
+            .spawn((cpu_with_words(&words, 10), LogDevice::default()))
+            .id();
+        let halted = world
+            .spawn((cpu_with_words(&words, 10), LogDevice::default()))
+            .id();
+
+        let retired = |world: &World| {
+            [running, halted].map(|entity| {
+                world
+                    .get::<CpuComponent>(entity)
+                    .expect("cpu")
+                    .instructions_retired()
+            })
+        };
+
+        world
+            .run_system_once(cpu_system::<LogOnlyConfig>)
+            .expect("run cpus");
+        assert_eq!(retired(&world), [10, 10]);
+
+        world.entity_mut(halted).insert(CpuHalted);
+        for _ in 0..3 {
+            world
+                .run_system_once(cpu_system::<LogOnlyConfig>)
+                .expect("run cpus");
+        }
+        assert_eq!(retired(&world), [40, 10]);
+
+        world.entity_mut(halted).remove::<CpuHalted>();
+        world
+            .run_system_once(cpu_system::<LogOnlyConfig>)
+            .expect("run cpus");
+        assert_eq!(retired(&world), [50, 20]);
+    }
+}
@@ -1,16 +1,63 @@
+use std::collections::VecDeque;
+
 use bevy::prelude::Component;
 use elf::{ElfBytes, abi::PT_LOAD, endian::LittleEndian};
 use tracing::{debug, trace};
 
-pub use instruction::Instruction;
+use crate::error::{AccessKind, EmulatorError, HaltReason, TrapCause};
+
+pub use instruction::{DecodeError, Instruction};
 mod instruction;
 
-#[derive(Debug)]
+/// Host side of `ecall`. The syscall number is in `a7` and the arguments in
+/// `a0..=a6`; the returned value is written to `a0`. Returning `None` marks the
+/// number as unknown, which stops the program with `EmulatorError::UnknownSyscall`.
+pub trait SyscallHandler: Send + Sync {
+    fn syscall(&mut self, number: u32, args: [u32; 7]) -> Option<u32>;
+}
+
 pub struct Hart {
     pub regs: [u32; 32],
-    pub fregs: [u32; 32],
+    /// Float registers, 64 bits wide for the D extension. Single-precision
+    /// values are NaN-boxed in the upper half.
+    pub fregs: [u64; 32],
     pub pc: u32,
     pub reservation_addr: Option<u32>,
+    /// Instructions retired since the hart was created (or `reset_counters`).
+    pub instret: u64,
+    /// Estimated cycles of the retired instructions; see `Instruction::cycle_cost`.
+    pub cycles: u64,
+    syscalls: Option<Box<dyn SyscallHandler>>,
+    /// Set by `wfi`; `run` ends with `EmulatorError::Yielded` after it.
+    waiting_for_interrupt: bool,
+    /// Decoded instructions by `pc`; `None` decodes every step. See `decode`.
+    decode_cache: Option<Box<[Option<DecodedInstruction>]>>,
+}
+
+/// Slots in the decoded-instruction cache; a power of two. Covers 2KiB of
+/// code without collisions, which holds the hot loops of a typical bot.
+const DECODE_CACHE_SLOTS: usize = 1024;
+
+#[derive(Clone, Copy)]
+struct DecodedInstruction {
+    pc: u32,
+    word: u32,
+    inst: Instruction,
+    len: u32,
+}
+
+impl std::fmt::Debug for Hart {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Hart")
+            .field("regs", &self.regs)
+            .field("fregs", &self.fregs)
+            .field("pc", &self.pc)
+            .field("reservation_addr", &self.reservation_addr)
+            .field("instret", &self.instret)
+            .field("cycles", &self.cycles)
+            .field("has_syscalls", &self.syscalls.is_some())
+            .finish()
+    }
 }
 
 impl Hart {
@@ -20,12 +67,126 @@ impl Hart {
             fregs: [0; 32],
             pc: entry,
             reservation_addr: None,
+            instret: 0,
+            cycles: 0,
+            syscalls: None,
+            waiting_for_interrupt: false,
+            decode_cache: None,
         };
+        cpu.set_decode_cache(true);
         cpu.regs[2] = (DRAM_SIZE - 16) & !0xf;
         cpu
     }
-    pub fn fetch(&self, dram: &impl RamLike) -> u32 {
-        dram.load(self.pc, 32).unwrap_or(0)
+
+    pub fn set_syscall_handler(&mut self, handler: Box<dyn SyscallHandler>) {
+        self.syscalls = Some(handler);
+    }
+
+    /// Turn the decoded-instruction cache on (the default) or off. Execution
+    /// is identical either way; the cache only skips re-decoding hot code.
+    pub fn set_decode_cache(&mut self, enabled: bool) {
+        self.decode_cache = enabled.then(|| vec![None; DECODE_CACHE_SLOTS].into_boxed_slice());
+    }
+
+    /// Fetch the instruction word at `pc` as 16-bit parcels, reading only
+    /// one for compressed encodings. Instructions only need 2-byte alignment,
+    /// even in memory that rejects misaligned loads.
+    pub fn fetch(&self, dram: &impl RamLike) -> Result<u32, EmulatorError> {
+        let fault = |cause| EmulatorError::memory(cause, self.pc, self.pc, AccessKind::Fetch);
+        let low = dram.load(self.pc, 16).map_err(fault)?;
+        if (low & 0x3) != 0x3 {
+            return Ok(low);
+        }
+        let high = dram.load(self.pc.wrapping_add(2), 16).map_err(fault)?;
+        Ok(low | (high << 16))
+    }
+
+    /// Fetch, decode and execute a single instruction.
+    pub fn step(&mut self, dram: &mut impl RamLike) -> Result<(), EmulatorError> {
+        let word = self.fetch(dram)?;
+        let (inst, len) = self.decode(word)?;
+        let cost = inst.cycle_cost();
+        self.execute(inst, len, dram)?;
+        self.instret += 1;
+        self.cycles += cost;
+        Ok(())
+    }
+
+    /// Decode `word`, fetched from `pc`, reusing the cached decoding when the
+    /// slot for `pc` holds the same word. Comparing the word instead of
+    /// tracking stores means code rewritten by the program, the host or a
+    /// snapshot restore is never served stale.
+    fn decode(&mut self, word: u32) -> Result<(Instruction, u32), EmulatorError> {
+        let pc = self.pc;
+        let parse = |word| {
+            Instruction::parse_with_len(word).map_err(|error| EmulatorError::Decode { pc, error })
+        };
+        let Some(cache) = self.decode_cache.as_mut() else {
+            return parse(word);
+        };
+        let slot = &mut cache[(pc as usize >> 1) & (DECODE_CACHE_SLOTS - 1)];
+        if let Some(cached) = slot
+            && cached.pc == pc
+            && cached.word == word
+        {
+            return Ok((cached.inst, cached.len));
+        }
+        let (inst, len) = parse(word)?;
+        *slot = Some(DecodedInstruction {
+            pc,
+            word,
+            inst,
+            len,
+        });
+        Ok((inst, len))
+    }
+
+    pub fn instructions_retired(&self) -> u64 {
+        self.instret
+    }
+
+    pub fn cycles(&self) -> u64 {
+        self.cycles
+    }
+
+    /// Value of a CSR as seen by the program. Only the unprivileged counters
+    /// exist; `time` has no wall clock behind it and follows `cycles`, which
+    /// keeps runs deterministic. Any other CSR reads as 0.
+    fn read_csr(&self, csr: u32) -> u32 {
+        use instruction::{CSR_CYCLE, CSR_CYCLEH, CSR_INSTRET, CSR_INSTRETH, CSR_TIME, CSR_TIMEH};
+        match csr {
+            CSR_CYCLE | CSR_TIME => self.cycles as u32,
+            CSR_CYCLEH | CSR_TIMEH => (self.cycles >> 32) as u32,
+            CSR_INSTRET => self.instret as u32,
+            CSR_INSTRETH => (self.instret >> 32) as u32,
+            _ => 0,
+        }
+    }
+
+    /// Zero the retired-instruction and cycle counters, e.g. at the start of
+    /// a graded run.
+    pub fn reset_counters(&mut self) {
+        self.instret = 0;
+        self.cycles = 0;
+    }
+
+    /// Step until the program halts, faults, yields, or `budget` instructions
+    /// have run. The returned error says which; running out of budget is
+    /// `EmulatorError::BudgetExhausted`, a store to a yielding device is
+    /// `EmulatorError::Yielded`, and in both cases execution can resume from `pc`.
+    /// `wfi` yields the same way: there are no interrupts to wait for, so the
+    /// closest thing is the next run.
+    pub fn run(&mut self, dram: &mut impl RamLike, budget: u64) -> EmulatorError {
+        for _ in 0..budget {
+            if let Err(error) = self.step(dram) {
+                return error;
+            }
+            let waited = std::mem::take(&mut self.waiting_for_interrupt);
+            if dram.take_yield() || waited {
+                return EmulatorError::Yielded;
+            }
+        }
+        EmulatorError::BudgetExhausted
     }
     pub fn set_reservation(&mut self, addr: u32) {
         self.reservation_addr = Some(addr);
@@ -44,10 +205,52 @@ impl Hart {
         }
     }
 
-    pub fn execute(&mut self, inst: Instruction, inst_len: u32, dram: &mut impl RamLike) {
+    pub fn execute(
+        &mut self,
+        inst: Instruction,
+        inst_len: u32,
+        dram: &mut impl RamLike,
+    ) -> Result<(), EmulatorError> {
         self.regs[0] = 0; // Simulate hard wired x0
+        let pc = self.pc;
         self.pc = self.pc.wrapping_add(inst_len);
 
+        fn load(dram: &impl RamLike, pc: u32, addr: u32, size: u32) -> Result<u32, EmulatorError> {
+            dram.load(addr, size)
+                .map_err(|cause| EmulatorError::memory(cause, pc, addr, AccessKind::Load))
+        }
+
+        fn store(
+            dram: &mut impl RamLike,
+            pc: u32,
+            addr: u32,
+            size: u32,
+            value: u32,
+        ) -> Result<(), EmulatorError> {
+            dram.store(addr, size, value)
+                .map_err(|cause| EmulatorError::memory(cause, pc, addr, AccessKind::Store))
+        }
+
+        /// FLD/FSD split into two word accesses, so check the doubleword
+        /// alignment up front when misaligned accesses trap.
+        fn check_doubleword(
+            dram: &impl RamLike,
+            pc: u32,
+            addr: u32,
+            access: AccessKind,
+        ) -> Result<(), EmulatorError> {
+            if dram.allows_misaligned() || addr.is_multiple_of(8) {
+                Ok(())
+            } else {
+                Err(EmulatorError::memory(
+                    TrapCause::Misaligned,
+                    pc,
+                    addr,
+                    access,
+                ))
+            }
+        }
+
         fn f32_from_bits(bits: u32) -> f32 {
             f32::from_bits(bits)
         }
@@ -56,6 +259,27 @@ impl Hart {
             value.to_bits()
         }
 
+        const D_SIGN: u64 = 1 << 63;
+
+        /// Single-precision values live NaN-boxed in the 64-bit float
+        /// registers: the upper 32 bits are all ones.
+        fn nan_box(bits: u32) -> u64 {
+            0xffff_ffff_0000_0000 | u64::from(bits)
+        }
+
+        /// The single-precision operand held in a float register. A register
+        /// whose upper half is not all ones holds no valid single and reads as
+        /// the canonical NaN; only `fmv.x.w` and `fsw` see the raw low bits.
+        fn unbox(bits: u64) -> u32 {
+            if bits >> 32 == 0xffff_ffff {
+                bits as u32
+            } else {
+                CANONICAL_NAN_S
+            }
+        }
+
+        const CANONICAL_NAN_S: u32 = 0x7fc0_0000;
+
         fn is_nan_bits(bits: u32) -> bool {
             let exp = (bits >> 23) & 0xff;
             let frac = bits & 0x7f_ffff;
@@ -89,21 +313,73 @@ impl Hart {
             }
         }
 
-        fn fcvt_w_s(value: f32, unsigned: bool) -> u32 {
+        fn fclass_d(bits: u64) -> u32 {
+            let sign = (bits >> 63) != 0;
+            let exp = (bits >> 52) & 0x7ff;
+            let frac = bits & 0x000f_ffff_ffff_ffff;
+            if exp == 0x7ff {
+                if frac == 0 {
+                    if sign { 1 << 0 } else { 1 << 7 }
+                } else {
+                    let is_quiet = ((frac >> 51) & 0x1) == 1;
+                    if is_quiet { 1 << 9 } else { 1 << 8 }
+                }
+            } else if exp == 0 {
+                if frac == 0 {
+                    if sign { 1 << 3 } else { 1 << 4 }
+                } else if sign {
+                    1 << 2
+                } else {
+                    1 << 5
+                }
+            } else if sign {
+                1 << 1
+            } else {
+                1 << 6
+            }
+        }
+
+        /// `fmin.d`/`fmax.d`: a NaN operand yields the other one, and -0.0
+        /// orders below +0.0.
+        fn fmin_max_d(a_bits: u64, b_bits: u64, max: bool) -> u64 {
+            let a = f64::from_bits(a_bits);
+            let b = f64::from_bits(b_bits);
+            if a.is_nan() && b.is_nan() {
+                f64::NAN.to_bits()
+            } else if a.is_nan() {
+                b_bits
+            } else if b.is_nan() {
+                a_bits
+            } else if a == b {
+                // Equal values differ only in the sign of zero.
+                if max {
+                    a_bits & b_bits
+                } else {
+                    a_bits | b_bits
+                }
+            } else if (a > b) == max {
+                a_bits
+            } else {
+                b_bits
+            }
+        }
+
+        /// Single operands are widened first; every f32 is exact as an f64.
+        fn fcvt_w(value: f64, unsigned: bool) -> u32 {
             if value.is_nan() {
                 return 0;
             }
             if unsigned {
                 if value <= 0.0 {
                     0
-                } else if value >= u32::MAX as f32 {
+                } else if value >= u32::MAX as f64 {
                     u32::MAX
                 } else {
                     value.trunc() as u32
                 }
-            } else if value <= i32::MIN as f32 {
+            } else if value <= i32::MIN as f64 {
                 i32::MIN as u32
-            } else if value >= i32::MAX as f32 {
+            } else if value >= i32::MAX as f64 {
                 i32::MAX as u32
             } else {
                 (value.trunc() as i32) as u32
@@ -232,33 +508,23 @@ impl Hart {
                     self.regs[rd] = back_addr
                 }
                 instruction::IFunct::LB => {
-                    self.regs[rd] = ((dram
-                        .load(self.regs[rs1].wrapping_add_signed(imm), 8)
-                        .unwrap_or(0)
+                    self.regs[rd] = ((load(dram, pc, self.regs[rs1].wrapping_add_signed(imm), 8)?
                         << 24) as i32
                         >> 24) as u32;
                 }
                 instruction::IFunct::LH => {
-                    self.regs[rd] = ((dram
-                        .load(self.regs[rs1].wrapping_add_signed(imm), 16)
-                        .unwrap_or(0)
+                    self.regs[rd] = ((load(dram, pc, self.regs[rs1].wrapping_add_signed(imm), 16)?
                         << 16) as i32
                         >> 16) as u32;
                 }
                 instruction::IFunct::LW => {
-                    self.regs[rd] = dram
-                        .load(self.regs[rs1].wrapping_add_signed(imm), 32)
-                        .unwrap_or(0);
+                    self.regs[rd] = load(dram, pc, self.regs[rs1].wrapping_add_signed(imm), 32)?;
                 }
                 instruction::IFunct::LBU => {
-                    self.regs[rd] = dram
-                        .load(self.regs[rs1].wrapping_add_signed(imm), 8)
-                        .unwrap_or(0);
+                    self.regs[rd] = load(dram, pc, self.regs[rs1].wrapping_add_signed(imm), 8)?;
                 }
                 instruction::IFunct::LHU => {
-                    self.regs[rd] = dram
-                        .load(self.regs[rs1].wrapping_add_signed(imm), 16)
-                        .unwrap_or(0);
+                    self.regs[rd] = load(dram, pc, self.regs[rs1].wrapping_add_signed(imm), 16)?;
                 }
                 instruction::IFunct::ADDI => {
                     self.regs[rd] = self.regs[rs1].wrapping_add_signed(imm);
@@ -299,17 +565,17 @@ impl Hart {
             } => match funct {
                 instruction::SFunct::SB => {
                     let addr = self.regs[rs1].wrapping_add_signed(imm);
-                    let _ = dram.store(addr, 8, self.regs[rs2]);
+                    store(dram, pc, addr, 8, self.regs[rs2])?;
                     self.invalidate_reservation_if_overlaps(addr);
                 }
                 instruction::SFunct::SH => {
                     let addr = self.regs[rs1].wrapping_add_signed(imm);
-                    let _ = dram.store(addr, 16, self.regs[rs2]);
+                    store(dram, pc, addr, 16, self.regs[rs2])?;
                     self.invalidate_reservation_if_overlaps(addr);
                 }
                 instruction::SFunct::SW => {
                     let addr = self.regs[rs1].wrapping_add_signed(imm);
-                    let _ = dram.store(addr, 32, self.regs[rs2]);
+                    store(dram, pc, addr, 32, self.regs[rs2])?;
                     self.invalidate_reservation_if_overlaps(addr);
                 }
             },
@@ -321,32 +587,32 @@ impl Hart {
             } => match funct {
                 instruction::BFunct::BEQ => {
                     if self.regs[rs1] == self.regs[rs2] {
-                        self.pc = self.pc.wrapping_add_signed(imm).wrapping_sub(inst_len);
+                        self.pc = pc.wrapping_add_signed(imm);
                     }
                 }
                 instruction::BFunct::BNE => {
                     if self.regs[rs1] != self.regs[rs2] {
-                        self.pc = self.pc.wrapping_add_signed(imm).wrapping_sub(inst_len);
+                        self.pc = pc.wrapping_add_signed(imm);
                     }
                 }
                 instruction::BFunct::BLT => {
                     if (self.regs[rs1] as i32) < (self.regs[rs2] as i32) {
-                        self.pc = self.pc.wrapping_add_signed(imm).wrapping_sub(inst_len);
+                        self.pc = pc.wrapping_add_signed(imm);
                     }
                 }
                 instruction::BFunct::BGE => {
                     if (self.regs[rs1] as i32) >= (self.regs[rs2] as i32) {
-                        self.pc = self.pc.wrapping_add_signed(imm).wrapping_sub(inst_len);
+                        self.pc = pc.wrapping_add_signed(imm);
                     }
                 }
                 instruction::BFunct::BLTU => {
                     if self.regs[rs1] < self.regs[rs2] {
-                        self.pc = self.pc.wrapping_add_signed(imm).wrapping_sub(inst_len);
+                        self.pc = pc.wrapping_add_signed(imm);
                     }
                 }
                 instruction::BFunct::BGEU => {
                     if self.regs[rs1] >= self.regs[rs2] {
-                        self.pc = self.pc.wrapping_add_signed(imm).wrapping_sub(inst_len);
+                        self.pc = pc.wrapping_add_signed(imm);
                     }
                 }
             },
@@ -355,13 +621,13 @@ impl Hart {
                     self.regs[rd] = imm as u32;
                 }
                 instruction::UFunct::AUIPC => {
-                    self.regs[rd] = self.pc.wrapping_add_signed(imm).wrapping_sub(inst_len);
+                    self.regs[rd] = pc.wrapping_add_signed(imm);
                 }
             },
             Instruction::J { funct, rd, imm } => match funct {
                 instruction::JFunct::JAL => {
                     self.regs[rd] = self.pc;
-                    self.pc = self.pc.wrapping_add_signed(imm).wrapping_sub(inst_len);
+                    self.pc = pc.wrapping_add_signed(imm);
                 }
             },
             Instruction::R4 {
@@ -372,16 +638,25 @@ impl Hart {
                 rs3,
                 rm: _,
             } => {
-                let a = f32_from_bits(self.fregs[rs1]);
-                let b = f32_from_bits(self.fregs[rs2]);
-                let c = f32_from_bits(self.fregs[rs3]);
-                let result = match funct {
-                    instruction::R4Funct::FmaddS => a.mul_add(b, c),
-                    instruction::R4Funct::FmsubS => a.mul_add(b, -c),
-                    instruction::R4Funct::FnmsubS => (-a).mul_add(b, c),
-                    instruction::R4Funct::FnmaddS => (-a).mul_add(b, -c),
+                use instruction::R4Funct::*;
+                let (a, b, c) = (self.fregs[rs1], self.fregs[rs2], self.fregs[rs3]);
+                let single = |op: fn(f32, f32, f32) -> f32| {
+                    let [a, b, c] = [a, b, c].map(|bits| f32_from_bits(unbox(bits)));
+                    nan_box(bits_from_f32(op(a, b, c)))
+                };
+                let double = |op: fn(f64, f64, f64) -> f64| {
+                    op(f64::from_bits(a), f64::from_bits(b), f64::from_bits(c)).to_bits()
+                };
+                self.fregs[rd] = match funct {
+                    FmaddS => single(|a, b, c| a.mul_add(b, c)),
+                    FmsubS => single(|a, b, c| a.mul_add(b, -c)),
+                    FnmsubS => single(|a, b, c| (-a).mul_add(b, c)),
+                    FnmaddS => single(|a, b, c| (-a).mul_add(b, -c)),
+                    FmaddD => double(|a, b, c| a.mul_add(b, c)),
+                    FmsubD => double(|a, b, c| a.mul_add(b, -c)),
+                    FnmsubD => double(|a, b, c| (-a).mul_add(b, c)),
+                    FnmaddD => double(|a, b, c| (-a).mul_add(b, -c)),
                 };
-                self.fregs[rd] = bits_from_f32(result);
             }
             Instruction::FR {
                 funct,
@@ -389,142 +664,178 @@ impl Hart {
                 rs1,
                 rs2,
                 rm: _,
-            } => match funct {
-                instruction::FRFunct::FaddS => {
-                    let result = f32_from_bits(self.fregs[rs1]) + f32_from_bits(self.fregs[rs2]);
-                    self.fregs[rd] = bits_from_f32(result);
-                }
-                instruction::FRFunct::FsubS => {
-                    let result = f32_from_bits(self.fregs[rs1]) - f32_from_bits(self.fregs[rs2]);
-                    self.fregs[rd] = bits_from_f32(result);
-                }
-                instruction::FRFunct::FmulS => {
-                    let result = f32_from_bits(self.fregs[rs1]) * f32_from_bits(self.fregs[rs2]);
-                    self.fregs[rd] = bits_from_f32(result);
-                }
-                instruction::FRFunct::FdivS => {
-                    let result = f32_from_bits(self.fregs[rs1]) / f32_from_bits(self.fregs[rs2]);
-                    self.fregs[rd] = bits_from_f32(result);
-                }
-                instruction::FRFunct::FsgnjS => {
-                    self.fregs[rd] =
-                        (self.fregs[rs1] & 0x7fff_ffff) | (self.fregs[rs2] & 0x8000_0000);
-                }
-                instruction::FRFunct::FsgnjnS => {
-                    self.fregs[rd] =
-                        (self.fregs[rs1] & 0x7fff_ffff) | ((!self.fregs[rs2]) & 0x8000_0000);
-                }
-                instruction::FRFunct::FsgnjxS => {
-                    let sign = (self.fregs[rs1] ^ self.fregs[rs2]) & 0x8000_0000;
-                    self.fregs[rd] = (self.fregs[rs1] & 0x7fff_ffff) | sign;
-                }
-                instruction::FRFunct::FminS => {
-                    let a_bits = self.fregs[rs1];
-                    let b_bits = self.fregs[rs2];
-                    let a = f32_from_bits(a_bits);
-                    let b = f32_from_bits(b_bits);
-                    self.fregs[rd] = if a.is_nan() && b.is_nan() {
-                        f32::NAN.to_bits()
-                    } else if a.is_nan() {
-                        b_bits
-                    } else if b.is_nan() {
-                        a_bits
-                    } else if a < b {
-                        a_bits
-                    } else if b < a {
-                        b_bits
-                    } else if a_bits == 0x8000_0000 || b_bits == 0x8000_0000 {
-                        0x8000_0000
-                    } else {
-                        a_bits
-                    };
-                }
-                instruction::FRFunct::FmaxS => {
-                    let a_bits = self.fregs[rs1];
-                    let b_bits = self.fregs[rs2];
-                    let a = f32_from_bits(a_bits);
-                    let b = f32_from_bits(b_bits);
-                    self.fregs[rd] = if a.is_nan() && b.is_nan() {
-                        f32::NAN.to_bits()
-                    } else if a.is_nan() {
-                        b_bits
-                    } else if b.is_nan() {
-                        a_bits
-                    } else if a > b {
-                        a_bits
-                    } else if b > a {
-                        b_bits
-                    } else if a_bits == 0x0000_0000 || b_bits == 0x0000_0000 {
-                        0x0000_0000
-                    } else {
-                        a_bits
-                    };
-                }
-                instruction::FRFunct::FeqS => {
-                    let a_bits = self.fregs[rs1];
-                    let b_bits = self.fregs[rs2];
-                    self.regs[rd] = if is_nan_bits(a_bits) || is_nan_bits(b_bits) {
-                        0
-                    } else if f32_from_bits(a_bits) == f32_from_bits(b_bits) {
-                        1
-                    } else {
-                        0
-                    };
-                }
-                instruction::FRFunct::FltS => {
-                    let a_bits = self.fregs[rs1];
-                    let b_bits = self.fregs[rs2];
-                    self.regs[rd] = if is_nan_bits(a_bits) || is_nan_bits(b_bits) {
-                        0
-                    } else if f32_from_bits(a_bits) < f32_from_bits(b_bits) {
-                        1
-                    } else {
-                        0
-                    };
-                }
-                instruction::FRFunct::FleS => {
-                    let a_bits = self.fregs[rs1];
-                    let b_bits = self.fregs[rs2];
-                    self.regs[rd] = if is_nan_bits(a_bits) || is_nan_bits(b_bits) {
-                        0
-                    } else if f32_from_bits(a_bits) <= f32_from_bits(b_bits) {
-                        1
-                    } else {
-                        0
-                    };
+            } => {
+                // Single-precision operands are the NaN-boxed low halves of their registers.
+                let a_bits = unbox(self.fregs[rs1]);
+                let b_bits = unbox(self.fregs[rs2]);
+                let a = f32_from_bits(a_bits);
+                let b = f32_from_bits(b_bits);
+                let da = f64::from_bits(self.fregs[rs1]);
+                let db = f64::from_bits(self.fregs[rs2]);
+                match funct {
+                    instruction::FRFunct::FaddS => {
+                        self.fregs[rd] = nan_box(bits_from_f32(a + b));
+                    }
+                    instruction::FRFunct::FsubS => {
+                        self.fregs[rd] = nan_box(bits_from_f32(a - b));
+                    }
+                    instruction::FRFunct::FmulS => {
+                        self.fregs[rd] = nan_box(bits_from_f32(a * b));
+                    }
+                    instruction::FRFunct::FdivS => {
+                        self.fregs[rd] = nan_box(bits_from_f32(a / b));
+                    }
+                    instruction::FRFunct::FsgnjS => {
+                        self.fregs[rd] = nan_box((a_bits & 0x7fff_ffff) | (b_bits & 0x8000_0000));
+                    }
+                    instruction::FRFunct::FsgnjnS => {
+                        self.fregs[rd] =
+                            nan_box((a_bits & 0x7fff_ffff) | ((!b_bits) & 0x8000_0000));
+                    }
+                    instruction::FRFunct::FsgnjxS => {
+                        let sign = (a_bits ^ b_bits) & 0x8000_0000;
+                        self.fregs[rd] = nan_box((a_bits & 0x7fff_ffff) | sign);
+                    }
+                    instruction::FRFunct::FminS => {
+                        self.fregs[rd] = nan_box(if a.is_nan() && b.is_nan() {
+                            CANONICAL_NAN_S
+                        } else if a.is_nan() {
+                            b_bits
+                        } else if b.is_nan() {
+                            a_bits
+                        } else if a < b {
+                            a_bits
+                        } else if b < a {
+                            b_bits
+                        } else if a_bits == 0x8000_0000 || b_bits == 0x8000_0000 {
+                            0x8000_0000
+                        } else {
+                            a_bits
+                        });
+                    }
+                    instruction::FRFunct::FmaxS => {
+                        self.fregs[rd] = nan_box(if a.is_nan() && b.is_nan() {
+                            CANONICAL_NAN_S
+                        } else if a.is_nan() {
+                            b_bits
+                        } else if b.is_nan() {
+                            a_bits
+                        } else if a > b {
+                            a_bits
+                        } else if b > a {
+                            b_bits
+                        } else if a_bits == 0x0000_0000 || b_bits == 0x0000_0000 {
+                            0x0000_0000
+                        } else {
+                            a_bits
+                        });
+                    }
+                    instruction::FRFunct::FeqS => {
+                        self.regs[rd] = if is_nan_bits(a_bits) || is_nan_bits(b_bits) {
+                            0
+                        } else if a == b {
+                            1
+                        } else {
+                            0
+                        };
+                    }
+                    instruction::FRFunct::FltS => {
+                        self.regs[rd] = if is_nan_bits(a_bits) || is_nan_bits(b_bits) {
+                            0
+                        } else if a < b {
+                            1
+                        } else {
+                            0
+                        };
+                    }
+                    instruction::FRFunct::FleS => {
+                        self.regs[rd] = if is_nan_bits(a_bits) || is_nan_bits(b_bits) {
+                            0
+                        } else if a <= b {
+                            1
+                        } else {
+                            0
+                        };
+                    }
+                    instruction::FRFunct::FaddD => self.fregs[rd] = (da + db).to_bits(),
+                    instruction::FRFunct::FsubD => self.fregs[rd] = (da - db).to_bits(),
+                    instruction::FRFunct::FmulD => self.fregs[rd] = (da * db).to_bits(),
+                    instruction::FRFunct::FdivD => self.fregs[rd] = (da / db).to_bits(),
+                    instruction::FRFunct::FsgnjD => {
+                        self.fregs[rd] = (self.fregs[rs1] & !D_SIGN) | (self.fregs[rs2] & D_SIGN);
+                    }
+                    instruction::FRFunct::FsgnjnD => {
+                        self.fregs[rd] = (self.fregs[rs1] & !D_SIGN) | (!self.fregs[rs2] & D_SIGN);
+                    }
+                    instruction::FRFunct::FsgnjxD => {
+                        self.fregs[rd] = self.fregs[rs1] ^ (self.fregs[rs2] & D_SIGN);
+                    }
+                    instruction::FRFunct::FminD => {
+                        self.fregs[rd] = fmin_max_d(self.fregs[rs1], self.fregs[rs2], false);
+                    }
+                    instruction::FRFunct::FmaxD => {
+                        self.fregs[rd] = fmin_max_d(self.fregs[rs1], self.fregs[rs2], true);
+                    }
+                    // IEEE comparisons are false whenever an operand is NaN.
+                    instruction::FRFunct::FeqD => self.regs[rd] = u32::from(da == db),
+                    instruction::FRFunct::FltD => self.regs[rd] = u32::from(da < db),
+                    instruction::FRFunct::FleD => self.regs[rd] = u32::from(da <= db),
                 }
-            },
+            }
             Instruction::FI {
                 funct,
                 rd,
                 rs1,
                 rm: _,
-            } => match funct {
-                instruction::FIFunct::FsqrtS => {
-                    self.fregs[rd] = bits_from_f32(f32_from_bits(self.fregs[rs1]).sqrt());
-                }
-                instruction::FIFunct::FcvtWS => {
-                    self.regs[rd] = fcvt_w_s(f32_from_bits(self.fregs[rs1]), false);
-                }
-                instruction::FIFunct::FcvtWuS => {
-                    self.regs[rd] = fcvt_w_s(f32_from_bits(self.fregs[rs1]), true);
-                }
-                instruction::FIFunct::FmvXW => {
-                    self.regs[rd] = self.fregs[rs1];
-                }
-                instruction::FIFunct::FclassS => {
-                    self.regs[rd] = fclass_s(self.fregs[rs1]);
-                }
-                instruction::FIFunct::FcvtSW => {
-                    self.fregs[rd] = bits_from_f32((self.regs[rs1] as i32) as f32);
-                }
-                instruction::FIFunct::FcvtSWU => {
-                    self.fregs[rd] = bits_from_f32(self.regs[rs1] as f32);
-                }
-                instruction::FIFunct::FmvWX => {
-                    self.fregs[rd] = self.regs[rs1];
+            } => {
+                let a_bits = unbox(self.fregs[rs1]);
+                let a = f32_from_bits(a_bits);
+                let da = f64::from_bits(self.fregs[rs1]);
+                match funct {
+                    instruction::FIFunct::FsqrtS => {
+                        self.fregs[rd] = nan_box(bits_from_f32(a.sqrt()));
+                    }
+                    instruction::FIFunct::FcvtWS => {
+                        self.regs[rd] = fcvt_w(a.into(), false);
+                    }
+                    instruction::FIFunct::FcvtWuS => {
+                        self.regs[rd] = fcvt_w(a.into(), true);
+                    }
+                    // A pure bit move: the low half is copied even when it is not boxed.
+                    instruction::FIFunct::FmvXW => {
+                        self.regs[rd] = self.fregs[rs1] as u32;
+                    }
+                    instruction::FIFunct::FclassS => {
+                        self.regs[rd] = fclass_s(a_bits);
+                    }
+                    instruction::FIFunct::FcvtSW => {
+                        self.fregs[rd] = nan_box(bits_from_f32((self.regs[rs1] as i32) as f32));
+                    }
+                    instruction::FIFunct::FcvtSWU => {
+                        self.fregs[rd] = nan_box(bits_from_f32(self.regs[rs1] as f32));
+                    }
+                    instruction::FIFunct::FmvWX => {
+                        self.fregs[rd] = nan_box(self.regs[rs1]);
+                    }
+                    instruction::FIFunct::FsqrtD => self.fregs[rd] = da.sqrt().to_bits(),
+                    instruction::FIFunct::FcvtWD => self.regs[rd] = fcvt_w(da, false),
+                    instruction::FIFunct::FcvtWuD => self.regs[rd] = fcvt_w(da, true),
+                    instruction::FIFunct::FclassD => self.regs[rd] = fclass_d(self.fregs[rs1]),
+                    // Every 32-bit integer is exactly representable as a double.
+                    instruction::FIFunct::FcvtDW => {
+                        self.fregs[rd] = f64::from(self.regs[rs1] as i32).to_bits();
+                    }
+                    instruction::FIFunct::FcvtDWU => {
+                        self.fregs[rd] = f64::from(self.regs[rs1]).to_bits();
+                    }
+                    instruction::FIFunct::FcvtSD => {
+                        self.fregs[rd] = nan_box(bits_from_f32(da as f32));
+                    }
+                    instruction::FIFunct::FcvtDS => {
+                        self.fregs[rd] = f64::from(a).to_bits();
+                    }
                 }
-            },
+            }
             Instruction::FL {
                 funct,
                 rd,
@@ -535,21 +846,19 @@ impl Hart {
                     // Minimal half-precision support: keep payload in low 16 bits.
                     // Proper IEEE half handling/NaN-boxing is out of scope for this phase.
                     let addr = self.regs[rs1].wrapping_add_signed(imm);
-                    self.fregs[rd] = match dram.load(addr, 16) {
-                        Ok(v) => v & 0xffff,
-                        Err(()) => 0,
-                    };
+                    self.fregs[rd] = u64::from(load(dram, pc, addr, 16)? & 0xffff);
                 }
                 instruction::FLFunct::FLW => {
                     let addr = self.regs[rs1].wrapping_add_signed(imm);
-                    self.fregs[rd] = dram.load(addr, 32).unwrap_or(0);
+                    self.fregs[rd] = nan_box(load(dram, pc, addr, 32)?);
                 }
                 instruction::FLFunct::FLD => {
-                    // Single-precision register model: consume 64-bit memory access but
-                    // preserve only low 32 bits in fregs.
+                    // Two little-endian words; both are read before the register changes.
                     let addr = self.regs[rs1].wrapping_add_signed(imm);
-                    self.fregs[rd] = dram.load(addr, 32).unwrap_or(0);
-                    let _ = dram.load(addr.wrapping_add(4), 32);
+                    check_doubleword(dram, pc, addr, AccessKind::Load)?;
+                    let low = load(dram, pc, addr, 32)?;
+                    let high = load(dram, pc, addr.wrapping_add(4), 32)?;
+                    self.fregs[rd] = (u64::from(high) << 32) | u64::from(low);
                 }
             },
             Instruction::FS {
@@ -560,20 +869,22 @@ impl Hart {
             } => match funct {
                 instruction::FSFunct::FSH => {
                     let addr = self.regs[rs1].wrapping_add_signed(imm);
-                    let _ = dram.store(addr, 16, self.fregs[rs2]);
+                    store(dram, pc, addr, 16, self.fregs[rs2] as u32)?;
                     self.invalidate_reservation_if_overlaps(addr);
                 }
                 instruction::FSFunct::FSW => {
                     let addr = self.regs[rs1].wrapping_add_signed(imm);
-                    let _ = dram.store(addr, 32, self.fregs[rs2]);
+                    store(dram, pc, addr, 32, self.fregs[rs2] as u32)?;
                     self.invalidate_reservation_if_overlaps(addr);
                 }
                 instruction::FSFunct::FSD => {
-                    // Single-precision register model: write low 32 bits and NaN-box upper.
                     let addr = self.regs[rs1].wrapping_add_signed(imm);
-                    let _ = dram.store(addr, 32, self.fregs[rs2]);
-                    let _ = dram.store(addr.wrapping_add(4), 32, u32::MAX);
+                    let value = self.fregs[rs2];
+                    check_doubleword(dram, pc, addr, AccessKind::Store)?;
+                    store(dram, pc, addr, 32, value as u32)?;
+                    store(dram, pc, addr.wrapping_add(4), 32, (value >> 32) as u32)?;
                     self.invalidate_reservation_if_overlaps(addr);
+                    self.invalidate_reservation_if_overlaps(addr.wrapping_add(4));
                 }
             },
             Instruction::A {
@@ -587,19 +898,19 @@ impl Hart {
                 let addr = self.regs[rs1];
                 match funct {
                     instruction::AFunct::LrW => {
-                        self.regs[rd] = dram.load(addr, 32).unwrap_or(0);
+                        self.regs[rd] = load(dram, pc, addr, 32)?;
                         self.set_reservation(addr);
                     }
                     instruction::AFunct::ScW => {
                         let success = self.reservation_addr == Some(addr);
                         if success {
-                            let _ = dram.store(addr, 32, self.regs[rs2]);
+                            store(dram, pc, addr, 32, self.regs[rs2])?;
                         }
                         self.regs[rd] = if success { 0 } else { 1 };
                         self.clear_reservation();
                     }
                     _ => {
-                        let old = dram.load(addr, 32).unwrap_or(0);
+                        let old = load(dram, pc, addr, 32)?;
                         let rhs = self.regs[rs2];
                         let new = match funct {
                             instruction::AFunct::AmoSwapW => rhs,
@@ -637,25 +948,69 @@ impl Hart {
                             }
                             instruction::AFunct::LrW | instruction::AFunct::ScW => unreachable!(),
                         };
-                        let _ = dram.store(addr, 32, new);
+                        store(dram, pc, addr, 32, new)?;
                         self.regs[rd] = old;
                         self.invalidate_reservation_if_overlaps(addr);
                     }
                 }
             }
             Instruction::Fence {
+                funct: instruction::FenceFunct::Fence,
+                ..
+            } => {
+                // A single hart without caches or store buffers already sees
+                // every memory access in program order.
+            }
+            Instruction::Fence {
+                funct: instruction::FenceFunct::FenceI,
+                ..
+            } => {
+                // Every step fetches straight from memory and the decode cache
+                // checks the fetched word, so stores are visible to fetch at
+                // once and there is nothing to synchronise.
+            }
+            Instruction::Csr {
                 funct: _,
-                pred: _,
-                succ: _,
-                fm: _,
+                rd,
+                rs1: _,
+                csr,
             } => {
-                // Single-hart simplified model: fence/fence.i are no-ops.
+                // Writes to the read-only counters are rejected at decode; the
+                // CSRs that remain have no state, so writes to them are dropped.
+                self.regs[rd] = self.read_csr(csr);
+            }
+            Instruction::Ecall => {
+                let number = self.regs[17];
+                let args = [
+                    self.regs[10],
+                    self.regs[11],
+                    self.regs[12],
+                    self.regs[13],
+                    self.regs[14],
+                    self.regs[15],
+                    self.regs[16],
+                ];
+                let Some(ret) = self
+                    .syscalls
+                    .as_mut()
+                    .and_then(|handler| handler.syscall(number, args))
+                else {
+                    self.regs[0] = 0;
+                    return Err(EmulatorError::UnknownSyscall { pc, number });
+                };
+                self.regs[10] = ret;
             }
             Instruction::Ebreak => {
-                panic!("ebreak");
+                self.regs[0] = 0;
+                return Err(EmulatorError::Halt(HaltReason::Ebreak));
+            }
+            Instruction::Wfi => {
+                // A hint: retiring it as a no-op is always correct.
+                self.waiting_for_interrupt = true;
             }
         }
         self.regs[0] = 0;
+        Ok(())
     }
 }
 
@@ -673,93 +1028,105 @@ fn dram_size_for_loaded_end(max_load_end: u32) -> u32 {
 }
 
 pub trait RamLike: Send + Sync {
-    fn load(&self, addr: u32, size: u32) -> Result<u32, ()>;
-    fn store(&mut self, addr: u32, size: u32, value: u32) -> Result<(), ()>;
+    fn load(&self, addr: u32, size: u32) -> Result<u32, TrapCause>;
+    fn store(&mut self, addr: u32, size: u32, value: u32) -> Result<(), TrapCause>;
+
+    /// Whether a store since the last call asked the hart to stop for this
+    /// run; calling it clears the request.
+    fn take_yield(&mut self) -> bool {
+        false
+    }
+
+    /// Whether misaligned accesses are served rather than trapping.
+    fn allows_misaligned(&self) -> bool {
+        true
+    }
 }
 
 /// The dynamic random access dram (DRAM).
 #[derive(Debug)]
 pub struct Dram {
     pub dram: Vec<u8>,
+    /// Serve misaligned loads and stores byte by byte instead of trapping with
+    /// `TrapCause::Misaligned`.
+    pub allow_misaligned: bool,
 }
 
 #[allow(dead_code)]
 impl RamLike for Dram {
     /// Load bytes from the little-endiam dram.
-    fn load(&self, addr: u32, size: u32) -> Result<u32, ()> {
+    fn load(&self, addr: u32, size: u32) -> Result<u32, TrapCause> {
         trace!("load(addr: {addr:x}, size: {size})");
-        let addr = addr as usize;
-        let width = match size {
-            8 => 1usize,
-            16 => 2usize,
-            32 => 4usize,
-            _ => return Err(()),
-        };
-        if addr
-            .checked_add(width)
-            .is_none_or(|end| end > self.dram.len())
-        {
-            return Err(());
-        }
+        self.check_access(addr, size)?;
         match size {
-            8 => Ok(self.load8(addr as u32)),
-            16 => Ok(self.load16(addr as u32)),
-            32 => Ok(self.load32(addr as u32)),
+            8 => Ok(self.load8(addr)),
+            16 => Ok(self.load16(addr)),
+            32 => Ok(self.load32(addr)),
             _ => unreachable!(),
         }
     }
 
     /// Store bytes to the little-endiam dram.
-    fn store(&mut self, addr: u32, size: u32, value: u32) -> Result<(), ()> {
+    fn store(&mut self, addr: u32, size: u32, value: u32) -> Result<(), TrapCause> {
         trace!("store(addr: {addr:x}, size: {size})");
-        let addr = addr as usize;
-        let width = match size {
-            8 => 1usize,
-            16 => 2usize,
-            32 => 4usize,
-            _ => return Err(()),
-        };
-        if addr
-            .checked_add(width)
-            .is_none_or(|end| end > self.dram.len())
-        {
-            return Err(());
-        }
+        self.check_access(addr, size)?;
         match size {
             8 => {
-                self.store8(addr as u32, value);
+                self.store8(addr, value);
                 Ok(())
             }
             16 => {
-                self.store16(addr as u32, value);
+                self.store16(addr, value);
                 Ok(())
             }
             32 => {
-                self.store32(addr as u32, value);
+                self.store32(addr, value);
                 Ok(())
             }
             _ => unreachable!(),
         }
     }
+
+    fn allows_misaligned(&self) -> bool {
+        self.allow_misaligned
+    }
 }
 
 impl Dram {
     /// Create a new `Dram` instance with default dram size.
-    pub fn new(code: &[u8]) -> (Dram, u32) {
-        let elf = ElfBytes::<LittleEndian>::minimal_parse(code).expect("failed to parse elf file");
+    pub fn new(code: &[u8]) -> Result<(Dram, u32), EmulatorError> {
+        let elf = ElfBytes::<LittleEndian>::minimal_parse(code)
+            .map_err(|e| EmulatorError::ElfLoad(format!("failed to parse elf file: {e}")))?;
 
         let all_load_phdrs = elf
             .segments()
-            .unwrap()
+            .ok_or_else(|| EmulatorError::ElfLoad("elf file has no program headers".into()))?
             .iter()
             .filter(|phdr| phdr.p_type == PT_LOAD)
             .collect::<Vec<_>>();
 
-        let max_load_end = all_load_phdrs
-            .iter()
-            .map(|phdr| phdr.p_vaddr as u32 + phdr.p_memsz as u32)
-            .max()
-            .unwrap_or(0);
+        let mut max_load_end = 0u32;
+        for phdr in &all_load_phdrs {
+            let in_file = phdr
+                .p_offset
+                .checked_add(phdr.p_filesz)
+                .is_some_and(|end| end <= code.len() as u64);
+            let end = phdr
+                .p_vaddr
+                .checked_add(phdr.p_memsz)
+                .filter(|end| *end <= u64::from(u32::MAX - STACK_HEADROOM));
+            match end {
+                Some(end) if in_file && phdr.p_filesz <= phdr.p_memsz => {
+                    max_load_end = max_load_end.max(end as u32);
+                }
+                _ => {
+                    return Err(EmulatorError::ElfLoad(format!(
+                        "segment at {:#x} does not fit the image or address space",
+                        phdr.p_vaddr
+                    )));
+                }
+            }
+        }
         let dram_size = dram_size_for_loaded_end(max_load_end) as usize;
         let mut mem = vec![0u8; dram_size];
 
@@ -767,13 +1134,44 @@ impl Dram {
             let vaddr = phdr.p_vaddr as usize;
             let offset = phdr.p_offset as usize;
             let filesz = phdr.p_filesz as usize;
+            let memsz = phdr.p_memsz as usize;
 
             mem[vaddr..vaddr + filesz].copy_from_slice(&code[offset..offset + filesz]);
+            // `.bss`: the rest of the segment reads as zero even where an
+            // earlier segment already placed bytes.
+            mem[vaddr + filesz..vaddr + memsz].fill(0);
         }
 
         let entry = elf.ehdr.e_entry as u32;
         debug!("entry: {entry:x}");
-        (Self { dram: mem }, entry)
+        Ok((
+            Self {
+                dram: mem,
+                allow_misaligned: true,
+            },
+            entry,
+        ))
+    }
+
+    /// Validate a `size`-bit access at `addr`: the width must be supported,
+    /// the bytes in bounds and, unless allowed, the address aligned.
+    fn check_access(&self, addr: u32, size: u32) -> Result<(), TrapCause> {
+        let width = match size {
+            8 => 1u32,
+            16 => 2u32,
+            32 => 4u32,
+            _ => return Err(TrapCause::AccessFault),
+        };
+        if (addr as usize)
+            .checked_add(width as usize)
+            .is_none_or(|end| end > self.dram.len())
+        {
+            return Err(TrapCause::AccessFault);
+        }
+        if !self.allow_misaligned && !addr.is_multiple_of(width) {
+            return Err(TrapCause::Misaligned);
+        }
+        Ok(())
     }
 
     /// Load a byte from the little-endian dram.
@@ -823,74 +1221,182 @@ impl Dram {
 pub struct Mmu<'dram, 'slice, 'dev> {
     pub dram: &'dram mut Dram,
     pub devices: &'slice mut [&'dev mut dyn Device],
+    yield_requested: bool,
 }
 
 impl<'dram, 'slice, 'dev> Mmu<'dram, 'slice, 'dev> {
     pub fn new(dram: &'dram mut Dram, devices: &'slice mut [&'dev mut dyn Device]) -> Self {
-        Self { dram, devices }
+        Self {
+            dram,
+            devices,
+            yield_requested: false,
+        }
     }
 }
 
 impl RamLike for Mmu<'_, '_, '_> {
-    fn load(&self, addr: u32, size: u32) -> Result<u32, ()> {
+    fn load(&self, addr: u32, size: u32) -> Result<u32, TrapCause> {
         if addr >= 0x1000 {
             self.dram.load(addr, size)
         } else if addr >= 0x100 {
             let device_index = ((addr >> 8) & 0xF) as usize - 1;
             if let Some(device) = self.devices.get(device_index) {
-                device.load(addr & 0xFF, size)
+                device
+                    .load(addr & 0xFF, size)
+                    .map_err(|()| TrapCause::AccessFault)
             } else {
-                Err(())
+                Err(TrapCause::AccessFault)
             }
         } else {
-            Err(())
+            Err(TrapCause::AccessFault)
         }
     }
 
-    fn store(&mut self, addr: u32, size: u32, value: u32) -> Result<(), ()> {
+    fn store(&mut self, addr: u32, size: u32, value: u32) -> Result<(), TrapCause> {
         if addr >= 0x1000 {
             self.dram.store(addr, size, value)
         } else if addr >= 0x100 {
             let device_index = ((addr >> 8) & 0xF) as usize - 1;
             if let Some(device) = self.devices.get_mut(device_index) {
-                device.store(addr & 0xFF, size, value)
+                device
+                    .store(addr & 0xFF, size, value)
+                    .map_err(|()| TrapCause::AccessFault)?;
+                self.yield_requested |= device.take_yield();
+                Ok(())
             } else {
-                Err(())
+                Err(TrapCause::AccessFault)
             }
         } else {
-            Err(())
+            Err(TrapCause::AccessFault)
         }
     }
+
+    fn take_yield(&mut self) -> bool {
+        std::mem::take(&mut self.yield_requested)
+    }
+
+    fn allows_misaligned(&self) -> bool {
+        self.dram.allow_misaligned
+    }
 }
 
 pub trait Device: Send + Sync {
     fn load(&self, addr: u32, size: u32) -> Result<u32, ()>;
     fn store(&mut self, addr: u32, size: u32, value: u32) -> Result<(), ()>;
+
+    /// Whether the last store asked the CPU to give up the rest of its
+    /// budget for this run; calling it clears the request.
+    fn take_yield(&mut self) -> bool {
+        false
+    }
+}
+
+/// Memory-mapped device whose only job is cooperative yielding: any store to
+/// it ends the current `Hart::run` with `EmulatorError::Yielded`, and the
+/// program continues after the store on the next run. Loads read 0.
+#[derive(Component, Default)]
+pub struct YieldDevice {
+    requested: bool,
 }
 
+impl Device for YieldDevice {
+    fn load(&self, _addr: u32, _size: u32) -> Result<u32, ()> {
+        Ok(0)
+    }
+
+    fn store(&mut self, _addr: u32, _size: u32, _value: u32) -> Result<(), ()> {
+        self.requested = true;
+        Ok(())
+    }
+
+    fn take_yield(&mut self) -> bool {
+        std::mem::take(&mut self.requested)
+    }
+}
+
+/// Default `LogDevice` capacity in bytes of UTF-8 output.
+pub const DEFAULT_LOG_CAPACITY: usize = 64 * 1024;
+
 /// Memory-mapped log device that captures character output into a buffer.
 /// Characters are written as 32-bit values (Unicode code points).
-/// The buffer can be drained to retrieve accumulated output.
+/// The buffer is a fixed-capacity ring: once full, the oldest characters are
+/// dropped and the next drain reports `truncated`.
 #[derive(Component)]
 pub struct LogDevice {
-    buffer: String,
+    buffer: VecDeque<u8>,
+    capacity: usize,
+    truncated: bool,
+}
+
+/// Output taken from a `LogDevice` by `drain_output`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LogOutput {
+    pub text: String,
+    /// Older output was dropped since the previous drain because the buffer was full.
+    pub truncated: bool,
 }
 
 impl LogDevice {
     pub fn new() -> Self {
+        Self::with_capacity(DEFAULT_LOG_CAPACITY)
+    }
+
+    /// A log buffering at most `capacity` bytes (at least one full character).
+    pub fn with_capacity(capacity: usize) -> Self {
+        let capacity = capacity.max(4);
         Self {
-            buffer: String::new(),
+            buffer: VecDeque::with_capacity(capacity),
+            capacity,
+            truncated: false,
         }
     }
 
-    /// Drain all accumulated output, leaving the buffer empty.
-    pub fn drain_output(&mut self) -> String {
-        std::mem::take(&mut self.buffer)
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Drain all accumulated output, leaving the buffer empty and clearing the
+    /// truncation flag.
+    pub fn drain_output(&mut self) -> LogOutput {
+        let bytes: Vec<u8> = self.buffer.drain(..).collect();
+        LogOutput {
+            text: String::from_utf8(bytes).expect("log buffer holds whole characters"),
+            truncated: std::mem::take(&mut self.truncated),
+        }
     }
 
     /// Read all accumulated output without clearing.
-    pub fn output(&self) -> &str {
-        &self.buffer
+    pub fn output(&self) -> String {
+        let (front, back) = self.buffer.as_slices();
+        let mut text = String::from_utf8_lossy(front).into_owned();
+        text.push_str(&String::from_utf8_lossy(back));
+        text
+    }
+
+    /// Whether output was dropped since the last drain.
+    pub fn truncated(&self) -> bool {
+        self.truncated
+    }
+
+    /// Append host-side text, e.g. a note that the program stopped.
+    pub fn push_str(&mut self, text: &str) {
+        for ch in text.chars() {
+            self.push_char(ch);
+        }
+    }
+
+    fn push_char(&mut self, ch: char) {
+        let mut encoded = [0; 4];
+        let encoded = ch.encode_utf8(&mut encoded).as_bytes();
+        while self.buffer.len() + encoded.len() > self.capacity {
+            // Drop one whole character so the buffer stays valid UTF-8.
+            self.buffer.pop_front();
+            while self.buffer.front().is_some_and(|byte| byte & 0xc0 == 0x80) {
+                self.buffer.pop_front();
+            }
+            self.truncated = true;
+        }
+        self.buffer.extend(encoded);
     }
 }
 
@@ -910,7 +1416,7 @@ impl Device for LogDevice {
             return Err(());
         }
         if let Some(ch) = char::from_u32(value) {
-            self.buffer.push(ch);
+            self.push_char(ch);
         }
         Ok(())
     }
@@ -934,7 +1440,7 @@ mod tests {
     }
 
     impl RamLike for TestRam {
-        fn load(&self, addr: u32, size: u32) -> Result<u32, ()> {
+        fn load(&self, addr: u32, size: u32) -> Result<u32, TrapCause> {
             let i = addr as usize;
             Ok(match size {
                 8 => self.bytes[i] as u32,
@@ -945,11 +1451,11 @@ mod tests {
                         | ((self.bytes[i + 2] as u32) << 16)
                         | ((self.bytes[i + 3] as u32) << 24)
                 }
-                _ => return Err(()),
+                _ => return Err(TrapCause::AccessFault),
             })
         }
 
-        fn store(&mut self, addr: u32, size: u32, value: u32) -> Result<(), ()> {
+        fn store(&mut self, addr: u32, size: u32, value: u32) -> Result<(), TrapCause> {
             let i = addr as usize;
             match size {
                 8 => self.bytes[i] = value as u8,
@@ -963,7 +1469,7 @@ mod tests {
                     self.bytes[i + 2] = ((value >> 16) & 0xff) as u8;
                     self.bytes[i + 3] = ((value >> 24) & 0xff) as u8;
                 }
-                _ => return Err(()),
+                _ => return Err(TrapCause::AccessFault),
             }
             Ok(())
         }
@@ -985,7 +1491,8 @@ mod tests {
             },
             4,
             &mut ram,
-        );
+        )
+        .expect("execute");
         assert_eq!(h.regs[3], i32::MIN as u32);
 
         h.regs[2] = 0;
@@ -998,7 +1505,8 @@ mod tests {
             },
             4,
             &mut ram,
-        );
+        )
+        .expect("execute");
         assert_eq!(h.regs[4], u32::MAX);
     }
 
@@ -1021,7 +1529,8 @@ mod tests {
             },
             4,
             &mut ram,
-        );
+        )
+        .expect("execute");
         assert_eq!(h.regs[3], 10);
 
         h.execute(
@@ -1035,7 +1544,8 @@ mod tests {
             },
             4,
             &mut ram,
-        );
+        )
+        .expect("execute");
         assert_eq!(h.regs[4], 0);
         assert_eq!(ram.load(100, 32).unwrap(), 55);
 
@@ -1051,7 +1561,8 @@ mod tests {
             },
             4,
             &mut ram,
-        );
+        )
+        .expect("execute");
         h.execute(
             Instruction::S {
                 funct: SFunct::SW,
@@ -1061,7 +1572,8 @@ mod tests {
             },
             4,
             &mut ram,
-        );
+        )
+        .expect("execute");
         h.execute(
             Instruction::A {
                 funct: AFunct::ScW,
@@ -1073,7 +1585,8 @@ mod tests {
             },
             4,
             &mut ram,
-        );
+        )
+        .expect("execute");
         assert_eq!(h.regs[5], 1);
     }
 
@@ -1096,7 +1609,8 @@ mod tests {
             },
             4,
             &mut ram,
-        );
+        )
+        .expect("execute");
         assert_eq!(h.regs[4], 3);
         assert_eq!(ram.load(100, 32).unwrap(), 10);
     }
@@ -1107,8 +1621,8 @@ mod tests {
         let mut ram = TestRam::new(1024);
         h.pc = 100;
         h.regs[1] = 200;
-        let (inst, len) = Instruction::parse_with_len(0x8082); // c.jr ra
-        h.execute(inst, len, &mut ram);
+        let (inst, len) = Instruction::parse_with_len(0x8082).expect("decode"); // c.jr ra
+        h.execute(inst, len, &mut ram).expect("execute");
         assert_eq!(h.pc, 200);
     }
 
@@ -1119,12 +1633,12 @@ mod tests {
         h.regs[2] = 128;
         h.fregs[1] = 0x3f80_0000;
 
-        let fswsp = Instruction::parse_with_len(0xe206).0; // c.fswsp f1, 4(sp)
-        h.execute(fswsp, 2, &mut ram);
+        let fswsp = Instruction::parse_with_len(0xe206).expect("decode").0; // c.fswsp f1, 4(sp)
+        h.execute(fswsp, 2, &mut ram).expect("execute");
         assert_eq!(ram.load(132, 32).unwrap(), 0x3f80_0000);
 
         h.fregs[1] = 0;
-        let flwsp = Instruction::parse_with_len(0x6092).0; // c.flwsp f1, 4(sp)
+        let flwsp = Instruction::parse_with_len(0x6092).expect("decode").0; // c.flwsp f1, 4(sp)
         h.execute(
             Instruction::FL {
                 funct: FLFunct::FLW,
@@ -1134,9 +1648,10 @@ mod tests {
             },
             4,
             &mut ram,
-        );
-        h.execute(flwsp, 2, &mut ram);
-        assert_eq!(h.fregs[1], 0x3f80_0000);
+        )
+        .expect("execute");
+        h.execute(flwsp, 2, &mut ram).expect("execute");
+        assert_eq!(h.fregs[1], 0xffff_ffff_3f80_0000);
     }
 
     #[test]
@@ -1144,7 +1659,7 @@ mod tests {
         let mut h = Hart::new(0);
         let mut ram = TestRam::new(1024);
         h.regs[1] = 200;
-        h.fregs[2] = 0x1234_5678;
+        h.fregs[2] = 0xffff_ffff_1234_5678;
 
         h.execute(
             Instruction::FS {
@@ -1155,7 +1670,8 @@ mod tests {
             },
             4,
             &mut ram,
-        );
+        )
+        .expect("execute");
         assert_eq!(ram.load(200, 16).unwrap(), 0x5678);
 
         h.fregs[3] = 0;
@@ -1168,7 +1684,8 @@ mod tests {
             },
             4,
             &mut ram,
-        );
+        )
+        .expect("execute");
         assert_eq!(h.fregs[3], 0x5678);
 
         h.execute(
@@ -1180,7 +1697,8 @@ mod tests {
             },
             4,
             &mut ram,
-        );
+        )
+        .expect("execute");
         assert_eq!(ram.load(208, 32).unwrap(), 0x1234_5678);
         assert_eq!(ram.load(212, 32).unwrap(), u32::MAX);
 
@@ -1194,8 +1712,94 @@ mod tests {
             },
             4,
             &mut ram,
-        );
-        assert_eq!(h.fregs[4], 0x1234_5678);
+        )
+        .expect("execute");
+        assert_eq!(h.fregs[4], 0xffff_ffff_1234_5678);
+    }
+
+    #[test]
+    fn double_precision_arithmetic_uses_full_registers() {
+        let mut h = Hart::new(0);
+        let mut ram = TestRam::new(1024);
+        let mut run = |h: &mut Hart, word: u32| {
+            let (inst, len) = Instruction::parse_with_len(word).expect("decode");
+            h.execute(inst, len, &mut ram).expect("execute");
+        };
+
+        h.regs[10] = (-3i32) as u32;
+        h.fregs[0] = 1.5f64.to_bits();
+        run(&mut h, 0xd20570d3); // fcvt.d.w ft1, a0
+        assert_eq!(h.fregs[1], (-3.0f64).to_bits());
+
+        h.fregs[1] = 2.25f64.to_bits();
+        run(&mut h, 0x02107153); // fadd.d ft2, ft0, ft1
+        assert_eq!(h.fregs[2], 3.75f64.to_bits());
+
+        run(&mut h, 0xc20115d3); // fcvt.w.d a1, ft2, rtz
+        assert_eq!(h.regs[11], 3);
+        run(&mut h, 0xa2212653); // feq.d a2, ft2, ft2
+        assert_eq!(h.regs[12], 1);
+
+        // Results keep double precision rather than rounding through f32.
+        h.fregs[0] = 0.1f64.to_bits();
+        run(&mut h, 0x02107153); // fadd.d ft2, ft0, ft1
+        assert_eq!(h.fregs[2], (0.1f64 + 2.25).to_bits());
+        assert_ne!(f64::from_bits(h.fregs[2]), f64::from(0.1f32 + 2.25));
+    }
+
+    #[test]
+    fn fclass_s_reports_one_class_bit_per_value() {
+        let mut h = Hart::new(0);
+        let mut ram = TestRam::new(1024);
+        let mut classify = |h: &mut Hart, freg: u64| {
+            h.fregs[10] = freg;
+            let (inst, len) = Instruction::parse_with_len(0xe0051553).expect("decode"); // fclass.s a0, fa0
+            h.execute(inst, len, &mut ram).expect("execute");
+            h.regs[10]
+        };
+        let boxed = |value: f32| 0xffff_ffff_0000_0000 | u64::from(value.to_bits());
+
+        assert_eq!(classify(&mut h, boxed(f32::NEG_INFINITY)), 1 << 0);
+        assert_eq!(classify(&mut h, boxed(-1.5)), 1 << 1);
+        assert_eq!(classify(&mut h, boxed(-f32::from_bits(1))), 1 << 2);
+        assert_eq!(classify(&mut h, boxed(-0.0)), 1 << 3);
+        assert_eq!(classify(&mut h, boxed(0.0)), 1 << 4);
+        assert_eq!(classify(&mut h, boxed(f32::from_bits(0x007f_ffff))), 1 << 5);
+        assert_eq!(classify(&mut h, boxed(f32::MIN_POSITIVE)), 1 << 6);
+        assert_eq!(classify(&mut h, boxed(f32::INFINITY)), 1 << 7);
+        assert_eq!(classify(&mut h, 0xffff_ffff_7f80_0001), 1 << 8); // sNaN
+        assert_eq!(classify(&mut h, 0xffff_ffff_7fc0_0000), 1 << 9); // qNaN
+        // Not NaN-boxed: reads as the canonical quiet NaN, whatever the low half holds.
+        assert_eq!(classify(&mut h, u64::from(1.0f32.to_bits())), 1 << 9);
+        assert_eq!(classify(&mut h, 1.0f64.to_bits()), 1 << 9);
+    }
+
+    #[test]
+    fn single_precision_operands_must_be_nan_boxed() {
+        let mut h = Hart::new(0);
+        let mut ram = TestRam::new(1024);
+        let mut run = |h: &mut Hart, word: u32| {
+            let (inst, len) = Instruction::parse_with_len(word).expect("decode");
+            h.execute(inst, len, &mut ram).expect("execute");
+        };
+
+        // fmv.w.x boxes, fmv.x.w moves the low half back unchanged.
+        h.regs[10] = 2.5f32.to_bits();
+        run(&mut h, 0xf0050553); // fmv.w.x fa0, a0
+        assert_eq!(h.fregs[10], 0xffff_ffff_4020_0000);
+        h.fregs[10] = 2.5f64.to_bits();
+        run(&mut h, 0xe00505d3); // fmv.x.w a1, fa0
+        assert_eq!(h.regs[11], 2.5f64.to_bits() as u32);
+
+        // Arithmetic on an unboxed operand sees a NaN and produces the canonical NaN.
+        h.fregs[11] = u64::from(1.0f32.to_bits());
+        h.fregs[12] = 0xffff_ffff_0000_0000 | u64::from(1.0f32.to_bits());
+        run(&mut h, 0x00c58553); // fadd.s fa0, fa1, fa2
+        assert_eq!(h.fregs[10], 0xffff_ffff_7fc0_0000);
+        run(&mut h, 0x20c58553); // fsgnj.s fa0, fa1, fa2
+        assert_eq!(h.fregs[10], 0xffff_ffff_7fc0_0000);
+        run(&mut h, 0x28c58553); // fmin.s fa0, fa1, fa2
+        assert_eq!(h.fregs[10], h.fregs[12]);
     }
 
     #[test]
@@ -1203,7 +1807,7 @@ mod tests {
         let mut h = Hart::new(0);
         let mut ram = TestRam::new(1024);
         h.regs[1] = 5;
-        let (inst, len) = Instruction::parse_with_len(0x0085); // c.addi x1, 1
+        let (inst, len) = Instruction::parse_with_len(0x0085).expect("decode"); // c.addi x1, 1
         match inst {
             Instruction::I {
                 funct: IFunct::ADDI,
@@ -1211,13 +1815,13 @@ mod tests {
             } => {}
             _ => panic!("unexpected decode"),
         }
-        h.execute(inst, len, &mut ram);
+        h.execute(inst, len, &mut ram).expect("execute");
         assert_eq!(h.regs[1], 6);
     }
 
     #[test]
     fn parse_compressed_lw_swsp_variants() {
-        let (inst, len) = Instruction::parse_with_len(0xc20c); // representative c.sw
+        let (inst, len) = Instruction::parse_with_len(0xc20c).expect("decode"); // representative c.sw
         assert_eq!(len, 2);
         match inst {
             Instruction::S {
@@ -1226,7 +1830,7 @@ mod tests {
             _ => panic!("unexpected decode"),
         }
 
-        let (inst, len) = Instruction::parse_with_len(0x4102); // representative c.lwsp
+        let (inst, len) = Instruction::parse_with_len(0x4102).expect("decode"); // representative c.lwsp
         assert_eq!(len, 2);
         match inst {
             Instruction::I {
@@ -1235,7 +1839,7 @@ mod tests {
             _ => panic!("unexpected decode"),
         }
 
-        let (inst, len) = Instruction::parse_with_len(0xc006); // representative c.swsp
+        let (inst, len) = Instruction::parse_with_len(0xc006).expect("decode"); // representative c.swsp
         assert_eq!(len, 2);
         match inst {
             Instruction::S {
@@ -1244,7 +1848,7 @@ mod tests {
             _ => panic!("unexpected decode"),
         }
 
-        let (inst, len) = Instruction::parse_with_len(0xe206); // c.fswsp
+        let (inst, len) = Instruction::parse_with_len(0xe206).expect("decode"); // c.fswsp
         assert_eq!(len, 2);
         match inst {
             Instruction::FS {
@@ -1273,4 +1877,579 @@ mod tests {
         assert!(sized >= large_end + STACK_HEADROOM);
         assert_eq!(sized & 0xf, 0);
     }
+
+    fn dram_with_words(words: &[(u32, u32)]) -> Dram {
+        let mut dram = Dram {
+            dram: vec![0; 0x100],
+            allow_misaligned: true,
+        };
+        for &(addr, word) in words {
+            dram.store(addr, 32, word).expect("store");
+        }
+        dram
+    }
+
+    /// Little-endian RV32 ELF with one `PT_LOAD` per `(vaddr, file bytes, memsz)`.
+    fn elf_with_segments(segments: &[(u32, &[u8], u32)]) -> Vec<u8> {
+        const EHDR_SIZE: u32 = 52;
+        const PHDR_SIZE: u32 = 32;
+        let mut elf = vec![0x7f, b'E', b'L', b'F', 1, 1, 1];
+        elf.resize(16, 0);
+        for half in [2u16, 0xf3] {
+            elf.extend_from_slice(&half.to_le_bytes());
+        }
+        let entry = segments.first().map_or(0, |segment| segment.0);
+        for word in [1, entry, EHDR_SIZE, 0, 0] {
+            elf.extend_from_slice(&word.to_le_bytes());
+        }
+        for half in [
+            EHDR_SIZE as u16,
+            PHDR_SIZE as u16,
+            segments.len() as u16,
+            40,
+            0,
+            0,
+        ] {
+            elf.extend_from_slice(&half.to_le_bytes());
+        }
+
+        let mut offset = EHDR_SIZE + PHDR_SIZE * segments.len() as u32;
+        for (vaddr, bytes, memsz) in segments {
+            let filesz = bytes.len() as u32;
+            for word in [1, offset, *vaddr, *vaddr, filesz, *memsz, 0b111, 4] {
+                elf.extend_from_slice(&word.to_le_bytes());
+            }
+            offset += filesz;
+        }
+        for (_, bytes, _) in segments {
+            elf.extend_from_slice(bytes);
+        }
+        elf
+    }
+
+    #[test]
+    fn elf_loader_places_every_segment_and_zeroes_bss() {
+        let elf = elf_with_segments(&[
+            (0x1000, &[0x13, 0, 0, 0], 4),
+            (0x2000, &[0xaa; 16], 16),
+            // Overlaps the tail of the previous segment with `.bss`.
+            (0x2008, &[0x44, 0x33, 0x22, 0x11], 0x100),
+        ]);
+        let (dram, entry) = Dram::new(&elf).expect("load elf");
+
+        assert_eq!(entry, 0x1000);
+        assert_eq!(dram.load32(0x1000), 0x13);
+        assert_eq!(dram.load32(0x2000), 0xaaaa_aaaa);
+        assert_eq!(dram.load32(0x2008), 0x1122_3344);
+        for addr in (0x200c..0x2108).step_by(4) {
+            assert_eq!(dram.load32(addr), 0, "bss word at {addr:#x}");
+        }
+    }
+
+    #[test]
+    fn elf_loader_rejects_segments_outside_the_address_space() {
+        let elf = elf_with_segments(&[(u32::MAX - 16, &[0; 4], 4)]);
+        assert!(matches!(Dram::new(&elf), Err(EmulatorError::ElfLoad(_))));
+
+        let mut truncated = elf_with_segments(&[(0x1000, &[0; 64], 64)]);
+        truncated.truncate(truncated.len() - 8);
+        assert!(matches!(
+            Dram::new(&truncated),
+            Err(EmulatorError::ElfLoad(_))
+        ));
+    }
+
+    #[test]
+    fn step_reports_decode_error_for_unknown_opcode() {
+        let mut h = Hart::new(0x10);
+        let mut dram = dram_with_words(&[(0x10, 0xffff_ffff)]);
+        match h.step(&mut dram) {
+            Err(EmulatorError::Decode { pc, error }) => {
+                assert_eq!(pc, 0x10);
+                assert_eq!(error.word, 0xffff_ffff);
+            }
+            other => panic!("expected decode error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn step_reports_memory_fault_when_pc_leaves_memory() {
+        let mut h = Hart::new(0x1000);
+        let mut dram = dram_with_words(&[]);
+        assert_eq!(
+            h.step(&mut dram),
+            Err(EmulatorError::MemoryFault {
+                pc: 0x1000,
+                addr: 0x1000,
+                access: AccessKind::Fetch,
+            })
+        );
+    }
+
+    #[test]
+    fn misaligned_word_load_at_the_end_of_dram() {
+        let mut dram = dram_with_words(&[(0xf8, 0x4433_2211), (0xfc, 0x8877_6655)]);
+        // Misaligned but in bounds: served byte by byte unless disallowed.
+        assert_eq!(dram.load(0xfa, 32), Ok(0x6655_4433));
+        // The last word would run one byte past the end.
+        assert_eq!(dram.load(0xfd, 32), Err(TrapCause::AccessFault));
+
+        dram.allow_misaligned = false;
+        assert_eq!(dram.load(0xfa, 32), Err(TrapCause::Misaligned));
+        assert_eq!(dram.load(0xfc, 32), Ok(0x8877_6655));
+        assert_eq!(dram.load(0xfd, 32), Err(TrapCause::AccessFault));
+    }
+
+    #[test]
+    fn out_of_bounds_store_is_an_access_fault_and_writes_nothing() {
+        let mut dram = dram_with_words(&[]);
+        assert_eq!(
+            dram.store(0xfe, 32, 0xffff_ffff),
+            Err(TrapCause::AccessFault)
+        );
+        assert_eq!(dram.store(0x100, 8, 0xff), Err(TrapCause::AccessFault));
+        assert!(dram.dram.iter().all(|&byte| byte == 0));
+    }
+
+    #[test]
+    fn misaligned_load_traps_when_disallowed() {
+        // c.nop, then `lw x5, 2(x0)` on a 2-byte boundary.
+        let mut dram = dram_with_words(&[(0x10, 0x2283_0001), (0x14, 0x0000_0020)]);
+        dram.allow_misaligned = false;
+        let mut h = Hart::new(0x10);
+        assert_eq!(h.step(&mut dram), Ok(()));
+        assert_eq!(
+            h.step(&mut dram),
+            Err(EmulatorError::Misaligned {
+                pc: 0x12,
+                addr: 2,
+                access: AccessKind::Load,
+            })
+        );
+    }
+
+    #[test]
+    fn word_aligned_doubleword_access_traps_when_misaligned_is_disallowed() {
+        let mut dram = dram_with_words(&[(0x10, 0x1111_1111), (0x14, 0x2222_2222)]);
+        dram.allow_misaligned = false;
+        let mut h = Hart::new(0x40);
+        h.regs[1] = 0x14;
+        h.fregs[2] = 0x3333_3333_4444_4444;
+
+        // 0x14 is 4-aligned, so each half alone would pass the word check.
+        assert_eq!(
+            h.execute(
+                Instruction::FL {
+                    funct: FLFunct::FLD,
+                    rd: 3,
+                    rs1: 1,
+                    imm: 0,
+                },
+                4,
+                &mut dram,
+            ),
+            Err(EmulatorError::Misaligned {
+                pc: 0x40,
+                addr: 0x14,
+                access: AccessKind::Load,
+            })
+        );
+        assert_eq!(h.fregs[3], 0);
+
+        h.pc = 0x40;
+        assert_eq!(
+            h.execute(
+                Instruction::FS {
+                    funct: FSFunct::FSD,
+                    rs1: 1,
+                    rs2: 2,
+                    imm: 0,
+                },
+                4,
+                &mut dram,
+            ),
+            Err(EmulatorError::Misaligned {
+                pc: 0x40,
+                addr: 0x14,
+                access: AccessKind::Store,
+            })
+        );
+        assert_eq!(dram.load(0x14, 32), Ok(0x2222_2222));
+        assert_eq!(dram.load(0x18, 32), Ok(0));
+
+        // 8-aligned doublewords are still served.
+        h.regs[1] = 0x10;
+        h.execute(
+            Instruction::FL {
+                funct: FLFunct::FLD,
+                rd: 3,
+                rs1: 1,
+                imm: 0,
+            },
+            4,
+            &mut dram,
+        )
+        .expect("execute");
+        assert_eq!(h.fregs[3], 0x2222_2222_1111_1111);
+    }
+
+    #[test]
+    fn wfi_retires_as_a_no_op_and_yields_the_run() {
+        let program = [
+            (0x10, 0x1050_0073), // wfi
+            (0x14, 0x0010_8093), // addi ra, ra, 1
+            (0x18, 0x0010_0073), // ebreak
+        ];
+        let mut h = Hart::new(0x10);
+        let mut dram = dram_with_words(&program);
+        assert_eq!(h.step(&mut dram), Ok(()));
+        assert_eq!(h.pc, 0x14);
+        assert_eq!(h.instret, 1);
+
+        let mut h = Hart::new(0x10);
+        assert_eq!(h.run(&mut dram, 100), EmulatorError::Yielded);
+        assert_eq!(h.pc, 0x14);
+        assert_eq!(
+            h.run(&mut dram, 100),
+            EmulatorError::Halt(HaltReason::Ebreak)
+        );
+        assert_eq!(h.regs[1], 1);
+    }
+
+    #[test]
+    fn mret_traps_as_an_illegal_instruction() {
+        let mut h = Hart::new(0x10);
+        let mut dram = dram_with_words(&[(0x10, 0x3020_0073)]);
+        let error = h.step(&mut dram).expect_err("mret is privileged");
+        assert!(error.is_trap());
+        assert_eq!(
+            error.to_string(),
+            "trap: illegal instruction at pc=0x10: cannot decode 0x30200073: mret is illegal in user mode"
+        );
+        assert_eq!(h.pc, 0x10);
+    }
+
+    #[test]
+    fn step_reports_halt_on_ebreak() {
+        let mut h = Hart::new(0x10);
+        let mut dram = dram_with_words(&[(0x10, 0x9002)]); // c.ebreak
+        assert_eq!(
+            h.step(&mut dram),
+            Err(EmulatorError::Halt(HaltReason::Ebreak))
+        );
+    }
+
+    #[test]
+    fn fence_i_runs_instructions_written_by_the_program() {
+        let program = [
+            (0x10, 0x02a0_00b7), // lui ra, 0x2a00
+            (0x14, 0x1130_8093), // addi ra, ra, 0x113 (ra = `addi sp, zero, 42`)
+            (0x18, 0x0410_2023), // sw ra, 0x40(zero)
+            (0x1c, 0x0000_100f), // fence.i
+            (0x20, 0x0200_006f), // j 0x40
+            (0x40, 0x0010_0073), // ebreak, overwritten before it runs
+            (0x44, 0x0010_0073), // ebreak
+        ];
+        let mut h = Hart::new(0x10);
+        let mut dram = dram_with_words(&program);
+        assert_eq!(
+            h.run(&mut dram, 100),
+            EmulatorError::Halt(HaltReason::Ebreak)
+        );
+        assert_eq!(h.regs[2], 42);
+        assert_eq!(h.instructions_retired(), 6);
+    }
+
+    /// Sums `i * i` over 1000 iterations through a 64-word scratch buffer;
+    /// the loop mixes M, loads, stores, a compressed `c.addi` and a branch.
+    const LOOP_PROGRAM: [(u32, u32); 13] = [
+        (0x00, 0x0000_0293), // addi t0, zero, 0
+        (0x04, 0x3e80_0313), // addi t1, zero, 1000
+        (0x08, 0x0000_0393), // addi t2, zero, 0
+        (0x0c, 0x0252_8e33), // loop: mul t3, t0, t0
+        (0x10, 0x01c3_83b3), // add t2, t2, t3
+        (0x14, 0x0022_9e93), // slli t4, t0, 2
+        (0x18, 0x0fce_fe93), // andi t4, t4, 0xfc
+        (0x1c, 0x407e_a023), // sw t2, 0x400(t4)
+        (0x20, 0x400e_af03), // lw t5, 0x400(t4)
+        (0x24, 0x01e3_83b3), // add t2, t2, t5
+        (0x28, 0x0285),      // c.addi t0, 1
+        (0x2a, 0xfe62_c1e3), // blt t0, t1, loop
+        (0x2e, 0x0010_0073), // ebreak
+    ];
+
+    fn dram_with_loop_program() -> Dram {
+        let mut dram = Dram {
+            dram: vec![0; 0x800],
+            allow_misaligned: true,
+        };
+        for &(addr, encoding) in &LOOP_PROGRAM {
+            let size = if encoding & 0x3 == 0x3 { 32 } else { 16 };
+            dram.store(addr, size, encoding).expect("store");
+        }
+        dram
+    }
+
+    #[test]
+    fn decode_cache_matches_uncached_execution() {
+        let run = |cached: bool| {
+            let mut h = Hart::new(0);
+            h.set_decode_cache(cached);
+            let mut dram = dram_with_loop_program();
+            let stop = h.run(&mut dram, 100_000);
+            (stop, h.regs, h.pc, h.instret, h.cycles, dram.dram)
+        };
+        let cached = run(true);
+        assert_eq!(cached.0, EmulatorError::Halt(HaltReason::Ebreak));
+        assert_eq!(cached.1[5], 1000);
+        assert_eq!(cached.3, 3 + 1000 * 9);
+        assert_eq!(cached, run(false));
+    }
+
+    #[test]
+    fn decode_cache_sees_code_rewritten_between_runs() {
+        let mut h = Hart::new(0);
+        let mut dram = dram_with_loop_program();
+        h.run(&mut dram, 100_000);
+        assert_eq!(h.regs[5], 1000);
+
+        // The host patches the loop bound in place, as a snapshot restore would.
+        dram.dram[0x04..0x08].copy_from_slice(&0x00a0_0313_u32.to_le_bytes()); // addi t1, zero, 10
+        h.pc = 0;
+        assert_eq!(
+            h.run(&mut dram, 100_000),
+            EmulatorError::Halt(HaltReason::Ebreak)
+        );
+        assert_eq!(h.regs[5], 10);
+    }
+
+    /// PC-relative instructions (`auipc`, `jal`, branches and the `jalr` of a
+    /// call thunk) must work from the address of the instruction itself, not
+    /// from the already advanced `pc`, whatever the length of the
+    /// instructions around them.
+    mod pc_relative {
+        use super::*;
+
+        /// DRAM holding `code` as `(addr, encoding)`; encodings whose low two
+        /// bits are not `0b11` are stored as 16-bit compressed parcels.
+        fn dram_with_code(code: &[(u32, u32)]) -> Dram {
+            let mut dram = dram_with_words(&[]);
+            for &(addr, encoding) in code {
+                let size = if encoding & 0x3 == 0x3 { 32 } else { 16 };
+                dram.store(addr, size, encoding).expect("store");
+            }
+            dram
+        }
+
+        #[test]
+        fn auipc_addi_forms_an_address_relative_to_the_auipc() {
+            let program = [
+                (0x10, 0x0000_1517), // auipc a0, 0x1
+                (0x14, 0xff85_0513), // addi a0, a0, -8
+                (0x18, 0xffff_f597), // auipc a1, 0xfffff
+                (0x1c, 0x0010_0073), // ebreak
+            ];
+            let mut h = Hart::new(0x10);
+            let mut dram = dram_with_code(&program);
+            assert_eq!(
+                h.run(&mut dram, 10),
+                EmulatorError::Halt(HaltReason::Ebreak)
+            );
+            assert_eq!(h.regs[10], 0x10 + 0x1000 - 8);
+            assert_eq!(h.regs[11], 0x18u32.wrapping_sub(0x1000));
+        }
+
+        #[test]
+        fn auipc_jalr_call_thunk_reaches_the_callee_and_returns() {
+            let program = [
+                (0x10, 0x0000_0097), // auipc ra, 0
+                (0x14, 0x0200_80e7), // jalr ra, 0x20(ra) -> 0x30
+                (0x18, 0x0010_0073), // ebreak
+                (0x30, 0x0070_0513), // addi a0, zero, 7
+                (0x34, 0x0000_8067), // ret
+            ];
+            let mut h = Hart::new(0x10);
+            let mut dram = dram_with_code(&program);
+            assert_eq!(
+                h.run(&mut dram, 10),
+                EmulatorError::Halt(HaltReason::Ebreak)
+            );
+            assert_eq!(h.regs[10], 7);
+            assert_eq!(h.regs[1], 0x18);
+            assert_eq!(h.instructions_retired(), 4);
+        }
+
+        #[test]
+        fn pc_relative_targets_follow_compressed_instruction_lengths() {
+            let program = [
+                (0x10, 0x0001),      // c.nop
+                (0x12, 0x0000_0517), // auipc a0, 0
+                (0x16, 0x0001),      // c.nop
+                (0x18, 0x0000_1597), // auipc a1, 0x1
+                (0x1c, 0x0001),      // c.nop
+                (0x1e, 0x0080_00ef), // jal ra, 8 -> 0x26
+                (0x22, 0x0010_0613), // addi a2, zero, 1 (skipped)
+                (0x26, 0x0000_0463), // beqz zero, 8 -> 0x2e
+                (0x2a, 0x0010_0613), // addi a2, zero, 1 (skipped)
+                (0x2e, 0x0010_0073), // ebreak
+            ];
+            let mut h = Hart::new(0x10);
+            let mut dram = dram_with_code(&program);
+            assert_eq!(
+                h.run(&mut dram, 20),
+                EmulatorError::Halt(HaltReason::Ebreak)
+            );
+            assert_eq!(h.regs[10], 0x12);
+            assert_eq!(h.regs[11], 0x1018);
+            assert_eq!(h.regs[1], 0x22);
+            assert_eq!(h.regs[12], 0);
+            assert_eq!(h.instructions_retired(), 7);
+        }
+    }
+
+    #[test]
+    fn counters_are_deterministic_and_weight_multiplies() {
+        // addi x1, x0, 5; loop: mul x2, x1, x1; addi x1, x1, -1; bne x1, x0, loop; ebreak
+        let program = [
+            (0x10, 0x0050_0093),
+            (0x14, 0x0210_8133),
+            (0x18, 0xfff0_8093),
+            (0x1c, 0xfe00_9ce3),
+            (0x20, 0x0010_0073),
+        ];
+        for _ in 0..2 {
+            let mut h = Hart::new(0x10);
+            let mut dram = dram_with_words(&program);
+            assert_eq!(
+                h.run(&mut dram, 100),
+                EmulatorError::Halt(HaltReason::Ebreak)
+            );
+            assert_eq!(h.regs[2], 1);
+            // ebreak stops the program without retiring.
+            assert_eq!(h.instructions_retired(), 1 + 5 * 3);
+            assert_eq!(h.cycles(), 1 + 5 * (3 + 1 + 1));
+
+            h.reset_counters();
+            assert_eq!((h.instructions_retired(), h.cycles()), (0, 0));
+        }
+    }
+
+    #[test]
+    fn csr_reads_expose_the_retired_counters() {
+        let program = [
+            (0x10, 0x0050_0093), // addi ra, zero, 5
+            (0x14, 0x0010_8093), // addi ra, ra, 1
+            (0x18, 0xc020_2573), // csrr a0, instret
+            (0x1c, 0xc000_2673), // csrr a2, cycle
+            (0x20, 0x3402_d073), // csrrwi zero, 0x340, 5
+            (0x24, 0x3400_26f3), // csrr a3, 0x340
+            (0x28, 0x0010_0073), // ebreak
+        ];
+        let mut h = Hart::new(0x10);
+        let mut dram = dram_with_words(&program);
+        assert_eq!(
+            h.run(&mut dram, 100),
+            EmulatorError::Halt(HaltReason::Ebreak)
+        );
+        assert_eq!(h.regs[10], 2);
+        assert_eq!(h.regs[12], 3);
+        // Unsupported CSRs ignore writes and read as 0.
+        assert_eq!(h.regs[13], 0);
+
+        let mut h = Hart::new(0x10);
+        h.instret = (1 << 32) + 7;
+        // csrr a0, instret; csrr a1, instreth
+        let mut dram = dram_with_words(&[(0x10, 0xc020_2573), (0x14, 0xc820_25f3)]);
+        h.step(&mut dram).expect("csrr instret");
+        h.step(&mut dram).expect("csrr instreth");
+        assert_eq!((h.regs[10], h.regs[11]), (7, 1));
+    }
+
+    #[test]
+    fn csr_write_to_counter_traps() {
+        let mut h = Hart::new(0x10);
+        let mut dram = dram_with_words(&[(0x10, 0xc025_1073)]); // csrw instret, a0
+        let error = h.step(&mut dram).expect_err("read-only CSR write");
+        assert!(error.is_trap());
+        assert!(matches!(error, EmulatorError::Decode { pc: 0x10, .. }));
+        assert_eq!(h.instructions_retired(), 0);
+    }
+
+    #[test]
+    fn run_reports_budget_exhausted_and_can_resume() {
+        let mut h = Hart::new(0x10);
+        // addi x1, x1, 1; jal x0, -4
+        let mut dram = dram_with_words(&[(0x10, 0x0010_8093), (0x14, 0xffdf_f06f)]);
+        assert_eq!(h.run(&mut dram, 10), EmulatorError::BudgetExhausted);
+        assert_eq!(h.regs[1], 5);
+        assert_eq!(h.pc, 0x10);
+        assert_eq!(h.run(&mut dram, 10), EmulatorError::BudgetExhausted);
+        assert_eq!(h.regs[1], 10);
+    }
+
+    struct RecordingSyscalls(std::sync::Arc<std::sync::Mutex<Vec<(u32, [u32; 7])>>>);
+
+    impl SyscallHandler for RecordingSyscalls {
+        fn syscall(&mut self, number: u32, args: [u32; 7]) -> Option<u32> {
+            self.0.lock().unwrap().push((number, args));
+            (number == 42).then_some(args[0] + args[1])
+        }
+    }
+
+    #[test]
+    fn ecall_dispatches_to_syscall_handler() {
+        let calls = std::sync::Arc::default();
+        let mut h = Hart::new(0x10);
+        h.set_syscall_handler(Box::new(RecordingSyscalls(std::sync::Arc::clone(&calls))));
+        // li a7, 42; li a0, 5; li a1, 7; ecall
+        let mut dram = dram_with_words(&[
+            (0x10, 0x02a0_0893),
+            (0x14, 0x0050_0513),
+            (0x18, 0x0070_0593),
+            (0x1c, 0x0000_0073),
+        ]);
+        assert_eq!(h.run(&mut dram, 4), EmulatorError::BudgetExhausted);
+        assert_eq!(*calls.lock().unwrap(), vec![(42, [5, 7, 0, 0, 0, 0, 0])]);
+        assert_eq!(h.regs[10], 12);
+        assert_eq!(h.pc, 0x20);
+    }
+
+    #[test]
+    fn ecall_without_handler_stops_with_unknown_syscall() {
+        let mut h = Hart::new(0x10);
+        // li a7, 3; ecall
+        let mut dram = dram_with_words(&[(0x10, 0x0030_0893), (0x14, 0x0000_0073)]);
+        assert_eq!(
+            h.run(&mut dram, 4),
+            EmulatorError::UnknownSyscall {
+                pc: 0x14,
+                number: 3
+            }
+        );
+    }
+
+    #[test]
+    fn log_device_keeps_newest_output_and_flags_truncation() {
+        let mut log = LogDevice::with_capacity(8);
+        for ch in "abcdefghij".chars() {
+            log.store(0, 32, ch as u32).unwrap();
+        }
+        // A multi-byte character evicts whole characters, never partial bytes.
+        log.push_str("é");
+        assert_eq!(log.output(), "efghijé");
+        assert!(log.truncated());
+
+        let drained = log.drain_output();
+        assert_eq!(drained.text, "efghijé");
+        assert!(drained.truncated);
+
+        log.push_str("ok");
+        assert_eq!(
+            log.drain_output(),
+            LogOutput {
+                text: "ok".to_string(),
+                truncated: false
+            }
+        );
+    }
 }
@@ -1,4 +1,6 @@
-#[derive(Debug)]
+use std::fmt;
+
+#[derive(Debug, Clone, Copy)]
 pub enum Instruction {
     R {
         funct: RFunct,
@@ -87,10 +89,21 @@ pub enum Instruction {
         succ: u32,
         fm: u32,
     },
+    /// Zicsr access; the `*i` forms keep their 5-bit immediate in `rs1`.
+    Csr {
+        funct: CsrFunct,
+        rd: usize,
+        rs1: usize,
+        csr: u32,
+    },
+    Ecall,
     Ebreak,
+    /// Wait for interrupt; retires as a no-op and ends the current `Hart::run`
+    /// like a yield.
+    Wfi,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 pub enum RFunct {
     ADD,
     SUB,
@@ -104,7 +117,7 @@ pub enum RFunct {
     AND,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 pub enum MFunct {
     MUL,
     MULH,
@@ -116,7 +129,7 @@ pub enum MFunct {
     REMU,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 pub enum IFunct {
     JALR,
     LB,
@@ -134,13 +147,13 @@ pub enum IFunct {
     SRLI,
     SRAI,
 }
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 pub enum SFunct {
     SB,
     SH,
     SW,
 }
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 pub enum BFunct {
     BEQ,
     BNE,
@@ -150,26 +163,30 @@ pub enum BFunct {
     BGEU,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 pub enum UFunct {
     LUI,
     AUIPC,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 pub enum JFunct {
     JAL,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 pub enum R4Funct {
     FmaddS,
     FmsubS,
     FnmsubS,
     FnmaddS,
+    FmaddD,
+    FmsubD,
+    FnmsubD,
+    FnmaddD,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 pub enum FRFunct {
     FaddS,
     FsubS,
@@ -183,9 +200,21 @@ pub enum FRFunct {
     FeqS,
     FltS,
     FleS,
+    FaddD,
+    FsubD,
+    FmulD,
+    FdivD,
+    FsgnjD,
+    FsgnjnD,
+    FsgnjxD,
+    FminD,
+    FmaxD,
+    FeqD,
+    FltD,
+    FleD,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 pub enum FIFunct {
     FsqrtS,
     FcvtWS,
@@ -195,23 +224,31 @@ pub enum FIFunct {
     FcvtSW,
     FcvtSWU,
     FmvWX,
+    FsqrtD,
+    FcvtWD,
+    FcvtWuD,
+    FclassD,
+    FcvtDW,
+    FcvtDWU,
+    FcvtSD,
+    FcvtDS,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 pub enum FLFunct {
     FLH,
     FLW,
     FLD,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 pub enum FSFunct {
     FSH,
     FSW,
     FSD,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 pub enum AFunct {
     LrW,
     ScW,
@@ -226,12 +263,397 @@ pub enum AFunct {
     AmoMaxuW,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 pub enum FenceFunct {
     Fence,
     FenceI,
 }
 
+#[derive(Debug, Clone, Copy)]
+pub enum CsrFunct {
+    Csrrw,
+    Csrrs,
+    Csrrc,
+    Csrrwi,
+    Csrrsi,
+    Csrrci,
+}
+
+pub const CSR_CYCLE: u32 = 0xc00;
+pub const CSR_TIME: u32 = 0xc01;
+pub const CSR_INSTRET: u32 = 0xc02;
+pub const CSR_CYCLEH: u32 = 0xc80;
+pub const CSR_TIMEH: u32 = 0xc81;
+pub const CSR_INSTRETH: u32 = 0xc82;
+
+/// CSRs whose address bits 11:10 are `0b11` are read-only.
+fn csr_is_read_only(csr: u32) -> bool {
+    (csr >> 10) == 0b11
+}
+
+fn csr_name(csr: u32) -> Option<&'static str> {
+    Some(match csr {
+        CSR_CYCLE => "cycle",
+        CSR_TIME => "time",
+        CSR_INSTRET => "instret",
+        CSR_CYCLEH => "cycleh",
+        CSR_TIMEH => "timeh",
+        CSR_INSTRETH => "instreth",
+        _ => return None,
+    })
+}
+
+const X_NAMES: [&str; 32] = [
+    "zero", "ra", "sp", "gp", "tp", "t0", "t1", "t2", "s0", "s1", "a0", "a1", "a2", "a3", "a4",
+    "a5", "a6", "a7", "s2", "s3", "s4", "s5", "s6", "s7", "s8", "s9", "s10", "s11", "t3", "t4",
+    "t5", "t6",
+];
+
+const F_NAMES: [&str; 32] = [
+    "ft0", "ft1", "ft2", "ft3", "ft4", "ft5", "ft6", "ft7", "fs0", "fs1", "fa0", "fa1", "fa2",
+    "fa3", "fa4", "fa5", "fa6", "fa7", "fs2", "fs3", "fs4", "fs5", "fs6", "fs7", "fs8", "fs9",
+    "fs10", "fs11", "ft8", "ft9", "ft10", "ft11",
+];
+
+fn x(reg: usize) -> &'static str {
+    X_NAMES.get(reg).copied().unwrap_or("x?")
+}
+
+fn f(reg: usize) -> &'static str {
+    F_NAMES.get(reg).copied().unwrap_or("f?")
+}
+
+/// Explicit rounding mode suffix; the dynamic mode (`0b111`) is left implicit.
+fn rm_suffix(rm: u32) -> &'static str {
+    match rm {
+        0 => ", rne",
+        1 => ", rtz",
+        2 => ", rdn",
+        3 => ", rup",
+        4 => ", rmm",
+        _ => "",
+    }
+}
+
+fn fence_set(bits: u32) -> String {
+    let mut set: String = [(8, 'i'), (4, 'o'), (2, 'r'), (1, 'w')]
+        .into_iter()
+        .filter(|(mask, _)| bits & mask != 0)
+        .map(|(_, name)| name)
+        .collect();
+    if set.is_empty() {
+        set.push('0');
+    }
+    set
+}
+
+/// Disassembles in GNU assembler syntax with ABI register names. Compressed
+/// instructions print as their 32-bit expansion, and branch/jump targets are
+/// shown as signed byte offsets from the instruction's own `pc`.
+impl fmt::Display for Instruction {
+    fn fmt(&self, fm: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::R {
+                funct,
+                rd,
+                rs1,
+                rs2,
+            } => {
+                let name = match funct {
+                    RFunct::ADD => "add",
+                    RFunct::SUB => "sub",
+                    RFunct::SLL => "sll",
+                    RFunct::SLT => "slt",
+                    RFunct::SLTU => "sltu",
+                    RFunct::XOR => "xor",
+                    RFunct::SRL => "srl",
+                    RFunct::SRA => "sra",
+                    RFunct::OR => "or",
+                    RFunct::AND => "and",
+                };
+                write!(fm, "{name} {}, {}, {}", x(*rd), x(*rs1), x(*rs2))
+            }
+            Self::M {
+                funct,
+                rd,
+                rs1,
+                rs2,
+            } => {
+                let name = match funct {
+                    MFunct::MUL => "mul",
+                    MFunct::MULH => "mulh",
+                    MFunct::MULHSU => "mulhsu",
+                    MFunct::MULHU => "mulhu",
+                    MFunct::DIV => "div",
+                    MFunct::DIVU => "divu",
+                    MFunct::REM => "rem",
+                    MFunct::REMU => "remu",
+                };
+                write!(fm, "{name} {}, {}, {}", x(*rd), x(*rs1), x(*rs2))
+            }
+            Self::I {
+                funct,
+                rd,
+                rs1,
+                imm,
+            } => {
+                let (name, memory) = match funct {
+                    IFunct::JALR => ("jalr", true),
+                    IFunct::LB => ("lb", true),
+                    IFunct::LH => ("lh", true),
+                    IFunct::LW => ("lw", true),
+                    IFunct::LBU => ("lbu", true),
+                    IFunct::LHU => ("lhu", true),
+                    IFunct::ADDI => ("addi", false),
+                    IFunct::SLTI => ("slti", false),
+                    IFunct::SLTIU => ("sltiu", false),
+                    IFunct::XORI => ("xori", false),
+                    IFunct::ORI => ("ori", false),
+                    IFunct::ANDI => ("andi", false),
+                    IFunct::SLLI => ("slli", false),
+                    IFunct::SRLI => ("srli", false),
+                    IFunct::SRAI => ("srai", false),
+                };
+                if memory {
+                    write!(fm, "{name} {}, {imm}({})", x(*rd), x(*rs1))
+                } else {
+                    write!(fm, "{name} {}, {}, {imm}", x(*rd), x(*rs1))
+                }
+            }
+            Self::S {
+                funct,
+                rs1,
+                rs2,
+                imm,
+            } => {
+                let name = match funct {
+                    SFunct::SB => "sb",
+                    SFunct::SH => "sh",
+                    SFunct::SW => "sw",
+                };
+                write!(fm, "{name} {}, {imm}({})", x(*rs2), x(*rs1))
+            }
+            Self::B {
+                funct,
+                rs1,
+                rs2,
+                imm,
+            } => {
+                let name = match funct {
+                    BFunct::BEQ => "beq",
+                    BFunct::BNE => "bne",
+                    BFunct::BLT => "blt",
+                    BFunct::BGE => "bge",
+                    BFunct::BLTU => "bltu",
+                    BFunct::BGEU => "bgeu",
+                };
+                write!(fm, "{name} {}, {}, {imm}", x(*rs1), x(*rs2))
+            }
+            Self::U { funct, rd, imm } => {
+                let name = match funct {
+                    UFunct::LUI => "lui",
+                    UFunct::AUIPC => "auipc",
+                };
+                write!(fm, "{name} {}, {:#x}", x(*rd), (*imm as u32) >> 12)
+            }
+            Self::J {
+                funct: JFunct::JAL,
+                rd,
+                imm,
+            } => write!(fm, "jal {}, {imm}", x(*rd)),
+            Self::R4 {
+                funct,
+                rd,
+                rs1,
+                rs2,
+                rs3,
+                rm,
+            } => {
+                let name = match funct {
+                    R4Funct::FmaddS => "fmadd.s",
+                    R4Funct::FmsubS => "fmsub.s",
+                    R4Funct::FnmsubS => "fnmsub.s",
+                    R4Funct::FnmaddS => "fnmadd.s",
+                    R4Funct::FmaddD => "fmadd.d",
+                    R4Funct::FmsubD => "fmsub.d",
+                    R4Funct::FnmsubD => "fnmsub.d",
+                    R4Funct::FnmaddD => "fnmadd.d",
+                };
+                write!(
+                    fm,
+                    "{name} {}, {}, {}, {}{}",
+                    f(*rd),
+                    f(*rs1),
+                    f(*rs2),
+                    f(*rs3),
+                    rm_suffix(*rm)
+                )
+            }
+            Self::FR {
+                funct,
+                rd,
+                rs1,
+                rs2,
+                rm,
+            } => {
+                // Only the arithmetic ops carry a rounding mode; for the rest
+                // funct3 selects the operation.
+                let (name, rd_name, rm) = match funct {
+                    FRFunct::FaddS => ("fadd.s", f(*rd), rm_suffix(*rm)),
+                    FRFunct::FsubS => ("fsub.s", f(*rd), rm_suffix(*rm)),
+                    FRFunct::FmulS => ("fmul.s", f(*rd), rm_suffix(*rm)),
+                    FRFunct::FdivS => ("fdiv.s", f(*rd), rm_suffix(*rm)),
+                    FRFunct::FsgnjS => ("fsgnj.s", f(*rd), ""),
+                    FRFunct::FsgnjnS => ("fsgnjn.s", f(*rd), ""),
+                    FRFunct::FsgnjxS => ("fsgnjx.s", f(*rd), ""),
+                    FRFunct::FminS => ("fmin.s", f(*rd), ""),
+                    FRFunct::FmaxS => ("fmax.s", f(*rd), ""),
+                    FRFunct::FeqS => ("feq.s", x(*rd), ""),
+                    FRFunct::FltS => ("flt.s", x(*rd), ""),
+                    FRFunct::FleS => ("fle.s", x(*rd), ""),
+                    FRFunct::FaddD => ("fadd.d", f(*rd), rm_suffix(*rm)),
+                    FRFunct::FsubD => ("fsub.d", f(*rd), rm_suffix(*rm)),
+                    FRFunct::FmulD => ("fmul.d", f(*rd), rm_suffix(*rm)),
+                    FRFunct::FdivD => ("fdiv.d", f(*rd), rm_suffix(*rm)),
+                    FRFunct::FsgnjD => ("fsgnj.d", f(*rd), ""),
+                    FRFunct::FsgnjnD => ("fsgnjn.d", f(*rd), ""),
+                    FRFunct::FsgnjxD => ("fsgnjx.d", f(*rd), ""),
+                    FRFunct::FminD => ("fmin.d", f(*rd), ""),
+                    FRFunct::FmaxD => ("fmax.d", f(*rd), ""),
+                    FRFunct::FeqD => ("feq.d", x(*rd), ""),
+                    FRFunct::FltD => ("flt.d", x(*rd), ""),
+                    FRFunct::FleD => ("fle.d", x(*rd), ""),
+                };
+                write!(fm, "{name} {rd_name}, {}, {}{rm}", f(*rs1), f(*rs2))
+            }
+            Self::FI { funct, rd, rs1, rm } => {
+                let (name, rd_name, rs1_name, rm) = match funct {
+                    FIFunct::FsqrtS => ("fsqrt.s", f(*rd), f(*rs1), rm_suffix(*rm)),
+                    FIFunct::FcvtWS => ("fcvt.w.s", x(*rd), f(*rs1), rm_suffix(*rm)),
+                    FIFunct::FcvtWuS => ("fcvt.wu.s", x(*rd), f(*rs1), rm_suffix(*rm)),
+                    FIFunct::FmvXW => ("fmv.x.w", x(*rd), f(*rs1), ""),
+                    FIFunct::FclassS => ("fclass.s", x(*rd), f(*rs1), ""),
+                    FIFunct::FcvtSW => ("fcvt.s.w", f(*rd), x(*rs1), rm_suffix(*rm)),
+                    FIFunct::FcvtSWU => ("fcvt.s.wu", f(*rd), x(*rs1), rm_suffix(*rm)),
+                    FIFunct::FmvWX => ("fmv.w.x", f(*rd), x(*rs1), ""),
+                    FIFunct::FsqrtD => ("fsqrt.d", f(*rd), f(*rs1), rm_suffix(*rm)),
+                    FIFunct::FcvtWD => ("fcvt.w.d", x(*rd), f(*rs1), rm_suffix(*rm)),
+                    FIFunct::FcvtWuD => ("fcvt.wu.d", x(*rd), f(*rs1), rm_suffix(*rm)),
+                    FIFunct::FclassD => ("fclass.d", x(*rd), f(*rs1), ""),
+                    FIFunct::FcvtDW => ("fcvt.d.w", f(*rd), x(*rs1), rm_suffix(*rm)),
+                    FIFunct::FcvtDWU => ("fcvt.d.wu", f(*rd), x(*rs1), rm_suffix(*rm)),
+                    FIFunct::FcvtSD => ("fcvt.s.d", f(*rd), f(*rs1), rm_suffix(*rm)),
+                    FIFunct::FcvtDS => ("fcvt.d.s", f(*rd), f(*rs1), rm_suffix(*rm)),
+                };
+                write!(fm, "{name} {rd_name}, {rs1_name}{rm}")
+            }
+            Self::FL {
+                funct,
+                rd,
+                rs1,
+                imm,
+            } => {
+                let name = match funct {
+                    FLFunct::FLH => "flh",
+                    FLFunct::FLW => "flw",
+                    FLFunct::FLD => "fld",
+                };
+                write!(fm, "{name} {}, {imm}({})", f(*rd), x(*rs1))
+            }
+            Self::FS {
+                funct,
+                rs1,
+                rs2,
+                imm,
+            } => {
+                let name = match funct {
+                    FSFunct::FSH => "fsh",
+                    FSFunct::FSW => "fsw",
+                    FSFunct::FSD => "fsd",
+                };
+                write!(fm, "{name} {}, {imm}({})", f(*rs2), x(*rs1))
+            }
+            Self::A {
+                funct,
+                rd,
+                rs1,
+                rs2,
+                aq,
+                rl,
+            } => {
+                let name = match funct {
+                    AFunct::LrW => "lr.w",
+                    AFunct::ScW => "sc.w",
+                    AFunct::AmoSwapW => "amoswap.w",
+                    AFunct::AmoAddW => "amoadd.w",
+                    AFunct::AmoXorW => "amoxor.w",
+                    AFunct::AmoAndW => "amoand.w",
+                    AFunct::AmoOrW => "amoor.w",
+                    AFunct::AmoMinW => "amomin.w",
+                    AFunct::AmoMaxW => "amomax.w",
+                    AFunct::AmoMinuW => "amominu.w",
+                    AFunct::AmoMaxuW => "amomaxu.w",
+                };
+                let ordering = match (aq, rl) {
+                    (true, true) => ".aqrl",
+                    (true, false) => ".aq",
+                    (false, true) => ".rl",
+                    (false, false) => "",
+                };
+                if matches!(funct, AFunct::LrW) {
+                    write!(fm, "{name}{ordering} {}, ({})", x(*rd), x(*rs1))
+                } else {
+                    write!(
+                        fm,
+                        "{name}{ordering} {}, {}, ({})",
+                        x(*rd),
+                        x(*rs2),
+                        x(*rs1)
+                    )
+                }
+            }
+            Self::Fence {
+                funct: FenceFunct::FenceI,
+                ..
+            } => write!(fm, "fence.i"),
+            Self::Fence {
+                funct: FenceFunct::Fence,
+                pred,
+                succ,
+                fm: mode,
+            } => {
+                if *mode == 0x8 && *pred == 0x3 && *succ == 0x3 {
+                    write!(fm, "fence.tso")
+                } else if *pred == 0xf && *succ == 0xf {
+                    write!(fm, "fence")
+                } else {
+                    write!(fm, "fence {}, {}", fence_set(*pred), fence_set(*succ))
+                }
+            }
+            Self::Csr {
+                funct,
+                rd,
+                rs1,
+                csr,
+            } => {
+                let csr = csr_name(*csr).map_or_else(|| format!("{csr:#x}"), str::to_string);
+                match funct {
+                    CsrFunct::Csrrs if *rs1 == 0 => write!(fm, "csrr {}, {csr}", x(*rd)),
+                    CsrFunct::Csrrw => write!(fm, "csrrw {}, {csr}, {}", x(*rd), x(*rs1)),
+                    CsrFunct::Csrrs => write!(fm, "csrrs {}, {csr}, {}", x(*rd), x(*rs1)),
+                    CsrFunct::Csrrc => write!(fm, "csrrc {}, {csr}, {}", x(*rd), x(*rs1)),
+                    CsrFunct::Csrrwi => write!(fm, "csrrwi {}, {csr}, {rs1}", x(*rd)),
+                    CsrFunct::Csrrsi => write!(fm, "csrrsi {}, {csr}, {rs1}", x(*rd)),
+                    CsrFunct::Csrrci => write!(fm, "csrrci {}, {csr}, {rs1}", x(*rd)),
+                }
+            }
+            Self::Ecall => write!(fm, "ecall"),
+            Self::Ebreak => write!(fm, "ebreak"),
+            Self::Wfi => write!(fm, "wfi"),
+        }
+    }
+}
+
 fn sign_extend(value: u32, bits: u32) -> i32 {
     let shift = 32 - bits;
     ((value << shift) as i32) >> shift
@@ -241,26 +663,93 @@ fn cbit(inst: u32, bit: u32) -> u32 {
     (inst >> bit) & 1
 }
 
+/// An instruction word that is not a supported RV32IMAFDC encoding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DecodeError {
+    pub word: u32,
+    pub reason: &'static str,
+}
+
+impl DecodeError {
+    fn new(word: u32, reason: &'static str) -> Self {
+        Self { word, reason }
+    }
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "cannot decode {:#010x}: {}", self.word, self.reason)
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
 impl Instruction {
-    pub fn parse(inst: u32) -> Self {
-        Self::parse_with_len(inst).0
+    /// Estimated cycles this instruction costs, for grading bots by
+    /// `Hart::cycles`. These are the only cycle weights in the emulator:
+    ///
+    /// | Instructions                                   | Cycles |
+    /// |------------------------------------------------|--------|
+    /// | integer ALU, loads/stores, branches, jumps, fences, CSR accesses, `ecall`/`ebreak`/`wfi` | 1 |
+    /// | atomics (`lr`/`sc`/`amo*`)                     | 2      |
+    /// | `mul*`                                         | 3      |
+    /// | float add/sub/mul, compare, sign, min/max, convert, move, class (single or double) | 4 |
+    /// | fused multiply-add (`fmadd`/`fmsub`/`fnmadd`/`fnmsub`) | 5 |
+    /// | `div*`/`rem*`, `fdiv.s`/`.d`, `fsqrt.s`/`.d`   | 20     |
+    ///
+    /// Float loads and stores are memory accesses and cost 1.
+    pub fn cycle_cost(&self) -> u64 {
+        match self {
+            Self::M { funct, .. } => match funct {
+                MFunct::MUL | MFunct::MULH | MFunct::MULHSU | MFunct::MULHU => 3,
+                MFunct::DIV | MFunct::DIVU | MFunct::REM | MFunct::REMU => 20,
+            },
+            Self::A { .. } => 2,
+            Self::FR {
+                funct: FRFunct::FdivS | FRFunct::FdivD,
+                ..
+            }
+            | Self::FI {
+                funct: FIFunct::FsqrtS | FIFunct::FsqrtD,
+                ..
+            } => 20,
+            Self::FR { .. } | Self::FI { .. } => 4,
+            Self::R4 { .. } => 5,
+            Self::R { .. }
+            | Self::I { .. }
+            | Self::S { .. }
+            | Self::B { .. }
+            | Self::U { .. }
+            | Self::J { .. }
+            | Self::FL { .. }
+            | Self::FS { .. }
+            | Self::Fence { .. }
+            | Self::Csr { .. }
+            | Self::Ecall
+            | Self::Ebreak
+            | Self::Wfi => 1,
+        }
     }
 
-    pub fn parse_with_len(inst: u32) -> (Self, u32) {
+    pub fn parse(inst: u32) -> Result<Self, DecodeError> {
+        Ok(Self::parse_with_len(inst)?.0)
+    }
+
+    pub fn parse_with_len(inst: u32) -> Result<(Self, u32), DecodeError> {
         if (inst & 0x3) != 0x3 {
-            (Self::parse_compressed(inst & 0xffff), 2)
+            Ok((Self::parse_compressed(inst & 0xffff)?, 2))
         } else {
-            (Self::parse_32(inst), 4)
+            Ok((Self::parse_32(inst)?, 4))
         }
     }
 
-    fn parse_32(inst: u32) -> Self {
+    fn parse_32(inst: u32) -> Result<Self, DecodeError> {
         let opcode = inst & 0x7f;
         let funct3 = (inst >> 12) & 0x7;
         let rd = ((inst >> 7) & 0x1f) as usize;
         let rs1 = ((inst >> 15) & 0x1f) as usize;
         let rs2 = ((inst >> 20) & 0x1f) as usize;
-        match opcode {
+        Ok(match opcode {
             0x03 => {
                 use IFunct::*;
                 // imm[11:0] = inst[31:20]
@@ -271,7 +760,7 @@ impl Instruction {
                     0x2 => LW,
                     0x4 => LBU,
                     0x5 => LHU,
-                    _ => panic!("invalid funct3"),
+                    _ => return Err(DecodeError::new(inst, "invalid funct3")),
                 };
                 Self::I {
                     funct,
@@ -284,7 +773,7 @@ impl Instruction {
                 let funct = match funct3 {
                     0x0 => FenceFunct::Fence,
                     0x1 => FenceFunct::FenceI,
-                    _ => panic!("invalid funct3"),
+                    _ => return Err(DecodeError::new(inst, "invalid funct3")),
                 };
                 let pred = (inst >> 24) & 0xf;
                 let succ = (inst >> 20) & 0xf;
@@ -314,7 +803,7 @@ impl Instruction {
                     }
                     0x6 => ORI,
                     0x7 => ANDI,
-                    _ => panic!("invalid funct3"),
+                    _ => return Err(DecodeError::new(inst, "invalid funct3")),
                 };
                 match funct {
                     SLLI | SRLI | SRAI => Self::I {
@@ -339,7 +828,7 @@ impl Instruction {
                     0x0 => SB,
                     0x1 => SH,
                     0x2 => SW,
-                    _ => panic!("invalid funct3"),
+                    _ => return Err(DecodeError::new(inst, "invalid funct3")),
                 };
                 Self::S {
                     funct,
@@ -350,7 +839,7 @@ impl Instruction {
             }
             0x2f => {
                 if funct3 != 0x2 {
-                    panic!("invalid atomic funct3");
+                    return Err(DecodeError::new(inst, "invalid atomic funct3"));
                 }
                 let funct5 = (inst >> 27) & 0x1f;
                 let aq = cbit(inst, 26) == 1;
@@ -367,10 +856,10 @@ impl Instruction {
                     0x14 => AFunct::AmoMaxW,
                     0x18 => AFunct::AmoMinuW,
                     0x1c => AFunct::AmoMaxuW,
-                    _ => panic!("invalid atomic funct5"),
+                    _ => return Err(DecodeError::new(inst, "invalid atomic funct5")),
                 };
                 if matches!(funct, AFunct::LrW) && rs2 != 0 {
-                    panic!("invalid LR.W encoding");
+                    return Err(DecodeError::new(inst, "invalid LR.W encoding"));
                 }
                 Self::A {
                     funct,
@@ -394,14 +883,14 @@ impl Instruction {
                         0x5 => DIVU,
                         0x6 => REM,
                         0x7 => REMU,
-                        _ => panic!("invalid M funct3"),
+                        _ => return Err(DecodeError::new(inst, "invalid M funct3")),
                     };
-                    return Self::M {
+                    return Ok(Self::M {
                         funct,
                         rd,
                         rs1,
                         rs2,
-                    };
+                    });
                 }
 
                 use RFunct::*;
@@ -426,7 +915,7 @@ impl Instruction {
                     }
                     0x6 => OR,
                     0x7 => AND,
-                    _ => panic!("invalid funct3"),
+                    _ => return Err(DecodeError::new(inst, "invalid funct3")),
                 };
                 Self::R {
                     funct,
@@ -465,7 +954,7 @@ impl Instruction {
                     0x5 => BGE,
                     0x6 => BLTU,
                     0x7 => BGEU,
-                    _ => panic!("invalid funct3"),
+                    _ => return Err(DecodeError::new(inst, "invalid funct3")),
                 };
                 Self::B {
                     funct,
@@ -489,7 +978,7 @@ impl Instruction {
                     0x1 => FLFunct::FLH,
                     0x2 => FLFunct::FLW,
                     0x3 => FLFunct::FLD,
-                    _ => panic!("invalid funct3"),
+                    _ => return Err(DecodeError::new(inst, "invalid funct3")),
                 };
                 Self::FL {
                     funct,
@@ -504,7 +993,7 @@ impl Instruction {
                     0x1 => FSFunct::FSH,
                     0x2 => FSFunct::FSW,
                     0x3 => FSFunct::FSD,
-                    _ => panic!("invalid funct3"),
+                    _ => return Err(DecodeError::new(inst, "invalid funct3")),
                 };
                 Self::FS {
                     funct,
@@ -516,16 +1005,22 @@ impl Instruction {
             0x43 | 0x47 | 0x4b | 0x4f => {
                 let rs3 = ((inst >> 27) & 0x1f) as usize;
                 let fmt = (inst >> 25) & 0x3;
-                if fmt != 0 {
-                    panic!("only single-precision format is supported");
-                }
                 let rm = funct3;
-                let funct = match opcode {
-                    0x43 => R4Funct::FmaddS,
-                    0x47 => R4Funct::FmsubS,
-                    0x4b => R4Funct::FnmsubS,
-                    0x4f => R4Funct::FnmaddS,
-                    _ => unreachable!(),
+                let funct = match (opcode, fmt) {
+                    (0x43, 0) => R4Funct::FmaddS,
+                    (0x47, 0) => R4Funct::FmsubS,
+                    (0x4b, 0) => R4Funct::FnmsubS,
+                    (0x4f, 0) => R4Funct::FnmaddS,
+                    (0x43, 1) => R4Funct::FmaddD,
+                    (0x47, 1) => R4Funct::FmsubD,
+                    (0x4b, 1) => R4Funct::FnmsubD,
+                    (0x4f, 1) => R4Funct::FnmaddD,
+                    _ => {
+                        return Err(DecodeError::new(
+                            inst,
+                            "only single- and double-precision formats are supported",
+                        ));
+                    }
                 };
                 Self::R4 {
                     funct,
@@ -573,7 +1068,7 @@ impl Instruction {
                             0x0 => FRFunct::FsgnjS,
                             0x1 => FRFunct::FsgnjnS,
                             0x2 => FRFunct::FsgnjxS,
-                            _ => panic!("invalid funct3"),
+                            _ => return Err(DecodeError::new(inst, "invalid funct3")),
                         };
                         Self::FR {
                             funct,
@@ -587,7 +1082,7 @@ impl Instruction {
                         let funct = match rm {
                             0x0 => FRFunct::FminS,
                             0x1 => FRFunct::FmaxS,
-                            _ => panic!("invalid funct3"),
+                            _ => return Err(DecodeError::new(inst, "invalid funct3")),
                         };
                         Self::FR {
                             funct,
@@ -608,7 +1103,7 @@ impl Instruction {
                             0x2 => FRFunct::FeqS,
                             0x1 => FRFunct::FltS,
                             0x0 => FRFunct::FleS,
-                            _ => panic!("invalid funct3"),
+                            _ => return Err(DecodeError::new(inst, "invalid funct3")),
                         };
                         Self::FR {
                             funct,
@@ -622,7 +1117,7 @@ impl Instruction {
                         let funct = match rs2 {
                             0x0 => FIFunct::FcvtWS,
                             0x1 => FIFunct::FcvtWuS,
-                            _ => panic!("invalid rs2"),
+                            _ => return Err(DecodeError::new(inst, "invalid rs2")),
                         };
                         Self::FI { funct, rd, rs1, rm }
                     }
@@ -630,7 +1125,7 @@ impl Instruction {
                         let funct = match rs2 {
                             0x0 => FIFunct::FcvtSW,
                             0x1 => FIFunct::FcvtSWU,
-                            _ => panic!("invalid rs2"),
+                            _ => return Err(DecodeError::new(inst, "invalid rs2")),
                         };
                         Self::FI { funct, rd, rs1, rm }
                     }
@@ -638,7 +1133,7 @@ impl Instruction {
                         let funct = match rm {
                             0x0 => FIFunct::FmvXW,
                             0x1 => FIFunct::FclassS,
-                            _ => panic!("invalid funct3"),
+                            _ => return Err(DecodeError::new(inst, "invalid funct3")),
                         };
                         Self::FI { funct, rd, rs1, rm }
                     }
@@ -648,7 +1143,158 @@ impl Instruction {
                         rs1,
                         rm,
                     },
-                    _ => panic!("invalid funct7"),
+                    0x01 => Self::FR {
+                        funct: FRFunct::FaddD,
+                        rd,
+                        rs1,
+                        rs2,
+                        rm,
+                    },
+                    0x05 => Self::FR {
+                        funct: FRFunct::FsubD,
+                        rd,
+                        rs1,
+                        rs2,
+                        rm,
+                    },
+                    0x09 => Self::FR {
+                        funct: FRFunct::FmulD,
+                        rd,
+                        rs1,
+                        rs2,
+                        rm,
+                    },
+                    0x0d => Self::FR {
+                        funct: FRFunct::FdivD,
+                        rd,
+                        rs1,
+                        rs2,
+                        rm,
+                    },
+                    0x11 => {
+                        let funct = match rm {
+                            0x0 => FRFunct::FsgnjD,
+                            0x1 => FRFunct::FsgnjnD,
+                            0x2 => FRFunct::FsgnjxD,
+                            _ => return Err(DecodeError::new(inst, "invalid funct3")),
+                        };
+                        Self::FR {
+                            funct,
+                            rd,
+                            rs1,
+                            rs2,
+                            rm,
+                        }
+                    }
+                    0x15 => {
+                        let funct = match rm {
+                            0x0 => FRFunct::FminD,
+                            0x1 => FRFunct::FmaxD,
+                            _ => return Err(DecodeError::new(inst, "invalid funct3")),
+                        };
+                        Self::FR {
+                            funct,
+                            rd,
+                            rs1,
+                            rs2,
+                            rm,
+                        }
+                    }
+                    0x20 if rs2 == 0x1 => Self::FI {
+                        funct: FIFunct::FcvtSD,
+                        rd,
+                        rs1,
+                        rm,
+                    },
+                    0x21 if rs2 == 0x0 => Self::FI {
+                        funct: FIFunct::FcvtDS,
+                        rd,
+                        rs1,
+                        rm,
+                    },
+                    0x2d => Self::FI {
+                        funct: FIFunct::FsqrtD,
+                        rd,
+                        rs1,
+                        rm,
+                    },
+                    0x51 => {
+                        let funct = match rm {
+                            0x2 => FRFunct::FeqD,
+                            0x1 => FRFunct::FltD,
+                            0x0 => FRFunct::FleD,
+                            _ => return Err(DecodeError::new(inst, "invalid funct3")),
+                        };
+                        Self::FR {
+                            funct,
+                            rd,
+                            rs1,
+                            rs2,
+                            rm,
+                        }
+                    }
+                    0x61 => {
+                        let funct = match rs2 {
+                            0x0 => FIFunct::FcvtWD,
+                            0x1 => FIFunct::FcvtWuD,
+                            _ => return Err(DecodeError::new(inst, "invalid rs2")),
+                        };
+                        Self::FI { funct, rd, rs1, rm }
+                    }
+                    0x69 => {
+                        let funct = match rs2 {
+                            0x0 => FIFunct::FcvtDW,
+                            0x1 => FIFunct::FcvtDWU,
+                            _ => return Err(DecodeError::new(inst, "invalid rs2")),
+                        };
+                        Self::FI { funct, rd, rs1, rm }
+                    }
+                    // RV32 has no fmv.x.d/fmv.d.x; only fclass.d lives here.
+                    0x71 if rm == 0x1 => Self::FI {
+                        funct: FIFunct::FclassD,
+                        rd,
+                        rs1,
+                        rm,
+                    },
+                    _ => return Err(DecodeError::new(inst, "invalid funct7")),
+                }
+            }
+            0x73 if funct3 == 0 => match inst {
+                0x0000_0073 => Self::Ecall,
+                0x0010_0073 => Self::Ebreak,
+                0x1050_0073 => Self::Wfi,
+                // Bots run in user mode, where returning from a trap handler
+                // is an illegal instruction.
+                0x1020_0073 => {
+                    return Err(DecodeError::new(inst, "sret is illegal in user mode"));
+                }
+                0x3020_0073 => {
+                    return Err(DecodeError::new(inst, "mret is illegal in user mode"));
+                }
+                _ => return Err(DecodeError::new(inst, "unsupported SYSTEM instruction")),
+            },
+            0x73 => {
+                use CsrFunct::*;
+                let funct = match funct3 {
+                    0x1 => Csrrw,
+                    0x2 => Csrrs,
+                    0x3 => Csrrc,
+                    0x5 => Csrrwi,
+                    0x6 => Csrrsi,
+                    0x7 => Csrrci,
+                    _ => return Err(DecodeError::new(inst, "invalid funct3")),
+                };
+                let csr = inst >> 20;
+                // csrrs/csrrc (and their immediate forms) with a zero source only read.
+                let writes = matches!(funct, Csrrw | Csrrwi) || rs1 != 0;
+                if writes && csr_is_read_only(csr) {
+                    return Err(DecodeError::new(inst, "write to read-only CSR"));
+                }
+                Self::Csr {
+                    funct,
+                    rd,
+                    rs1,
+                    csr,
                 }
             }
             0x6f => {
@@ -664,18 +1310,15 @@ impl Instruction {
                     imm: sign_extend(imm_u, 21),
                 }
             }
-            _ => {
-                dbg!("opcode not implemented yet", opcode);
-                panic!()
-            }
-        }
+            _ => return Err(DecodeError::new(inst, "opcode not implemented")),
+        })
     }
 
-    fn parse_compressed(inst: u32) -> Self {
+    fn parse_compressed(inst: u32) -> Result<Self, DecodeError> {
         let quadrant = inst & 0x3;
         let funct3 = (inst >> 13) & 0x7;
 
-        match quadrant {
+        Ok(match quadrant {
             0b00 => match funct3 {
                 0b000 => {
                     // C.ADDI4SPN
@@ -684,7 +1327,7 @@ impl Instruction {
                         | ((inst >> 11) & 0x3) << 4
                         | ((inst >> 7) & 0xf) << 6;
                     if nzuimm == 0 {
-                        panic!("illegal C.ADDI4SPN");
+                        return Err(DecodeError::new(inst, "illegal C.ADDI4SPN"));
                     }
                     let rd = 8 + ((inst >> 2) & 0x7) as usize;
                     Self::I {
@@ -708,6 +1351,18 @@ impl Instruction {
                         imm: uimm as i32,
                     }
                 }
+                0b001 => {
+                    // C.FLD
+                    let uimm = ((inst >> 10) & 0x7) << 3 | ((inst >> 5) & 0x3) << 6;
+                    let rd = 8 + ((inst >> 2) & 0x7) as usize;
+                    let rs1 = 8 + ((inst >> 7) & 0x7) as usize;
+                    Self::FL {
+                        funct: FLFunct::FLD,
+                        rd,
+                        rs1,
+                        imm: uimm as i32,
+                    }
+                }
                 0b011 => {
                     // C.FLW (RV32)
                     let uimm = ((inst >> 6) & 0x1) << 2
@@ -722,6 +1377,18 @@ impl Instruction {
                         imm: uimm as i32,
                     }
                 }
+                0b101 => {
+                    // C.FSD
+                    let uimm = ((inst >> 10) & 0x7) << 3 | ((inst >> 5) & 0x3) << 6;
+                    let rs1 = 8 + ((inst >> 7) & 0x7) as usize;
+                    let rs2 = 8 + ((inst >> 2) & 0x7) as usize;
+                    Self::FS {
+                        funct: FSFunct::FSD,
+                        rs1,
+                        rs2,
+                        imm: uimm as i32,
+                    }
+                }
                 0b110 => {
                     // C.SW
                     let uimm = ((inst >> 6) & 0x1) << 2
@@ -750,7 +1417,12 @@ impl Instruction {
                         imm: uimm as i32,
                     }
                 }
-                _ => panic!("illegal compressed instruction (quadrant 0)"),
+                _ => {
+                    return Err(DecodeError::new(
+                        inst,
+                        "illegal compressed instruction (quadrant 0)",
+                    ));
+                }
             },
             0b01 => match funct3 {
                 0b000 => {
@@ -794,7 +1466,7 @@ impl Instruction {
                             | (((inst >> 3) & 0x3) << 7)
                             | (cbit(inst, 2) << 5);
                         if nzimm == 0 {
-                            panic!("illegal C.ADDI16SP");
+                            return Err(DecodeError::new(inst, "illegal C.ADDI16SP"));
                         }
                         Self::I {
                             funct: IFunct::ADDI,
@@ -806,7 +1478,7 @@ impl Instruction {
                         // C.LUI
                         let imm6 = ((inst >> 2) & 0x1f) | (cbit(inst, 12) << 5);
                         if rd == 0 || rd == 2 || imm6 == 0 {
-                            panic!("illegal C.LUI");
+                            return Err(DecodeError::new(inst, "illegal C.LUI"));
                         }
                         Self::U {
                             funct: UFunct::LUI,
@@ -822,7 +1494,7 @@ impl Instruction {
                         0b00 => {
                             // C.SRLI
                             if cbit(inst, 12) == 1 {
-                                panic!("illegal C.SRLI for RV32");
+                                return Err(DecodeError::new(inst, "illegal C.SRLI for RV32"));
                             }
                             let shamt = (((inst >> 12) & 0x1) << 5) | ((inst >> 2) & 0x1f);
                             Self::I {
@@ -835,7 +1507,7 @@ impl Instruction {
                         0b01 => {
                             // C.SRAI
                             if cbit(inst, 12) == 1 {
-                                panic!("illegal C.SRAI for RV32");
+                                return Err(DecodeError::new(inst, "illegal C.SRAI for RV32"));
                             }
                             let shamt = (((inst >> 12) & 0x1) << 5) | ((inst >> 2) & 0x1f);
                             Self::I {
@@ -859,7 +1531,7 @@ impl Instruction {
                             // C.SUB/C.XOR/C.OR/C.AND
                             if cbit(inst, 12) == 1 {
                                 // RV64C uses this space for C.SUBW/C.ADDW.
-                                panic!("illegal RV32C ALU op");
+                                return Err(DecodeError::new(inst, "illegal RV32C ALU op"));
                             }
                             let rs2 = 8 + ((inst >> 2) & 0x7) as usize;
                             let funct2 = (inst >> 5) & 0x3;
@@ -909,7 +1581,12 @@ impl Instruction {
                         imm: decode_cb_imm(inst),
                     }
                 }
-                _ => panic!("illegal compressed instruction (quadrant 1)"),
+                _ => {
+                    return Err(DecodeError::new(
+                        inst,
+                        "illegal compressed instruction (quadrant 1)",
+                    ));
+                }
             },
             0b10 => match funct3 {
                 0b000 => {
@@ -917,7 +1594,7 @@ impl Instruction {
                     let rd = ((inst >> 7) & 0x1f) as usize;
                     let shamt = (((inst >> 12) & 0x1) << 5) | ((inst >> 2) & 0x1f);
                     if rd == 0 || cbit(inst, 12) == 1 {
-                        panic!("illegal C.SLLI");
+                        return Err(DecodeError::new(inst, "illegal C.SLLI"));
                     }
                     Self::I {
                         funct: IFunct::SLLI,
@@ -930,7 +1607,7 @@ impl Instruction {
                     // C.LWSP
                     let rd = ((inst >> 7) & 0x1f) as usize;
                     if rd == 0 {
-                        panic!("illegal C.LWSP");
+                        return Err(DecodeError::new(inst, "illegal C.LWSP"));
                     }
                     let uimm = ((inst >> 4) & 0x7) << 2
                         | ((inst >> 12) & 0x1) << 5
@@ -942,11 +1619,24 @@ impl Instruction {
                         imm: uimm as i32,
                     }
                 }
+                0b001 => {
+                    // C.FLDSP
+                    let rd = ((inst >> 7) & 0x1f) as usize;
+                    let uimm = ((inst >> 5) & 0x3) << 3
+                        | ((inst >> 12) & 0x1) << 5
+                        | ((inst >> 2) & 0x7) << 6;
+                    Self::FL {
+                        funct: FLFunct::FLD,
+                        rd,
+                        rs1: 2,
+                        imm: uimm as i32,
+                    }
+                }
                 0b011 => {
                     // C.FLWSP
                     let rd = ((inst >> 7) & 0x1f) as usize;
                     if rd == 0 {
-                        panic!("illegal C.FLWSP");
+                        return Err(DecodeError::new(inst, "illegal C.FLWSP"));
                     }
                     let uimm = ((inst >> 4) & 0x7) << 2
                         | ((inst >> 12) & 0x1) << 5
@@ -966,7 +1656,7 @@ impl Instruction {
                         if rs2 == 0 {
                             // C.JR
                             if rd == 0 {
-                                panic!("illegal C.JR");
+                                return Err(DecodeError::new(inst, "illegal C.JR"));
                             }
                             Self::I {
                                 funct: IFunct::JALR,
@@ -977,7 +1667,7 @@ impl Instruction {
                         } else {
                             // C.MV
                             if rd == 0 {
-                                panic!("illegal C.MV");
+                                return Err(DecodeError::new(inst, "illegal C.MV"));
                             }
                             Self::R {
                                 funct: RFunct::ADD,
@@ -991,7 +1681,7 @@ impl Instruction {
                     } else if rs2 == 0 {
                         // C.JALR
                         if rd == 0 {
-                            panic!("illegal C.JALR");
+                            return Err(DecodeError::new(inst, "illegal C.JALR"));
                         }
                         Self::I {
                             funct: IFunct::JALR,
@@ -1002,7 +1692,7 @@ impl Instruction {
                     } else {
                         // C.ADD
                         if rd == 0 {
-                            panic!("illegal C.ADD");
+                            return Err(DecodeError::new(inst, "illegal C.ADD"));
                         }
                         Self::R {
                             funct: RFunct::ADD,
@@ -1023,6 +1713,17 @@ impl Instruction {
                         imm: uimm as i32,
                     }
                 }
+                0b101 => {
+                    // C.FSDSP
+                    let uimm = ((inst >> 10) & 0x7) << 3 | ((inst >> 7) & 0x7) << 6;
+                    let rs2 = ((inst >> 2) & 0x1f) as usize;
+                    Self::FS {
+                        funct: FSFunct::FSD,
+                        rs1: 2,
+                        rs2,
+                        imm: uimm as i32,
+                    }
+                }
                 0b111 => {
                     // C.FSWSP
                     let uimm = ((inst >> 9) & 0xf) << 2 | ((inst >> 7) & 0x3) << 6;
@@ -1034,10 +1735,15 @@ impl Instruction {
                         imm: uimm as i32,
                     }
                 }
-                _ => panic!("illegal compressed instruction (quadrant 2)"),
+                _ => {
+                    return Err(DecodeError::new(
+                        inst,
+                        "illegal compressed instruction (quadrant 2)",
+                    ));
+                }
             },
-            _ => panic!("illegal compressed quadrant"),
-        }
+            _ => return Err(DecodeError::new(inst, "illegal compressed quadrant")),
+        })
     }
 }
 
@@ -1066,10 +1772,57 @@ fn decode_cb_imm(inst: u32) -> i32 {
 mod tests {
     use super::*;
 
+    #[test]
+    fn parses_ecall_and_ebreak() {
+        assert!(matches!(
+            Instruction::parse_with_len(0x0000_0073),
+            Ok((Instruction::Ecall, 4))
+        ));
+        assert!(matches!(
+            Instruction::parse_with_len(0x0010_0073),
+            Ok((Instruction::Ebreak, 4))
+        ));
+        assert!(Instruction::parse_with_len(0x0020_0073).is_err());
+    }
+
+    #[test]
+    fn parses_wfi_and_rejects_trap_returns() {
+        assert!(matches!(
+            Instruction::parse_with_len(0x1050_0073),
+            Ok((Instruction::Wfi, 4))
+        ));
+        let sret = Instruction::parse(0x1020_0073).expect_err("sret");
+        assert_eq!(sret.reason, "sret is illegal in user mode");
+        let mret = Instruction::parse(0x3020_0073).expect_err("mret");
+        assert_eq!(mret.reason, "mret is illegal in user mode");
+        // wfi with a nonzero rd is reserved.
+        assert!(Instruction::parse(0x1050_00f3).is_err());
+    }
+
+    #[test]
+    fn rejects_writes_to_read_only_csrs() {
+        // csrw instret, a0
+        let error = Instruction::parse(0xc0251073).expect_err("read-only write");
+        assert_eq!(error.reason, "write to read-only CSR");
+        // csrrs a0, cycle, a1 sets bits; with a zero source it only reads.
+        assert!(Instruction::parse(0xc005a573).is_err());
+        assert!(Instruction::parse(0xc0002573).is_ok());
+        // csrrsi a0, cycle, 0 is a plain read as well.
+        assert!(Instruction::parse(0xc0006573).is_ok());
+    }
+
+    #[test]
+    fn illegal_words_decode_to_errors() {
+        let err = Instruction::parse_with_len(0xffff_ffff).expect_err("all-ones is illegal");
+        assert_eq!(err.word, 0xffff_ffff);
+        // The all-zero halfword is the canonical illegal compressed instruction.
+        assert!(Instruction::parse_with_len(0x0000_0000).is_err());
+    }
+
     #[test]
     fn parses_mul_as_rv32m() {
         let inst = 0x02b50533; // mul a0, a0, a1
-        let (decoded, len) = Instruction::parse_with_len(inst);
+        let (decoded, len) = Instruction::parse_with_len(inst).expect("decode");
         assert_eq!(len, 4);
         match decoded {
             Instruction::M {
@@ -1089,7 +1842,7 @@ mod tests {
     #[test]
     fn parses_amoadd_w() {
         let inst = 0x06b5202f; // amoadd.w.aqrl zero, a1, (a0)
-        let (decoded, len) = Instruction::parse_with_len(inst);
+        let (decoded, len) = Instruction::parse_with_len(inst).expect("decode");
         assert_eq!(len, 4);
         match decoded {
             Instruction::A {
@@ -1113,7 +1866,7 @@ mod tests {
     #[test]
     fn parses_c_jr_with_len_2() {
         let inst = 0x8082; // c.jr ra
-        let (decoded, len) = Instruction::parse_with_len(inst);
+        let (decoded, len) = Instruction::parse_with_len(inst).expect("decode");
         assert_eq!(len, 2);
         match decoded {
             Instruction::I {
@@ -1133,7 +1886,7 @@ mod tests {
     #[test]
     fn parses_c_flwsp_with_len_2() {
         let inst = 0x6092; // c.flwsp f1, 4(sp)
-        let (decoded, len) = Instruction::parse_with_len(inst);
+        let (decoded, len) = Instruction::parse_with_len(inst).expect("decode");
         assert_eq!(len, 2);
         match decoded {
             Instruction::FL {
@@ -1153,7 +1906,7 @@ mod tests {
     #[test]
     fn parses_fence() {
         let inst = 0x0330_000f;
-        let (decoded, len) = Instruction::parse_with_len(inst);
+        let (decoded, len) = Instruction::parse_with_len(inst).expect("decode");
         assert_eq!(len, 4);
         match decoded {
             Instruction::Fence {
@@ -1166,7 +1919,7 @@ mod tests {
 
     #[test]
     fn parses_flh_fld_and_fsh_fsd() {
-        let (decoded, len) = Instruction::parse_with_len(0x0000_9007); // flh f0,0(x1)
+        let (decoded, len) = Instruction::parse_with_len(0x0000_9007).expect("decode"); // flh f0,0(x1)
         assert_eq!(len, 4);
         match decoded {
             Instruction::FL {
@@ -1176,7 +1929,7 @@ mod tests {
             _ => panic!("wrong decode"),
         }
 
-        let (decoded, len) = Instruction::parse_with_len(0x0000_b007); // fld f0,0(x1)
+        let (decoded, len) = Instruction::parse_with_len(0x0000_b007).expect("decode"); // fld f0,0(x1)
         assert_eq!(len, 4);
         match decoded {
             Instruction::FL {
@@ -1186,7 +1939,7 @@ mod tests {
             _ => panic!("wrong decode"),
         }
 
-        let (decoded, len) = Instruction::parse_with_len(0x0000_9027); // fsh f0,0(x1)
+        let (decoded, len) = Instruction::parse_with_len(0x0000_9027).expect("decode"); // fsh f0,0(x1)
         assert_eq!(len, 4);
         match decoded {
             Instruction::FS {
@@ -1196,7 +1949,7 @@ mod tests {
             _ => panic!("wrong decode"),
         }
 
-        let (decoded, len) = Instruction::parse_with_len(0x0000_b027); // fsd f0,0(x1)
+        let (decoded, len) = Instruction::parse_with_len(0x0000_b027).expect("decode"); // fsd f0,0(x1)
         assert_eq!(len, 4);
         match decoded {
             Instruction::FS {
@@ -1207,10 +1960,52 @@ mod tests {
         }
     }
 
+    #[test]
+    fn disassembles_known_encodings() {
+        let cases: &[(u32, &str)] = &[
+            (0xff010113, "addi sp, sp, -16"),
+            (0x00112623, "sw ra, 12(sp)"),
+            (0x00c12083, "lw ra, 12(sp)"),
+            (0x02b50533, "mul a0, a0, a1"),
+            (0x00b50463, "beq a0, a1, 8"),
+            (0xff1ff0ef, "jal ra, -16"),
+            (0x12345537, "lui a0, 0x12345"),
+            (0x00b57553, "fadd.s fa0, fa0, fa1"),
+            (0xa0b52553, "feq.s a0, fa0, fa1"),
+            (0xc0051553, "fcvt.w.s a0, fa0, rtz"),
+            (0x00412507, "flw fa0, 4(sp)"),
+            (0x00a12227, "fsw fa0, 4(sp)"),
+            (0x02b57553, "fadd.d fa0, fa0, fa1"),
+            (0x62b57543, "fmadd.d fa0, fa0, fa1, fa2"),
+            (0xd2057553, "fcvt.d.w fa0, a0"),
+            (0xc20115d3, "fcvt.w.d a1, ft2, rtz"),
+            (0xa2212653, "feq.d a2, ft2, ft2"),
+            (0x1005a52f, "lr.w a0, (a1)"),
+            (0x06b5202f, "amoadd.w.aqrl zero, a1, (a0)"),
+            (0x0ff0000f, "fence"),
+            (0x0000100f, "fence.i"),
+            (0x0000_0073, "ecall"),
+            (0x1050_0073, "wfi"),
+            (0xc0202573, "csrr a0, instret"),
+            (0xc82025f3, "csrr a1, instreth"),
+            (0x3402d073, "csrrwi zero, 0x340, 5"),
+            (0x340595f3, "csrrw a1, 0x340, a1"),
+            // Compressed words print as their expansion.
+            (0x8082, "jalr zero, 0(ra)"),
+            (0x16fd, "addi a3, a3, -1"),
+            (0x20a2, "fld ft1, 8(sp)"),
+            (0xa406, "fsd ft1, 8(sp)"),
+        ];
+        for &(word, expected) in cases {
+            let decoded = Instruction::parse(word).expect("decode");
+            assert_eq!(decoded.to_string(), expected, "word {word:#010x}");
+        }
+    }
+
     #[test]
     fn compressed_sign_extension_regressions() {
         // c.addi a3, -1
-        let (decoded, len) = Instruction::parse_with_len(0x16fd);
+        let (decoded, len) = Instruction::parse_with_len(0x16fd).expect("decode");
         assert_eq!(len, 2);
         match decoded {
             Instruction::I {
@@ -1227,7 +2022,7 @@ mod tests {
         }
 
         // c.li x5, -1
-        let (decoded, len) = Instruction::parse_with_len(0x52fd);
+        let (decoded, len) = Instruction::parse_with_len(0x52fd).expect("decode");
         assert_eq!(len, 2);
         match decoded {
             Instruction::I {
@@ -1244,7 +2039,7 @@ mod tests {
         }
 
         // c.lui x9, -1
-        let (decoded, len) = Instruction::parse_with_len(0x74fd);
+        let (decoded, len) = Instruction::parse_with_len(0x74fd).expect("decode");
         assert_eq!(len, 2);
         match decoded {
             Instruction::U {
@@ -1259,7 +2054,7 @@ mod tests {
         }
 
         // c.andi x9, -1
-        let (decoded, len) = Instruction::parse_with_len(0x98fd);
+        let (decoded, len) = Instruction::parse_with_len(0x98fd).expect("decode");
         assert_eq!(len, 2);
         match decoded {
             Instruction::I {
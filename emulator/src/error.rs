@@ -0,0 +1,118 @@
+use std::fmt;
+
+use crate::cpu::DecodeError;
+
+/// Why a program stopped itself.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HaltReason {
+    Ebreak,
+}
+
+/// Which kind of memory access faulted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccessKind {
+    Fetch,
+    Load,
+    Store,
+}
+
+impl fmt::Display for AccessKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Fetch => "instruction",
+            Self::Load => "load",
+            Self::Store => "store",
+        })
+    }
+}
+
+/// Why a `RamLike` access failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrapCause {
+    /// Nothing is mapped at the address, or the device refused the access.
+    AccessFault,
+    /// The address is not a multiple of the access width and the memory
+    /// does not allow misaligned accesses.
+    Misaligned,
+}
+
+/// Failure modes of loading and running a program.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EmulatorError {
+    /// The program image is not a loadable ELF file.
+    ElfLoad(String),
+    /// The word fetched at `pc` is not a supported instruction.
+    Decode { pc: u32, error: DecodeError },
+    /// The instruction at `pc` touched unmapped memory at `addr`.
+    MemoryFault {
+        pc: u32,
+        addr: u32,
+        access: AccessKind,
+    },
+    /// The instruction at `pc` made a misaligned access at `addr`.
+    Misaligned {
+        pc: u32,
+        addr: u32,
+        access: AccessKind,
+    },
+    /// `ecall` at `pc` asked for syscall `number`, which no handler serves.
+    UnknownSyscall { pc: u32, number: u32 },
+    /// The program stopped itself.
+    Halt(HaltReason),
+    /// The instruction budget ran out before the program stopped.
+    BudgetExhausted,
+    /// The program gave up the rest of its budget by storing to a yielding
+    /// device; like `BudgetExhausted`, it can be resumed.
+    Yielded,
+}
+
+impl fmt::Display for EmulatorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ElfLoad(message) => write!(f, "elf load failed: {message}"),
+            Self::Decode { pc, error } => {
+                write!(f, "trap: illegal instruction at pc={pc:#x}: {error}")
+            }
+            Self::MemoryFault { pc, addr, access } => {
+                write!(f, "trap: {access} access fault at {addr:#x}, pc={pc:#x}")
+            }
+            Self::Misaligned { pc, addr, access } => {
+                write!(f, "trap: misaligned {access} at {addr:#x}, pc={pc:#x}")
+            }
+            Self::UnknownSyscall { pc, number } => {
+                write!(f, "unknown syscall {number} at pc={pc:#x}")
+            }
+            Self::Halt(HaltReason::Ebreak) => write!(f, "halted by ebreak"),
+            Self::BudgetExhausted => write!(f, "instruction budget exhausted"),
+            Self::Yielded => write!(f, "yielded until the next run"),
+        }
+    }
+}
+
+impl EmulatorError {
+    /// The trap for a memory access at `addr` by the instruction at `pc`.
+    pub fn memory(cause: TrapCause, pc: u32, addr: u32, access: AccessKind) -> Self {
+        match cause {
+            TrapCause::AccessFault => Self::MemoryFault { pc, addr, access },
+            TrapCause::Misaligned => Self::Misaligned { pc, addr, access },
+        }
+    }
+
+    /// Whether the program was stopped by a fault in its own code (as opposed
+    /// to halting itself, an unserved syscall, or running out of budget).
+    pub fn is_trap(&self) -> bool {
+        matches!(
+            self,
+            Self::Decode { .. } | Self::MemoryFault { .. } | Self::Misaligned { .. }
+        )
+    }
+}
+
+impl std::error::Error for EmulatorError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Decode { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
@@ -1,10 +1,17 @@
-use cpu::{Dram, Hart};
+use cpu::{Dram, Hart, SyscallHandler};
 
 pub mod bevy;
 pub mod cpu;
+pub mod error;
+pub mod snapshot;
+
+pub use error::{AccessKind, EmulatorError, HaltReason, TrapCause};
+pub use snapshot::CpuSnapshot;
 
 #[derive(Default)]
-pub struct CpuBuilder {}
+pub struct CpuBuilder {
+    syscall_handler: Option<Box<dyn SyscallHandler>>,
+}
 
 fn stack_pointer_for_dram_len(dram_len: u32) -> u32 {
     let stack_top = dram_len & !0xf;
@@ -12,17 +19,27 @@ fn stack_pointer_for_dram_len(dram_len: u32) -> u32 {
 }
 
 impl CpuBuilder {
-    pub fn build(self, elf: &[u8]) -> (Hart, Dram) {
-        let (dram, entry) = Dram::new(elf);
+    /// Serve the program's `ecall`s with `handler`. Without one, any `ecall`
+    /// stops the program with `EmulatorError::UnknownSyscall`.
+    pub fn with_syscall_handler(mut self, handler: impl SyscallHandler + 'static) -> Self {
+        self.syscall_handler = Some(Box::new(handler));
+        self
+    }
+
+    pub fn build(self, elf: &[u8]) -> Result<(Hart, Dram), EmulatorError> {
+        let (dram, entry) = Dram::new(elf)?;
         let mut hart = Hart::new(entry);
         hart.regs[2] = stack_pointer_for_dram_len(dram.dram.len() as u32);
-        (hart, dram)
+        if let Some(handler) = self.syscall_handler {
+            hart.set_syscall_handler(handler);
+        }
+        Ok((hart, dram))
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::stack_pointer_for_dram_len;
+    use super::{CpuBuilder, EmulatorError, stack_pointer_for_dram_len};
 
     #[test]
     fn stack_pointer_for_dram_len_is_16_byte_aligned() {
@@ -30,4 +47,12 @@ mod tests {
         assert_eq!(stack_pointer_for_dram_len(0x42560), 0x42550);
         assert_eq!(stack_pointer_for_dram_len(15), 0);
     }
+
+    #[test]
+    fn build_rejects_non_elf_input() {
+        match CpuBuilder::default().build(b"definitely not an elf") {
+            Err(EmulatorError::ElfLoad(message)) => assert!(message.contains("parse")),
+            other => panic!("expected elf load error, got {:?}", other.map(|_| ())),
+        }
+    }
 }
@@ -1,5 +1,5 @@
-use emulator::CpuBuilder;
-use emulator::cpu::{Device, Dram, Hart, Instruction, LogDevice, Mmu};
+use emulator::cpu::{Device, Dram, Hart, LogDevice, Mmu};
+use emulator::{CpuBuilder, EmulatorError};
 use std::env;
 use std::fs;
 
@@ -8,28 +8,129 @@ fn main() {
     //    .with_max_level(LevelFilter::DEBUG)
     //    .init();
 
-    let args: Vec<String> = env::args().collect();
+    let Some((path, max_steps)) = parse_args(env::args().skip(1)) else {
+        eprintln!("Usage: emulator [--max-steps N] <filename>");
+        std::process::exit(2);
+    };
+    let code = fs::read(&path).unwrap();
+    let (cpu, dram) = match CpuBuilder::default().build(&code) {
+        Ok(cpu) => cpu,
+        Err(error) => {
+            eprintln!("{error}");
+            std::process::exit(1);
+        }
+    };
 
-    if args.len() != 2 {
-        panic!("Usage: emulator <filename>");
-    }
-    let code = fs::read(&args[1]).unwrap();
-    let (cpu, dram) = CpuBuilder::default().build(&code);
+    run_plain(cpu, dram, max_steps);
+}
 
-    run_plain(cpu, dram);
+/// `<filename>` plus an optional `--max-steps N`; without it the program runs
+/// until it stops itself or traps.
+fn parse_args(args: impl Iterator<Item = String>) -> Option<(String, u64)> {
+    let mut path = None;
+    let mut max_steps = u64::MAX;
+    let mut args = args;
+    while let Some(arg) = args.next() {
+        if arg == "--max-steps" {
+            max_steps = args.next()?.parse().ok()?;
+        } else if path.is_none() {
+            path = Some(arg);
+        } else {
+            return None;
+        }
+    }
+    Some((path?, max_steps))
 }
 
-fn run_plain(mut cpu: Hart, mut dram: Dram) {
+fn run_plain(mut cpu: Hart, mut dram: Dram, max_steps: u64) {
     let mut log = LogDevice::new();
-    let mut devices: Vec<&mut dyn Device> = vec![&mut log];
-    let mut mmu = Mmu::new(&mut dram, &mut devices);
-    loop {
-        // 1. Fetch.
-        let inst = cpu.fetch(&mmu);
-
-        // 2. Decode.
-        let (decoded, len) = Instruction::parse_with_len(inst);
-        // 3. Execute.
-        cpu.execute(decoded, len, &mut mmu);
+    let stop = {
+        let mut devices: Vec<&mut dyn Device> = vec![&mut log];
+        let mut mmu = Mmu::new(&mut dram, &mut devices);
+        loop {
+            let remaining = max_steps.saturating_sub(cpu.instructions_retired());
+            match cpu.run(&mut mmu, remaining) {
+                // `wfi` yields; with no host tick to wait for, keep going.
+                EmulatorError::Yielded => continue,
+                stop => break stop,
+            }
+        }
+    };
+    print!("{}", log.output());
+    eprintln!(
+        "emulator stopped after {} instructions: {stop}",
+        cpu.instructions_retired()
+    );
+    std::process::exit(exit_status(&stop));
+}
+
+/// 1 when the program trapped, 0 when it halted, ran out of steps or made an
+/// unserved syscall.
+fn exit_status(stop: &EmulatorError) -> i32 {
+    if stop.is_trap() { 1 } else { 0 }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{exit_status, parse_args};
+    use emulator::{AccessKind, EmulatorError, HaltReason};
+
+    fn args(list: &[&str]) -> impl Iterator<Item = String> {
+        list.iter()
+            .map(|arg| arg.to_string())
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+
+    #[test]
+    fn parses_filename_with_and_without_max_steps() {
+        assert_eq!(
+            parse_args(args(&["bot.elf"])),
+            Some(("bot.elf".to_string(), u64::MAX))
+        );
+        assert_eq!(
+            parse_args(args(&["--max-steps", "500", "bot.elf"])),
+            Some(("bot.elf".to_string(), 500))
+        );
+        assert_eq!(
+            parse_args(args(&["bot.elf", "--max-steps", "7"])),
+            Some(("bot.elf".to_string(), 7))
+        );
+    }
+
+    #[test]
+    fn rejects_missing_or_invalid_max_steps() {
+        assert_eq!(parse_args(args(&["bot.elf", "--max-steps"])), None);
+        assert_eq!(parse_args(args(&["--max-steps", "many", "bot.elf"])), None);
+        assert_eq!(parse_args(args(&["--max-steps", "-1", "bot.elf"])), None);
+    }
+
+    #[test]
+    fn rejects_missing_or_extra_filename() {
+        assert_eq!(parse_args(args(&[])), None);
+        assert_eq!(parse_args(args(&["--max-steps", "500"])), None);
+        assert_eq!(parse_args(args(&["a.elf", "b.elf"])), None);
+    }
+
+    #[test]
+    fn only_traps_exit_with_failure() {
+        assert_eq!(exit_status(&EmulatorError::Halt(HaltReason::Ebreak)), 0);
+        assert_eq!(exit_status(&EmulatorError::BudgetExhausted), 0);
+        assert_eq!(
+            exit_status(&EmulatorError::UnknownSyscall { pc: 0, number: 9 }),
+            0
+        );
+        let fault = EmulatorError::MemoryFault {
+            pc: 0x1000,
+            addr: 0,
+            access: AccessKind::Load,
+        };
+        assert_eq!(exit_status(&fault), 1);
+        let misaligned = EmulatorError::Misaligned {
+            pc: 0x1000,
+            addr: 0x3,
+            access: AccessKind::Store,
+        };
+        assert_eq!(exit_status(&misaligned), 1);
     }
 }
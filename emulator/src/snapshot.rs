@@ -0,0 +1,98 @@
+use serde::{Deserialize, Serialize};
+
+use crate::cpu::{Dram, Hart};
+
+/// Zero bytes that may sit inside one DRAM run before it is split in two.
+const MAX_ZERO_GAP: usize = 16;
+
+/// Complete architectural state of a hart and its DRAM, for replays and ghosts.
+/// Device state and the syscall handler live on the host and are not included.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CpuSnapshot {
+    pub regs: [u32; 32],
+    pub fregs: [u64; 32],
+    pub pc: u32,
+    pub reservation_addr: Option<u32>,
+    pub instret: u64,
+    #[serde(default)]
+    pub cycles: u64,
+    /// DRAM size in bytes.
+    pub dram_len: u32,
+    /// Non-zero DRAM regions as `(offset, bytes)`; everything else is zero.
+    pub dram_runs: Vec<(u32, Vec<u8>)>,
+}
+
+impl CpuSnapshot {
+    pub fn capture(hart: &Hart, dram: &Dram) -> Self {
+        Self {
+            regs: hart.regs,
+            fregs: hart.fregs,
+            pc: hart.pc,
+            reservation_addr: hart.reservation_addr,
+            instret: hart.instret,
+            cycles: hart.cycles,
+            dram_len: dram.dram.len() as u32,
+            dram_runs: nonzero_runs(&dram.dram),
+        }
+    }
+
+    /// Overwrite `hart` and `dram` with this snapshot. Panics if a run lies
+    /// outside `dram_len`, which only a hand-edited snapshot can produce.
+    pub fn restore(&self, hart: &mut Hart, dram: &mut Dram) {
+        hart.regs = self.regs;
+        hart.fregs = self.fregs;
+        hart.pc = self.pc;
+        hart.reservation_addr = self.reservation_addr;
+        hart.instret = self.instret;
+        hart.cycles = self.cycles;
+
+        dram.dram.clear();
+        dram.dram.resize(self.dram_len as usize, 0);
+        for (offset, bytes) in &self.dram_runs {
+            let start = *offset as usize;
+            dram.dram
+                .get_mut(start..start + bytes.len())
+                .expect("snapshot run outside dram")
+                .copy_from_slice(bytes);
+        }
+    }
+}
+
+fn nonzero_runs(bytes: &[u8]) -> Vec<(u32, Vec<u8>)> {
+    let mut runs = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == 0 {
+            i += 1;
+            continue;
+        }
+        let start = i;
+        let mut end = i;
+        while i < bytes.len() && i - end < MAX_ZERO_GAP {
+            if bytes[i] != 0 {
+                end = i + 1;
+            }
+            i += 1;
+        }
+        runs.push((start as u32, bytes[start..end].to_vec()));
+    }
+    runs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nonzero_runs_split_on_long_zero_gaps() {
+        let mut bytes = vec![0u8; 64];
+        bytes[2] = 1;
+        bytes[5] = 2;
+        bytes[40] = 3;
+        assert_eq!(
+            nonzero_runs(&bytes),
+            vec![(2, vec![1, 0, 0, 2]), (40, vec![3])]
+        );
+        assert!(nonzero_runs(&[0; 32]).is_empty());
+    }
+}
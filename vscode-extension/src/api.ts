@@ -1,10 +1,13 @@
 import { resolveServerUrl } from './config';
+import { downloadErrorMessage } from './download';
+import { logoutEndedSession, whoamiErrorMessage } from './session';
 import {
   ArtifactSummary,
   Capabilities,
   LoginResponse,
   UploadArtifactRequest,
-  UploadArtifactResponse
+  UploadArtifactResponse,
+  UserInfo
 } from './types';
 
 function authHeaders(token?: string): Record<string, string> {
@@ -38,8 +41,34 @@ export async function loginRequest(username: string, password: string): Promise<
   return await resp.json() as LoginResponse;
 }
 
+export async function fetchMe(token?: string): Promise<UserInfo> {
+  const resp = await fetch(`${resolveServerUrl()}/api/v1/me`, {
+    headers: authHeaders(token)
+  });
+
+  if (!resp.ok) {
+    const text = await resp.text();
+    throw new Error(whoamiErrorMessage(resp.status, text));
+  }
+
+  return await resp.json() as UserInfo;
+}
+
+/** Ends the session behind `token`; an already ended session counts as logged out. */
+export async function logoutRequest(token: string): Promise<void> {
+  const resp = await fetch(`${resolveServerUrl()}/api/v1/auth/logout`, {
+    method: 'POST',
+    headers: authHeaders(token)
+  });
+
+  if (!logoutEndedSession(resp.status)) {
+    const text = await resp.text();
+    throw new Error(`logout failed: ${resp.status} ${text}`);
+  }
+}
+
 export async function listArtifacts(token?: string): Promise<ArtifactSummary[]> {
-  const resp = await fetch(`${resolveServerUrl()}/api/v1/artifacts`, {
+  const resp = await fetch(`${resolveServerUrl()}/api/v1/artifacts?limit=200`, {
     headers: authHeaders(token)
   });
 
@@ -66,6 +95,19 @@ export async function uploadArtifact(request: UploadArtifactRequest, token?: str
   return await resp.json() as UploadArtifactResponse;
 }
 
+export async function downloadArtifact(id: number, token?: string): Promise<Uint8Array> {
+  const resp = await fetch(`${resolveServerUrl()}/api/v1/artifacts/${id}`, {
+    headers: authHeaders(token)
+  });
+
+  if (!resp.ok) {
+    const text = await resp.text();
+    throw new Error(downloadErrorMessage(id, resp.status, text));
+  }
+
+  return new Uint8Array(await resp.arrayBuffer());
+}
+
 export async function deleteArtifact(id: number, token?: string): Promise<void> {
   const resp = await fetch(`${resolveServerUrl()}/api/v1/artifacts/${id}`, {
     method: 'DELETE',
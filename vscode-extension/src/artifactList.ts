@@ -0,0 +1,27 @@
+import { ArtifactSummary } from './types';
+
+/**
+ * Artifacts matching `filter`: `#12`/`12` selects by id, anything else is a case-insensitive
+ * substring of the name. An empty filter keeps everything.
+ */
+export function filterArtifacts(artifacts: ArtifactSummary[], filter?: string): ArtifactSummary[] {
+  const query = (filter ?? '').trim();
+  if (query.length === 0) {
+    return artifacts;
+  }
+  const idMatch = /^#?(\d+)$/.exec(query);
+  if (idMatch) {
+    const id = Number(idMatch[1]);
+    return artifacts.filter((artifact) => artifact.id === id);
+  }
+  const needle = query.toLowerCase();
+  return artifacts.filter((artifact) => artifact.name.toLowerCase().includes(needle));
+}
+
+/** Tab-separated `id`, `target`, `created_at`, `name` rows under a header line. */
+export function formatArtifactTable(artifacts: ArtifactSummary[]): string {
+  const rows = artifacts.map((artifact) =>
+    [artifact.id, artifact.target, artifact.created_at, artifact.name.replace(/[\t\r\n]+/g, ' ')].join('\t')
+  );
+  return ['id\ttarget\tcreated_at\tname', ...rows].join('\n');
+}
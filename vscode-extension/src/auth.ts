@@ -1,15 +1,31 @@
 import * as vscode from 'vscode';
 
+import { resolveServerUrl } from './config';
+import { LEGACY_TOKEN_KEY, tokenKeyForServer } from './servers';
 import { openLoginWebview } from './views/loginWebview';
 
-const TOKEN_KEY = 'botracers.token';
-
+/**
+ * Token for the configured server. A token saved before tokens were kept per server is
+ * moved to the current server's key on first read.
+ */
 export async function readToken(context: vscode.ExtensionContext): Promise<string | undefined> {
-  return await context.secrets.get(TOKEN_KEY) ?? undefined;
+  const key = tokenKeyForServer(resolveServerUrl());
+  const token = await context.secrets.get(key);
+  if (token !== undefined) {
+    return token;
+  }
+
+  const legacy = await context.secrets.get(LEGACY_TOKEN_KEY);
+  if (legacy === undefined) {
+    return undefined;
+  }
+  await context.secrets.store(key, legacy);
+  await context.secrets.delete(LEGACY_TOKEN_KEY);
+  return legacy;
 }
 
 export async function clearToken(context: vscode.ExtensionContext): Promise<void> {
-  await context.secrets.delete(TOKEN_KEY);
+  await context.secrets.delete(tokenKeyForServer(resolveServerUrl()));
 }
 
 export async function loginViaWebview(context: vscode.ExtensionContext): Promise<boolean> {
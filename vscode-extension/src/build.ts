@@ -1,6 +1,7 @@
 import { spawn } from 'child_process';
 import * as vscode from 'vscode';
 
+import { cargoBuildArgs } from './buildMeta';
 import { defaultArtifactTarget } from './config';
 
 export async function buildBinary(rootPath: string, binName: string, targetTriple?: string): Promise<void> {
@@ -18,7 +19,7 @@ export async function buildBinary(rootPath: string, binName: string, targetTripl
 
 function runCargoBuild(rootPath: string, binName: string, target: string): Promise<void> {
   return new Promise((resolve, reject) => {
-    const args = ['build', '--release', '--target', target, '--bin', binName];
+    const args = cargoBuildArgs(binName, target);
     const child = spawn('cargo', args, {
       cwd: rootPath,
       shell: false
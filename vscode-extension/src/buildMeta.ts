@@ -0,0 +1,44 @@
+import { execFile } from 'child_process';
+
+/** Arguments `buildBinary` passes to cargo; also recorded in the build metadata. */
+export function cargoBuildArgs(binName: string, target: string): string[] {
+  return ['build', '--release', '--target', target, '--bin', binName];
+}
+
+/** The JSON object sent as `UploadArtifactRequest.build_meta_json`. */
+export type BuildMeta = {
+  /** `rustc --version` in the bot workspace (honours its toolchain file). */
+  rustc: string | null;
+  /** `HEAD` of the bot workspace; null outside a git repository. */
+  commit: string | null;
+  /** Whether the working tree had uncommitted changes; null outside a git repository. */
+  dirty: boolean | null;
+  bin: string;
+  target: string;
+  cargo_args: string[];
+};
+
+/** Describe the build of `binName` in `rootPath`; tools that fail to run are recorded as null. */
+export async function collectBuildMeta(rootPath: string, binName: string, target: string): Promise<BuildMeta> {
+  const [rustc, commit, status] = await Promise.all([
+    runTrimmed('rustc', ['--version'], rootPath),
+    runTrimmed('git', ['rev-parse', 'HEAD'], rootPath),
+    runTrimmed('git', ['status', '--porcelain'], rootPath)
+  ]);
+  return {
+    rustc,
+    commit,
+    dirty: status === null ? null : status.length > 0,
+    bin: binName,
+    target,
+    cargo_args: cargoBuildArgs(binName, target)
+  };
+}
+
+function runTrimmed(command: string, args: string[], cwd: string): Promise<string | null> {
+  return new Promise((resolve) => {
+    execFile(command, args, { cwd }, (error, stdout) => {
+      resolve(error ? null : stdout.trim());
+    });
+  });
+}
@@ -1,10 +1,9 @@
 import * as vscode from 'vscode';
 
-export const PRODUCTION_SERVER_URL = 'https://botrace.rs';
-export const LOCALHOST_SERVER_URL = 'http://127.0.0.1:8787';
-export const DEFAULT_TARGET_TRIPLE = 'riscv32imafc-unknown-none-elf';
+import { LOCALHOST_SERVER_URL, PRODUCTION_SERVER_URL, ServerProfile, serverUrlForProfile } from './servers';
 
-export type ServerProfile = 'production' | 'localhost' | 'custom';
+export { LOCALHOST_SERVER_URL, PRODUCTION_SERVER_URL, ServerProfile } from './servers';
+export const DEFAULT_TARGET_TRIPLE = 'riscv32imafc-unknown-none-elf';
 
 export function defaultArtifactTarget(): string {
   const cfg = vscode.workspace.getConfiguration('botracers');
@@ -36,25 +35,7 @@ export function getCustomServerUrl(): string {
 }
 
 export function resolveServerUrl(): string {
-  const profile = getServerProfile();
-  if (profile === 'production') {
-    return PRODUCTION_SERVER_URL;
-  }
-  if (profile === 'localhost') {
-    return LOCALHOST_SERVER_URL;
-  }
-
-  const custom = getCustomServerUrl();
-  if (custom.length === 0) {
-    throw new Error('Custom server profile is selected, but botracers.customServerUrl is empty.');
-  }
-
-  try {
-    const parsed = new URL(custom);
-    return parsed.toString().replace(/\/$/, '');
-  } catch {
-    throw new Error(`Invalid botracers.customServerUrl: '${custom}'`);
-  }
+  return serverUrlForProfile(getServerProfile(), getCustomServerUrl());
 }
 
 async function setServerProfile(profile: ServerProfile): Promise<void> {
@@ -0,0 +1,22 @@
+import { ArtifactSummary } from './types';
+
+/** Default file name for a downloaded artifact: its name made filesystem-safe, with an `.elf` extension. */
+export function downloadFileName(artifact: Pick<ArtifactSummary, 'id' | 'name'>): string {
+  const safe = artifact.name.trim().replace(/[^A-Za-z0-9._-]+/g, '_').replace(/^[._]+/, '');
+  const stem = safe.length > 0 ? safe : `artifact-${artifact.id}`;
+  return stem.toLowerCase().endsWith('.elf') ? stem : `${stem}.elf`;
+}
+
+/** Error text for a failed download; spells out the statuses a user can act on. */
+export function downloadErrorMessage(id: number, status: number, body: string): string {
+  switch (status) {
+    case 401:
+      return `download artifact #${id} failed: not logged in or session expired (401)`;
+    case 403:
+      return `download artifact #${id} failed: the artifact is private to another user (403)`;
+    case 404:
+      return `download artifact #${id} failed: artifact not found (404)`;
+    default:
+      return `download artifact #${id} failed: ${status} ${body}`;
+  }
+}
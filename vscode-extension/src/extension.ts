@@ -30,7 +30,7 @@ export function activate(context: vscode.ExtensionContext): void {
     treeDataProvider: provider,
     showCollapseAll: true
   });
-  context.subscriptions.push(view);
+  context.subscriptions.push(view, provider);
 
   const refresh = (): void => {
     void provider.refreshArtifacts();
@@ -70,6 +70,14 @@ export function activate(context: vscode.ExtensionContext): void {
     }
   });
 
+  registerCommand(context, 'botracers.whoami', async () => {
+    await provider.showWhoami();
+  });
+
+  registerCommand(context, 'botracers.logout', async () => {
+    await provider.logout();
+  });
+
   registerCommand(context, 'botracers.initializeBotProject', async () => {
     await initializeBotProject(context);
     await provider.refreshArtifacts();
@@ -79,6 +87,10 @@ export function activate(context: vscode.ExtensionContext): void {
     await openBotProject();
   });
 
+  registerCommand(context, 'botracers.listArtifacts', async () => {
+    await provider.listArtifactsToOutput();
+  });
+
   registerCommand(context, 'botracers.view.refresh', async () => {
     await provider.refreshArtifacts();
   });
@@ -95,6 +107,10 @@ export function activate(context: vscode.ExtensionContext): void {
     await provider.revealElfPath(item);
   });
 
+  registerCommand(context, 'botracers.view.toggleWatch', async (item?: BotRacersItem) => {
+    await provider.toggleWatch(item);
+  });
+
   registerCommand(context, 'botracers.view.replaceArtifact', async (item?: BotRacersItem) => {
     await provider.replaceArtifact(item);
   });
@@ -103,6 +119,10 @@ export function activate(context: vscode.ExtensionContext): void {
     await provider.deleteArtifact(item);
   });
 
+  registerCommand(context, 'botracers.view.downloadArtifact', async (item?: BotRacersItem) => {
+    await provider.downloadArtifact(item);
+  });
+
   registerCommand(context, 'botracers.view.toggleVisibility', async (item?: BotRacersItem) => {
     await provider.toggleVisibility(item);
   });
@@ -0,0 +1,34 @@
+export const PRODUCTION_SERVER_URL = 'https://botrace.rs';
+export const LOCALHOST_SERVER_URL = 'http://127.0.0.1:8787';
+
+export type ServerProfile = 'production' | 'localhost' | 'custom';
+
+/** Secret-storage key of the token from before tokens were kept per server. */
+export const LEGACY_TOKEN_KEY = 'botracers.token';
+
+/** Base URL `profile` points at; `customUrl` only matters for the custom profile. */
+export function serverUrlForProfile(profile: ServerProfile, customUrl: string): string {
+  if (profile === 'production') {
+    return PRODUCTION_SERVER_URL;
+  }
+  if (profile === 'localhost') {
+    return LOCALHOST_SERVER_URL;
+  }
+
+  const custom = customUrl.trim();
+  if (custom.length === 0) {
+    throw new Error('Custom server profile is selected, but botracers.customServerUrl is empty.');
+  }
+
+  try {
+    const parsed = new URL(custom);
+    return parsed.toString().replace(/\/$/, '');
+  } catch {
+    throw new Error(`Invalid botracers.customServerUrl: '${custom}'`);
+  }
+}
+
+/** Secret-storage key of the bearer token for `serverUrl`, so switching profiles keeps each login. */
+export function tokenKeyForServer(serverUrl: string): string {
+  return `${LEGACY_TOKEN_KEY}:${serverUrl}`;
+}
@@ -0,0 +1,22 @@
+import { UserInfo } from './types';
+
+/** One-line description of the logged-in user for `BotRacers: Who am I`. */
+export function formatWhoami(user: UserInfo): string {
+  return `Logged in as ${user.username} (user #${user.id})`;
+}
+
+/** Error text for a failed `GET /api/v1/me`; a 401 means there is no valid session. */
+export function whoamiErrorMessage(status: number, body: string): string {
+  if (status === 401) {
+    return 'who am I failed: not logged in or session expired (401)';
+  }
+  return `who am I failed: ${status} ${body}`;
+}
+
+/**
+ * Whether a `POST /api/v1/auth/logout` status means the session is gone: 2xx ended it,
+ * 401 means it had already expired or been logged out elsewhere.
+ */
+export function logoutEndedSession(status: number): boolean {
+  return (status >= 200 && status < 300) || status === 401;
+}
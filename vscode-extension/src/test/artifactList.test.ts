@@ -0,0 +1,44 @@
+import * as assert from 'node:assert/strict';
+import { test } from 'node:test';
+
+import { filterArtifacts, formatArtifactTable } from '../artifactList';
+import { ArtifactSummary } from '../types';
+
+function artifact(id: number, name: string): ArtifactSummary {
+  return {
+    id,
+    owner_user_id: 1,
+    owner_username: 'alice',
+    name,
+    note: null,
+    target: 'riscv32imafc-unknown-none-elf',
+    is_public: false,
+    owned_by_me: true,
+    created_at: `2026-01-0${id}T00:00:00Z`
+  };
+}
+
+const artifacts = [artifact(1, 'Fast Bot'), artifact(2, 'slow bot'), artifact(3, 'cruiser')];
+
+test('empty filters keep every artifact', () => {
+  assert.equal(filterArtifacts(artifacts).length, 3);
+  assert.equal(filterArtifacts(artifacts, '  ').length, 3);
+});
+
+test('numeric filters select by id', () => {
+  assert.deepEqual(filterArtifacts(artifacts, '#2').map((a) => a.id), [2]);
+  assert.deepEqual(filterArtifacts(artifacts, '3').map((a) => a.id), [3]);
+  assert.deepEqual(filterArtifacts(artifacts, '9'), []);
+});
+
+test('text filters match names case-insensitively', () => {
+  assert.deepEqual(filterArtifacts(artifacts, 'BOT').map((a) => a.id), [1, 2]);
+});
+
+test('artifact table is tab separated with a header', () => {
+  const table = formatArtifactTable([artifact(1, 'tab\there')]);
+  assert.equal(
+    table,
+    'id\ttarget\tcreated_at\tname\n1\triscv32imafc-unknown-none-elf\t2026-01-01T00:00:00Z\ttab here'
+  );
+});
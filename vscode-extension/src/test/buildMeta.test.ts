@@ -0,0 +1,23 @@
+import * as assert from 'node:assert/strict';
+import * as fs from 'node:fs';
+import * as os from 'node:os';
+import * as path from 'node:path';
+import { test } from 'node:test';
+
+import { cargoBuildArgs, collectBuildMeta } from '../buildMeta';
+
+test('build metadata records the cargo invocation', async () => {
+  const dir = fs.mkdtempSync(path.join(os.tmpdir(), 'botracers-meta-'));
+  try {
+    const meta = await collectBuildMeta(dir, 'car', 'riscv32imafc-unknown-none-elf');
+    assert.equal(meta.bin, 'car');
+    assert.equal(meta.target, 'riscv32imafc-unknown-none-elf');
+    assert.deepEqual(meta.cargo_args, cargoBuildArgs('car', 'riscv32imafc-unknown-none-elf'));
+    assert.ok(meta.rustc === null || meta.rustc.startsWith('rustc '));
+    // A fresh temp dir is not a git repository.
+    assert.equal(meta.commit, null);
+    assert.equal(meta.dirty, null);
+  } finally {
+    fs.rmSync(dir, { recursive: true, force: true });
+  }
+});
@@ -0,0 +1,22 @@
+import * as assert from 'node:assert/strict';
+import { test } from 'node:test';
+
+import { downloadErrorMessage, downloadFileName } from '../download';
+
+test('download file names are filesystem-safe and end in .elf', () => {
+  assert.equal(downloadFileName({ id: 3, name: 'fast bot' }), 'fast_bot.elf');
+  assert.equal(downloadFileName({ id: 3, name: '../../etc/passwd' }), 'etc_passwd.elf');
+  assert.equal(downloadFileName({ id: 3, name: 'car.ELF' }), 'car.ELF');
+});
+
+test('download falls back to the artifact id for unusable names', () => {
+  assert.equal(downloadFileName({ id: 42, name: '   ' }), 'artifact-42.elf');
+  assert.equal(downloadFileName({ id: 42, name: '...' }), 'artifact-42.elf');
+});
+
+test('download errors explain auth and missing artifacts', () => {
+  assert.match(downloadErrorMessage(7, 401, ''), /#7.*not logged in.*\(401\)/);
+  assert.match(downloadErrorMessage(7, 403, ''), /#7.*private to another user \(403\)/);
+  assert.match(downloadErrorMessage(7, 404, ''), /#7.*not found \(404\)/);
+  assert.equal(downloadErrorMessage(7, 500, 'boom'), 'download artifact #7 failed: 500 boom');
+});
@@ -0,0 +1,31 @@
+import * as assert from 'node:assert/strict';
+import { test } from 'node:test';
+
+import {
+  LEGACY_TOKEN_KEY,
+  LOCALHOST_SERVER_URL,
+  PRODUCTION_SERVER_URL,
+  serverUrlForProfile,
+  tokenKeyForServer
+} from '../servers';
+
+test('a non-default profile selects its own server and token', () => {
+  const production = serverUrlForProfile('production', '');
+  const localhost = serverUrlForProfile('localhost', '');
+  assert.equal(production, PRODUCTION_SERVER_URL);
+  assert.equal(localhost, LOCALHOST_SERVER_URL);
+  assert.notEqual(tokenKeyForServer(localhost), tokenKeyForServer(production));
+  assert.notEqual(tokenKeyForServer(localhost), LEGACY_TOKEN_KEY);
+});
+
+test('the custom profile normalizes its URL and keys its token by it', () => {
+  const custom = serverUrlForProfile('custom', ' https://dev.example.com/ ');
+  assert.equal(custom, 'https://dev.example.com');
+  assert.equal(tokenKeyForServer(custom), tokenKeyForServer('https://dev.example.com'));
+  assert.notEqual(tokenKeyForServer(custom), tokenKeyForServer(PRODUCTION_SERVER_URL));
+});
+
+test('the custom profile rejects a missing or invalid URL', () => {
+  assert.throws(() => serverUrlForProfile('custom', '  '), /customServerUrl is empty/);
+  assert.throws(() => serverUrlForProfile('custom', 'not a url'), /Invalid botracers.customServerUrl/);
+});
@@ -0,0 +1,21 @@
+import * as assert from 'node:assert/strict';
+import { test } from 'node:test';
+
+import { formatWhoami, logoutEndedSession, whoamiErrorMessage } from '../session';
+
+test('whoami shows the username and id', () => {
+  assert.equal(formatWhoami({ id: 3, username: 'alice' }), 'Logged in as alice (user #3)');
+});
+
+test('whoami errors explain a missing session', () => {
+  assert.match(whoamiErrorMessage(401, ''), /not logged in or session expired \(401\)/);
+  assert.equal(whoamiErrorMessage(500, 'boom'), 'who am I failed: 500 boom');
+});
+
+test('logout treats an already ended session as logged out', () => {
+  assert.equal(logoutEndedSession(204), true);
+  assert.equal(logoutEndedSession(200), true);
+  assert.equal(logoutEndedSession(401), true);
+  assert.equal(logoutEndedSession(500), false);
+  assert.equal(logoutEndedSession(403), false);
+});
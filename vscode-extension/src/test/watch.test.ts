@@ -0,0 +1,111 @@
+import * as assert from 'node:assert/strict';
+import { test } from 'node:test';
+
+import { isWatchedSourcePath, RebuildDebouncer, Timers } from '../watch';
+
+class FakeTimers implements Timers {
+  private nextId = 1;
+  private readonly pending = new Map<number, () => void>();
+
+  setTimeout = (callback: () => void): unknown => {
+    const id = this.nextId++;
+    this.pending.set(id, callback);
+    return id;
+  };
+
+  clearTimeout = (handle: unknown): void => {
+    this.pending.delete(handle as number);
+  };
+
+  get scheduled(): number {
+    return this.pending.size;
+  }
+
+  flush(): void {
+    const callbacks = [...this.pending.values()];
+    this.pending.clear();
+    for (const callback of callbacks) {
+      callback();
+    }
+  }
+}
+
+const tick = (): Promise<void> => new Promise((resolve) => setImmediate(resolve));
+
+test('ignores build output and unrelated files', () => {
+  assert.equal(isWatchedSourcePath('/bot/src/bin/car.rs'), true);
+  assert.equal(isWatchedSourcePath('/bot/Cargo.toml'), true);
+  assert.equal(isWatchedSourcePath('/bot/.cargo/config.toml'), true);
+  assert.equal(isWatchedSourcePath('/bot/link.x'), true);
+  assert.equal(isWatchedSourcePath('/bot/target/release/build/out.rs'), false);
+  assert.equal(isWatchedSourcePath('/bot/README.md'), false);
+});
+
+test('a burst of events triggers a single rebuild', async () => {
+  const timers = new FakeTimers();
+  let rebuilds = 0;
+  const debouncer = new RebuildDebouncer(300, async () => {
+    rebuilds++;
+  }, timers);
+
+  debouncer.notify('/bot/src/bin/car.rs');
+  debouncer.notify('/bot/src/bin/car.rs');
+  debouncer.notify('/bot/Cargo.toml');
+  debouncer.notify('/bot/target/release/car');
+  assert.equal(timers.scheduled, 1);
+  assert.equal(rebuilds, 0);
+
+  timers.flush();
+  await tick();
+  assert.equal(rebuilds, 1);
+
+  debouncer.notify('/bot/target/release/car');
+  assert.equal(timers.scheduled, 0);
+});
+
+test('changes during a rebuild schedule exactly one follow-up', async () => {
+  const timers = new FakeTimers();
+  let rebuilds = 0;
+  let release: () => void = () => undefined;
+  const debouncer = new RebuildDebouncer(300, async () => {
+    rebuilds++;
+    if (rebuilds === 1) {
+      await new Promise<void>((resolve) => {
+        release = resolve;
+      });
+    }
+  }, timers);
+
+  debouncer.notify('/bot/src/bin/car.rs');
+  timers.flush();
+  await tick();
+  assert.equal(rebuilds, 1);
+
+  debouncer.notify('/bot/src/bin/car.rs');
+  timers.flush();
+  debouncer.notify('/bot/src/bin/car.rs');
+  timers.flush();
+  await tick();
+  assert.equal(rebuilds, 1);
+
+  release();
+  await tick();
+  await tick();
+  assert.equal(rebuilds, 2);
+});
+
+test('dispose cancels a pending rebuild', async () => {
+  const timers = new FakeTimers();
+  let rebuilds = 0;
+  const debouncer = new RebuildDebouncer(300, async () => {
+    rebuilds++;
+  }, timers);
+
+  debouncer.notify('/bot/src/bin/car.rs');
+  debouncer.dispose();
+  assert.equal(timers.scheduled, 0);
+  debouncer.notify('/bot/src/bin/car.rs');
+  assert.equal(timers.scheduled, 0);
+  await tick();
+  assert.equal(rebuilds, 0);
+});
@@ -2,6 +2,7 @@ export type Capabilities = {
   auth_required: boolean;
   mode: string;
   registration_enabled?: boolean;
+  supported_targets?: string[];
 };
 
 export type UserInfo = {
@@ -14,6 +15,7 @@ export type LoginResponse = {
   user: UserInfo;
 };
 
+/** Mirrors `botracers_protocol::ArtifactSummary`; keep the two in sync. */
 export type ArtifactSummary = {
   id: number;
   owner_user_id: number;
@@ -24,6 +26,14 @@ export type ArtifactSummary = {
   is_public: boolean;
   owned_by_me: boolean;
   created_at: string;
+  /** Hex sha256 of the ELF; null for artifacts uploaded before checksums existed. */
+  sha256?: string | null;
+  /** JSON object describing the build (see `BuildMeta`); null when the upload had none. */
+  build_meta_json?: string | null;
+  /** Successful downloads of the ELF. */
+  download_count?: number;
+  /** Owner-assigned labels, sorted. */
+  tags?: string[];
 };
 
 export type UploadArtifactRequest = {
@@ -31,6 +41,7 @@ export type UploadArtifactRequest = {
   note: string | null;
   target: string;
   elf_base64: string;
+  build_meta_json?: string | null;
 };
 
 export type UploadArtifactResponse = {
@@ -4,15 +4,23 @@ import * as vscode from 'vscode';
 
 import {
   deleteArtifact,
+  downloadArtifact,
   fetchCapabilities,
+  fetchMe,
   listArtifacts,
+  logoutRequest,
   updateArtifactVisibility,
   uploadArtifact
 } from '../api';
+import { filterArtifacts, formatArtifactTable } from '../artifactList';
 import { clearToken, readToken } from '../auth';
 import { buildBinary } from '../build';
+import { collectBuildMeta } from '../buildMeta';
 import { defaultArtifactTarget } from '../config';
+import { downloadFileName } from '../download';
+import { formatWhoami } from '../session';
 import { ArtifactSummary } from '../types';
+import { RebuildDebouncer } from '../watch';
 import {
   artifactOutputPath,
   getWorkspaceRoot,
@@ -26,9 +34,19 @@ type ViewStateDetail = 'none' | 'notLoggedIn' | 'sessionExpired' | 'workspaceMis
 
 type RootNodeKind = 'localRoot' | 'remoteRoot';
 
+const WATCH_DEBOUNCE_MS = 500;
+
+type WatchSession = {
+  bin: LocalBinary;
+  artifactName: string;
+  lastArtifactId?: number;
+  watcher: vscode.FileSystemWatcher;
+  debouncer: RebuildDebouncer;
+};
+
 type Node =
   | { kind: RootNodeKind }
-  | { kind: 'localBin'; bin: LocalBinary }
+  | { kind: 'localBin'; bin: LocalBinary; watching: boolean }
   | { kind: 'remoteArtifact'; artifact: ArtifactSummary }
   | { kind: 'message'; message: string };
 
@@ -38,7 +56,7 @@ export class BotRacersItem extends vscode.TreeItem {
     this.contextValue = contextValue(node);
 
     if (node.kind === 'localBin') {
-      this.description = node.bin.rootPath;
+      this.description = node.watching ? `${node.bin.rootPath} · watching` : node.bin.rootPath;
       this.tooltip = `${node.bin.name} (${node.bin.rootPath})`;
       this.iconPath = new vscode.ThemeIcon('symbol-method');
     }
@@ -98,7 +116,7 @@ function contextValue(node: Node): string | undefined {
   }
 }
 
-export class BotRacersViewProvider implements vscode.TreeDataProvider<BotRacersItem> {
+export class BotRacersViewProvider implements vscode.TreeDataProvider<BotRacersItem>, vscode.Disposable {
   private readonly onDidChangeTreeDataEmitter = new vscode.EventEmitter<BotRacersItem | undefined>();
   readonly onDidChangeTreeData = this.onDidChangeTreeDataEmitter.event;
 
@@ -108,9 +126,19 @@ export class BotRacersViewProvider implements vscode.TreeDataProvider<BotRacersI
   private workspaceRoot: string | undefined;
   private localBinaries: LocalBinary[] = [];
   private artifacts: ArtifactSummary[] = [];
+  private supportedTargets: string[] | undefined;
+  private watchSession: WatchSession | undefined;
+  private readonly watchOutput = vscode.window.createOutputChannel('BotRacers Watch');
+  private readonly artifactsOutput = vscode.window.createOutputChannel('BotRacers Artifacts');
 
   constructor(private readonly context: vscode.ExtensionContext) {}
 
+  dispose(): void {
+    this.stopWatch();
+    this.watchOutput.dispose();
+    this.artifactsOutput.dispose();
+  }
+
   async refreshArtifacts(): Promise<void> {
     this.artifacts = [];
     this.stateDetail = 'none';
@@ -119,6 +147,7 @@ export class BotRacersViewProvider implements vscode.TreeDataProvider<BotRacersI
 
     try {
       const caps = await fetchCapabilities();
+      this.supportedTargets = caps.supported_targets;
       if (caps.auth_required) {
         this.token = await readToken(this.context);
         if (!this.token) {
@@ -205,7 +234,14 @@ export class BotRacersViewProvider implements vscode.TreeDataProvider<BotRacersI
 
     const node = element.node;
     if (node.kind === 'localRoot') {
-      return this.localBinaries.map((bin) => new BotRacersItem({ kind: 'localBin', bin }));
+      return this.localBinaries.map(
+        (bin) =>
+          new BotRacersItem({
+            kind: 'localBin',
+            bin,
+            watching: this.watchSession?.bin.name === bin.name && this.watchSession.bin.rootPath === bin.rootPath
+          })
+      );
     }
 
     if (node.kind === 'remoteRoot') {
@@ -251,6 +287,87 @@ export class BotRacersViewProvider implements vscode.TreeDataProvider<BotRacersI
     await this.refreshArtifacts();
   }
 
+  async toggleWatch(item?: BotRacersItem): Promise<void> {
+    const node = item?.node;
+    if (!node || node.kind !== 'localBin') {
+      return;
+    }
+
+    if (this.watchSession) {
+      const previous = this.watchSession.bin;
+      this.stopWatch();
+      this.onDidChangeTreeDataEmitter.fire(undefined);
+      void vscode.window.showInformationMessage(`Stopped watching '${previous.name}'`);
+      if (previous.name === node.bin.name && previous.rootPath === node.bin.rootPath) {
+        return;
+      }
+    }
+
+    const artifactName = await vscode.window.showInputBox({
+      title: 'Artifact Name (watch mode)',
+      value: node.bin.name
+    });
+    if (!artifactName) {
+      return;
+    }
+
+    const bin = node.bin;
+    const debouncer = new RebuildDebouncer(WATCH_DEBOUNCE_MS, async () => await this.rebuildWatched());
+    const watcher = vscode.workspace.createFileSystemWatcher(new vscode.RelativePattern(bin.rootPath, '**/*'));
+    const onEvent = (uri: vscode.Uri): void => debouncer.notify(uri.fsPath);
+    watcher.onDidChange(onEvent);
+    watcher.onDidCreate(onEvent);
+    watcher.onDidDelete(onEvent);
+    this.watchSession = { bin, artifactName, watcher, debouncer };
+
+    this.watchOutput.show(true);
+    this.watchOutput.appendLine(`[watch] watching ${bin.rootPath} for '${bin.name}', uploading as '${artifactName}'`);
+    this.onDidChangeTreeDataEmitter.fire(undefined);
+    await this.rebuildWatched();
+  }
+
+  private stopWatch(): void {
+    if (!this.watchSession) {
+      return;
+    }
+    this.watchSession.debouncer.dispose();
+    this.watchSession.watcher.dispose();
+    this.watchOutput.appendLine(`[watch] stopped watching '${this.watchSession.bin.name}'`);
+    this.watchSession = undefined;
+  }
+
+  /** Build and upload the watched binary, replacing the previous upload of this watch session. */
+  private async rebuildWatched(): Promise<void> {
+    const session = this.watchSession;
+    if (!session) {
+      return;
+    }
+
+    const started = new Date().toLocaleTimeString();
+    this.watchOutput.appendLine(`[watch] ${started} building '${session.bin.name}'...`);
+    let artifactId: number;
+    try {
+      await buildBinary(session.bin.rootPath, session.bin.name);
+      artifactId = await this.uploadBuiltElf(session.bin, session.artifactName, null);
+    } catch (error) {
+      this.watchOutput.appendLine(`[watch] build or upload failed, still watching:\n${String(error)}`);
+      return;
+    }
+    this.watchOutput.appendLine(`[watch] uploaded artifact #${artifactId} as '${session.artifactName}'`);
+
+    if (session.lastArtifactId !== undefined) {
+      try {
+        await deleteArtifact(session.lastArtifactId, this.token);
+      } catch (error) {
+        this.watchOutput.appendLine(
+          `[watch] failed to delete previous artifact #${session.lastArtifactId}: ${String(error)}`
+        );
+      }
+    }
+    session.lastArtifactId = artifactId;
+    await this.refreshArtifacts();
+  }
+
   async replaceArtifact(item?: BotRacersItem): Promise<void> {
     const node = item?.node;
     if (!node || node.kind !== 'remoteArtifact') {
@@ -294,13 +411,47 @@ export class BotRacersViewProvider implements vscode.TreeDataProvider<BotRacersI
     await this.refreshArtifacts();
   }
 
+  async listArtifactsToOutput(): Promise<void> {
+    const filter = await vscode.window.showInputBox({
+      title: 'List artifacts',
+      prompt: 'Optional filter: artifact id (#12) or part of the name'
+    });
+    if (filter === undefined) {
+      return;
+    }
+
+    const artifacts = filterArtifacts(await listArtifacts(this.token), filter);
+    this.artifactsOutput.clear();
+    this.artifactsOutput.appendLine(formatArtifactTable(artifacts));
+    this.artifactsOutput.show(true);
+  }
+
+  async showWhoami(): Promise<void> {
+    const user = await fetchMe(await readToken(this.context));
+    void vscode.window.showInformationMessage(formatWhoami(user));
+  }
+
+  /** Ends the server session (if it still exists) and forgets the stored token. */
+  async logout(): Promise<void> {
+    const token = await readToken(this.context);
+    if (!token) {
+      void vscode.window.showInformationMessage('Not logged in to BotRacers.');
+      return;
+    }
+
+    await logoutRequest(token);
+    await clearToken(this.context);
+    void vscode.window.showInformationMessage('Logged out of BotRacers.');
+    await this.refreshArtifacts();
+  }
+
   async deleteArtifact(item?: BotRacersItem): Promise<void> {
     const node = item?.node;
-    if (!node || node.kind !== 'remoteArtifact') {
+    const artifact = node?.kind === 'remoteArtifact' ? node.artifact : await this.pickOwnedArtifact();
+    if (!artifact) {
       return;
     }
 
-    const artifact = node.artifact;
     if (!artifact.owned_by_me) {
       throw new Error('You can only delete artifacts you own.');
     }
@@ -320,6 +471,48 @@ export class BotRacersViewProvider implements vscode.TreeDataProvider<BotRacersI
     await this.refreshArtifacts();
   }
 
+  async downloadArtifact(item?: BotRacersItem): Promise<void> {
+    const node = item?.node;
+    if (!node || node.kind !== 'remoteArtifact') {
+      return;
+    }
+
+    const artifact = node.artifact;
+    const defaultDir = this.workspaceRoot ?? vscode.workspace.workspaceFolders?.[0]?.uri.fsPath;
+    const fileName = downloadFileName(artifact);
+    const target = await vscode.window.showSaveDialog({
+      title: `Download artifact '${artifact.name}' (#${artifact.id})`,
+      defaultUri: defaultDir ? vscode.Uri.file(path.join(defaultDir, fileName)) : undefined,
+      filters: { 'ELF binaries': ['elf'], 'All files': ['*'] }
+    });
+    if (!target) {
+      return;
+    }
+
+    const bytes = await downloadArtifact(artifact.id, this.token);
+    await fs.promises.writeFile(target.fsPath, bytes);
+    void vscode.window.showInformationMessage(
+      `Downloaded artifact '${artifact.name}' (#${artifact.id}): wrote ${bytes.length} bytes to ${target.fsPath}`
+    );
+  }
+
+  /** Lets the command palette variant of an artifact action choose its target. */
+  private async pickOwnedArtifact(): Promise<ArtifactSummary | undefined> {
+    const owned = (await listArtifacts(this.token)).filter((artifact) => artifact.owned_by_me);
+    if (owned.length === 0) {
+      throw new Error('You do not own any artifacts.');
+    }
+    const picked = await vscode.window.showQuickPick(
+      owned.map((artifact) => ({
+        label: `#${artifact.id} ${artifact.name}`,
+        description: `${artifact.target} · ${artifact.created_at}`,
+        artifact
+      })),
+      { title: 'Select artifact' }
+    );
+    return picked?.artifact;
+  }
+
   async toggleVisibility(item?: BotRacersItem): Promise<void> {
     const node = item?.node;
     if (!node || node.kind !== 'remoteArtifact') {
@@ -360,21 +553,37 @@ export class BotRacersViewProvider implements vscode.TreeDataProvider<BotRacersI
       value: ''
     });
 
+    const artifactId = await this.uploadBuiltElf(bin, name, note && note.trim().length > 0 ? note.trim() : null);
+
+    void vscode.window.showInformationMessage(`Artifact uploaded: #${artifactId} from '${bin.name}'`);
+  }
+
+  private async uploadBuiltElf(bin: LocalBinary, name: string, note: string | null): Promise<number> {
+    const elfPath = artifactOutputPath(bin.rootPath, bin.name);
+    if (!fs.existsSync(elfPath)) {
+      throw new Error(`ELF not found after build: ${elfPath}`);
+    }
+
     const target = defaultArtifactTarget();
+    if (this.supportedTargets && !this.supportedTargets.includes(target)) {
+      throw new Error(
+        `Server does not accept target '${target}' (supported: ${this.supportedTargets.join(', ')}). Check botracers.defaultArtifactTarget.`
+      );
+    }
 
     const bytes = fs.readFileSync(elfPath);
-
+    const buildMeta = await collectBuildMeta(bin.rootPath, bin.name, target);
     const data = await uploadArtifact(
       {
         name,
-        note: note && note.trim().length > 0 ? note.trim() : null,
+        note,
         target,
-        elf_base64: bytes.toString('base64')
+        elf_base64: bytes.toString('base64'),
+        build_meta_json: JSON.stringify(buildMeta)
       },
       this.token
     );
-
-    void vscode.window.showInformationMessage(`Artifact uploaded: #${data.artifact_id} from '${bin.name}'`);
+    return data.artifact_id;
   }
 
   private getCurrentLocalBinaries(): LocalBinary[] {
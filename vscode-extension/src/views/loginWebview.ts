@@ -2,8 +2,7 @@ import * as vscode from 'vscode';
 
 import { loginRequest } from '../api';
 import { resolveServerUrl } from '../config';
-
-const TOKEN_KEY = 'botracers.token';
+import { tokenKeyForServer } from '../servers';
 
 function html(serverUrl: string): string {
   const registerUrl = `${serverUrl}/register`;
@@ -98,7 +97,7 @@ export async function openLoginWebview(context: vscode.ExtensionContext): Promis
 
       try {
         const resp = await loginRequest(username, password);
-        await context.secrets.store(TOKEN_KEY, resp.token);
+        await context.secrets.store(tokenKeyForServer(serverUrl), resp.token);
         void vscode.window.showInformationMessage(`BotRacers login successful as ${resp.user.username}`);
         panel.dispose();
         resolve(true);
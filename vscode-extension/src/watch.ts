@@ -0,0 +1,75 @@
+import * as path from 'path';
+
+export type Timers = {
+  setTimeout: (callback: () => void, ms: number) => unknown;
+  clearTimeout: (handle: unknown) => void;
+};
+
+const defaultTimers: Timers = {
+  setTimeout: (callback, ms) => setTimeout(callback, ms),
+  clearTimeout: (handle) => clearTimeout(handle as NodeJS.Timeout)
+};
+
+/** Whether a changed file can affect the bot build (sources, manifests, linker script). */
+export function isWatchedSourcePath(filePath: string): boolean {
+  const segments = filePath.split(/[\\/]+/);
+  if (segments.includes('target') || segments.includes('.git')) {
+    return false;
+  }
+  const base = path.basename(filePath);
+  return base.endsWith('.rs') || base === 'Cargo.toml' || base === 'config.toml' || base === 'link.x';
+}
+
+/**
+ * Collapses bursts of file events into one rebuild, fired `delayMs` after the last relevant event.
+ * Events that arrive while a rebuild is running schedule exactly one follow-up rebuild.
+ */
+export class RebuildDebouncer {
+  private timer: unknown;
+  private running = false;
+  private pending = false;
+  private disposed = false;
+
+  constructor(
+    private readonly delayMs: number,
+    private readonly rebuild: () => Promise<void>,
+    private readonly timers: Timers = defaultTimers
+  ) {}
+
+  notify(filePath: string): void {
+    if (this.disposed || !isWatchedSourcePath(filePath)) {
+      return;
+    }
+    if (this.timer !== undefined) {
+      this.timers.clearTimeout(this.timer);
+    }
+    this.timer = this.timers.setTimeout(() => {
+      this.timer = undefined;
+      void this.fire();
+    }, this.delayMs);
+  }
+
+  dispose(): void {
+    this.disposed = true;
+    if (this.timer !== undefined) {
+      this.timers.clearTimeout(this.timer);
+      this.timer = undefined;
+    }
+  }
+
+  private async fire(): Promise<void> {
+    if (this.running) {
+      this.pending = true;
+      return;
+    }
+    this.running = true;
+    try {
+      do {
+        this.pending = false;
+        await this.rebuild();
+      } while (this.pending && !this.disposed);
+    } finally {
+      this.running = false;
+    }
+  }
+}
@@ -5,9 +5,29 @@ use core::fmt::Write;
 
 use botracers_bot_sdk::{
     driving::{CarControls, CarState},
-    log, SLOT2, SLOT3,
+    log, yield_tick, SLOT2, SLOT3,
 };
 
+// Devices are memory-mapped (see the SLOT constants). Host calls use `ecall`
+// via `botracers_bot_sdk::syscall::syscall(number, args)`: number in a7,
+// arguments in a0..a6, result in a0.
+//
+// Slot map (little-endian):
+//   SLOT1 0x100  log: write u32 code points
+//   SLOT2 0x200  CarState: speed, position x/y, forward x/y (f32), race_started, tick_us (u32)
+//   SLOT3 0x300  CarControls: accelerator, brake, steering (f32, written)
+//   SLOT4 0x400  SplineQuery: write t, read x/y and t_max (f32)
+//   SLOT5 0x500  TrackRadar: ray distances right to left (f32, NaN = no hit),
+//                ray count at +0xFC (u32, 7 by default)
+//   SLOT6 0x600  CarRadar: 4 nearest cars as x/y pairs (f32, NaN = none)
+//   SLOT7 0x700  LapProgress: lap_fraction, track_length, lap_distance (f32)
+//   SLOT8 0x800  Speedometer: speed (u32, thousandths of a unit per second)
+//   SLOT9 0x900  TrackAhead: 8 × signed curvature (i32, 1/radius × 10000) every
+//                10 units ahead; positive turns left, 0 on straights
+//   SLOT10 0xA00 Yield: any write ends this tick early (`yield_tick()`); the bot
+//                resumes after the write next tick instead of spinning on
+//                stale readings for the rest of its instruction budget
+
 #[unsafe(export_name = "main")]
 fn main() -> ! {
     writeln!(log(), "Starter car bot running...").ok();
@@ -26,5 +46,8 @@ fn main() -> ! {
         car_controls.set_accelerator(accel);
         car_controls.set_brake(brake);
         car_controls.set_steering(steering);
+
+        // Sensors only change once per tick, so wait for fresh readings.
+        yield_tick();
     }
 }
@@ -0,0 +1,158 @@
+//! Controllers and conversions for the fixed-point device readouts.
+
+use botracers_bot_sdk::driving::{Speedometer, TrackAhead};
+
+/// PID controller with output clamping and conditional-integration
+/// anti-windup: while the output is saturated, the integral only accumulates
+/// error that pulls it back into range.
+#[derive(Debug, Clone, Copy)]
+pub struct Pid {
+    pub kp: f32,
+    pub ki: f32,
+    pub kd: f32,
+    /// Output range, `-1.0..=1.0` by default to match `CarControls` inputs.
+    pub output_min: f32,
+    pub output_max: f32,
+    integral: f32,
+    prev_measured: Option<f32>,
+}
+
+impl Pid {
+    pub const fn new(kp: f32, ki: f32, kd: f32) -> Self {
+        Self {
+            kp,
+            ki,
+            kd,
+            output_min: -1.0,
+            output_max: 1.0,
+            integral: 0.0,
+            prev_measured: None,
+        }
+    }
+
+    pub const fn with_limits(mut self, output_min: f32, output_max: f32) -> Self {
+        self.output_min = output_min;
+        self.output_max = output_max;
+        self
+    }
+
+    /// Forget the accumulated integral and derivative history.
+    pub fn reset(&mut self) {
+        self.integral = 0.0;
+        self.prev_measured = None;
+    }
+
+    /// Advance the controller by `dt` seconds and return the clamped output.
+    ///
+    /// The derivative acts on `measured` rather than on the error, so moving
+    /// the setpoint does not kick the output. A non-positive `dt` only
+    /// applies the proportional term.
+    pub fn update(&mut self, setpoint: f32, measured: f32, dt: f32) -> f32 {
+        let error = setpoint - measured;
+        if dt <= 0.0 {
+            return (self.kp * error + self.ki * self.integral)
+                .clamp(self.output_min, self.output_max);
+        }
+
+        let derivative = match self.prev_measured {
+            Some(prev) => -(measured - prev) / dt,
+            None => 0.0,
+        };
+        self.prev_measured = Some(measured);
+
+        let integral = self.integral + error * dt;
+        let unclamped = self.kp * error + self.ki * integral + self.kd * derivative;
+        let output = unclamped.clamp(self.output_min, self.output_max);
+        let winding_up = (unclamped > self.output_max && error > 0.0)
+            || (unclamped < self.output_min && error < 0.0);
+        if !winding_up {
+            self.integral = integral;
+        }
+        output
+    }
+}
+
+/// `Speedometer::speed_fixed()` in world units per second.
+pub fn speed_from_fixed(raw: u32) -> f32 {
+    raw as f32 / Speedometer::SCALE as f32
+}
+
+/// World units per second in the `Speedometer` encoding, for comparing
+/// against `speed_fixed()` without floats. Negative speeds read 0.
+pub fn speed_to_fixed(speed: f32) -> u32 {
+    // Float-to-int `as` saturates and maps NaN to 0.
+    (speed * Speedometer::SCALE as f32 + 0.5) as u32
+}
+
+/// A `TrackAhead` sample as 1 / turn radius in world units; positive turns
+/// left.
+pub fn curvature_from_fixed(raw: i32) -> f32 {
+    raw as f32 / TrackAhead::SCALE as f32
+}
+
+/// Turn radius in world units for a `TrackAhead` sample, or `None` on a
+/// straight. The sign follows the curvature: positive turns left.
+pub fn turn_radius_from_fixed(raw: i32) -> Option<f32> {
+    (raw != 0).then(|| TrackAhead::SCALE as f32 / raw as f32)
+}
+
+/// Sharpest (largest magnitude) curvature among the `TrackAhead` samples,
+/// still in fixed point. Useful for picking a corner speed.
+pub fn sharpest_curvature_fixed(samples: &[i32]) -> i32 {
+    samples
+        .iter()
+        .copied()
+        .max_by_key(|sample| sample.unsigned_abs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// First-order plant `dv/dt = gain * u - drag * v`, roughly a car's
+    /// speed response to the accelerator.
+    fn simulate(pid: &mut Pid, setpoint: f32, steps: usize, dt: f32) -> f32 {
+        let (gain, drag) = (20.0, 0.5);
+        let mut value = 0.0;
+        for _ in 0..steps {
+            let input = pid.update(setpoint, value, dt);
+            value += (gain * input - drag * value) * dt;
+        }
+        value
+    }
+
+    #[test]
+    fn pid_step_response_converges_to_setpoint() {
+        let mut pid = Pid::new(0.2, 0.1, 0.01).with_limits(0.0, 1.0);
+        let value = simulate(&mut pid, 18.0, 60 * 30, 1.0 / 60.0);
+        assert!((value - 18.0).abs() < 0.05, "settled at {value}");
+    }
+
+    #[test]
+    fn saturated_output_does_not_wind_up_the_integral() {
+        // The plant tops out at gain / drag = 40; asking for 100 saturates
+        // the output for the whole run.
+        let mut pid = Pid::new(0.2, 0.5, 0.0).with_limits(0.0, 1.0);
+        simulate(&mut pid, 100.0, 600, 1.0 / 60.0);
+        assert_eq!(pid.integral, 0.0);
+
+        // Dropping the setpoint below the measurement releases the
+        // accelerator straight away instead of unwinding a huge integral.
+        assert_eq!(pid.update(10.0, 39.0, 1.0 / 60.0), 0.0);
+    }
+
+    #[test]
+    fn fixed_point_helpers_match_device_encodings() {
+        assert_eq!(speed_from_fixed(18_250), 18.25);
+        assert_eq!(speed_to_fixed(18.25), 18_250);
+        assert_eq!(speed_to_fixed(-3.0), 0);
+        assert_eq!(speed_to_fixed(f32::NAN), 0);
+
+        assert_eq!(curvature_from_fixed(-200), -0.02);
+        assert_eq!(turn_radius_from_fixed(200), Some(50.0));
+        assert_eq!(turn_radius_from_fixed(0), None);
+        assert_eq!(sharpest_curvature_fixed(&[10, -300, 250, 0]), -300);
+        assert_eq!(sharpest_curvature_fixed(&[]), 0);
+    }
+}
@@ -0,0 +1,8 @@
+//! Helpers shared by the bot binaries in `src/bin/`.
+//!
+//! Everything here is `core`-only so it links into the bare-metal car binary.
+//! Run the unit tests on the host, e.g.
+//! `cargo test --lib --target x86_64-unknown-linux-gnu`.
+#![cfg_attr(not(test), no_std)]
+
+pub mod control;